@@ -0,0 +1,63 @@
+// Compares the fixed-array KeyFrequencyMap against the HashMap it replaced.
+// Run with `cargo bench --bench key_freq`.
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[path = "../src/key_freq.rs"]
+#[allow(dead_code, unused_imports)]
+mod key_freq;
+
+use key_freq::KeyFrequencyMap;
+
+// A stream of letter, space and modifier keycodes resembling ordinary typing
+fn typing_stream(len: usize) -> Vec<u32> {
+    const KEYS: [u32; 12] = [30, 18, 23, 24, 31, 20, 57, 57, 42, 14, 32, 38];
+    (0..len).map(|i| KEYS[(i * 7 + i / 3) % KEYS.len()]).collect()
+}
+
+fn bench_increment(c: &mut Criterion) {
+    let stream = typing_stream(10_000);
+
+    c.bench_function("hashmap increment", |b| {
+        b.iter(|| {
+            let mut counts: HashMap<u32, u64> = HashMap::new();
+            for &code in &stream {
+                *counts.entry(black_box(code)).or_insert(0) += 1;
+            }
+            counts
+        })
+    });
+
+    c.bench_function("array increment", |b| {
+        b.iter(|| {
+            let mut counts = KeyFrequencyMap::new();
+            for &code in &stream {
+                counts.increment(black_box(code));
+            }
+            counts
+        })
+    });
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let stream = typing_stream(10_000);
+
+    let mut hashed: HashMap<u32, u64> = HashMap::new();
+    let mut array = KeyFrequencyMap::new();
+    for &code in &stream {
+        *hashed.entry(code).or_insert(0) += 1;
+        array.increment(code);
+    }
+
+    c.bench_function("hashmap lookup", |b| {
+        b.iter(|| stream.iter().map(|code| hashed.get(black_box(code)).copied().unwrap_or(0)).sum::<u64>())
+    });
+
+    c.bench_function("array lookup", |b| {
+        b.iter(|| stream.iter().map(|&code| array.count(black_box(code))).sum::<u64>())
+    });
+}
+
+criterion_group!(benches, bench_increment, bench_lookup);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use input::event::{DeviceEvent, EventTrait, PointerEvent};
+use input::Event;
+
+use crate::configure::profile_name;
+
+// Weight of the newest event in the smoothed ratio
+const SMOOTHING: f64 = 0.2;
+// Slower motion is mostly rounding, so it says little about the curve
+const MIN_RAW_SPEED: f64 = 0.1;
+// The sparkline gets a sample per redraw: 40 of them cover two seconds
+const REDRAW_INTERVAL: Duration = Duration::from_millis(50);
+const SPARKLINE_WIDTH: usize = 40;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+// Lower bounds of the raw speed buckets for the curve at exit, in raw units per event
+const SPEED_BUCKETS: [f64; 7] = [0.0, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0];
+
+// --show-accel-curve: how much libinput's pointer acceleration scales motion,
+// as the ratio of each motion event's reported delta to its unaccelerated
+// delta. The ratio is low-pass filtered and plotted as a sparkline on the
+// terminal's open line; at exit, the average ratio per raw speed traces
+// the curve itself.
+pub struct AccelCurveVisualizer {
+    ratio: Option<f64>,
+    raw_speed: f64,
+    history: VecDeque<f64>,
+    // Sum of ratios and event count per speed bucket
+    buckets: [(f64, u64); SPEED_BUCKETS.len()],
+    last_draw: Option<Instant>,
+}
+
+impl AccelCurveVisualizer {
+    pub fn new() -> Self {
+        AccelCurveVisualizer {
+            ratio: None,
+            raw_speed: 0.0,
+            history: VecDeque::with_capacity(SPARKLINE_WIDTH),
+            buckets: [(0.0, 0); SPEED_BUCKETS.len()],
+            last_draw: None,
+        }
+    }
+
+    // Returns the line to draw when a redraw is due
+    pub fn observe(&mut self, event: &Event) -> Option<String> {
+        let Event::Pointer(PointerEvent::Motion(motion)) = event else {
+            return None;
+        };
+        self.record((motion.dx_unaccelerated(), motion.dy_unaccelerated()), (motion.dx(), motion.dy()));
+        let now = Instant::now();
+        if self.last_draw.is_some_and(|last| now.duration_since(last) < REDRAW_INTERVAL) {
+            return None;
+        }
+        self.last_draw = Some(now);
+        if self.history.len() == SPARKLINE_WIDTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.ratio?);
+        Some(self.line())
+    }
+
+    fn record(&mut self, raw: (f64, f64), reported: (f64, f64)) {
+        let raw_speed = raw.0.hypot(raw.1);
+        if raw_speed < MIN_RAW_SPEED {
+            return;
+        }
+        let ratio = reported.0.hypot(reported.1) / raw_speed;
+        self.ratio = Some(self.ratio.map_or(ratio, |smoothed| smoothed + SMOOTHING * (ratio - smoothed)));
+        self.raw_speed = raw_speed;
+        let bucket = SPEED_BUCKETS.iter().rposition(|low| raw_speed >= *low).unwrap_or(0);
+        self.buckets[bucket].0 += ratio;
+        self.buckets[bucket].1 += 1;
+    }
+
+    // e.g. "📈 Accel x1.42 ▁▂▃▅▆▇ at 8.1 raw units/event"
+    pub fn line(&self) -> String {
+        format!("📈 Accel x{:.2} {} at {:.1} raw units/event", self.ratio.unwrap_or(1.0), sparkline(&self.history), self.raw_speed)
+    }
+
+    // One row per speed bucket that saw motion, e.g. "  4-8: x1.35 (212 events)"
+    pub fn curve(&self) -> Vec<String> {
+        self.buckets.iter().enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(index, (sum, count))| {
+                let range = match SPEED_BUCKETS.get(index + 1) {
+                    Some(high) => format!("{}-{}", SPEED_BUCKETS[index], high),
+                    None => format!("{}+", SPEED_BUCKETS[index]),
+                };
+                format!("  {:>5}: x{:.2} ({} event{})", range, sum / *count as f64, count, if *count == 1 { "" } else { "s" })
+            })
+            .collect()
+    }
+}
+
+// The acceleration settings of a newly added pointer device, e.g.
+// "Logitech USB Mouse: accel profile adaptive, speed 0.00", with the
+// device name as `name` shows it
+pub fn describe_device(event: &Event, name: impl FnOnce(&str) -> String) -> Option<String> {
+    let Event::Device(DeviceEvent::Added(added)) = event else {
+        return None;
+    };
+    let device = added.device();
+    if !device.config_accel_is_available() {
+        return None;
+    }
+    let profile = device.config_accel_profile().as_ref().map(profile_name).unwrap_or_else(|| "unknown".to_string());
+    Some(format!("{}: accel profile {}, speed {:.2}", name(device.name()), profile, device.config_accel_speed()))
+}
+
+// Scaled so the tallest bar is the window's largest ratio, or 1.0
+fn sparkline(ratios: &VecDeque<f64>) -> String {
+    let top = ratios.iter().copied().fold(1.0, f64::max);
+    ratios.iter()
+        .map(|ratio| SPARKS[((ratio / top) * (SPARKS.len() - 1) as f64).round().clamp(0.0, (SPARKS.len() - 1) as f64) as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smooths_ratios_and_buckets_them_by_speed() {
+        let mut curve = AccelCurveVisualizer::new();
+        curve.record((3.0, 4.0), (6.0, 8.0));
+        assert_eq!(curve.ratio, Some(2.0));
+        curve.record((0.0, 0.05), (0.0, 1.0));
+        assert_eq!(curve.ratio, Some(2.0));
+        curve.record((1.0, 0.0), (1.0, 0.0));
+        assert!((curve.ratio.unwrap() - 1.8).abs() < 1e-9);
+        assert_eq!(curve.curve(), vec!["    1-2: x1.00 (1 event)", "    4-8: x2.00 (1 event)"]);
+
+        assert_eq!(sparkline(&VecDeque::from(vec![0.0, 1.0, 2.0])), "▁▅█");
+    }
+}
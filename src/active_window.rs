@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+
+// Events that arrived while no focused window could be determined
+pub const UNKNOWN_WINDOW: &str = "(unknown)";
+
+// Samples the focused window's title on a background thread; the event loop
+// only reads the latest value, so a slow xdotool never holds up events
+pub struct WindowSampler {
+    title: Arc<Mutex<String>>,
+}
+
+impl WindowSampler {
+    pub fn start(interval: Duration) -> Self {
+        let title = Arc::new(Mutex::new(active_window_title().unwrap_or_default()));
+        let latest = Arc::clone(&title);
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            let sampled = active_window_title().unwrap_or_default();
+            if let Ok(mut title) = latest.lock() {
+                *title = sampled;
+            }
+        });
+        WindowSampler { title }
+    }
+
+    // The last sampled title, if any window could be determined
+    pub fn current(&self) -> Option<String> {
+        self.title.lock().ok().map(|title| title.clone()).filter(|title| !title.is_empty())
+    }
+}
+
+// How many events arrived while each window had focus
+#[derive(Default)]
+pub struct WindowBreakdown {
+    counts: HashMap<String, u64>,
+    total: u64,
+}
+
+impl WindowBreakdown {
+    pub fn record(&mut self, title: &str) {
+        *self.counts.entry(title.to_string()).or_insert(0) += 1;
+        self.total += 1;
+    }
+
+    // Busiest windows first, with their share of all recorded events
+    pub fn top_n(&self, n: usize) -> Vec<(&str, f64)> {
+        let mut windows: Vec<_> = self.counts.iter().collect();
+        windows.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        windows.into_iter()
+            .take(n)
+            .map(|(title, count)| (title.as_str(), *count as f64 / self.total as f64))
+            .collect()
+    }
+}
+
+// Sway is asked directly, since xdotool only sees XWayland windows there;
+// everything else goes through X11
+fn active_window_title() -> Option<String> {
+    if env::var_os("SWAYSOCK").is_some() {
+        if let Some(title) = sway_focused_title() {
+            return Some(title);
+        }
+    }
+    if env::var_os("DISPLAY").is_some() {
+        return xdotool_title();
+    }
+    None
+}
+
+fn xdotool_title() -> Option<String> {
+    if let Some(title) = command_output("xdotool", &["getactivewindow", "getwindowname"]) {
+        return Some(title);
+    }
+    // Some windows have no name; the owning process still says what it is
+    let pid = command_output("xdotool", &["getactivewindow", "getwindowpid"])?;
+    process_name(pid.parse().ok()?)
+}
+
+fn sway_focused_title() -> Option<String> {
+    let tree = command_output("swaymsg", &["-t", "get_tree", "--raw"])?;
+    let tree: Value = serde_json::from_str(&tree).ok()?;
+    focused_title(&tree)
+}
+
+// The focused node of a `swaymsg -t get_tree` dump: its title, else its
+// Wayland app id, else the name of its process
+fn focused_title(tree: &Value) -> Option<String> {
+    let node = focused_node(tree)?;
+    let text = |key: &str| node[key].as_str().filter(|value| !value.is_empty()).map(str::to_string);
+    text("name")
+        .or_else(|| text("app_id"))
+        .or_else(|| node["pid"].as_u64().and_then(|pid| process_name(pid as u32)))
+}
+
+fn focused_node(node: &Value) -> Option<&Value> {
+    if node["focused"].as_bool() == Some(true) {
+        return Some(node);
+    }
+    ["nodes", "floating_nodes"].iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(focused_node)
+}
+
+fn process_name(pid: u32) -> Option<String> {
+    let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim().to_string()).filter(|comm| !comm.is_empty())
+}
+
+// Trimmed stdout of a command that succeeded and printed something
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(text).filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sway_tree_yields_the_focused_title() {
+        let tree = serde_json::json!({
+            "focused": false,
+            "nodes": [{
+                "focused": false,
+                "nodes": [
+                    { "focused": false, "name": "Terminal", "nodes": [] },
+                    { "focused": false, "name": "Other", "nodes": [] },
+                ],
+                "floating_nodes": [
+                    { "focused": true, "name": null, "app_id": "firefox", "nodes": [] },
+                ],
+            }],
+        });
+        assert_eq!(focused_title(&tree).as_deref(), Some("firefox"));
+        assert_eq!(focused_title(&serde_json::json!({ "focused": false, "nodes": [] })), None);
+    }
+
+    #[test]
+    fn breakdown_is_sorted_by_share() {
+        let mut breakdown = WindowBreakdown::default();
+        for title in ["Firefox", "Terminal", "Firefox", "Firefox"] {
+            breakdown.record(title);
+        }
+        assert_eq!(breakdown.top_n(5), vec![("Firefox", 0.75), ("Terminal", 0.25)]);
+        assert_eq!(breakdown.top_n(1).len(), 1);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::keys::key_name;
+use crate::output::{self, emit, Output, Severity};
+use crate::Colors;
+
+// How a key alert is signalled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertStyle {
+    Bell,
+    Flash,
+    Both,
+}
+
+impl AlertStyle {
+    pub fn parse(value: &str) -> Option<AlertStyle> {
+        match value {
+            "bell" => Some(AlertStyle::Bell),
+            "flash" => Some(AlertStyle::Flash),
+            "both" => Some(AlertStyle::Both),
+            _ => None,
+        }
+    }
+}
+
+// Audible/visual alert when one of the watched keys is pressed,
+// e.g. a warning when Caps Lock is hit
+pub struct KeyAlert {
+    codes: Vec<u32>,
+    style: AlertStyle,
+}
+
+impl KeyAlert {
+    pub fn new(codes: Vec<u32>, style: AlertStyle) -> Self {
+        KeyAlert { codes, style }
+    }
+
+    pub fn on_key_press(&self, code: u32, out: &mut Output) {
+        if !self.codes.contains(&code) {
+            return;
+        }
+
+        // A bell means nothing outside a terminal, so logs always get the alert line
+        if self.style != AlertStyle::Bell || !out.is_terminal() {
+            // Reverse video makes the line stand out from the regular event stream
+            emit!(out, Severity::Warning, "{}{}\x1b[7m 🔔 ALERT: {} pressed {}", Colors::RED, Colors::BOLD, key_name(code), Colors::RESET);
+        }
+        if self.style != AlertStyle::Flash && out.is_terminal() {
+            output::write_stdout("\x07");
+        }
+
+        out.flush();
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+use crate::event::{InputEvent, ScrollSource, TimedEvent};
+use crate::histogram::LatencyHistogram;
+use crate::signals;
+use crate::Monitor;
+
+// Largest synthetic batch, like a busy dispatch()
+const MAX_BATCH: u64 = 128;
+
+// Events slower than this are counted separately
+const SLOW_EVENT: Duration = Duration::from_millis(10);
+
+// Small deterministic xorshift generator so runs are comparable
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Synthetic mix of keyboard and mouse traffic, weighted towards motion like real use
+fn synthetic_event(rng: &mut Xorshift, time_usec: u64) -> TimedEvent {
+    let roll = rng.next();
+    let event = match roll % 10 {
+        0..=4 => InputEvent::PointerMotion {
+            dx: (roll >> 8) as f64 % 7.0 - 3.0,
+            dy: (roll >> 16) as f64 % 7.0 - 3.0,
+        },
+        5 | 6 => InputEvent::Key { code: 16 + (roll >> 8) as u32 % 35, pressed: roll & 0x100 == 0 },
+        7 => InputEvent::PointerButton { button: 272 + (roll >> 8) as u32 % 3, pressed: roll & 0x100 == 0 },
+        _ => InputEvent::Scroll { source: ScrollSource::Wheel, horizontal: 0.0, vertical: if roll & 0x100 == 0 { 120.0 } else { -120.0 } },
+    };
+    TimedEvent { time_usec, event, fingers: None, sysname: None, seat: None }
+}
+
+// Replay synthetic batches through the normal event pipeline as fast as possible
+// (no poll wait) and report throughput and latency on stderr
+pub fn run(monitor: &mut Monitor, duration: Duration) {
+    let mut rng = Xorshift(0x2545_f491_4f6c_dd1d);
+    let mut latencies = LatencyHistogram::new();
+    let mut batches = 0u64;
+    let mut largest_batch = 0usize;
+    let mut slow_events = 0u64;
+    let mut time_usec = 0u64;
+
+    let started = Instant::now();
+    while started.elapsed() < duration && !signals::exit_requested() {
+        let batch_size = 1 + (rng.next() % MAX_BATCH) as usize;
+        let batch: Vec<TimedEvent> = (0..batch_size)
+            .map(|_| {
+                time_usec += 1000;
+                synthetic_event(&mut rng, time_usec)
+            })
+            .collect();
+
+        for timed in &batch {
+            let event_start = Instant::now();
+            monitor.handle_event(timed);
+            let elapsed = event_start.elapsed();
+            latencies.record(elapsed);
+            if elapsed > SLOW_EVENT {
+                slow_events += 1;
+            }
+        }
+
+        batches += 1;
+        largest_batch = largest_batch.max(batch_size);
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+
+    eprintln!();
+    eprintln!("Benchmark results ({:.2} s)", elapsed);
+    eprintln!("  Events processed:   {}", latencies.count());
+    eprintln!("  Batches:            {} (largest: {} events)", batches, largest_batch);
+    eprintln!("  Throughput:         {:.0} events/s", latencies.count() as f64 / elapsed);
+    eprintln!("  Mean per event:     {:.2} µs", latencies.mean_us());
+    eprintln!("  p50 / p95 / p99:    {:.2} / {:.2} / {:.2} µs",
+        latencies.percentile_us(50.0), latencies.percentile_us(95.0), latencies.percentile_us(99.0));
+    eprintln!("  Max:                {:.2} µs", latencies.max_us());
+    if slow_events > 0 {
+        eprintln!("  Slower than 10 ms:  {} events", slow_events);
+    }
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use crate::keys::is_character_key;
+
+// Counts which character keys follow each other (for finger-travel analysis).
+// Non-character keys are skipped without breaking the chain; an idle gap does.
+pub struct BigramCounter {
+    idle_threshold_usec: u64,
+    previous: Option<(u32, u64)>,
+    counts: HashMap<(u32, u32), u64>,
+}
+
+impl BigramCounter {
+    pub fn new(idle_threshold_usec: u64) -> Self {
+        BigramCounter { idle_threshold_usec, previous: None, counts: HashMap::new() }
+    }
+
+    pub fn record_press(&mut self, code: u32, time_usec: u64) {
+        if !is_character_key(code) {
+            return;
+        }
+        if let Some((previous, last_usec)) = self.previous {
+            if time_usec.saturating_sub(last_usec) <= self.idle_threshold_usec {
+                *self.counts.entry((previous, code)).or_insert(0) += 1;
+            }
+        }
+        self.previous = Some((code, time_usec));
+    }
+
+    // The `n` most frequent pairs, most frequent first (ties by keycodes)
+    pub fn top_n(&self, n: usize) -> Vec<((u32, u32), u64)> {
+        let mut pairs: Vec<((u32, u32), u64)> = self.counts.iter().map(|(&pair, &count)| (pair, count)).collect();
+        pairs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        pairs.truncate(n);
+        pairs
+    }
+}
@@ -0,0 +1,355 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use crate::event::{InputEvent, JsonEvent, ScrollSource, TimedEvent};
+use crate::output::{self, FlushPolicy};
+use crate::Colors;
+
+// Layout of `--format binary`, all little-endian:
+//
+//   header (20 bytes): b"RKEY", u8 version, [u8; 10] seat (NUL-padded),
+//                      u8 reserved, u32 event count (0 when streamed)
+//   event  (40 bytes): u8 type, u8 flags, u16 key_or_button,
+//                      f32 x, f32 y, f32 dx, f32 dy, f32 scroll_x, f32 scroll_y,
+//                      u32 slot, u64 timestamp_us
+//
+// `slot` is only used by touch events (which keep their position in x / y)
+// and finger count changes (the previous count; the new one is in
+// key_or_button) and tablet pad controls (the mode; a pad button's mode
+// group goes in x, a ring or strip position in x, its number in
+// key_or_button); it also keeps the timestamp 8-byte aligned.
+// The finger count JSON adds to pointer events isn't stored.
+const MAGIC: &[u8; 4] = b"RKEY";
+const VERSION: u8 = 1;
+pub const HEADER_SIZE: usize = 20;
+pub const RECORD_SIZE: usize = 40;
+const COUNT_OFFSET: u64 = 16;
+
+// Event type byte
+const TYPE_OTHER: u8 = 0;
+const TYPE_KEY: u8 = 1;
+const TYPE_BUTTON: u8 = 2;
+const TYPE_MOTION: u8 = 3;
+const TYPE_MOTION_ABSOLUTE: u8 = 4;
+const TYPE_SCROLL: u8 = 5;
+const TYPE_DEVICE_ADDED: u8 = 6;
+const TYPE_DEVICE_REMOVED: u8 = 7;
+const TYPE_TOUCH: u8 = 8;
+const TYPE_GESTURE: u8 = 9;
+const TYPE_TABLET: u8 = 10;
+const TYPE_SWITCH: u8 = 11;
+const TYPE_TOUCHPAD_FINGERS: u8 = 12;
+const TYPE_PAD_BUTTON: u8 = 13;
+const TYPE_PAD_RING: u8 = 14;
+const TYPE_PAD_STRIP: u8 = 15;
+
+// Flags: bit 0 is "pressed", bits 1-2 the scroll source, bits 3-4 which
+// optional touch fields are present (bit 4 also for pad ring / strip positions)
+const FLAG_PRESSED: u8 = 0x01;
+const SCROLL_SOURCE_SHIFT: u8 = 1;
+const FLAG_TOUCH_SLOT: u8 = 0x08;
+const FLAG_TOUCH_POSITION: u8 = 0x10;
+
+// Touch and gesture kinds are stored as an index into these tables
+const TOUCH_KINDS: &[&str] = &["Other", "Down", "Up", "Motion", "Cancel", "Frame"];
+const GESTURE_KINDS: &[&str] = &[
+    "Other", "SwipeBegin", "SwipeUpdate", "SwipeEnd",
+    "PinchBegin", "PinchUpdate", "PinchEnd", "HoldBegin", "HoldEnd",
+];
+
+// Options for `rustkey decode <file.bin>`
+pub struct DecodeOptions {
+    pub input: PathBuf,
+    pub output: Option<PathBuf>,
+}
+
+// Writes the binary header and one fixed-size record per event
+pub struct BinaryWriter {
+    writer: BufWriter<Box<dyn Write>>,
+    // Set for regular files, so the final event count can go into the header
+    file: Option<File>,
+    count: u32,
+    // Flush after every record unless --flush asked for block buffering
+    flush_each: bool,
+}
+
+impl BinaryWriter {
+    pub fn to_file(path: &std::path::Path, seat: &str) -> io::Result<Self> {
+        let file = File::create(path)?;
+        let seek_handle = file.try_clone()?;
+        Self::start(Box::new(file), Some(seek_handle), seat)
+    }
+
+    pub fn to_stdout(seat: &str) -> io::Result<Self> {
+        Self::start(Box::new(io::stdout()), None, seat)
+    }
+
+    fn start(writer: Box<dyn Write>, file: Option<File>, seat: &str) -> io::Result<Self> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(&encode_header(seat, 0))?;
+        let flush_each = output::flush_policy(false) != FlushPolicy::Block;
+        Ok(BinaryWriter { writer, file, count: 0, flush_each })
+    }
+
+    pub fn write(&mut self, timed: &TimedEvent) -> io::Result<()> {
+        self.count = self.count.saturating_add(1);
+        self.writer.write_all(&encode_event(timed))?;
+        if self.flush_each {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    // Flush, and record the event count in a file's header
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        if let Some(mut file) = self.file.take() {
+            file.seek(SeekFrom::Start(COUNT_OFFSET))?;
+            file.write_all(&self.count.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+// True if the file starts like a `--format binary` recording
+pub fn has_magic(header: &[u8]) -> bool {
+    header.starts_with(MAGIC)
+}
+
+pub fn check_header(header: &[u8; HEADER_SIZE]) -> io::Result<()> {
+    if !has_magic(header) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a rustkey binary file (bad magic)"));
+    }
+    if header[4] != VERSION {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported format version {}", header[4])));
+    }
+    Ok(())
+}
+
+fn encode_header(seat: &str, count: u32) -> [u8; HEADER_SIZE] {
+    let mut header = [0u8; HEADER_SIZE];
+    header[0..4].copy_from_slice(MAGIC);
+    header[4] = VERSION;
+    let seat = seat.as_bytes();
+    let len = seat.len().min(10);
+    header[5..5 + len].copy_from_slice(&seat[..len]);
+    header[16..20].copy_from_slice(&count.to_le_bytes());
+    header
+}
+
+pub fn encode_event(timed: &TimedEvent) -> [u8; RECORD_SIZE] {
+    let mut kind = TYPE_OTHER;
+    let mut flags = 0u8;
+    let mut code = 0u16;
+    // x, y, dx, dy, scroll_x, scroll_y
+    let mut values = [0f32; 6];
+    let mut slot_value = 0u32;
+
+    match &timed.event {
+        InputEvent::Key { code: key, pressed } => {
+            kind = TYPE_KEY;
+            code = *key as u16;
+            flags = if *pressed { FLAG_PRESSED } else { 0 };
+        },
+        InputEvent::PointerButton { button, pressed } => {
+            kind = TYPE_BUTTON;
+            code = *button as u16;
+            flags = if *pressed { FLAG_PRESSED } else { 0 };
+        },
+        InputEvent::PointerMotion { dx, dy } => {
+            kind = TYPE_MOTION;
+            values[2] = *dx as f32;
+            values[3] = *dy as f32;
+        },
+        InputEvent::PointerMotionAbsolute { x, y } => {
+            kind = TYPE_MOTION_ABSOLUTE;
+            values[0] = *x as f32;
+            values[1] = *y as f32;
+        },
+        InputEvent::Scroll { source, horizontal, vertical } => {
+            kind = TYPE_SCROLL;
+            let source_bits = match source {
+                ScrollSource::Wheel => 0,
+                ScrollSource::Finger => 1,
+                ScrollSource::Continuous => 2,
+            };
+            flags = source_bits << SCROLL_SOURCE_SHIFT;
+            values[4] = *horizontal as f32;
+            values[5] = *vertical as f32;
+        },
+        // Device names don't fit the fixed record
+        InputEvent::DeviceAdded { .. } => kind = TYPE_DEVICE_ADDED,
+        InputEvent::DeviceRemoved { .. } => kind = TYPE_DEVICE_REMOVED,
+        InputEvent::Touch { kind: touch, slot, x, y } => {
+            kind = TYPE_TOUCH;
+            code = kind_index(TOUCH_KINDS, touch);
+            if let Some(slot) = slot {
+                flags |= FLAG_TOUCH_SLOT;
+                slot_value = *slot;
+            }
+            if let (Some(x), Some(y)) = (x, y) {
+                flags |= FLAG_TOUCH_POSITION;
+                values[0] = *x as f32;
+                values[1] = *y as f32;
+            }
+        },
+        InputEvent::Gesture { kind: gesture, .. } => {
+            kind = TYPE_GESTURE;
+            code = kind_index(GESTURE_KINDS, gesture);
+        },
+        InputEvent::TouchpadFingerCountChange { previous, count } => {
+            kind = TYPE_TOUCHPAD_FINGERS;
+            code = *count as u16;
+            slot_value = *previous;
+        },
+        InputEvent::Tablet => kind = TYPE_TABLET,
+        InputEvent::PadButton { button, pressed, mode, group } => {
+            kind = TYPE_PAD_BUTTON;
+            code = *button as u16;
+            flags = if *pressed { FLAG_PRESSED } else { 0 };
+            values[0] = *group as f32;
+            slot_value = *mode;
+        },
+        InputEvent::PadRing { number, position, mode } | InputEvent::PadStrip { number, position, mode } => {
+            kind = if matches!(timed.event, InputEvent::PadRing { .. }) { TYPE_PAD_RING } else { TYPE_PAD_STRIP };
+            code = *number as u16;
+            if let Some(position) = position {
+                flags |= FLAG_TOUCH_POSITION;
+                values[0] = *position as f32;
+            }
+            slot_value = *mode;
+        },
+        InputEvent::Switch => kind = TYPE_SWITCH,
+        InputEvent::Other { .. } => {},
+    }
+
+    let mut record = [0u8; RECORD_SIZE];
+    record[0] = kind;
+    record[1] = flags;
+    record[2..4].copy_from_slice(&code.to_le_bytes());
+    for (index, value) in values.iter().enumerate() {
+        let offset = 4 + index * 4;
+        record[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    record[28..32].copy_from_slice(&slot_value.to_le_bytes());
+    record[32..40].copy_from_slice(&timed.time_usec.to_le_bytes());
+    record
+}
+
+fn kind_index(kinds: &[&str], kind: &str) -> u16 {
+    kinds.iter().position(|known| *known == kind).unwrap_or(0) as u16
+}
+
+pub fn decode_event(record: &[u8; RECORD_SIZE]) -> io::Result<TimedEvent> {
+    let flags = record[1];
+    let code = u16::from_le_bytes([record[2], record[3]]) as u32;
+    let value = |index: usize| {
+        let offset = 4 + index * 4;
+        f32::from_le_bytes(record[offset..offset + 4].try_into().unwrap()) as f64
+    };
+    let pressed = flags & FLAG_PRESSED != 0;
+    let slot = || u32::from_le_bytes(record[28..32].try_into().unwrap());
+    let pad_position = || (flags & FLAG_TOUCH_POSITION != 0).then(|| value(0));
+
+    let event = match record[0] {
+        TYPE_KEY => InputEvent::Key { code, pressed },
+        TYPE_BUTTON => InputEvent::PointerButton { button: code, pressed },
+        TYPE_MOTION => InputEvent::PointerMotion { dx: value(2), dy: value(3) },
+        TYPE_MOTION_ABSOLUTE => InputEvent::PointerMotionAbsolute { x: value(0), y: value(1) },
+        TYPE_SCROLL => {
+            let source = match (flags >> SCROLL_SOURCE_SHIFT) & 0x03 {
+                0 => ScrollSource::Wheel,
+                1 => ScrollSource::Finger,
+                _ => ScrollSource::Continuous,
+            };
+            InputEvent::Scroll { source, horizontal: value(4), vertical: value(5) }
+        },
+        TYPE_DEVICE_ADDED => InputEvent::DeviceAdded { device: String::new(), info: None },
+        TYPE_DEVICE_REMOVED => InputEvent::DeviceRemoved { device: String::new(), info: None },
+        TYPE_TOUCH => {
+            let has_position = flags & FLAG_TOUCH_POSITION != 0;
+            InputEvent::Touch {
+                kind: kind_name(TOUCH_KINDS, code),
+                slot: (flags & FLAG_TOUCH_SLOT != 0).then(|| u32::from_le_bytes(record[28..32].try_into().unwrap())),
+                x: has_position.then(|| value(0)),
+                y: has_position.then(|| value(1)),
+            }
+        },
+        TYPE_GESTURE => InputEvent::Gesture {
+            kind: kind_name(GESTURE_KINDS, code),
+            finger_count: None,
+            dx: None,
+            dy: None,
+            scale: None,
+            angle: None,
+            cancelled: None,
+        },
+        TYPE_TOUCHPAD_FINGERS => InputEvent::TouchpadFingerCountChange {
+            previous: u32::from_le_bytes(record[28..32].try_into().unwrap()),
+            count: code,
+        },
+        TYPE_TABLET => InputEvent::Tablet,
+        TYPE_PAD_BUTTON => InputEvent::PadButton { button: code, pressed, mode: slot(), group: value(0) as u32 },
+        TYPE_PAD_RING => InputEvent::PadRing { number: code, position: pad_position(), mode: slot() },
+        TYPE_PAD_STRIP => InputEvent::PadStrip { number: code, position: pad_position(), mode: slot() },
+        TYPE_SWITCH => InputEvent::Switch,
+        TYPE_OTHER => InputEvent::Other { source: "event".to_string(), kind: "Other".to_string(), debug: None },
+        other => {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown event type {}", other)));
+        },
+    };
+    let time_usec = u64::from_le_bytes(record[32..40].try_into().unwrap());
+
+    Ok(TimedEvent { time_usec, event, fingers: None, sysname: None, seat: None })
+}
+
+fn kind_name(kinds: &[&str], index: u32) -> String {
+    kinds.get(index as usize).unwrap_or(&"Other").to_string()
+}
+
+// Convert a binary recording to `--format json` lines; returns the process exit code
+pub fn run_decode(options: &DecodeOptions) -> i32 {
+    match decode_file(options) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}error: cannot decode {}: {}{}", Colors::RED, options.input.display(), err, Colors::RESET);
+            1
+        },
+    }
+}
+
+fn decode_file(options: &DecodeOptions) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(&options.input)?);
+
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    check_header(&header)?;
+
+    let writer: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    let mut last_usec = None;
+    let mut first_usec = None;
+    let mut record = [0u8; RECORD_SIZE];
+    loop {
+        match reader.read_exact(&mut record) {
+            Ok(()) => {},
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err),
+        }
+        let timed = decode_event(&record)?;
+        let delta_usec = last_usec.map_or(0, |last: u64| timed.time_usec.saturating_sub(last));
+        last_usec = Some(timed.time_usec);
+        let elapsed_usec = timed.time_usec.saturating_sub(*first_usec.get_or_insert(timed.time_usec));
+
+        let line = JsonEvent { time_usec: timed.time_usec, delta_usec, elapsed_usec, event: &timed.event, fingers: None, seat: None, window: None, mapped_action: None };
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,288 @@
+use phf::phf_map;
+
+// Kernel BTN_* names from linux/input-event-codes.h, keyed by button code.
+// Where the header has aliases (BTN_SOUTH / BTN_A) the first is used, and
+// the range markers (BTN_MISC, BTN_MOUSE, ...) only work as reverse lookups.
+static BUTTON_NAMES: phf::Map<u32, &'static str> = phf_map! {
+    // Misc
+    0x100u32 => "BTN_0",
+    0x101u32 => "BTN_1",
+    0x102u32 => "BTN_2",
+    0x103u32 => "BTN_3",
+    0x104u32 => "BTN_4",
+    0x105u32 => "BTN_5",
+    0x106u32 => "BTN_6",
+    0x107u32 => "BTN_7",
+    0x108u32 => "BTN_8",
+    0x109u32 => "BTN_9",
+    // Mouse (272-279). The thumb buttons most mice label back and forward
+    // report BTN_SIDE (275) and BTN_EXTRA (276); BTN_FORWARD (277) and
+    // BTN_BACK (278) are separate codes that few mice use.
+    0x110u32 => "BTN_LEFT",
+    0x111u32 => "BTN_RIGHT",
+    0x112u32 => "BTN_MIDDLE",
+    0x113u32 => "BTN_SIDE",
+    0x114u32 => "BTN_EXTRA",
+    0x115u32 => "BTN_FORWARD",
+    0x116u32 => "BTN_BACK",
+    0x117u32 => "BTN_TASK",
+    // The header leaves 280-287 unnamed, but hid-input maps HID buttons 9-16
+    // of mice there (BTN_MOUSE + button - 1), so they're named by HID button
+    0x118u32 => "BTN_MOUSE_9",
+    0x119u32 => "BTN_MOUSE_10",
+    0x11au32 => "BTN_MOUSE_11",
+    0x11bu32 => "BTN_MOUSE_12",
+    0x11cu32 => "BTN_MOUSE_13",
+    0x11du32 => "BTN_MOUSE_14",
+    0x11eu32 => "BTN_MOUSE_15",
+    0x11fu32 => "BTN_MOUSE_16",
+    // Joystick
+    0x120u32 => "BTN_TRIGGER",
+    0x121u32 => "BTN_THUMB",
+    0x122u32 => "BTN_THUMB2",
+    0x123u32 => "BTN_TOP",
+    0x124u32 => "BTN_TOP2",
+    0x125u32 => "BTN_PINKIE",
+    0x126u32 => "BTN_BASE",
+    0x127u32 => "BTN_BASE2",
+    0x128u32 => "BTN_BASE3",
+    0x129u32 => "BTN_BASE4",
+    0x12au32 => "BTN_BASE5",
+    0x12bu32 => "BTN_BASE6",
+    0x12fu32 => "BTN_DEAD",
+    // Gamepad
+    0x130u32 => "BTN_SOUTH",
+    0x131u32 => "BTN_EAST",
+    0x132u32 => "BTN_C",
+    0x133u32 => "BTN_NORTH",
+    0x134u32 => "BTN_WEST",
+    0x135u32 => "BTN_Z",
+    0x136u32 => "BTN_TL",
+    0x137u32 => "BTN_TR",
+    0x138u32 => "BTN_TL2",
+    0x139u32 => "BTN_TR2",
+    0x13au32 => "BTN_SELECT",
+    0x13bu32 => "BTN_START",
+    0x13cu32 => "BTN_MODE",
+    0x13du32 => "BTN_THUMBL",
+    0x13eu32 => "BTN_THUMBR",
+    // Digitizer: tablet tools (BTN_TOOL_*) and stylus barrel buttons
+    0x140u32 => "BTN_TOOL_PEN",
+    0x141u32 => "BTN_TOOL_RUBBER",
+    0x142u32 => "BTN_TOOL_BRUSH",
+    0x143u32 => "BTN_TOOL_PENCIL",
+    0x144u32 => "BTN_TOOL_AIRBRUSH",
+    0x145u32 => "BTN_TOOL_FINGER",
+    0x146u32 => "BTN_TOOL_MOUSE",
+    0x147u32 => "BTN_TOOL_LENS",
+    0x148u32 => "BTN_TOOL_QUINTTAP",
+    0x149u32 => "BTN_STYLUS3",
+    0x14au32 => "BTN_TOUCH",
+    0x14bu32 => "BTN_STYLUS",
+    0x14cu32 => "BTN_STYLUS2",
+    0x14du32 => "BTN_TOOL_DOUBLETAP",
+    0x14eu32 => "BTN_TOOL_TRIPLETAP",
+    0x14fu32 => "BTN_TOOL_QUADTAP",
+    // Wheel
+    0x150u32 => "BTN_GEAR_DOWN",
+    0x151u32 => "BTN_GEAR_UP",
+    // D-pad
+    0x220u32 => "BTN_DPAD_UP",
+    0x221u32 => "BTN_DPAD_DOWN",
+    0x222u32 => "BTN_DPAD_LEFT",
+    0x223u32 => "BTN_DPAD_RIGHT",
+    // Grip buttons on the back of gamepads
+    0x224u32 => "BTN_GRIPL",
+    0x225u32 => "BTN_GRIPR",
+    0x226u32 => "BTN_GRIPL2",
+    0x227u32 => "BTN_GRIPR2",
+    // Trigger happy (extra buttons on gaming devices)
+    0x2c0u32 => "BTN_TRIGGER_HAPPY1",
+    0x2c1u32 => "BTN_TRIGGER_HAPPY2",
+    0x2c2u32 => "BTN_TRIGGER_HAPPY3",
+    0x2c3u32 => "BTN_TRIGGER_HAPPY4",
+    0x2c4u32 => "BTN_TRIGGER_HAPPY5",
+    0x2c5u32 => "BTN_TRIGGER_HAPPY6",
+    0x2c6u32 => "BTN_TRIGGER_HAPPY7",
+    0x2c7u32 => "BTN_TRIGGER_HAPPY8",
+    0x2c8u32 => "BTN_TRIGGER_HAPPY9",
+    0x2c9u32 => "BTN_TRIGGER_HAPPY10",
+    0x2cau32 => "BTN_TRIGGER_HAPPY11",
+    0x2cbu32 => "BTN_TRIGGER_HAPPY12",
+    0x2ccu32 => "BTN_TRIGGER_HAPPY13",
+    0x2cdu32 => "BTN_TRIGGER_HAPPY14",
+    0x2ceu32 => "BTN_TRIGGER_HAPPY15",
+    0x2cfu32 => "BTN_TRIGGER_HAPPY16",
+    0x2d0u32 => "BTN_TRIGGER_HAPPY17",
+    0x2d1u32 => "BTN_TRIGGER_HAPPY18",
+    0x2d2u32 => "BTN_TRIGGER_HAPPY19",
+    0x2d3u32 => "BTN_TRIGGER_HAPPY20",
+    0x2d4u32 => "BTN_TRIGGER_HAPPY21",
+    0x2d5u32 => "BTN_TRIGGER_HAPPY22",
+    0x2d6u32 => "BTN_TRIGGER_HAPPY23",
+    0x2d7u32 => "BTN_TRIGGER_HAPPY24",
+    0x2d8u32 => "BTN_TRIGGER_HAPPY25",
+    0x2d9u32 => "BTN_TRIGGER_HAPPY26",
+    0x2dau32 => "BTN_TRIGGER_HAPPY27",
+    0x2dbu32 => "BTN_TRIGGER_HAPPY28",
+    0x2dcu32 => "BTN_TRIGGER_HAPPY29",
+    0x2ddu32 => "BTN_TRIGGER_HAPPY30",
+    0x2deu32 => "BTN_TRIGGER_HAPPY31",
+    0x2dfu32 => "BTN_TRIGGER_HAPPY32",
+    0x2e0u32 => "BTN_TRIGGER_HAPPY33",
+    0x2e1u32 => "BTN_TRIGGER_HAPPY34",
+    0x2e2u32 => "BTN_TRIGGER_HAPPY35",
+    0x2e3u32 => "BTN_TRIGGER_HAPPY36",
+    0x2e4u32 => "BTN_TRIGGER_HAPPY37",
+    0x2e5u32 => "BTN_TRIGGER_HAPPY38",
+    0x2e6u32 => "BTN_TRIGGER_HAPPY39",
+    0x2e7u32 => "BTN_TRIGGER_HAPPY40",
+};
+
+// Every BTN_* name, aliases and range markers included, for the reverse lookup
+static BUTTON_CODES: phf::Map<&'static str, u32> = phf_map! {
+    "BTN_MISC" => 0x100,
+    "BTN_0" => 0x100,
+    "BTN_1" => 0x101,
+    "BTN_2" => 0x102,
+    "BTN_3" => 0x103,
+    "BTN_4" => 0x104,
+    "BTN_5" => 0x105,
+    "BTN_6" => 0x106,
+    "BTN_7" => 0x107,
+    "BTN_8" => 0x108,
+    "BTN_9" => 0x109,
+    "BTN_MOUSE" => 0x110,
+    "BTN_LEFT" => 0x110,
+    "BTN_RIGHT" => 0x111,
+    "BTN_MIDDLE" => 0x112,
+    "BTN_SIDE" => 0x113,
+    "BTN_EXTRA" => 0x114,
+    "BTN_FORWARD" => 0x115,
+    "BTN_BACK" => 0x116,
+    "BTN_TASK" => 0x117,
+    "BTN_MOUSE_9" => 0x118,
+    "BTN_MOUSE_10" => 0x119,
+    "BTN_MOUSE_11" => 0x11a,
+    "BTN_MOUSE_12" => 0x11b,
+    "BTN_MOUSE_13" => 0x11c,
+    "BTN_MOUSE_14" => 0x11d,
+    "BTN_MOUSE_15" => 0x11e,
+    "BTN_MOUSE_16" => 0x11f,
+    "BTN_JOYSTICK" => 0x120,
+    "BTN_TRIGGER" => 0x120,
+    "BTN_THUMB" => 0x121,
+    "BTN_THUMB2" => 0x122,
+    "BTN_TOP" => 0x123,
+    "BTN_TOP2" => 0x124,
+    "BTN_PINKIE" => 0x125,
+    "BTN_BASE" => 0x126,
+    "BTN_BASE2" => 0x127,
+    "BTN_BASE3" => 0x128,
+    "BTN_BASE4" => 0x129,
+    "BTN_BASE5" => 0x12a,
+    "BTN_BASE6" => 0x12b,
+    "BTN_DEAD" => 0x12f,
+    "BTN_GAMEPAD" => 0x130,
+    "BTN_SOUTH" => 0x130,
+    "BTN_A" => 0x130,
+    "BTN_EAST" => 0x131,
+    "BTN_B" => 0x131,
+    "BTN_C" => 0x132,
+    "BTN_NORTH" => 0x133,
+    "BTN_X" => 0x133,
+    "BTN_WEST" => 0x134,
+    "BTN_Y" => 0x134,
+    "BTN_Z" => 0x135,
+    "BTN_TL" => 0x136,
+    "BTN_TR" => 0x137,
+    "BTN_TL2" => 0x138,
+    "BTN_TR2" => 0x139,
+    "BTN_SELECT" => 0x13a,
+    "BTN_START" => 0x13b,
+    "BTN_MODE" => 0x13c,
+    "BTN_THUMBL" => 0x13d,
+    "BTN_THUMBR" => 0x13e,
+    "BTN_DIGI" => 0x140,
+    "BTN_TOOL_PEN" => 0x140,
+    "BTN_TOOL_RUBBER" => 0x141,
+    "BTN_TOOL_BRUSH" => 0x142,
+    "BTN_TOOL_PENCIL" => 0x143,
+    "BTN_TOOL_AIRBRUSH" => 0x144,
+    "BTN_TOOL_FINGER" => 0x145,
+    "BTN_TOOL_MOUSE" => 0x146,
+    "BTN_TOOL_LENS" => 0x147,
+    "BTN_TOOL_QUINTTAP" => 0x148,
+    "BTN_STYLUS3" => 0x149,
+    "BTN_TOUCH" => 0x14a,
+    "BTN_STYLUS" => 0x14b,
+    "BTN_STYLUS2" => 0x14c,
+    "BTN_TOOL_DOUBLETAP" => 0x14d,
+    "BTN_TOOL_TRIPLETAP" => 0x14e,
+    "BTN_TOOL_QUADTAP" => 0x14f,
+    "BTN_GEAR_DOWN" => 0x150,
+    "BTN_WHEEL" => 0x150,
+    "BTN_GEAR_UP" => 0x151,
+    "BTN_DPAD_UP" => 0x220,
+    "BTN_DPAD_DOWN" => 0x221,
+    "BTN_DPAD_LEFT" => 0x222,
+    "BTN_DPAD_RIGHT" => 0x223,
+    "BTN_GRIPL" => 0x224,
+    "BTN_GRIPR" => 0x225,
+    "BTN_GRIPL2" => 0x226,
+    "BTN_GRIPR2" => 0x227,
+    "BTN_TRIGGER_HAPPY" => 0x2c0,
+    "BTN_TRIGGER_HAPPY1" => 0x2c0,
+    "BTN_TRIGGER_HAPPY2" => 0x2c1,
+    "BTN_TRIGGER_HAPPY3" => 0x2c2,
+    "BTN_TRIGGER_HAPPY4" => 0x2c3,
+    "BTN_TRIGGER_HAPPY5" => 0x2c4,
+    "BTN_TRIGGER_HAPPY6" => 0x2c5,
+    "BTN_TRIGGER_HAPPY7" => 0x2c6,
+    "BTN_TRIGGER_HAPPY8" => 0x2c7,
+    "BTN_TRIGGER_HAPPY9" => 0x2c8,
+    "BTN_TRIGGER_HAPPY10" => 0x2c9,
+    "BTN_TRIGGER_HAPPY11" => 0x2ca,
+    "BTN_TRIGGER_HAPPY12" => 0x2cb,
+    "BTN_TRIGGER_HAPPY13" => 0x2cc,
+    "BTN_TRIGGER_HAPPY14" => 0x2cd,
+    "BTN_TRIGGER_HAPPY15" => 0x2ce,
+    "BTN_TRIGGER_HAPPY16" => 0x2cf,
+    "BTN_TRIGGER_HAPPY17" => 0x2d0,
+    "BTN_TRIGGER_HAPPY18" => 0x2d1,
+    "BTN_TRIGGER_HAPPY19" => 0x2d2,
+    "BTN_TRIGGER_HAPPY20" => 0x2d3,
+    "BTN_TRIGGER_HAPPY21" => 0x2d4,
+    "BTN_TRIGGER_HAPPY22" => 0x2d5,
+    "BTN_TRIGGER_HAPPY23" => 0x2d6,
+    "BTN_TRIGGER_HAPPY24" => 0x2d7,
+    "BTN_TRIGGER_HAPPY25" => 0x2d8,
+    "BTN_TRIGGER_HAPPY26" => 0x2d9,
+    "BTN_TRIGGER_HAPPY27" => 0x2da,
+    "BTN_TRIGGER_HAPPY28" => 0x2db,
+    "BTN_TRIGGER_HAPPY29" => 0x2dc,
+    "BTN_TRIGGER_HAPPY30" => 0x2dd,
+    "BTN_TRIGGER_HAPPY31" => 0x2de,
+    "BTN_TRIGGER_HAPPY32" => 0x2df,
+    "BTN_TRIGGER_HAPPY33" => 0x2e0,
+    "BTN_TRIGGER_HAPPY34" => 0x2e1,
+    "BTN_TRIGGER_HAPPY35" => 0x2e2,
+    "BTN_TRIGGER_HAPPY36" => 0x2e3,
+    "BTN_TRIGGER_HAPPY37" => 0x2e4,
+    "BTN_TRIGGER_HAPPY38" => 0x2e5,
+    "BTN_TRIGGER_HAPPY39" => 0x2e6,
+    "BTN_TRIGGER_HAPPY40" => 0x2e7,
+};
+
+// Kernel name for a button code, e.g. 272 -> "BTN_LEFT"
+pub fn button_name(btn_code: u32) -> &'static str {
+    BUTTON_NAMES.get(&btn_code).copied().unwrap_or("UNKNOWN BUTTON")
+}
+
+// Reverse of `button_name`. Case-insensitive, and the BTN_ prefix is optional ("left", "BTN_SOUTH").
+#[cfg_attr(not(feature = "simulation"), allow(dead_code))] // only --simulate looks buttons up by name
+pub fn button_code(name: &str) -> Option<u32> {
+    let upper = name.trim().to_uppercase();
+    let full = if upper.starts_with("BTN_") { upper } else { format!("BTN_{}", upper) };
+    BUTTON_CODES.get(full.as_str()).copied()
+}
@@ -0,0 +1,190 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread::{self, sleep};
+use std::time::Duration;
+
+use input::Libinput;
+use serde::{Deserialize, Serialize};
+
+use crate::event::{self, InputEvent};
+use crate::{Colors, Interface};
+
+// How far the user is asked to scroll / move during calibration
+const SCROLL_NOTCHES: f64 = 10.0;
+const POINTER_DISTANCE_CM: f64 = 10.0;
+const CM_PER_INCH: f64 = 2.54;
+
+pub struct CalibrateOptions {
+    pub output: PathBuf,
+    pub pointer: bool,
+}
+
+// Contents of a calibration file, e.g.
+//   [scroll]
+//   vertical_scale = 0.42
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Calibration {
+    pub scroll: Option<ScrollCalibration>,
+    pub pointer: Option<PointerCalibration>,
+}
+
+// Multiplying a raw scroll value by the scale gives wheel notches
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ScrollCalibration {
+    pub vertical_scale: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PointerCalibration {
+    pub counts_per_cm: f64,
+    pub dpi: f64,
+}
+
+impl Calibration {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        toml::from_str(&text).map_err(|err| err.to_string())
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string_pretty(self).map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| err.to_string())
+    }
+
+    // Raw scroll value converted to notches, if the scroll axis was calibrated
+    pub fn scroll_notches(&self, raw: f64) -> Option<f64> {
+        self.scroll.map(|scroll| raw * scroll.vertical_scale)
+    }
+
+    // Pointer motion converted to centimetres, if the pointer was calibrated
+    pub fn pointer_cm(&self, counts: f64) -> Option<f64> {
+        self.pointer.map(|pointer| counts / pointer.counts_per_cm)
+    }
+}
+
+// Interactive calibration; returns the process exit code
+pub fn run(options: &CalibrateOptions) -> i32 {
+    // Keep whatever an earlier run calibrated, so scroll and pointer can be done separately
+    let mut calibration = if options.output.exists() {
+        match Calibration::load(&options.output) {
+            Ok(existing) => existing,
+            Err(err) => {
+                eprintln!("{}error: cannot read {}: {}{}", Colors::RED, options.output.display(), err, Colors::RESET);
+                return 2;
+            }
+        }
+    } else {
+        Calibration::default()
+    };
+
+    let mut input = Libinput::new_with_udev(Interface);
+    if input.udev_assign_seat("seat0").is_err() {
+        eprintln!("{}error: cannot assign seat0{}", Colors::RED, Colors::RESET);
+        return 2;
+    }
+    // Throw away the initial device-added burst
+    drain(&mut input);
+
+    let stdin_lines = spawn_stdin_reader();
+
+    if options.pointer {
+        println!("{}{}🖱️  POINTER CALIBRATION{}", Colors::CYAN, Colors::BOLD, Colors::RESET);
+        println!("Put a ruler next to your mouse. Press {}Enter{}, move the mouse exactly {} cm",
+            Colors::YELLOW, Colors::RESET, POINTER_DISTANCE_CM);
+        println!("in a straight line, then press {}Enter{} again.", Colors::YELLOW, Colors::RESET);
+
+        let total = match capture(&mut input, &stdin_lines, |event| match event {
+            InputEvent::PointerMotion { dx, dy } => (dx * dx + dy * dy).sqrt(),
+            _ => 0.0,
+        }) {
+            Some(total) => total,
+            None => return 1,
+        };
+        if total <= 0.0 {
+            eprintln!("{}error: no pointer motion was captured{}", Colors::RED, Colors::RESET);
+            return 1;
+        }
+
+        let counts_per_cm = total / POINTER_DISTANCE_CM;
+        let dpi = counts_per_cm * CM_PER_INCH;
+        println!("{}Captured {:.0} counts → {:.1} counts/cm ({:.0} DPI){}",
+            Colors::GREEN, total, counts_per_cm, dpi, Colors::RESET);
+        calibration.pointer = Some(PointerCalibration { counts_per_cm, dpi });
+    } else {
+        println!("{}{}🖱️  SCROLL CALIBRATION{}", Colors::CYAN, Colors::BOLD, Colors::RESET);
+        println!("Press {}Enter{}, scroll up {} notches slowly (or the same distance on a touchpad),",
+            Colors::YELLOW, Colors::RESET, SCROLL_NOTCHES);
+        println!("then press {}Enter{} again.", Colors::YELLOW, Colors::RESET);
+
+        let total = match capture(&mut input, &stdin_lines, |event| match event {
+            InputEvent::Scroll { vertical, .. } => vertical.abs(),
+            _ => 0.0,
+        }) {
+            Some(total) => total,
+            None => return 1,
+        };
+        if total <= 0.0 {
+            eprintln!("{}error: no vertical scrolling was captured{}", Colors::RED, Colors::RESET);
+            return 1;
+        }
+
+        let vertical_scale = SCROLL_NOTCHES / total;
+        println!("{}Captured a total scroll value of {:.2} → vertical_scale = {:.4}{}",
+            Colors::GREEN, total, vertical_scale, Colors::RESET);
+        calibration.scroll = Some(ScrollCalibration { vertical_scale });
+    }
+
+    if let Err(err) = calibration.save(&options.output) {
+        eprintln!("{}error: cannot write {}: {}{}", Colors::RED, options.output.display(), err, Colors::RESET);
+        return 2;
+    }
+    println!("Calibration written to {} (load it with --calibration {})",
+        options.output.display(), options.output.display());
+    0
+}
+
+// Wait for Enter, sum `measure` over every event until the next Enter.
+// Returns None if stdin was closed.
+fn capture<F: Fn(&InputEvent) -> f64>(input: &mut Libinput, lines: &Receiver<()>, measure: F) -> Option<f64> {
+    print!("Ready? ");
+    io::stdout().flush().ok();
+    lines.recv().ok()?;
+    drain(input);
+    println!("{}Recording... press Enter when done{}", Colors::YELLOW, Colors::RESET);
+
+    let mut total = 0.0;
+    loop {
+        match lines.try_recv() {
+            Ok(()) => return Some(total),
+            Err(TryRecvError::Disconnected) => return None,
+            Err(TryRecvError::Empty) => {},
+        }
+
+        input.dispatch().ok()?;
+        for event in &mut *input {
+            total += measure(&event::decode(&event, event::monotonic_usec()).event);
+        }
+        sleep(Duration::from_millis(5));
+    }
+}
+
+fn drain(input: &mut Libinput) {
+    if input.dispatch().is_ok() {
+        for _ in &mut *input {}
+    }
+}
+
+// stdin blocks, so read it on its own thread and signal each Enter
+fn spawn_stdin_reader() -> Receiver<()> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if line.is_err() || sender.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
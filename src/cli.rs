@@ -0,0 +1,1244 @@
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::alert::AlertStyle;
+use crate::highlight::HighlightPattern;
+use crate::key_command::KeyBinding;
+use crate::pointer_constraint::PointerConstraint;
+use crate::template::TemplateEngine;
+use crate::theme::ColorTheme;
+use crate::binary::DecodeOptions;
+use crate::calibrate::CalibrateOptions;
+use crate::configure::{ConfigureOptions, PointerAccel};
+use crate::diff::DiffOptions;
+use crate::export::{ExportFormat, ExportOptions};
+use crate::keyboard_macro::{Hotkey, MacroOptions};
+use crate::key_repeat::SetRepeatOptions;
+use crate::keys;
+use crate::log_rotate::{self, Rotation};
+use crate::output::{FlushPolicy, OutputFormat};
+use crate::heatmap::{KeyboardLayout, QWERTY};
+use crate::idle;
+use crate::priority::{EventClass, EventPriorities};
+use crate::sqlite_log::QueryOptions;
+use crate::warmup::WarmupStart;
+
+// Decimals for coordinates, deltas and scroll values unless --precision says otherwise
+const DEFAULT_PRECISION: usize = 2;
+const MAX_PRECISION: usize = 9;
+
+// What the program was asked to do
+pub enum Command {
+    Monitor(Box<Options>),
+    Diff(DiffOptions),
+    Calibrate(CalibrateOptions),
+    ConfigureDevice(ConfigureOptions),
+    Decode(DecodeOptions),
+    Query(QueryOptions),
+    RecordMacro(MacroOptions),
+    Export(ExportOptions),
+    ShowRepeatConfig,
+    SetRepeat(SetRepeatOptions),
+}
+
+// Parse the subcommand (if any) and its options from the process arguments
+pub fn command_from_args() -> Result<Command, String> {
+    let mut args = env::args().skip(1).peekable();
+
+    match args.peek().map(String::as_str) {
+        Some("diff") => {
+            args.next();
+            parse_diff(args).map(Command::Diff)
+        },
+        Some("calibrate") => {
+            args.next();
+            parse_calibrate(args).map(Command::Calibrate)
+        },
+        Some("configure-device") => {
+            args.next();
+            parse_configure(args).map(Command::ConfigureDevice)
+        },
+        Some("decode") => {
+            args.next();
+            parse_decode(args).map(Command::Decode)
+        },
+        Some("query") => {
+            args.next();
+            parse_query(args).map(Command::Query)
+        },
+        Some("export") => {
+            args.next();
+            parse_export(args).map(Command::Export)
+        },
+        Some("show-repeat-config") => {
+            args.next();
+            match args.next() {
+                Some(flag) if flag == "-h" || flag == "--help" => {
+                    print_usage();
+                    std::process::exit(0);
+                },
+                Some(other) => Err(format!("unknown option '{}'", other)),
+                None => Ok(Command::ShowRepeatConfig),
+            }
+        },
+        Some("set-repeat") => {
+            args.next();
+            parse_set_repeat(args).map(Command::SetRepeat)
+        },
+        Some("record-macro") => {
+            args.next();
+            parse_record_macro(args).map(Command::RecordMacro)
+        },
+        _ => Options::parse(args).map(|options| Command::Monitor(Box::new(options))),
+    }
+}
+
+fn parse_diff<I: Iterator<Item = String>>(mut args: I) -> Result<DiffOptions, String> {
+    let mut files = Vec::new();
+    let mut timestamp_tolerance_ms = 50;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--timestamp-tolerance-ms" => timestamp_tolerance_ms = parse_value(&mut args, &arg)?,
+            "--json" => json = true,
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            file => files.push(PathBuf::from(file)),
+        }
+    }
+
+    if files.len() != 2 {
+        return Err("diff expects exactly two recording files".to_string());
+    }
+    let file_b = files.pop().unwrap();
+    let file_a = files.pop().unwrap();
+
+    Ok(DiffOptions { file_a, file_b, timestamp_tolerance_ms, json })
+}
+
+fn parse_decode<I: Iterator<Item = String>>(mut args: I) -> Result<DecodeOptions, String> {
+    let mut input = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            file if input.is_none() => input = Some(PathBuf::from(file)),
+            _ => return Err("decode expects a single binary file".to_string()),
+        }
+    }
+
+    let input = input.ok_or_else(|| "decode expects a binary file".to_string())?;
+    Ok(DecodeOptions { input, output })
+}
+
+fn parse_query<I: Iterator<Item = String>>(args: I) -> Result<QueryOptions, String> {
+    let mut positional = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let database = PathBuf::from(positional.next().ok_or_else(|| "query expects a database file".to_string())?);
+    let filter = positional.next();
+    if positional.next().is_some() {
+        return Err("query expects the WHERE clause as a single (quoted) argument".to_string());
+    }
+    Ok(QueryOptions { database, filter })
+}
+
+fn parse_export<I: Iterator<Item = String>>(mut args: I) -> Result<ExportOptions, String> {
+    let mut format = None;
+    let mut inputs = Vec::new();
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = next_value(&mut args, &arg)?;
+                format = Some(ExportFormat::parse(&value)
+                    .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected keylogger-stats)", value, arg))?);
+            },
+            "--input" => inputs.push(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--output" => output = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    let format = format.ok_or_else(|| "export needs --format keylogger-stats".to_string())?;
+    if inputs.is_empty() {
+        return Err("export needs at least one --input file".to_string());
+    }
+    Ok(ExportOptions { format, inputs, output })
+}
+
+fn parse_record_macro<I: Iterator<Item = String>>(mut args: I) -> Result<MacroOptions, String> {
+    let mut output = PathBuf::from("macro.sh");
+    let mut trigger = Hotkey::parse("Ctrl+F9")?;
+    let mut stop = Hotkey::parse("Ctrl+F10")?;
+    let mut ignore_timing = false;
+    let mut replay = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = PathBuf::from(next_value(&mut args, &arg)?),
+            "--trigger" => {
+                trigger = Hotkey::parse(&next_value(&mut args, &arg)?)
+                    .map_err(|err| format!("invalid value for option '{}': {}", arg, err))?;
+            },
+            "--stop" => {
+                stop = Hotkey::parse(&next_value(&mut args, &arg)?)
+                    .map_err(|err| format!("invalid value for option '{}': {}", arg, err))?;
+            },
+            "--ignore-timing" => ignore_timing = true,
+            "--replay" => replay = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    if trigger == stop {
+        return Err("--trigger and --stop must be different hotkeys".to_string());
+    }
+    Ok(MacroOptions { output, trigger, stop, ignore_timing, replay })
+}
+
+fn parse_calibrate<I: Iterator<Item = String>>(mut args: I) -> Result<CalibrateOptions, String> {
+    let mut output = PathBuf::from("calibration.toml");
+    let mut pointer = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = PathBuf::from(next_value(&mut args, &arg)?),
+            "--pointer" => pointer = true,
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    Ok(CalibrateOptions { output, pointer })
+}
+
+fn parse_configure<I: Iterator<Item = String>>(mut args: I) -> Result<ConfigureOptions, String> {
+    let mut options = ConfigureOptions {
+        device: None,
+        accel_speed: None,
+        pointer_accel: None,
+        natural_scroll: None,
+        tap_to_click: None,
+        dpi: None,
+        persist: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--device" => options.device = Some(next_value(&mut args, &arg)?),
+            "--accel-speed" => {
+                let speed: f64 = parse_value(&mut args, &arg)?;
+                if !(-1.0..=1.0).contains(&speed) {
+                    return Err(format!("option '{}' must be between -1.0 and 1.0", arg));
+                }
+                options.accel_speed = Some(speed);
+            },
+            "--pointer-accel" => {
+                let value = next_value(&mut args, &arg)?;
+                options.pointer_accel = Some(PointerAccel::parse(&value)
+                    .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected flat, adaptive or none)", value, arg))?);
+            },
+            "--natural-scroll" => options.natural_scroll = Some(parse_on_off(&mut args, &arg)?),
+            "--tap-to-click" => options.tap_to_click = Some(parse_on_off(&mut args, &arg)?),
+            "--dpi" => options.dpi = Some(parse_value(&mut args, &arg)?),
+            "--persist" => options.persist = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    if options.pointer_accel == Some(PointerAccel::None) && options.accel_speed.is_some() {
+        return Err("--pointer-accel none already fixes the speed; drop --accel-speed".to_string());
+    }
+    if options.device.is_none() && options.has_changes() {
+        return Err("configure-device needs --device <name> to change settings".to_string());
+    }
+
+    Ok(options)
+}
+
+fn parse_set_repeat<I: Iterator<Item = String>>(mut args: I) -> Result<SetRepeatOptions, String> {
+    let mut device = None;
+    let mut delay_ms = None;
+    let mut rate = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--device" => device = Some(next_value(&mut args, &arg)?),
+            "--delay" => {
+                let ms: u32 = parse_value(&mut args, &arg)?;
+                if !(1..=1000).contains(&ms) {
+                    return Err(format!("option '{}' must be between 1 and 1000 ms", arg));
+                }
+                delay_ms = Some(ms);
+            },
+            "--rate" => {
+                let keys_per_sec: u32 = parse_value(&mut args, &arg)?;
+                if !(1..=250).contains(&keys_per_sec) {
+                    return Err(format!("option '{}' must be between 1 and 250 keys per second", arg));
+                }
+                rate = Some(keys_per_sec);
+            },
+            "-h" | "--help" => {
+                print_usage();
+                std::process::exit(0);
+            },
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    Ok(SetRepeatOptions {
+        device: device.ok_or_else(|| "set-repeat needs --device <name>".to_string())?,
+        delay_ms: delay_ms.ok_or_else(|| "set-repeat needs --delay <ms>".to_string())?,
+        rate: rate.ok_or_else(|| "set-repeat needs --rate <keys_per_sec>".to_string())?,
+    })
+}
+
+// Command line options for the monitor
+pub struct Options {
+    pub health_export: Option<PathBuf>,
+    pub idle_threshold: Duration,
+    pub session_gap: Duration,
+    pub histogram_interval: Option<Duration>,
+    pub warmup: Option<Duration>,
+    pub warmup_from: WarmupStart,
+    pub warmup_show: bool,
+    // Draw a key press heatmap in this layout in the summary
+    pub heatmap: Option<&'static KeyboardLayout>,
+    // Draw a live keyboard in place of event lines, in this layout
+    pub kbd_view: bool,
+    pub rollover_test: bool,
+    pub all_seats: bool,
+    pub layout: &'static KeyboardLayout,
+    pub timeout_idle: Option<Duration>,
+    pub timeout_idle_overrides: Vec<(EventClass, Duration)>,
+    pub record: Option<PathBuf>,
+    pub log_sqlite: Option<PathBuf>,
+    // Watch only these devices (libinput path backend) instead of the whole seat
+    pub device_paths: Vec<PathBuf>,
+    pub exit_if_empty: bool,
+    pub max_events: Option<u64>,
+    // Event types to keep, globally and per device name
+    pub filter_config: Option<PathBuf>,
+    // --one-shot [type]: print the first matching event and exit
+    pub one_shot: bool,
+    pub one_shot_class: Option<EventClass>,
+    // How long --one-shot waits before giving up
+    pub timeout: Option<Duration>,
+    // Cap on printed event lines per second (--max-lines-per-sec)
+    pub max_lines_per_sec: Option<u32>,
+    pub alert_keys: Vec<u32>,
+    pub alert_style: AlertStyle,
+    // Shell commands to run on key presses (--on-key)
+    pub key_bindings: Vec<KeyBinding>,
+    // Display labels for key codes (--alias), applied after --alias-file
+    pub key_aliases: Vec<(u32, String)>,
+    pub alias_file: Option<PathBuf>,
+    pub benchmark: bool,
+    pub benchmark_duration: Duration,
+    pub syslog: bool,
+    pub journal: bool,
+    pub syslog_ident: String,
+    pub log_file: Option<PathBuf>,
+    pub log_max_size_mb: Option<u64>,
+    pub log_rotate_daily: bool,
+    pub log_compress: bool,
+    pub log_keep_files: Option<usize>,
+    pub summary_out: Option<PathBuf>,
+    pub summary_append: bool,
+    pub output_fifo: Option<PathBuf>,
+    pub fifo_wait_for_reader: bool,
+    pub daemon: bool,
+    pub pid_file: Option<PathBuf>,
+    pub history: usize,
+    pub event_priorities: EventPriorities,
+    pub calibration: Option<PathBuf>,
+    pub probe_permissions: bool,
+    pub scroll_debounce: Duration,
+    pub dedup_window: Duration,
+    // Print runs of identical events as a count (--coalesce)
+    pub coalesce: bool,
+    pub raw_scroll: bool,
+    // Plot libinput's pointer acceleration instead of the event lines
+    pub show_accel_curve: bool,
+    // Replace wheel scrolling with decaying glides (--smooth-scroll)
+    pub smooth_scroll: bool,
+    pub scroll_friction: Option<f64>,
+    pub smooth_scroll_rate: Option<u32>,
+    pub show_codes: bool,
+    pub replay: Option<PathBuf>,
+    // Scenario of synthetic events to play instead of live input
+    pub simulate: Option<PathBuf>,
+    pub follow_cursor: bool,
+    pub record_mouse_path: Option<PathBuf>,
+    pub path_simplify_epsilon: Option<f64>,
+    // Gesture-to-action mappings on top of the built-in ones
+    pub gesture_map: Option<PathBuf>,
+    // Detected from the primary output when not given (see screen_size)
+    pub screen: Option<(u32, u32)>,
+    pub grid_size: u32,
+    pub precision: usize,
+    pub xkb: bool,
+    pub format: OutputFormat,
+    // Destination of `--format binary` (stdout if unset)
+    pub output: Option<PathBuf>,
+    // None: decide from the locale and terminal
+    pub emoji: Option<bool>,
+    pub no_banner: bool,
+    pub color_theme: ColorTheme,
+    // Colors for key press lines per key category
+    pub key_colors: Option<PathBuf>,
+    // --no-color: no escape sequences at all
+    pub no_color: bool,
+    pub banner_text: Option<String>,
+    pub legend: bool,
+    pub status_line: bool,
+    pub debug_unknown: bool,
+    // Hide typed characters and device names in every output
+    pub sanitize: bool,
+    // Tag events with the focused window's title, sampled this often
+    // (default 500 ms)
+    pub correlate_window: bool,
+    pub window_sample: Option<Duration>,
+    // Number of -v flags; raises which libinput messages are shown
+    pub verbosity: u8,
+    // Announce key presses (and mouse buttons) through espeak-ng
+    pub screen_reader: bool,
+    pub speak_mouse_buttons: bool,
+    pub speech_rate: Option<u32>,
+    pub highlight_patterns: Vec<HighlightPattern>,
+    // Replaces the event lines (--output-template)
+    pub output_template: Option<TemplateEngine>,
+    // Print the held modifiers whenever they change
+    pub show_modifiers: bool,
+    pub detect_ghosting: bool,
+    pub classify_presses: bool,
+    // Presses held this long are long presses (--long-press-threshold)
+    pub long_press_threshold: Option<Duration>,
+    // Annotate motion that a confinement region would have stopped
+    pub constrain_pointer: Option<PointerConstraint>,
+    // None: line buffering on a terminal, block buffering elsewhere
+    pub flush: Option<FlushPolicy>,
+    // Step through the --replay recording one event at a time
+    pub interactive: bool,
+    pub selftest: bool,
+    // Inject probe key presses through uinput and time their return
+    pub measure_latency: bool,
+    // Play a recording back as real input; needs --confirm-inject
+    pub replay_into_uinput: Option<PathBuf>,
+    pub confirm_inject: bool,
+    pub grab: bool,
+    pub confirm_grab: bool,
+    // Also print the kernel's input_event structs of one event node
+    pub raw_evdev: bool,
+    pub raw_evdev_device: Option<PathBuf>,
+}
+
+impl Options {
+    fn default() -> Self {
+        Options {
+            health_export: None,
+            idle_threshold: Duration::from_secs(10),
+            session_gap: Duration::from_secs(300),
+            histogram_interval: None,
+            warmup: None,
+            warmup_from: WarmupStart::Startup,
+            warmup_show: false,
+            heatmap: None,
+            kbd_view: false,
+            rollover_test: false,
+            all_seats: false,
+            layout: &QWERTY,
+            timeout_idle: None,
+            timeout_idle_overrides: Vec::new(),
+            record: None,
+            log_sqlite: None,
+            device_paths: Vec::new(),
+            exit_if_empty: false,
+            max_events: None,
+            filter_config: None,
+            one_shot: false,
+            one_shot_class: None,
+            timeout: None,
+            max_lines_per_sec: None,
+            alert_keys: Vec::new(),
+            alert_style: AlertStyle::Both,
+            key_bindings: Vec::new(),
+            key_aliases: Vec::new(),
+            alias_file: None,
+            benchmark: false,
+            benchmark_duration: Duration::from_secs(10),
+            syslog: false,
+            journal: false,
+            syslog_ident: "rustkey".to_string(),
+            log_file: None,
+            log_max_size_mb: None,
+            log_rotate_daily: false,
+            log_compress: false,
+            log_keep_files: None,
+            summary_out: None,
+            summary_append: false,
+            output_fifo: None,
+            fifo_wait_for_reader: false,
+            daemon: false,
+            pid_file: None,
+            history: 100,
+            event_priorities: EventPriorities::default(),
+            calibration: None,
+            probe_permissions: false,
+            scroll_debounce: Duration::from_millis(150),
+            dedup_window: Duration::from_millis(200),
+            coalesce: false,
+            raw_scroll: false,
+            show_accel_curve: false,
+            smooth_scroll: false,
+            scroll_friction: None,
+            smooth_scroll_rate: None,
+            show_codes: false,
+            replay: None,
+            simulate: None,
+            follow_cursor: false,
+            record_mouse_path: None,
+            path_simplify_epsilon: None,
+            gesture_map: None,
+            screen: None,
+            grid_size: 3,
+            precision: DEFAULT_PRECISION,
+            xkb: false,
+            format: OutputFormat::Text,
+            output: None,
+            emoji: None,
+            no_banner: false,
+            color_theme: ColorTheme::Default,
+            key_colors: None,
+            no_color: false,
+            banner_text: None,
+            legend: false,
+            status_line: false,
+            debug_unknown: false,
+            sanitize: false,
+            correlate_window: false,
+            window_sample: None,
+            verbosity: 0,
+            screen_reader: false,
+            speak_mouse_buttons: false,
+            speech_rate: None,
+            highlight_patterns: Vec::new(),
+            output_template: None,
+            constrain_pointer: None,
+            show_modifiers: false,
+            detect_ghosting: false,
+            classify_presses: false,
+            long_press_threshold: None,
+            flush: None,
+            interactive: false,
+            selftest: false,
+            measure_latency: false,
+            replay_into_uinput: None,
+            confirm_inject: false,
+            grab: false,
+            confirm_grab: false,
+            raw_evdev: false,
+            raw_evdev_device: None,
+        }
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        // For flags whose value is optional, like `--one-shot [type]`
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--health-export" => {
+                    options.health_export = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--idle-threshold" => options.idle_threshold = parse_seconds(&mut args, &arg)?,
+                "--session-gap" => options.session_gap = parse_seconds(&mut args, &arg)?,
+                "--histogram-interval" => options.histogram_interval = Some(parse_seconds(&mut args, &arg)?),
+                "--warmup" => options.warmup = Some(parse_seconds(&mut args, &arg)?),
+                "--warmup-from" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.warmup_from = WarmupStart::parse(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected startup or first-event)", value, arg))?;
+                },
+                "--warmup-show" => options.warmup_show = true,
+                "--kbd-view" => options.kbd_view = true,
+                "--rollover-test" => options.rollover_test = true,
+                "--all-seats" => options.all_seats = true,
+                "--layout" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.layout = KeyboardLayout::by_name(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected qwerty, qwertz or azerty)", value, arg))?;
+                },
+                "--heatmap" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.heatmap = Some(KeyboardLayout::by_name(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected qwerty, qwertz or azerty)", value, arg))?);
+                },
+                "--timeout-idle" => options.timeout_idle = Some(parse_seconds(&mut args, &arg)?),
+                "--timeout-idle-for" => {
+                    let spec = next_value(&mut args, &arg)?;
+                    options.timeout_idle_overrides.push(idle::parse_override(&spec)
+                        .map_err(|err| format!("invalid value for option '{}': {}", arg, err))?);
+                },
+                "--benchmark" => options.benchmark = true,
+                "--syslog" => options.syslog = true,
+                "--journal" => options.journal = true,
+                "--syslog-ident" => options.syslog_ident = next_value(&mut args, &arg)?,
+                "--probe-permissions" => options.probe_permissions = true,
+                "--selftest" => options.selftest = true,
+                "--measure-latency" => options.measure_latency = true,
+                "--replay-into-uinput" => {
+                    options.replay_into_uinput = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--confirm-inject" => options.confirm_inject = true,
+                "--grab" => options.grab = true,
+                "--confirm-grab" => options.confirm_grab = true,
+                "--scroll-debounce" => {
+                    options.scroll_debounce = Duration::from_millis(parse_value(&mut args, &arg)?);
+                },
+                "--raw-scroll" => options.raw_scroll = true,
+                "--smooth-scroll" => options.smooth_scroll = true,
+                "--show-accel-curve" => options.show_accel_curve = true,
+                "--scroll-friction" => {
+                    let friction: f64 = parse_value(&mut args, &arg)?;
+                    if !(0.0..=1.0).contains(&friction) {
+                        return Err(format!("option '{}' must be between 0.0 and 1.0", arg));
+                    }
+                    options.scroll_friction = Some(friction);
+                },
+                "--smooth-scroll-rate" => {
+                    let rate: u32 = parse_value(&mut args, &arg)?;
+                    if !(1..=1000).contains(&rate) {
+                        return Err(format!("option '{}' must be between 1 and 1000", arg));
+                    }
+                    options.smooth_scroll_rate = Some(rate);
+                },
+                "--dedup-window-ms" => {
+                    options.dedup_window = Duration::from_millis(parse_value(&mut args, &arg)?);
+                },
+                "--coalesce" => options.coalesce = true,
+                "--show-codes" => options.show_codes = true,
+                "--follow-cursor" => options.follow_cursor = true,
+                "--gesture-map" => {
+                    options.gesture_map = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--record-mouse-path" => {
+                    options.record_mouse_path = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--path-simplify-epsilon" => {
+                    let epsilon: f64 = parse_value(&mut args, &arg)?;
+                    if epsilon < 0.0 || !epsilon.is_finite() {
+                        return Err(format!("option '{}' must be a non-negative number of pixels", arg));
+                    }
+                    options.path_simplify_epsilon = Some(epsilon);
+                },
+                "--screen" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.screen = Some(parse_dimensions(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected WxH, e.g. 2560x1440)", value, arg))?);
+                },
+                "--grid-size" => {
+                    options.grid_size = parse_value(&mut args, &arg)?;
+                    if options.grid_size != 3 && options.grid_size != 5 {
+                        return Err(format!("option '{}' must be 3 or 5", arg));
+                    }
+                },
+                "--xkb" => options.xkb = true,
+                "--precision" => {
+                    options.precision = parse_value(&mut args, &arg)?;
+                    if options.precision > MAX_PRECISION {
+                        return Err(format!("option '{}' must be at most {}", arg, MAX_PRECISION));
+                    }
+                },
+                "--log-file" => {
+                    options.log_file = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--log-max-size-mb" => options.log_max_size_mb = Some(parse_value(&mut args, &arg)?),
+                "--log-rotate-daily" => options.log_rotate_daily = true,
+                "--log-compress" => options.log_compress = true,
+                "--log-keep-files" => options.log_keep_files = Some(parse_value(&mut args, &arg)?),
+                "--summary-out" => {
+                    options.summary_out = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--summary-append" => options.summary_append = true,
+                "--output-fifo" => {
+                    options.output_fifo = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--fifo-wait-for-reader" => options.fifo_wait_for_reader = true,
+                "--daemon" => options.daemon = true,
+                "--history" => options.history = parse_value(&mut args, &arg)?,
+                "--pid-file" => {
+                    options.pid_file = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--calibration" => {
+                    options.calibration = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--benchmark-duration" => options.benchmark_duration = parse_seconds(&mut args, &arg)?,
+                "--simulate" => options.simulate = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "--replay" => {
+                    options.replay = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--interactive" => options.interactive = true,
+                "--record" => {
+                    options.record = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--device-path" => {
+                    options.device_paths.push(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--raw-evdev" => options.raw_evdev = true,
+                "--raw-evdev-device" => {
+                    options.raw_evdev_device = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--exit-if-empty" => options.exit_if_empty = true,
+                "--filter-config" => options.filter_config = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "--max-events" => {
+                    let max: u64 = parse_value(&mut args, &arg)?;
+                    if max == 0 {
+                        return Err(format!("option '{}' must be at least 1", arg));
+                    }
+                    options.max_events = Some(max);
+                },
+                "--one-shot" => {
+                    options.one_shot = true;
+                    // Only a known type name is taken as the flag's value
+                    if let Some(class) = args.peek().and_then(|next| EventClass::parse(next)) {
+                        options.one_shot_class = Some(class);
+                        args.next();
+                    }
+                },
+                "--timeout" => options.timeout = Some(parse_seconds(&mut args, &arg)?),
+                "--max-lines-per-sec" => {
+                    let max: u32 = parse_value(&mut args, &arg)?;
+                    if max == 0 {
+                        return Err(format!("option '{}' must be at least 1", arg));
+                    }
+                    options.max_lines_per_sec = Some(max);
+                },
+                "--log-sqlite" => {
+                    options.log_sqlite = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--alert-key" => {
+                    let name = next_value(&mut args, &arg)?;
+                    let code = keys::key_code(&name).ok_or_else(|| format!("unknown key '{}' for option '{}'", name, arg))?;
+                    options.alert_keys.push(code);
+                },
+                "--on-key" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.key_bindings.push(KeyBinding::parse(&value)
+                        .map_err(|err| format!("invalid value '{}' for option '{}': {}", value, arg, err))?);
+                },
+                "--alias" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.key_aliases.push(keys::parse_alias(&value)
+                        .map_err(|err| format!("invalid value '{}' for option '{}': {}", value, arg, err))?);
+                },
+                "--alias-file" => options.alias_file = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "--highlight-pattern" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.highlight_patterns.push(HighlightPattern::parse(&value)
+                        .map_err(|err| format!("invalid value '{}' for option '{}': {}", value, arg, err))?);
+                },
+                "--constrain-pointer" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.constrain_pointer = Some(PointerConstraint::parse(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected x1,y1,x2,y2, e.g. 100,100,900,700)", value, arg))?);
+                },
+                "--event-priority" => {
+                    let spec = next_value(&mut args, &arg)?;
+                    options.event_priorities.apply_override(&spec)
+                        .map_err(|err| format!("invalid value for option '{}': {}", arg, err))?;
+                },
+                "--no-emoji" => options.emoji = Some(false),
+                "--no-color" => options.no_color = true,
+                "--color-theme" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.color_theme = ColorTheme::parse(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected one of: {})", value, arg, ColorTheme::NAMES))?;
+                },
+                "--key-colors" => options.key_colors = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "--emoji" => options.emoji = Some(true),
+                "--no-banner" => options.no_banner = true,
+                "--banner-text" => options.banner_text = Some(next_value(&mut args, &arg)?),
+                "--legend" => options.legend = true,
+                "--status-line" => options.status_line = true,
+                "--debug-unknown" => options.debug_unknown = true,
+                "--sanitize" => options.sanitize = true,
+                "--show-modifiers" => options.show_modifiers = true,
+                "--detect-ghosting" => options.detect_ghosting = true,
+                "--classify-presses" => options.classify_presses = true,
+                "--long-press-threshold" => {
+                    let ms: u64 = parse_value(&mut args, &arg)?;
+                    if ms == 0 {
+                        return Err(format!("option '{}' must be at least 1", arg));
+                    }
+                    options.long_press_threshold = Some(Duration::from_millis(ms));
+                },
+                "-v" | "--verbose" => options.verbosity = options.verbosity.saturating_add(1),
+                "-vv" => options.verbosity = options.verbosity.saturating_add(2),
+                "--correlate-window" => options.correlate_window = true,
+                "--screen-reader" => options.screen_reader = true,
+                "--speak-mouse-buttons" => options.speak_mouse_buttons = true,
+                "--speech-rate" => {
+                    let rate: u32 = parse_value(&mut args, &arg)?;
+                    if rate == 0 {
+                        return Err(format!("option '{}' must be at least 1", arg));
+                    }
+                    options.speech_rate = Some(rate);
+                },
+                "--window-sample-ms" => {
+                    let ms: u64 = parse_value(&mut args, &arg)?;
+                    if ms == 0 {
+                        return Err(format!("option '{}' must be at least 1", arg));
+                    }
+                    options.window_sample = Some(Duration::from_millis(ms));
+                },
+                "--format" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.format = OutputFormat::parse(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected text, json, binary, msgpack, libinput-debug or evtest)", value, arg))?;
+                },
+                "--output-template" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.output_template = Some(TemplateEngine::parse(&value)
+                        .map_err(|err| format!("invalid value for option '{}': {}", arg, err))?);
+                },
+                "--flush" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.flush = Some(FlushPolicy::parse(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected immediate, line or block)", value, arg))?);
+                },
+                "--output" => {
+                    options.output = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                },
+                "--alert-style" => {
+                    let value = next_value(&mut args, &arg)?;
+                    options.alert_style = AlertStyle::parse(&value)
+                        .ok_or_else(|| format!("invalid value '{}' for option '{}' (expected bell, flash or both)", value, arg))?;
+                },
+                "-h" | "--help" => {
+                    print_usage();
+                    std::process::exit(0);
+                },
+                other => return Err(format!("unknown option '{}'", other)),
+            }
+        }
+
+        let destinations = [options.syslog, options.journal, options.log_file.is_some(), options.output_fifo.is_some()];
+        if destinations.iter().filter(|&&chosen| chosen).count() > 1 {
+            return Err("--syslog, --journal, --log-file and --output-fifo cannot be combined".to_string());
+        }
+        if (options.warmup_from != WarmupStart::Startup || options.warmup_show) && options.warmup.is_none() {
+            return Err("--warmup-from and --warmup-show are only used with --warmup".to_string());
+        }
+        let rotation_flags = options.log_max_size_mb.is_some() || options.log_rotate_daily || options.log_compress || options.log_keep_files.is_some();
+        if rotation_flags && options.log_file.is_none() {
+            return Err("--log-max-size-mb, --log-rotate-daily, --log-compress and --log-keep-files are only used with --log-file".to_string());
+        }
+        if options.path_simplify_epsilon.is_some() && options.record_mouse_path.is_none() {
+            return Err("--path-simplify-epsilon is only used with --record-mouse-path".to_string());
+        }
+        if options.summary_append && options.summary_out.is_none() {
+            return Err("--summary-append is only used with --summary-out".to_string());
+        }
+        if options.fifo_wait_for_reader && options.output_fifo.is_none() {
+            return Err("--fifo-wait-for-reader is only used with --output-fifo".to_string());
+        }
+        // A detached process has no terminal to print to
+        if options.daemon && !destinations.contains(&true) && options.output.is_none() {
+            return Err("--daemon needs --syslog, --journal, --log-file or --output-fifo; refusing to detach while output goes to the terminal".to_string());
+        }
+        if options.output_template.is_some() && (options.format != OutputFormat::Text || options.status_line || options.kbd_view) {
+            return Err("--output-template replaces the text event lines; it cannot be combined with --format, --status-line or --kbd-view".to_string());
+        }
+        let binary_format = matches!(options.format, OutputFormat::Binary | OutputFormat::Msgpack);
+        if options.output.is_some() && !binary_format {
+            return Err("--output is only used with --format binary or msgpack".to_string());
+        }
+        if binary_format && destinations.contains(&true) {
+            return Err("--format binary and msgpack write to stdout or --output, not to --syslog, --journal, --log-file or --output-fifo".to_string());
+        }
+        if !options.device_paths.is_empty() && (options.benchmark || options.replay.is_some()) {
+            return Err("--device-path is only used when monitoring live input".to_string());
+        }
+        if !options.timeout_idle_overrides.is_empty() && options.timeout_idle.is_none() {
+            return Err("--timeout-idle-for is only used with --timeout-idle".to_string());
+        }
+        if options.benchmark && options.max_events.is_some() {
+            return Err("--max-events cannot be combined with --benchmark (use --benchmark-duration)".to_string());
+        }
+        if options.benchmark && options.replay.is_some() {
+            return Err("--benchmark and --replay cannot be combined".to_string());
+        }
+        // Today's window titles say nothing about recorded or synthetic events
+        if options.correlate_window && (options.benchmark || options.replay.is_some()) {
+            return Err("--correlate-window is only used when monitoring live input".to_string());
+        }
+        if options.kbd_view && (options.status_line || options.benchmark || options.replay.is_some()) {
+            return Err("--kbd-view is only used when monitoring live input, without --status-line".to_string());
+        }
+        if options.sanitize && options.correlate_window {
+            return Err("--sanitize cannot be combined with --correlate-window (window titles are not masked)".to_string());
+        }
+        if options.sanitize && options.raw_evdev {
+            return Err("--sanitize cannot be combined with --raw-evdev (kernel key codes are not masked)".to_string());
+        }
+        if options.window_sample.is_some() && !options.correlate_window {
+            return Err("--window-sample-ms is only used with --correlate-window".to_string());
+        }
+        if (options.speak_mouse_buttons || options.speech_rate.is_some()) && !options.screen_reader {
+            return Err("--speak-mouse-buttons and --speech-rate are only used with --screen-reader".to_string());
+        }
+        // Probes need the udev seat to pick up the virtual device, and would
+        // keep waking an idle monitor
+        if options.all_seats && (options.benchmark || options.replay.is_some() || options.simulate.is_some() || !options.device_paths.is_empty()) {
+            return Err("--all-seats is only used when monitoring live input, and cannot be combined with --device-path".to_string());
+        }
+        // Both need the libinput events themselves, which stay on the seat threads
+        if options.all_seats && (options.measure_latency || options.show_accel_curve) {
+            return Err("--all-seats cannot be combined with --measure-latency or --show-accel-curve".to_string());
+        }
+        if options.measure_latency && (options.benchmark || options.replay.is_some() || !options.device_paths.is_empty()) {
+            return Err("--measure-latency only works when monitoring the whole seat".to_string());
+        }
+        if options.measure_latency && options.timeout_idle.is_some() {
+            return Err("--measure-latency cannot be combined with --timeout-idle".to_string());
+        }
+        if options.simulate.is_some() && (options.replay.is_some() || options.benchmark || !options.device_paths.is_empty()) {
+            return Err("--simulate cannot be combined with --replay, --benchmark or --device-path".to_string());
+        }
+        if options.simulate.is_some() && (options.measure_latency || options.kbd_view) {
+            return Err("--measure-latency and --kbd-view need live input, not --simulate".to_string());
+        }
+        if options.coalesce && (options.format != OutputFormat::Text || options.output_template.is_some() || options.status_line || options.kbd_view) {
+            return Err("--coalesce folds the text event lines; it cannot be combined with --format, --output-template, --status-line or --kbd-view".to_string());
+        }
+        if options.raw_evdev != options.raw_evdev_device.is_some() {
+            return Err("--raw-evdev and --raw-evdev-device /dev/input/eventN are used together".to_string());
+        }
+        if options.raw_evdev && (options.benchmark || options.replay.is_some() || options.simulate.is_some()) {
+            return Err("--raw-evdev is only used when monitoring live input".to_string());
+        }
+        if options.raw_evdev && (options.status_line || options.kbd_view) {
+            return Err("--raw-evdev prints lines of its own; it cannot be combined with --status-line or --kbd-view".to_string());
+        }
+        if (options.scroll_friction.is_some() || options.smooth_scroll_rate.is_some()) && !options.smooth_scroll {
+            return Err("--scroll-friction and --smooth-scroll-rate are only used with --smooth-scroll".to_string());
+        }
+        if options.show_accel_curve && (options.benchmark || options.replay.is_some() || options.simulate.is_some()) {
+            return Err("--show-accel-curve needs live input: recordings don't keep the unaccelerated motion".to_string());
+        }
+        if options.show_accel_curve && (options.format != OutputFormat::Text || options.output_template.is_some() || options.status_line || options.kbd_view) {
+            return Err("--show-accel-curve draws on the terminal; it cannot be combined with --format, --output-template, --status-line or --kbd-view".to_string());
+        }
+        if options.rollover_test && (options.format != OutputFormat::Text || options.output_template.is_some() || options.status_line || options.kbd_view || options.show_accel_curve) {
+            return Err("--rollover-test draws on the terminal; it cannot be combined with --format, --output-template, --status-line, --kbd-view or --show-accel-curve".to_string());
+        }
+        if options.long_press_threshold.is_some() && !options.classify_presses {
+            return Err("--long-press-threshold is only used with --classify-presses".to_string());
+        }
+        // It types for real, so it has to be asked for twice
+        if options.replay_into_uinput.is_some() && !options.confirm_inject {
+            return Err("--replay-into-uinput types and clicks for real in the focused window; add --confirm-inject to go ahead".to_string());
+        }
+        if options.confirm_inject && options.replay_into_uinput.is_none() {
+            return Err("--confirm-inject is only used with --replay-into-uinput".to_string());
+        }
+        // Grabbed keyboards stop typing anywhere else, so it has to be asked for twice too
+        if options.grab && options.device_paths.is_empty() {
+            return Err("--grab is only used with --device-path".to_string());
+        }
+        if options.grab && !options.confirm_grab {
+            return Err("--grab takes the devices away from every other program, including your terminal; add --confirm-grab to go ahead".to_string());
+        }
+        if options.confirm_grab && !options.grab {
+            return Err("--confirm-grab is only used with --grab".to_string());
+        }
+        if options.replay_into_uinput.is_some() && (options.replay.is_some() || options.simulate.is_some() || options.benchmark || options.daemon) {
+            return Err("--replay-into-uinput cannot be combined with --replay, --simulate, --benchmark or --daemon".to_string());
+        }
+        if options.timeout.is_some() && !options.one_shot {
+            return Err("--timeout is only used with --one-shot".to_string());
+        }
+        if options.one_shot && (options.max_events.is_some() || options.benchmark || options.interactive || options.daemon) {
+            return Err("--one-shot cannot be combined with --max-events, --benchmark, --interactive or --daemon".to_string());
+        }
+        if options.one_shot && (options.status_line || options.kbd_view) {
+            return Err("--one-shot prints a single event; it cannot be combined with --status-line or --kbd-view".to_string());
+        }
+        if options.interactive && options.replay.is_none() {
+            return Err("--interactive is only used with --replay".to_string());
+        }
+        if options.pid_file.is_some() && !options.daemon {
+            return Err("--pid-file is only used with --daemon".to_string());
+        }
+
+        Ok(options)
+    }
+
+    // --log-file rotation; a size limit of 0 rotates by date alone
+    pub fn log_rotation(&self) -> Rotation {
+        let max_size_mb = self.log_max_size_mb.unwrap_or(log_rotate::DEFAULT_MAX_SIZE_MB);
+        Rotation {
+            max_bytes: if max_size_mb == 0 { u64::MAX } else { max_size_mb.saturating_mul(1024 * 1024) },
+            daily: self.log_rotate_daily,
+            compress: self.log_compress,
+            keep: self.log_keep_files.unwrap_or(log_rotate::DEFAULT_KEEP_FILES),
+        }
+    }
+}
+
+// Fetch the value that follows a flag such as `--health-export <path>`
+fn next_value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("option '{}' requires a value", flag))
+}
+
+// Fetch and parse a flag's value, e.g. `--idle-threshold 10`
+fn parse_value<T: FromStr, I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<T, String> {
+    let value = next_value(args, flag)?;
+    value.parse().map_err(|_| format!("invalid value '{}' for option '{}'", value, flag))
+}
+
+// Parse an `on`/`off` switch, e.g. `--natural-scroll on`
+fn parse_on_off<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<bool, String> {
+    let value = next_value(args, flag)?;
+    match value.to_ascii_lowercase().as_str() {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("invalid value '{}' for option '{}' (expected on or off)", value, flag)),
+    }
+}
+
+// Parse screen dimensions written as `WxH`
+pub fn parse_dimensions(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once(['x', 'X'])?;
+    let width: u32 = width.trim().parse().ok()?;
+    let height: u32 = height.trim().parse().ok()?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+// Parse a positive number of seconds, e.g. `--idle-threshold 2.5` or `2.5s`
+fn parse_seconds<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<Duration, String> {
+    let value = next_value(args, flag)?;
+    let secs: f64 = value.strip_suffix('s').unwrap_or(&value).parse()
+        .map_err(|_| format!("invalid value '{}' for option '{}'", value, flag))?;
+    if secs <= 0.0 || !secs.is_finite() {
+        return Err(format!("option '{}' must be a positive number of seconds", flag));
+    }
+    Ok(Duration::from_secs_f64(secs))
+}
+
+fn print_usage() {
+    println!("Usage: libinput_project [OPTIONS]");
+    println!("       libinput_project calibrate [--output <calibration.toml>] [--pointer]");
+    println!("       libinput_project configure-device [--device <name>] [SETTINGS]");
+    println!("       libinput_project show-repeat-config");
+    println!("       libinput_project set-repeat --device <name> --delay <ms> --rate <keys_per_sec>");
+    println!("       libinput_project decode <file.bin> [--output <file.json>]");
+    println!("       libinput_project query <events.db> [\"<SQL WHERE clause>\"]");
+    println!("       libinput_project export --format keylogger-stats --input <file>... [--output <stats.json>]");
+    println!("       libinput_project record-macro [--output <macro.sh>] [--trigger <hotkey>] [--stop <hotkey>] [--ignore-timing]");
+    println!("       libinput_project diff <file_a.rkey> <file_b.rkey> [--timestamp-tolerance-ms <ms>] [--json]");
+    println!();
+    println!("Options:");
+    println!("  --health-export <path>   Write per-minute activity CSV (typing, mouse movement, scrolling)");
+    println!("  --idle-threshold <secs>  Gaps longer than this count as idle, not typing pauses (default: 10)");
+    println!("  --heatmap <layout>       Draw a key press heatmap in the summary: qwerty, qwertz or azerty (terminal only)");
+    println!("  --kbd-view               Draw a keyboard that lights up pressed keys, instead of event lines (terminal only)");
+    println!("  --layout <layout>        Key labels for --kbd-view: qwerty (default), qwertz or azerty");
+    println!("  --session-gap <secs>     Input after a gap this long starts a new activity session in the summary (default: 300)");
+    println!("  --histogram-interval <secs>  Print the events-per-second histogram every N seconds while monitoring");
+    println!("  --warmup <secs>          Leave the first N seconds of events out of the statistics and output");
+    println!("  --warmup-from <start>    Measure --warmup from `startup` (default) or the `first-event` of input");
+    println!("  --warmup-show            Still print the --warmup events, only leaving them out of the statistics");
+    println!("  --correlate-window       Tag JSON events with the focused window's title and break the summary down by window");
+    println!("  --window-sample-ms <ms>  How often --correlate-window checks the focused window (default: 500)");
+    println!("  -v, --verbose            Also show libinput's info messages; twice (-vv) for its debug messages");
+    println!("  --debug-unknown          Print the full Debug dump of events rustkey doesn't decode (also in JSON as \"debug\")");
+    println!("  --timeout-idle <secs>    Pause polling after this long without input, until the next event arrives");
+    println!("  --timeout-idle-for <type>=<secs>  Idle timeout for one event type (default: motion counts a quarter)");
+    println!("  --record <path>          Record decoded events to an .rkey file");
+    println!("  --sanitize               Show and save letters, digits and symbols only as LETTER, DIGIT and SYMBOL,");
+    println!("                           and devices as \"Device 1\", ..., for logs to attach to bug reports");
+    println!("  --device-path <path>     Only monitor this device, e.g. /dev/input/event5 (repeatable)");
+    println!("  --grab                   Take the --device-path devices exclusively (EVIOCGRAB): their input reaches");
+    println!("                           nothing else, not even your terminal; Ctrl+C on a grabbed keyboard exits.");
+    println!("                           Needs --confirm-grab");
+    println!("  --confirm-grab           Confirm that --grab may take the keyboard or mouse away from the desktop");
+    println!("  --all-seats              Monitor every udev seat (seat0, seat1, ...), not just seat0");
+    println!("  --raw-evdev              Also print the kernel's raw input_event structs of one device, to compare");
+    println!("                           with what libinput makes of them; needs --raw-evdev-device");
+    println!("  --raw-evdev-device <path>");
+    println!("                           The event node --raw-evdev reads, e.g. /dev/input/event5");
+    println!("  --exit-if-empty          Exit with status 1 if no input devices show up on the seat");
+    println!("  --filter-config <file.toml>  Keep only some event types, per device name substring");
+    println!("                           (types = [\"keyboard\"]; [device_filters.\"mouse\"] types = [\"pointer\"])");
+    println!("  --max-events <n>         Stop and print the summary after N events");
+    println!("  --one-shot [type]        Print the first event of the type (keyboard, button, scroll, motion or");
+    println!("                           other; any type if omitted) and exit. Releases don't count");
+    println!("  --timeout <secs>         With --one-shot, exit with status 1 if nothing matched in time");
+    println!("  --max-lines-per-sec <n>  Print at most N events per second; the rest are counted but not printed");
+    println!("  --replay-into-uinput <path>  Type and click a recording back into the system as real input, with");
+    println!("                           the original timing (needs --confirm-inject and the `uinput-replay` cargo feature)");
+    println!("  --confirm-inject         Confirm that --replay-into-uinput may generate real key presses");
+    println!("  --measure-latency        Time injected KEY_UNKNOWN presses from uinput to libinput and report");
+    println!("                           mean/min/max/p99 in the summary (requires the `uinput` cargo feature)");
+    println!("  --log-sqlite <path>      Store every event in a SQLite database (requires the `sqlite` cargo feature)");
+    println!("  --replay <path>          Process a recorded .rkey file instead of live input, then exit");
+    println!("  --simulate <file.toml>   Play a scenario of synthetic events instead of live input, then exit");
+    println!("                           (requires the `simulation` cargo feature)");
+    println!("  --interactive            With --replay, step through the events one at a time (also reads");
+    println!("                           --format binary files); type n, p, s, t <secs>, f <key> or q");
+    println!("  --alert-key <key>        Ring the bell / flash when this key is pressed (repeatable, e.g. CAPS_LOCK)");
+    println!("  --on-key <key>=<command>  Run a shell command when this key is pressed, e.g. F12='notify-send hi'");
+    println!("                           (repeatable; runs with RustKey's privileges, see the README)");
+    println!("  --alias <key>=<label>    Show this label for a key, e.g. 58=CTRL for a remapped Caps Lock (repeatable)");
+    println!("  --alias-file <path>      Read labels from a file, one <key>=<label> per line (--alias entries win)");
+    println!("  --alert-style <style>    How alerts are shown: bell, flash or both (default: both)");
+    println!("  --highlight-pattern <regex>  Point out key sequences matching this regex, e.g. \"CTRL A\" (repeatable)");
+    println!("  --show-modifiers         Show which of CTRL, SHIFT, ALT and SUPER are held, whenever that changes");
+    println!("  --detect-ghosting        Flag phantom key presses from the keyboard matrix and report the rollover");
+    println!("  --rollover-test          Show the keys held at once live, and warn about presses the keyboard dropped");
+    println!("  --classify-presses       Label each completed key, button or touch press as TAP or LONG PRESS");
+    println!("  --long-press-threshold <ms>  Presses held at least this long are long presses (default: 500)");
+    println!("  --constrain-pointer <x1,y1,x2,y2>  Mark pointer motion that a confinement region would have stopped");
+    println!("  --screen-reader          Speak each key press aloud with espeak-ng, e.g. \"Control A\"");
+    println!("  --speak-mouse-buttons    Also speak mouse button presses (with --screen-reader)");
+    println!("  --speech-rate <wpm>      Speaking rate passed to espeak-ng as -s (default: espeak-ng's own)");
+    println!("  --benchmark              Push a synthetic workload through the pipeline and report throughput on stderr");
+    println!("  --benchmark-duration <secs>  How long the benchmark runs (default: 10)");
+    println!("  --format <format>        Event output: text (default), json (one object per line)");
+    println!("                           binary (fixed 40-byte records, read back with `decode`)");
+    println!("                           msgpack (length-prefixed MessagePack frames of the JSON fields)");
+    println!("                           libinput-debug (lines like `libinput debug-events`, for diffing)");
+    println!("                           or evtest (raw type/code/value lines like `evtest`)");
+    println!("  --output <path>          Write --format binary or msgpack to a file instead of stdout");
+    println!("  --output-template <tpl>  Print each event as the template, e.g. '{{type}}: {{key_name}} at {{timestamp_us}}'");
+    println!("                           ({{?field:...?}} only renders when the event has the field)");
+    println!("  --flush <policy>         When output is flushed: immediate, line or block");
+    println!("                           (default: line on a terminal, block for files and pipes)");
+    println!("  --no-emoji               Use ASCII tags like [KEY] and [MOUSE] instead of emoji");
+    println!("  --color-theme <name>     Colors for text output: default, solarized, monokai, nord, gruvbox or");
+    println!("                           high-contrast (the 8 basic ANSI colors only)");
+    println!("  --key-colors <file.toml>  Color key press lines by key category ([colors] letters = \"#a3be8c\";");
+    println!("                           letters, digits, modifiers, navigation, function, media)");
+    println!("  --no-color               No colors at all (also set by the NO_COLOR environment variable)");
+    println!("  --emoji                  Use emoji even if the locale or terminal doesn't look capable");
+    println!("  --no-banner              Don't show the welcome banner (it's skipped anyway when stdout isn't a terminal)");
+    println!("  --banner-text <text>     Replace the banner's title line");
+    println!("  --legend                 Explain the event colors once at startup (terminal only, skipped with NO_COLOR)");
+    println!("  --status-line            Keep one line of running totals (keys, clicks, WPM, last key) updated in place (terminal only)");
+    println!("  --syslog                 Send event and summary lines to syslog/journal instead of stdout");
+    println!("                           (requires the `syslog` cargo feature)");
+    println!("  --journal                Send event and summary lines to the systemd journal instead of stdout");
+    println!("                           (requires the `journald` cargo feature)");
+    println!("  --syslog-ident <tag>     Identifier for --syslog and --journal entries (default: rustkey)");
+    println!("  --log-file <path>        Append plain-text event and summary lines to a file instead of stdout");
+    println!("  --log-max-size-mb <n>    Rotate the --log-file once it reaches N MiB (default: 100, 0 disables)");
+    println!("  --log-rotate-daily       Also rotate the --log-file when the (UTC) date changes");
+    println!("  --log-compress           gzip rotated log files (<path>.1.gz, ...)");
+    println!("  --log-keep-files <n>     Rotated log files to keep before deleting the oldest (default: 5)");
+    println!("  --summary-out <path>     Also write the session summary to this file, at exit and on `kill -USR1 <pid>`");
+    println!("                           (JSON lines with --format json; replaced each time unless --summary-append)");
+    println!("  --summary-append         Add each summary to the end of the --summary-out file");
+    println!("  --output-fifo <path>     Create a named pipe and stream events to it (dropped while nobody reads)");
+    println!("  --fifo-wait-for-reader   Wait at startup until a reader opens the --output-fifo pipe");
+    println!("  --daemon                 Detach and run in the background (needs a --syslog, --journal,");
+    println!("                           --log-file or --output-fifo destination)");
+    println!("  --pid-file <path>        Write the daemon's PID to this file, removed on exit");
+    println!("  --history <n>            Keep the last N events; `kill -USR2 <pid>` prints them (default: 100, 0 disables)");
+    println!("  --event-priority <type>=<n>  Display order within a batch: keyboard, button, scroll, motion, other");
+    println!("                           (repeatable; defaults keyboard=3 button=2 scroll=1 motion=0 other=0)");
+    println!("  --scroll-debounce <ms>   Pause that ends a scroll gesture (default: 150)");
+    println!("  --raw-scroll             Also print every individual scroll event");
+    println!("  --show-accel-curve       Plot how much pointer acceleration scales motion, instead of the event lines");
+    println!("  --smooth-scroll          Replace each wheel notch with a decaying glide of continuous scroll events");
+    println!("  --scroll-friction <f>    How fast a glide slows down: 0.0 = not at all, 1.0 = instantly (default: 0.85)");
+    println!("  --smooth-scroll-rate <hz>  Glide steps per second (default: 60)");
+    println!("  --dedup-window-ms <ms>   Fold identical motion/scroll lines arriving within this gap into one (xN) line (default: 200, 0 = off)");
+    println!("  --coalesce               Print a run of identical events once, then a count like 'KEY A pressed x12' when it ends");
+    println!("  --show-codes             Add the raw evdev code to key and mouse button lines, e.g. (code: 30)");
+    println!("  --xkb                    Name keys by what they type in your XKB layout, Shift and AltGr levels");
+    println!("                           and dead keys included (requires the `xkb` cargo feature)");
+    println!("  --precision <n>          Decimals for positions, motion deltas and scroll values (default: 2, max: 9)");
+    println!("  --follow-cursor          Show which part of the screen the cursor is in when it changes");
+    println!("  --record-mouse-path <path>  Draw the mouse trajectory and clicks to an SVG file on exit");
+    println!("  --path-simplify-epsilon <px>  Drop trajectory points closer than this to the simplified path (default: 1.0)");
+    println!("  --gesture-map <file.toml>  Map touchpad gestures to action names ([actions] SWIPE_3_LEFT = \"browser_back\")");
+    println!("  --screen <WxH>           Screen size for absolute positions, --follow-cursor and --record-mouse-path");
+    println!("                           (default: the primary output's resolution, if it can be detected)");
+    println!("  --grid-size <3|5>        Grid used by --follow-cursor (default: 3)");
+    println!("  --calibration <path>     Normalize scroll values (and mouse distance) using a `calibrate` file");
+    println!("  --probe-permissions      Check access to /dev/input and explain how to fix it, then exit");
+    println!("  --selftest               Check permissions, the seat, its devices and incoming events; report");
+    println!("                           PASS/FAIL and exit with status 0 or 1");
+    println!("  -h, --help               Show this help message");
+    println!();
+    println!("Calibrate options:");
+    println!("  --output <path>                Calibration file to write (default: calibration.toml)");
+    println!("  --pointer                      Calibrate mouse DPI instead of scroll sensitivity");
+    println!();
+    println!("Export options:");
+    println!("  --format keylogger-stats       Per-key press counts as {{\"keys\": {{..}}, \"total\": N, \"session_start\": ..}}");
+    println!("  --input <path>                 .rkey recording, --format json output or keylogger-stats file");
+    println!("                                 (repeatable; the counts are merged)");
+    println!("  --output <path>                File to write (default: stdout)");
+    println!();
+    println!("Record-macro options:");
+    println!("  --output <path>                xdotool script to write (default: macro.sh)");
+    println!("  --trigger <hotkey>             Start recording on this hotkey (default: Ctrl+F9)");
+    println!("  --stop <hotkey>                Stop recording and write the script (default: Ctrl+F10)");
+    println!("  --ignore-timing                Leave out the pauses between keys (no `xdotool sleep`)");
+    println!("  --replay <path>                Build the macro from an .rkey recording instead of live input");
+    println!();
+    println!("Configure-device options (without settings, lists devices or the device's current config):");
+    println!("  --device <name>                Device name (substring) or kernel name such as event5");
+    println!("  --accel-speed <-1.0..1.0>      Pointer acceleration speed");
+    println!("  --pointer-accel <profile>      flat, adaptive or none");
+    println!("  --natural-scroll <on|off>      Natural (reversed) scrolling");
+    println!("  --tap-to-click <on|off>        Tap-to-click on touchpads");
+    println!("  --dpi <n> --persist <file>     Write a udev hwdb MOUSE_DPI entry for the device");
+    println!();
+    println!("Diff options:");
+    println!("  --timestamp-tolerance-ms <ms>  Ignore timestamp differences up to this size (default: 50)");
+    println!("  --json                         Print the differences as JSON");
+}
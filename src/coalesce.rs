@@ -0,0 +1,83 @@
+use crate::button_names::button_name;
+use crate::event::{InputEvent, TimedEvent};
+use crate::sanitize;
+
+// --coalesce: the first event of a run of identical ones is printed as
+// usual, the rest only as a count once something else arrives, e.g.
+// "KEY A pressed x12". Identical means the same decoded event from the
+// same device; timestamps don't matter.
+#[derive(Default)]
+pub struct Coalescer {
+    // The current run's event and device, and its length so far
+    run: Option<(InputEvent, Option<String>)>,
+    count: u32,
+}
+
+impl Coalescer {
+    // True if the event starts a new run and should be printed. The run it
+    // ends, if longer than one event, is returned to be reported first.
+    pub fn observe(&mut self, timed: &TimedEvent) -> (bool, Option<(InputEvent, u32)>) {
+        if self.run.as_ref().is_some_and(|(event, sysname)| *event == timed.event && *sysname == timed.sysname) {
+            self.count += 1;
+            return (false, None);
+        }
+        let ended = self.finish();
+        self.run = Some((timed.event.clone(), timed.sysname.clone()));
+        self.count = 1;
+        (true, ended)
+    }
+
+    // The run in progress, if it repeated; at exit
+    pub fn finish(&mut self) -> Option<(InputEvent, u32)> {
+        let (event, _) = self.run.take()?;
+        let count = std::mem::take(&mut self.count);
+        (count > 1).then_some((event, count))
+    }
+}
+
+// Short name for a run's event, e.g. "KEY A pressed" or "MOTION (1.00, 0.00)";
+// `precision` is the number of decimals for --precision
+pub fn run_label(event: &InputEvent, sanitizing: bool, precision: usize) -> String {
+    let state = |pressed: bool| if pressed { "pressed" } else { "released" };
+    match event {
+        InputEvent::Key { code, pressed } => format!("KEY {} {}", sanitize::key_label(*code, sanitizing), state(*pressed)),
+        InputEvent::PointerButton { button, pressed } => {
+            format!("BUTTON {} {}", button_name(*button).trim_start_matches("BTN_"), state(*pressed))
+        },
+        InputEvent::PointerMotion { dx, dy } => format!("MOTION ({:.p$}, {:.p$})", dx, dy, p = precision),
+        InputEvent::PointerMotionAbsolute { x, y } => format!("POSITION ({:.p$}, {:.p$})", x, y, p = precision),
+        InputEvent::Scroll { horizontal, vertical, .. } => format!("SCROLL ({:.p$}, {:.p$})", horizontal, vertical, p = precision),
+        InputEvent::Touch { kind, .. } => format!("TOUCH {}", kind),
+        InputEvent::Gesture { kind, .. } => format!("GESTURE {}", kind),
+        InputEvent::Other { source, kind, .. } => format!("{} {}", source.to_uppercase(), kind),
+        // The JSON type, e.g. "PAD_RING"
+        other => serde_json::to_value(other).ok()
+            .and_then(|value| value["type"].as_str().map(str::to_uppercase))
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: u32, pressed: bool) -> TimedEvent {
+        TimedEvent { time_usec: 0, event: InputEvent::Key { code, pressed }, fingers: None, sysname: None, seat: None }
+    }
+
+    #[test]
+    fn counts_runs_of_identical_events() {
+        let mut coalescer = Coalescer::default();
+        assert_eq!(coalescer.observe(&key(30, true)), (true, None));
+        assert_eq!(coalescer.observe(&key(30, true)), (false, None));
+        assert_eq!(coalescer.observe(&key(30, true)), (false, None));
+        // A different event ends the run
+        assert_eq!(coalescer.observe(&key(30, false)), (true, Some((InputEvent::Key { code: 30, pressed: true }, 3))));
+        // A run of one isn't reported
+        assert_eq!(coalescer.finish(), None);
+
+        assert_eq!(run_label(&InputEvent::Key { code: 30, pressed: true }, false, 2), "KEY A pressed");
+        assert_eq!(run_label(&InputEvent::TouchpadFingerCountChange { previous: 1, count: 2 }, false, 2), "TOUCHPAD_FINGER_COUNT_CHANGE");
+        assert_eq!(run_label(&InputEvent::PointerMotion { dx: 1.0, dy: -0.5 }, false, 0), "MOTION (1, -0)");
+    }
+}
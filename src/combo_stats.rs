@@ -0,0 +1,85 @@
+use std::collections::{BTreeSet, HashMap};
+
+// How often each keyboard shortcut was used, keyed by its canonical form,
+// e.g. "Ctrl+Shift+Z"
+#[derive(Debug, Default)]
+pub struct ComboFrequencyMap(HashMap<String, u64>);
+
+impl ComboFrequencyMap {
+    // `combo` in any modifier order and spelling, e.g. "SHIFT+CTRL+Z"
+    pub fn increment(&mut self, combo: &str) {
+        *self.0.entry(canonical(combo)).or_insert(0) += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // The `n` most used combos, most frequent first (ties alphabetically)
+    pub fn top_n(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut combos: Vec<(&str, u64)> = self.0.iter().map(|(combo, &count)| (combo.as_str(), count)).collect();
+        combos.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        combos.truncate(n);
+        combos
+    }
+}
+
+fn modifier_name(part: &str) -> Option<&'static str> {
+    match part.to_ascii_lowercase().as_str() {
+        "alt" => Some("Alt"),
+        "ctrl" | "control" => Some("Ctrl"),
+        "meta" | "super" | "logo" | "win" => Some("Meta"),
+        "shift" => Some("Shift"),
+        _ => None,
+    }
+}
+
+// Modifiers deduplicated and in alphabetical order, then the key, so that
+// "Shift+Ctrl+S" and "ctrl+shift+S" are both "Ctrl+Shift+S". Single letter
+// keys are upper case; a trailing "+" is the plus key itself.
+pub fn canonical(combo: &str) -> String {
+    let (body, plus_key) = match combo.strip_suffix("++") {
+        Some(body) => (body, true),
+        None => (combo, combo == "+"),
+    };
+    let mut modifiers = BTreeSet::new();
+    let mut key = Vec::new();
+    for part in body.split('+').map(str::trim).filter(|part| !part.is_empty()) {
+        match modifier_name(part) {
+            Some(name) => {
+                modifiers.insert(name);
+            },
+            None => key.push(part),
+        }
+    }
+    let mut key = key.join("+");
+    if plus_key {
+        key = "+".to_string();
+    }
+    if key.chars().count() == 1 {
+        key = key.to_uppercase();
+    }
+    modifiers.into_iter().chain((!key.is_empty()).then_some(key.as_str())).collect::<Vec<_>>().join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_sorts_modifiers() {
+        assert_eq!(canonical("Shift+Ctrl+S"), "Ctrl+Shift+S");
+        assert_eq!(canonical("Ctrl+Shift+S"), "Ctrl+Shift+S");
+        assert_eq!(canonical("SHIFT+SUPER+CTRL+z"), "Ctrl+Meta+Shift+Z");
+        assert_eq!(canonical("ALT+TAB"), "Alt+TAB");
+        assert_eq!(canonical("Ctrl+ctrl+C"), "Ctrl+C");
+        assert_eq!(canonical("Ctrl++"), "Ctrl++");
+        assert_eq!(canonical("Ctrl+Shift"), "Ctrl+Shift");
+
+        let mut combos = ComboFrequencyMap::default();
+        for combo in ["CTRL+S", "SHIFT+CTRL+Z", "CTRL+SHIFT+Z", "CTRL+C"] {
+            combos.increment(combo);
+        }
+        assert_eq!(combos.top_n(2), vec![("Ctrl+Shift+Z", 2), ("Ctrl+C", 1)]);
+    }
+}
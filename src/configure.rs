@@ -0,0 +1,218 @@
+use std::fs;
+use std::path::PathBuf;
+
+use input::event::EventTrait;
+use input::{AccelProfile, Device, DeviceConfigError, Libinput};
+
+use crate::{Colors, Interface};
+
+// Pointer acceleration as given on the command line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerAccel {
+    Flat,
+    Adaptive,
+    // libinput has no "off" profile; a flat profile at speed 0 maps motion 1:1
+    None,
+}
+
+impl PointerAccel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "flat" => Some(PointerAccel::Flat),
+            "adaptive" => Some(PointerAccel::Adaptive),
+            "none" => Some(PointerAccel::None),
+            _ => None,
+        }
+    }
+}
+
+pub struct ConfigureOptions {
+    pub device: Option<String>,
+    pub accel_speed: Option<f64>,
+    pub pointer_accel: Option<PointerAccel>,
+    pub natural_scroll: Option<bool>,
+    pub tap_to_click: Option<bool>,
+    pub dpi: Option<u32>,
+    pub persist: Option<PathBuf>,
+}
+
+impl ConfigureOptions {
+    pub fn has_changes(&self) -> bool {
+        self.accel_speed.is_some() || self.pointer_accel.is_some() || self.natural_scroll.is_some()
+            || self.tap_to_click.is_some() || self.dpi.is_some()
+    }
+}
+
+// List or change a device's libinput configuration; returns the process exit code
+pub fn run(options: &ConfigureOptions) -> i32 {
+    let devices = match enumerate_devices() {
+        Ok(devices) => devices,
+        Err(err) => {
+            eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+            return 2;
+        }
+    };
+
+    let Some(wanted) = &options.device else {
+        println!("{}{}Input devices{} (pick one with --device <name>):", Colors::CYAN, Colors::BOLD, Colors::RESET);
+        for device in &devices {
+            println!("  {} ({})", device.name(), device.sysname());
+        }
+        return 0;
+    };
+
+    // Match the kernel name exactly ("event5") or the product name by substring
+    let wanted_lower = wanted.to_lowercase();
+    let mut matches: Vec<Device> = devices.into_iter()
+        .filter(|device| device.sysname() == wanted || device.name().to_lowercase().contains(&wanted_lower))
+        .collect();
+    let mut device = match matches.len() {
+        0 => {
+            eprintln!("{}error: no input device matches '{}'{}", Colors::RED, wanted, Colors::RESET);
+            return 2;
+        },
+        1 => matches.remove(0),
+        _ => {
+            eprintln!("{}error: '{}' matches several devices, be more specific:{}", Colors::RED, wanted, Colors::RESET);
+            for device in &matches {
+                eprintln!("  {} ({})", device.name(), device.sysname());
+            }
+            return 2;
+        },
+    };
+
+    if !options.has_changes() {
+        print_config(&device);
+        return 0;
+    }
+
+    let mut failed = false;
+    let mut report = |setting: &str, result: Result<(), DeviceConfigError>| match result {
+        Ok(()) => println!("{}✅ {} applied{}", Colors::GREEN, setting, Colors::RESET),
+        Err(DeviceConfigError::Unsupported) => {
+            eprintln!("{}⚠️ {}: not supported by this device{}", Colors::RED, setting, Colors::RESET);
+            failed = true;
+        },
+        Err(DeviceConfigError::Invalid) => {
+            eprintln!("{}⚠️ {}: value rejected by libinput{}", Colors::RED, setting, Colors::RESET);
+            failed = true;
+        },
+    };
+
+    match options.pointer_accel {
+        Some(PointerAccel::Flat) => report("pointer-accel flat", device.config_accel_set_profile(AccelProfile::Flat)),
+        Some(PointerAccel::Adaptive) => report("pointer-accel adaptive", device.config_accel_set_profile(AccelProfile::Adaptive)),
+        Some(PointerAccel::None) => {
+            report("pointer-accel none", device.config_accel_set_profile(AccelProfile::Flat)
+                .and_then(|_| device.config_accel_set_speed(0.0)));
+        },
+        None => {},
+    }
+    if let Some(speed) = options.accel_speed {
+        report(&format!("accel-speed {:.2}", speed), device.config_accel_set_speed(speed));
+    }
+    if let Some(enabled) = options.natural_scroll {
+        report(&format!("natural-scroll {}", on_off(enabled)), device.config_scroll_set_natural_scroll_enabled(enabled));
+    }
+    if let Some(enabled) = options.tap_to_click {
+        report(&format!("tap-to-click {}", on_off(enabled)), device.config_tap_set_enabled(enabled));
+    }
+
+    // libinput settings live in each libinput context, so the compositor keeps its own
+    println!();
+    println!("Note: libinput settings apply to the context that sets them; your compositor keeps its own.");
+    println!("Use your desktop's input settings to make accel, natural scroll and tap-to-click permanent.");
+
+    if let Some(dpi) = options.dpi {
+        match &options.persist {
+            Some(path) => match write_hwdb(path, &device, dpi) {
+                Ok(()) => {
+                    println!("{}✅ MOUSE_DPI={} written to {}{}", Colors::GREEN, dpi, path.display(), Colors::RESET);
+                    println!("Apply it with: sudo systemd-hwdb update && sudo udevadm trigger /dev/input/{}", device.sysname());
+                },
+                Err(err) => {
+                    eprintln!("{}error: cannot write {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                    failed = true;
+                },
+            },
+            None => {
+                // DPI is a hardware property read from udev, not a libinput setting
+                eprintln!("{}⚠️ --dpi is only stored through udev; add --persist <file.hwdb>{}", Colors::RED, Colors::RESET);
+                failed = true;
+            },
+        }
+    } else if options.persist.is_some() {
+        eprintln!("{}⚠️ Only --dpi can be persisted to hwdb; nothing written{}", Colors::RED, Colors::RESET);
+        failed = true;
+    }
+
+    if failed { 1 } else { 0 }
+}
+
+// Collect the devices libinput sees on seat0
+fn enumerate_devices() -> Result<Vec<Device>, String> {
+    let mut input = Libinput::new_with_udev(Interface);
+    input.udev_assign_seat("seat0").map_err(|_| "cannot assign seat0".to_string())?;
+    input.dispatch().map_err(|err| format!("libinput dispatch failed: {}", err))?;
+
+    let mut devices: Vec<Device> = (&mut input)
+        .filter(|event| matches!(event, input::Event::Device(_)))
+        .map(|event| event.device())
+        .collect();
+    devices.sort_by(|a, b| a.sysname().cmp(b.sysname()));
+    Ok(devices)
+}
+
+fn print_config(device: &Device) {
+    println!("{}{}⚙️  {} ({}){}", Colors::CYAN, Colors::BOLD, device.name(), device.sysname(), Colors::RESET);
+
+    if device.config_accel_is_available() {
+        println!("  accel-speed:     {:.2} (default {:.2}, range -1.0 to 1.0)",
+            device.config_accel_speed(), device.config_accel_default_speed());
+        let profiles: Vec<String> = device.config_accel_profiles().iter().map(profile_name).collect();
+        println!("  pointer-accel:   {} (available: {}, none)",
+            device.config_accel_profile().as_ref().map(profile_name).unwrap_or_else(|| "unknown".to_string()),
+            profiles.join(", "));
+    } else {
+        println!("  accel-speed:     not available");
+        println!("  pointer-accel:   not available");
+    }
+
+    if device.config_scroll_has_natural_scroll() {
+        println!("  natural-scroll:  {} (default {})",
+            on_off(device.config_scroll_natural_scroll_enabled()),
+            on_off(device.config_scroll_default_natural_scroll_enabled()));
+    } else {
+        println!("  natural-scroll:  not available");
+    }
+
+    if device.config_tap_finger_count() > 0 {
+        println!("  tap-to-click:    {} (default {}, {} fingers)",
+            on_off(device.config_tap_enabled()),
+            on_off(device.config_tap_default_enabled()),
+            device.config_tap_finger_count());
+    } else {
+        println!("  tap-to-click:    not available");
+    }
+}
+
+pub fn profile_name(profile: &AccelProfile) -> String {
+    match profile {
+        AccelProfile::Flat => "flat".to_string(),
+        AccelProfile::Adaptive => "adaptive".to_string(),
+        other => format!("{:?}", other).to_lowercase(),
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled { "on" } else { "off" }
+}
+
+// udev hwdb entry setting the mouse DPI, matched on vendor/product id and name
+fn write_hwdb(path: &PathBuf, device: &Device, dpi: u32) -> std::io::Result<()> {
+    let entry = format!(
+        "# Written by rustkey configure-device\n\
+         mouse:*:v{:04x}p{:04x}:name:{}:*\n MOUSE_DPI={}\n",
+        device.id_vendor(), device.id_product(), device.name(), dpi);
+    fs::write(path, entry)
+}
@@ -0,0 +1,69 @@
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// PID file that is removed again when the daemon exits
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Detach from the controlling terminal: fork (the parent exits), start a new
+// session, write the PID file, move to `/` and point stdio at /dev/null.
+// SIGTERM handling is shared with the foreground mode via `signals`.
+pub fn daemonize(pid_file: Option<&Path>) -> io::Result<Option<PidFile>> {
+    // Resolve the PID file before changing directory
+    let pid_path = match pid_file {
+        Some(path) if path.is_relative() => Some(std::env::current_dir()?.join(path)),
+        Some(path) => Some(path.to_path_buf()),
+        None => None,
+    };
+
+    match unsafe { libc::fork() } {
+        -1 => return Err(io::Error::last_os_error()),
+        0 => {},
+        _ => std::process::exit(0),
+    }
+
+    if unsafe { libc::setsid() } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let pid_file = match pid_path {
+        Some(path) => {
+            fs::write(&path, format!("{}\n", std::process::id()))?;
+            Some(PidFile { path })
+        },
+        None => None,
+    };
+
+    std::env::set_current_dir("/")?;
+    redirect_stdio_to_null()?;
+
+    Ok(pid_file)
+}
+
+fn redirect_stdio_to_null() -> io::Result<()> {
+    let dev_null = CString::new("/dev/null").unwrap();
+    let fd = unsafe { libc::open(dev_null.as_ptr(), libc::O_RDWR) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    for target in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        if unsafe { libc::dup2(fd, target) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    if fd > libc::STDERR_FILENO {
+        unsafe { libc::close(fd) };
+    }
+
+    Ok(())
+}
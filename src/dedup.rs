@@ -0,0 +1,63 @@
+// Folds runs of identical scroll / motion events into one line. Key events
+// never go through here: every key press matters.
+pub struct Deduplicator {
+    window_usec: u64,
+    // Event kind and its values (deltas or scroll axes)
+    last: Option<(&'static str, [f64; 2])>,
+    last_usec: u64,
+    count: u32,
+}
+
+impl Deduplicator {
+    // A zero window turns deduplication off
+    pub fn new(window_usec: u64) -> Self {
+        Deduplicator { window_usec, last: None, last_usec: 0, count: 0 }
+    }
+
+    // Returns how many identical events in a row this one makes (1 for a new run)
+    pub fn observe(&mut self, kind: &'static str, values: [f64; 2], time_usec: u64) -> u32 {
+        let repeat = self.window_usec > 0
+            && self.last == Some((kind, values))
+            && time_usec.saturating_sub(self.last_usec) <= self.window_usec;
+
+        self.count = if repeat { self.count + 1 } else { 1 };
+        self.last = Some((kind, values));
+        self.last_usec = time_usec;
+        self.count
+    }
+
+    // Any other event ends the current run
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_events_within_the_window_fold() {
+        let mut dedup = Deduplicator::new(100_000);
+        assert_eq!(dedup.observe("motion", [1.0, 0.0], 0), 1);
+        assert_eq!(dedup.observe("motion", [1.0, 0.0], 100_000), 2);
+        assert_eq!(dedup.observe("motion", [1.0, 0.0], 150_000), 3);
+        // Too late, different values, a different kind
+        assert_eq!(dedup.observe("motion", [1.0, 0.0], 300_000), 1);
+        assert_eq!(dedup.observe("motion", [0.0, 1.0], 310_000), 1);
+        assert_eq!(dedup.observe("absolute", [0.0, 1.0], 320_000), 1);
+    }
+
+    #[test]
+    fn reset_and_zero_window_end_runs() {
+        let mut dedup = Deduplicator::new(100_000);
+        dedup.observe("scroll", [0.0, 15.0], 0);
+        dedup.reset();
+        assert_eq!(dedup.observe("scroll", [0.0, 15.0], 10_000), 1);
+
+        let mut off = Deduplicator::new(0);
+        off.observe("scroll", [0.0, 15.0], 0);
+        assert_eq!(off.observe("scroll", [0.0, 15.0], 0), 1);
+    }
+}
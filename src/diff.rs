@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde_json::{json, Map, Value};
+
+use crate::recording::{self, RecordedEvent};
+use crate::Colors;
+
+// Options for `rustkey diff <file_a> <file_b>`
+pub struct DiffOptions {
+    pub file_a: PathBuf,
+    pub file_b: PathBuf,
+    pub timestamp_tolerance_ms: u64,
+    pub json: bool,
+}
+
+// One field whose value differs between the two recordings
+pub struct FieldChange {
+    pub field: String,
+    pub a: Value,
+    pub b: Value,
+}
+
+pub enum Divergence {
+    OnlyInA(RecordedEvent),
+    OnlyInB(RecordedEvent),
+    Changed { seq: u64, changes: Vec<FieldChange> },
+}
+
+// Align both recordings by sequence number and collect every divergence.
+// Timestamps within `tolerance_usec` of each other are considered equal.
+pub fn compare(a: &[RecordedEvent], b: &[RecordedEvent], tolerance_usec: u64) -> Vec<Divergence> {
+    let by_seq_a: BTreeMap<u64, &RecordedEvent> = a.iter().map(|e| (e.seq, e)).collect();
+    let by_seq_b: BTreeMap<u64, &RecordedEvent> = b.iter().map(|e| (e.seq, e)).collect();
+
+    let mut seqs: Vec<u64> = by_seq_a.keys().chain(by_seq_b.keys()).copied().collect();
+    seqs.sort_unstable();
+    seqs.dedup();
+
+    let mut divergences = Vec::new();
+    for seq in seqs {
+        match (by_seq_a.get(&seq), by_seq_b.get(&seq)) {
+            (Some(event_a), Some(event_b)) => {
+                let changes = field_changes(event_a, event_b, tolerance_usec);
+                if !changes.is_empty() {
+                    divergences.push(Divergence::Changed { seq, changes });
+                }
+            },
+            (Some(event_a), None) => divergences.push(Divergence::OnlyInA((*event_a).clone())),
+            (None, Some(event_b)) => divergences.push(Divergence::OnlyInB((*event_b).clone())),
+            (None, None) => {},
+        }
+    }
+
+    divergences
+}
+
+fn field_changes(a: &RecordedEvent, b: &RecordedEvent, tolerance_usec: u64) -> Vec<FieldChange> {
+    let fields_a = event_fields(a);
+    let fields_b = event_fields(b);
+
+    let mut names: Vec<&String> = fields_a.keys().chain(fields_b.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let mut changes = Vec::new();
+    for name in names {
+        let value_a = fields_a.get(name).cloned().unwrap_or(Value::Null);
+        let value_b = fields_b.get(name).cloned().unwrap_or(Value::Null);
+
+        let equal = if name == "time_usec" {
+            a.event.time_usec.abs_diff(b.event.time_usec) <= tolerance_usec
+        } else {
+            value_a == value_b
+        };
+
+        if !equal {
+            changes.push(FieldChange { field: name.clone(), a: value_a, b: value_b });
+        }
+    }
+
+    changes
+}
+
+// The event's fields as a JSON object, without the sequence number used for alignment
+fn event_fields(event: &RecordedEvent) -> Map<String, Value> {
+    match serde_json::to_value(&event.event) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+// Run the diff subcommand, returning the process exit code:
+// 0 when the recordings match, 1 when they diverge, 2 on error
+pub fn run(options: &DiffOptions) -> i32 {
+    let (a, b) = match (recording::read_recording(&options.file_a), recording::read_recording(&options.file_b)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(err), _) | (_, Err(err)) => {
+            eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+            return 2;
+        }
+    };
+
+    let divergences = compare(&a, &b, options.timestamp_tolerance_ms * 1000);
+
+    if options.json {
+        print_json(options, &divergences);
+    } else {
+        print_unified(options, &divergences);
+    }
+
+    if divergences.is_empty() { 0 } else { 1 }
+}
+
+fn print_unified(options: &DiffOptions, divergences: &[Divergence]) {
+    println!("{}--- {}{}", Colors::RED, options.file_a.display(), Colors::RESET);
+    println!("{}+++ {}{}", Colors::GREEN, options.file_b.display(), Colors::RESET);
+
+    for divergence in divergences {
+        match divergence {
+            Divergence::OnlyInA(event) => {
+                println!("{}- #{} {}{}", Colors::RED, event.seq, Value::Object(event_fields(event)), Colors::RESET);
+            },
+            Divergence::OnlyInB(event) => {
+                println!("{}+ #{} {}{}", Colors::GREEN, event.seq, Value::Object(event_fields(event)), Colors::RESET);
+            },
+            Divergence::Changed { seq, changes } => {
+                println!("{}~ #{}{}", Colors::YELLOW, seq, Colors::RESET);
+                for change in changes {
+                    println!("{}-   {}: {}{}", Colors::RED, change.field, change.a, Colors::RESET);
+                    println!("{}+   {}: {}{}", Colors::GREEN, change.field, change.b, Colors::RESET);
+                }
+            },
+        }
+    }
+
+    if divergences.is_empty() {
+        println!("{}Recordings match{} (timestamp tolerance: {} ms)",
+            Colors::GREEN, Colors::RESET, options.timestamp_tolerance_ms);
+    }
+}
+
+fn print_json(options: &DiffOptions, divergences: &[Divergence]) {
+    let mut only_in_a = Vec::new();
+    let mut only_in_b = Vec::new();
+    let mut changed = Vec::new();
+
+    for divergence in divergences {
+        match divergence {
+            Divergence::OnlyInA(event) => only_in_a.push(json!(event)),
+            Divergence::OnlyInB(event) => only_in_b.push(json!(event)),
+            Divergence::Changed { seq, changes } => {
+                let fields: Vec<Value> = changes.iter()
+                    .map(|c| json!({ "field": c.field, "a": c.a, "b": c.b }))
+                    .collect();
+                changed.push(json!({ "seq": seq, "fields": fields }));
+            },
+        }
+    }
+
+    let report = json!({
+        "file_a": options.file_a.display().to_string(),
+        "file_b": options.file_b.display().to_string(),
+        "timestamp_tolerance_ms": options.timestamp_tolerance_ms,
+        "only_in_a": only_in_a,
+        "only_in_b": only_in_b,
+        "changed": changed,
+    });
+    println!("{}", report);
+}
@@ -0,0 +1,133 @@
+use std::ffi::{CStr, CString};
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+
+use crate::Colors;
+
+const INPUT_DIR: &str = "/dev/input";
+const INPUT_GROUP: &str = "input";
+
+// What happened when we tried to open the input devices
+pub enum Probe {
+    Ok,
+    NoDevices,
+    Denied { device: PathBuf, group: Option<String> },
+    Failed { device: PathBuf, error: io::Error },
+}
+
+// Try to open the first /dev/input/event* node the way libinput would
+pub fn probe() -> Probe {
+    let mut devices: Vec<PathBuf> = match fs::read_dir(INPUT_DIR) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("event")))
+            .collect(),
+        Err(_) => return Probe::NoDevices,
+    };
+    devices.sort();
+
+    let Some(device) = devices.into_iter().next() else {
+        return Probe::NoDevices;
+    };
+
+    match File::open(&device) {
+        Ok(_) => Probe::Ok,
+        Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+            let group = fs::metadata(&device).ok().and_then(|meta| group_name(meta.gid()));
+            Probe::Denied { device, group }
+        },
+        Err(error) => Probe::Failed { device, error },
+    }
+}
+
+// Print the probe result with remediation steps; returns true if devices are readable
+pub fn report(probe: &Probe) -> bool {
+    match probe {
+        Probe::Ok => {
+            println!("{}✅ Input devices are readable{}", Colors::GREEN, Colors::RESET);
+            true
+        },
+        Probe::NoDevices => {
+            eprintln!("{}⚠️ No {}/event* devices found{}", Colors::YELLOW, INPUT_DIR, Colors::RESET);
+            eprintln!("   Is this a container or a system without evdev? libinput needs access to {}.", INPUT_DIR);
+            false
+        },
+        Probe::Failed { device, error } => {
+            eprintln!("{}error: cannot open {}: {}{}", Colors::RED, device.display(), error, Colors::RESET);
+            false
+        },
+        Probe::Denied { device, group } => {
+            let user = user_name().unwrap_or_else(|| "$USER".to_string());
+            let groups = user_groups();
+            let device_group = group.as_deref().unwrap_or(INPUT_GROUP);
+
+            eprintln!("{}{}🔒 Permission denied opening {}{}", Colors::RED, Colors::BOLD, device.display(), Colors::RESET);
+            eprintln!("   Device group:  {}", device_group);
+            eprintln!("   You are:       {}", user);
+            eprintln!("   Your groups:   {}", if groups.is_empty() { "(none)".to_string() } else { groups.join(", ") });
+            eprintln!();
+
+            if groups.iter().any(|name| name == device_group) {
+                // In the group on paper, but this login session predates the change
+                eprintln!("{}You are in the '{}' group, but this session doesn't have it yet.{}",
+                    Colors::YELLOW, device_group, Colors::RESET);
+                eprintln!("Log out and back in (or run {}newgrp {}{}) and try again.",
+                    Colors::BOLD, device_group, Colors::RESET);
+            } else {
+                eprintln!("{}You are not in the '{}' group. To fix it, run:{}", Colors::YELLOW, device_group, Colors::RESET);
+                eprintln!();
+                eprintln!("    {}sudo usermod -aG {} {}{}", Colors::BOLD, device_group, user, Colors::RESET);
+                eprintln!();
+                eprintln!("then log out and back in. Alternatively, run RustKey with sudo.");
+            }
+            false
+        },
+    }
+}
+
+fn user_name() -> Option<String> {
+    let passwd = unsafe { libc::getpwuid(libc::geteuid()) };
+    if passwd.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr((*passwd).pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+// Groups from the user database, so a fresh `usermod` shows up even before re-login
+fn user_groups() -> Vec<String> {
+    let Some(user) = user_name() else {
+        return Vec::new();
+    };
+    let Ok(c_user) = CString::new(user) else {
+        return Vec::new();
+    };
+    let primary = unsafe { libc::getegid() };
+
+    let mut count: libc::c_int = 32;
+    let mut gids: Vec<libc::gid_t> = vec![0; count as usize];
+    loop {
+        let result = unsafe { libc::getgrouplist(c_user.as_ptr(), primary, gids.as_mut_ptr(), &mut count) };
+        if result >= 0 {
+            gids.truncate(count as usize);
+            break;
+        }
+        // `count` now holds the required size
+        gids.resize(count as usize, 0);
+    }
+
+    gids.into_iter().filter_map(group_name).collect()
+}
+
+fn group_name(gid: libc::gid_t) -> Option<String> {
+    let group = unsafe { libc::getgrgid(gid) };
+    if group.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr((*group).gr_name) };
+    Some(name.to_string_lossy().into_owned())
+}
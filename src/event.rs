@@ -0,0 +1,302 @@
+use input::event::gesture::{
+    GestureEndEvent, GestureEventCoordinates, GestureEventTrait, GestureHoldEvent, GesturePinchEvent,
+    GesturePinchEventTrait, GestureSwipeEvent,
+};
+use input::event::keyboard::{KeyState, KeyboardEventTrait};
+use input::event::pointer::{Axis, ButtonState, PointerEventTrait, PointerScrollEvent};
+use input::event::switch::SwitchEventTrait;
+use input::event::tablet_pad::TabletPadEventTrait;
+use input::event::tablet_tool::TabletToolEventTrait;
+use input::event::touch::{TouchEventPosition, TouchEventSlot, TouchEventTrait};
+use input::event::{DeviceEvent, EventTrait, GestureEvent, KeyboardEvent, PointerEvent, TouchEvent};
+use input::Event;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::hotplug::DeviceInfo;
+use crate::tablet_pad;
+
+static DEBUG_UNKNOWN: AtomicBool = AtomicBool::new(false);
+// Screen size for absolute positions; 0 keeps libinput's millimeters
+static SCREEN_WIDTH: AtomicU32 = AtomicU32::new(0);
+static SCREEN_HEIGHT: AtomicU32 = AtomicU32::new(0);
+
+// Where a scroll event came from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollSource {
+    Wheel,
+    Finger,
+    Continuous,
+}
+
+// A libinput event decoded into plain data, so it can be printed, recorded and
+// compared without holding on to the libinput context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputEvent {
+    // `info` is missing from binary recordings and older .rkey files
+    DeviceAdded {
+        device: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        info: Option<DeviceInfo>,
+    },
+    DeviceRemoved {
+        device: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        info: Option<DeviceInfo>,
+    },
+    Key { code: u32, pressed: bool },
+    PointerMotion { dx: f64, dy: f64 },
+    PointerMotionAbsolute { x: f64, y: f64 },
+    PointerButton { button: u32, pressed: bool },
+    // Wheel values are v120-normalized, finger/continuous values are in pointer units
+    Scroll { source: ScrollSource, horizontal: f64, vertical: f64 },
+    // Touchscreen contacts: `slot` is the seat-wide slot (all but Frame), the
+    // position is in mm from the top-left corner (Down and Motion only)
+    Touch {
+        kind: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        slot: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        x: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        y: Option<f64>,
+    },
+    // Touchpad gestures as libinput recognizes them. `dx`/`dy` are the finger
+    // centroid's motion since the last update, `scale` a pinch's spread
+    // relative to its start, `angle` its rotation in degrees since the last
+    // update (clockwise positive); each only on the events that have it.
+    // Missing from binary recordings and older .rkey files.
+    Gesture {
+        kind: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        finger_count: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dx: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dy: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scale: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        angle: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cancelled: Option<bool>,
+    },
+    // Derived from the touch slots of a device that also moves the pointer
+    // (see `touchpad`), emitted when a touch frame changes the finger count
+    TouchpadFingerCountChange { previous: u32, count: u32 },
+    Tablet,
+    // Tablet pad controls (see `tablet_pad`). Ring positions are degrees,
+    // strip positions 0..1; None when the finger left the ring or strip.
+    PadButton { button: u32, pressed: bool, mode: u32, group: u32 },
+    PadRing { number: u32, position: Option<f64>, mode: u32 },
+    PadStrip { number: u32, position: Option<f64>, mode: u32 },
+    Switch,
+    // Anything not decoded above; `source` is the libinput event family (device, keyboard, pointer, ...),
+    // `debug` the event's full Debug dump with --debug-unknown
+    Other {
+        source: String,
+        kind: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        debug: Option<String>,
+    },
+}
+
+// A decoded event together with its libinput timestamp
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimedEvent {
+    pub time_usec: u64,
+    #[serde(flatten)]
+    pub event: InputEvent,
+    // Fingers down on the touchpad that produced this pointer event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingers: Option<u32>,
+    // Kernel name of the device's event node, e.g. "event3"; missing from
+    // binary recordings, older .rkey files and synthetic events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sysname: Option<String>,
+    // The udev seat it came from; only set with --all-seats
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seat: Option<String>,
+}
+
+// Line format of `--format json`: the event's libinput timestamp
+// (CLOCK_MONOTONIC, microseconds), the time since the previous event and
+// since the start, and the focused window's title with --correlate-window
+#[derive(Debug, Serialize)]
+pub struct JsonEvent<'a> {
+    pub time_usec: u64,
+    pub delta_usec: u64,
+    pub elapsed_usec: u64,
+    #[serde(flatten)]
+    pub event: &'a InputEvent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingers: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seat: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<&'a str>,
+    // Action mapped to the gesture a gesture end event completes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapped_action: Option<&'a str>,
+}
+
+// Decode a libinput event. Events that carry no timestamp of their own
+// (device notifications) use `fallback_usec` instead.
+pub fn decode(event: &Event, fallback_usec: u64) -> TimedEvent {
+    let sysname = Some(event.device().sysname().to_string());
+    let (time_usec, event) = match event {
+        Event::Device(device_event) => {
+            let info = DeviceInfo::of(&device_event.device());
+            let device = info.name.clone();
+            match device_event {
+                DeviceEvent::Added(_) => (fallback_usec, InputEvent::DeviceAdded { device, info: Some(info) }),
+                DeviceEvent::Removed(_) => (fallback_usec, InputEvent::DeviceRemoved { device, info: Some(info) }),
+                other => (fallback_usec, other_event("device", "Other", other)),
+            }
+        },
+        Event::Keyboard(KeyboardEvent::Key(key_event)) => (
+            key_event.time_usec(),
+            InputEvent::Key {
+                code: key_event.key(),
+                pressed: key_event.key_state() == KeyState::Pressed,
+            },
+        ),
+        Event::Keyboard(other) => (fallback_usec, other_event("keyboard", "Other", other)),
+        Event::Pointer(pointer_event) => (pointer_event.time_usec(), decode_pointer(pointer_event)),
+        Event::Touch(touch_event) => (touch_event.time_usec(), decode_touch(touch_event)),
+        Event::Gesture(gesture_event) => (gesture_event.time_usec(), decode_gesture(gesture_event)),
+        Event::Tablet(tablet_event) => (tablet_event.time_usec(), InputEvent::Tablet),
+        Event::TabletPad(pad_event) => (pad_event.time_usec(), tablet_pad::decode(pad_event)),
+        Event::Switch(switch_event) => (switch_event.time_usec(), InputEvent::Switch),
+        other => (fallback_usec, other_event("event", &variant_name(other), other)),
+    };
+
+    TimedEvent { time_usec, event, fingers: None, sysname, seat: None }
+}
+
+fn decode_pointer(pointer_event: &PointerEvent) -> InputEvent {
+    match pointer_event {
+        PointerEvent::Motion(motion) => InputEvent::PointerMotion {
+            dx: motion.dx(),
+            dy: motion.dy(),
+        },
+        PointerEvent::MotionAbsolute(abs_motion) => {
+            let (x, y) = match screen() {
+                Some((width, height)) => (abs_motion.absolute_x_transformed(width), abs_motion.absolute_y_transformed(height)),
+                None => (abs_motion.absolute_x(), abs_motion.absolute_y()),
+            };
+            InputEvent::PointerMotionAbsolute { x, y }
+        },
+        PointerEvent::Button(button) => InputEvent::PointerButton {
+            button: button.button(),
+            pressed: button.button_state() == ButtonState::Pressed,
+        },
+        PointerEvent::ScrollWheel(scroll) => InputEvent::Scroll {
+            source: ScrollSource::Wheel,
+            horizontal: axis_or_zero(scroll.has_axis(Axis::Horizontal), || scroll.scroll_value_v120(Axis::Horizontal)),
+            vertical: axis_or_zero(scroll.has_axis(Axis::Vertical), || scroll.scroll_value_v120(Axis::Vertical)),
+        },
+        PointerEvent::ScrollFinger(scroll) => InputEvent::Scroll {
+            source: ScrollSource::Finger,
+            horizontal: axis_or_zero(scroll.has_axis(Axis::Horizontal), || scroll.scroll_value(Axis::Horizontal)),
+            vertical: axis_or_zero(scroll.has_axis(Axis::Vertical), || scroll.scroll_value(Axis::Vertical)),
+        },
+        PointerEvent::ScrollContinuous(scroll) => InputEvent::Scroll {
+            source: ScrollSource::Continuous,
+            horizontal: axis_or_zero(scroll.has_axis(Axis::Horizontal), || scroll.scroll_value(Axis::Horizontal)),
+            vertical: axis_or_zero(scroll.has_axis(Axis::Vertical), || scroll.scroll_value(Axis::Vertical)),
+        },
+        other => other_event("pointer", &variant_name(other), other),
+    }
+}
+
+pub fn other_event<T: std::fmt::Debug>(source: &str, kind: &str, event: &T) -> InputEvent {
+    let debug = DEBUG_UNKNOWN.load(Ordering::Relaxed).then(|| format!("{:?}", event));
+    InputEvent::Other { source: source.to_string(), kind: kind.to_string(), debug }
+}
+
+// Keep the full Debug dump of undecoded events (--debug-unknown), for
+// working out how to decode new event types
+pub fn set_debug_unknown(enabled: bool) {
+    DEBUG_UNKNOWN.store(enabled, Ordering::Relaxed);
+}
+
+// Report absolute pointer positions in pixels of a screen this size
+// (--screen, or the detected resolution) instead of millimeters
+pub fn set_screen(size: Option<(u32, u32)>) {
+    let (width, height) = size.unwrap_or((0, 0));
+    SCREEN_WIDTH.store(width, Ordering::Relaxed);
+    SCREEN_HEIGHT.store(height, Ordering::Relaxed);
+}
+
+fn screen() -> Option<(u32, u32)> {
+    let size = (SCREEN_WIDTH.load(Ordering::Relaxed), SCREEN_HEIGHT.load(Ordering::Relaxed));
+    (size.0 > 0 && size.1 > 0).then_some(size)
+}
+
+// libinput only guarantees a value for axes the event actually has
+fn axis_or_zero<F: FnOnce() -> f64>(has_axis: bool, value: F) -> f64 {
+    if has_axis { value() } else { 0.0 }
+}
+
+fn decode_touch(touch_event: &TouchEvent) -> InputEvent {
+    let (kind, slot, position) = match touch_event {
+        TouchEvent::Down(down) => ("Down", Some(down.seat_slot()), Some((down.x(), down.y()))),
+        TouchEvent::Up(up) => ("Up", Some(up.seat_slot()), None),
+        TouchEvent::Motion(motion) => ("Motion", Some(motion.seat_slot()), Some((motion.x(), motion.y()))),
+        TouchEvent::Cancel(cancel) => ("Cancel", Some(cancel.seat_slot()), None),
+        TouchEvent::Frame(_) => ("Frame", None, None),
+        _ => ("Other", None, None),
+    };
+    InputEvent::Touch {
+        kind: kind.to_string(),
+        slot,
+        x: position.map(|(x, _)| x),
+        y: position.map(|(_, y)| y),
+    }
+}
+
+fn decode_gesture(gesture_event: &GestureEvent) -> InputEvent {
+    let (kind, delta, scale, angle, cancelled) = match gesture_event {
+        GestureEvent::Swipe(GestureSwipeEvent::Begin(_)) => ("SwipeBegin", None, None, None, None),
+        GestureEvent::Swipe(GestureSwipeEvent::Update(update)) => ("SwipeUpdate", Some((update.dx(), update.dy())), None, None, None),
+        GestureEvent::Swipe(GestureSwipeEvent::End(end)) => ("SwipeEnd", None, None, None, Some(end.cancelled())),
+        GestureEvent::Pinch(GesturePinchEvent::Begin(begin)) => ("PinchBegin", None, Some(begin.scale()), None, None),
+        GestureEvent::Pinch(GesturePinchEvent::Update(update)) => {
+            ("PinchUpdate", Some((update.dx(), update.dy())), Some(update.scale()), Some(update.angle_delta()), None)
+        },
+        GestureEvent::Pinch(GesturePinchEvent::End(end)) => ("PinchEnd", None, Some(end.scale()), None, Some(end.cancelled())),
+        GestureEvent::Hold(GestureHoldEvent::Begin(_)) => ("HoldBegin", None, None, None, None),
+        GestureEvent::Hold(GestureHoldEvent::End(end)) => ("HoldEnd", None, None, None, Some(end.cancelled())),
+        _ => ("Other", None, None, None, None),
+    };
+    InputEvent::Gesture {
+        kind: kind.to_string(),
+        finger_count: u32::try_from(gesture_event.finger_count()).ok(),
+        dx: delta.map(|(dx, _)| dx),
+        dy: delta.map(|(_, dy)| dy),
+        scale,
+        angle,
+        cancelled,
+    }
+}
+
+// Extract the variant name from a Debug representation such as `Axis(PointerAxisEvent @0x...)`
+fn variant_name<T: std::fmt::Debug>(value: &T) -> String {
+    let debug_str = format!("{:?}", value);
+    match debug_str.find('(') {
+        Some(end) => debug_str[..end].to_string(),
+        None => debug_str,
+    }
+}
+
+// Current CLOCK_MONOTONIC time in microseconds, the same clock libinput timestamps use
+pub fn monotonic_usec() -> u64 {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts);
+    }
+    ts.tv_sec as u64 * 1_000_000 + ts.tv_nsec as u64 / 1000
+}
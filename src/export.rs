@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::key_freq::{KeyFrequencyMap, KEYCODE_SLOTS};
+use crate::keys::{self, key_name};
+use crate::timefmt;
+use crate::Colors;
+
+// Formats `rustkey export` can write
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    KeyloggerStats,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "keylogger-stats" => Some(ExportFormat::KeyloggerStats),
+            _ => None,
+        }
+    }
+}
+
+// Options for `rustkey export --format <format> --input <file>... --output <file>`
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub inputs: Vec<PathBuf>,
+    pub output: Option<PathBuf>,
+}
+
+// The keylogger-stats file format:
+//   {"keys": {"A": 1234, "ENTER": 567}, "total": 98765, "session_start": "2024-05-01T13:37:00Z"}
+#[derive(Serialize, Deserialize)]
+struct KeyloggerStats {
+    keys: Map<String, Value>,
+    total: u64,
+    session_start: String,
+}
+
+// A JSON line as far as the export cares: a key event or anything else,
+// including the records --format json writes next to the events
+// (typing_mode_change, latency_sample, ...)
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum EventLine {
+    Key { code: u32, pressed: bool },
+    #[serde(other)]
+    Other,
+}
+
+// Keys are written by name where the name leads back to the same keycode,
+// otherwise (unnamed or duplicate names) as KEY_<code>
+pub fn export_keylogger_stats_json(freq: &KeyFrequencyMap, session_start: SystemTime) -> String {
+    let mut keys = Map::new();
+    let mut total: u64 = 0;
+    for (code, count) in freq.top_n(usize::MAX) {
+        let name = key_name(code);
+        let name = if keys::key_code_by_name(name) == Some(code) { name.to_string() } else { format!("KEY_{}", code) };
+        keys.insert(name, Value::from(count));
+        total = total.saturating_add(count);
+    }
+
+    let stats = KeyloggerStats { keys, total, session_start: timefmt::iso8601(session_start) };
+    serde_json::to_string_pretty(&stats).unwrap_or_default()
+}
+
+// Read a keylogger-stats file back into press counts, so stats from several
+// tools can be merged
+pub fn import_keylogger_stats_json(json: &str) -> Result<KeyFrequencyMap, String> {
+    let stats: KeyloggerStats = serde_json::from_str(json).map_err(|err| err.to_string())?;
+    let mut freq = KeyFrequencyMap::new();
+    for (name, count) in &stats.keys {
+        let code = name.strip_prefix("KEY_")
+            .and_then(|code| code.parse().ok())
+            .or_else(|| keys::key_code_by_name(name))
+            .filter(|&code| (code as usize) < KEYCODE_SLOTS)
+            .ok_or_else(|| format!("unknown key '{}'", name))?;
+        let count = count.as_u64().ok_or_else(|| format!("invalid count for key '{}'", name))?;
+        freq.add(code, count);
+    }
+    Ok(freq)
+}
+
+// Convert and merge inputs; returns the process exit code
+pub fn run(options: &ExportOptions) -> i32 {
+    match export(options) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+            1
+        },
+    }
+}
+
+fn export(options: &ExportOptions) -> Result<(), String> {
+    let mut freq = KeyFrequencyMap::new();
+    for path in &options.inputs {
+        let text = fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        load_into(&mut freq, &text).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+
+    // The events carry monotonic timestamps only, so the first input's file
+    // time stands in for the session start
+    let session_start = options.inputs.first().map_or(SystemTime::now(), |path| file_time(path));
+    let json = match options.format {
+        ExportFormat::KeyloggerStats => export_keylogger_stats_json(&freq, session_start),
+    };
+
+    match &options.output {
+        Some(path) => fs::write(path, json + "\n").map_err(|err| format!("cannot write {}: {}", path.display(), err)),
+        None => {
+            println!("{}", json);
+            Ok(())
+        },
+    }
+}
+
+// An input is either a keylogger-stats file or JSON lines of events
+// (an .rkey recording or `--format json` output)
+fn load_into(freq: &mut KeyFrequencyMap, text: &str) -> Result<(), String> {
+    if text.trim_start().starts_with('{') && serde_json::from_str::<KeyloggerStats>(text).is_ok() {
+        let imported = import_keylogger_stats_json(text)?;
+        for (code, count) in imported.top_n(usize::MAX) {
+            freq.add(code, count);
+        }
+        return Ok(());
+    }
+
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: EventLine = serde_json::from_str(line).map_err(|err| format!("line {}: {}", index + 1, err))?;
+        if let EventLine::Key { code, pressed: true } = event {
+            freq.increment(code);
+        }
+    }
+    Ok(())
+}
+
+fn file_time(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.created().or_else(|_| metadata.modified()))
+        .unwrap_or_else(|_| SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn round_trip_is_identity(presses in prop::collection::vec((0..KEYCODE_SLOTS as u32, 1..u64::MAX), 0..64)) {
+            let mut freq = KeyFrequencyMap::new();
+            for (code, count) in presses {
+                freq.add(code, count);
+            }
+
+            let json = export_keylogger_stats_json(&freq, SystemTime::UNIX_EPOCH);
+            prop_assert_eq!(import_keylogger_stats_json(&json).unwrap(), freq);
+        }
+    }
+
+    #[test]
+    fn named_keys_use_names() {
+        let mut freq = KeyFrequencyMap::new();
+        freq.add(30, 3);
+        freq.add(28, 2);
+        freq.add(2, 4);
+        freq.add(127, 1);
+        // An unnamed key falls back to its code
+        freq.add(600, 1);
+
+        let value: Value = serde_json::from_str(&export_keylogger_stats_json(&freq, SystemTime::UNIX_EPOCH)).unwrap();
+        assert_eq!(value["keys"]["A"], 3);
+        assert_eq!(value["keys"]["ENTER"], 2);
+        assert_eq!(value["keys"]["1"], 4);
+        assert_eq!(value["keys"]["COMPOSE"], 1);
+        assert_eq!(value["keys"]["KEY_600"], 1);
+        assert_eq!(value["total"], 11);
+        assert_eq!(value["session_start"], "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn huge_counts_saturate_and_out_of_range_codes_are_rejected() {
+        let json = r#"{"keys": {"A": 18446744073709551615, "KEY_30": 5}, "total": 0, "session_start": ""}"#;
+        let freq = import_keylogger_stats_json(json).unwrap();
+        assert_eq!(freq.top_n(1), vec![(30, u64::MAX)]);
+        let value: Value = serde_json::from_str(&export_keylogger_stats_json(&freq, SystemTime::UNIX_EPOCH)).unwrap();
+        assert_eq!(value["total"], u64::MAX);
+
+        let json = r#"{"keys": {"KEY_768": 1}, "total": 1, "session_start": ""}"#;
+        assert_eq!(import_keylogger_stats_json(json).unwrap_err(), "unknown key 'KEY_768'");
+    }
+}
@@ -0,0 +1,83 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use nix::errno::Errno;
+use nix::sys::stat::Mode;
+
+// Named pipe for shell pipelines. Writes never block: while no reader is
+// connected (or the reader falls behind) lines are dropped, not buffered.
+pub struct Fifo {
+    path: PathBuf,
+    // Only a pipe we made ourselves is removed on exit
+    created: bool,
+    writer: Option<File>,
+}
+
+impl Fifo {
+    // Create the pipe (an existing one is reused); with `wait_for_reader`,
+    // block until something opens it for reading
+    pub fn create(path: &Path, wait_for_reader: bool) -> io::Result<Fifo> {
+        let created = match nix::unistd::mkfifo(path, Mode::S_IRUSR | Mode::S_IWUSR) {
+            Ok(()) => true,
+            Err(Errno::EEXIST) if fs::metadata(path)?.file_type().is_fifo() => false,
+            Err(Errno::EEXIST) => {
+                return Err(io::Error::new(io::ErrorKind::AlreadyExists, "file exists and is not a FIFO"));
+            },
+            Err(errno) => return Err(errno.into()),
+        };
+        let mut fifo = Fifo { path: path.to_path_buf(), created, writer: None };
+
+        if wait_for_reader {
+            // Opening for writing blocks until a reader shows up
+            let file = OpenOptions::new().write(true).open(path)?;
+            let fd = file.as_raw_fd();
+            if unsafe { libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            fifo.writer = Some(file);
+        }
+        Ok(fifo)
+    }
+
+    pub fn write_line(&mut self, line: &str) {
+        if self.writer.is_none() {
+            self.writer = self.connect();
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+
+        // Lines are shorter than PIPE_BUF, so each write is all or nothing
+        match writer.write(format!("{}\n", line).as_bytes()) {
+            Ok(_) => {},
+            // Pipe full: the reader is behind, drop the line
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {},
+            // The reader went away; reconnect on a later line
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => self.writer = None,
+            Err(err) => {
+                eprintln!("fifo write failed: {}", err);
+                self.writer = None;
+            },
+        }
+    }
+
+    // A non-blocking open fails with ENXIO while nobody is reading
+    fn connect(&self) -> Option<File> {
+        OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&self.path)
+            .ok()
+    }
+}
+
+impl Drop for Fifo {
+    fn drop(&mut self) {
+        if self.created {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
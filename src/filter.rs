@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::event::{InputEvent, TimedEvent};
+
+// Event types a filter can let through
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EventType {
+    Keyboard,
+    // Motion, buttons and scrolling together
+    Pointer,
+    Motion,
+    Button,
+    Scroll,
+    Touch,
+    Gesture,
+    // Tablet tools and pads
+    Tablet,
+    Switch,
+}
+
+impl EventType {
+    const NAMES: &'static str = "keyboard, pointer, motion, button, scroll, touch, gesture, tablet, switch";
+
+    fn parse(name: &str) -> Option<EventType> {
+        match name.to_ascii_lowercase().as_str() {
+            "keyboard" => Some(EventType::Keyboard),
+            "pointer" => Some(EventType::Pointer),
+            "motion" => Some(EventType::Motion),
+            "button" => Some(EventType::Button),
+            "scroll" => Some(EventType::Scroll),
+            "touch" => Some(EventType::Touch),
+            "gesture" => Some(EventType::Gesture),
+            "tablet" => Some(EventType::Tablet),
+            "switch" => Some(EventType::Switch),
+            _ => None,
+        }
+    }
+
+    fn matches(self, event: &InputEvent) -> bool {
+        let motion = matches!(event, InputEvent::PointerMotion { .. } | InputEvent::PointerMotionAbsolute { .. });
+        let button = matches!(event, InputEvent::PointerButton { .. });
+        let scroll = matches!(event, InputEvent::Scroll { .. });
+        match self {
+            EventType::Keyboard => matches!(event, InputEvent::Key { .. }),
+            EventType::Pointer => motion || button || scroll || matches!(event, InputEvent::TouchpadFingerCountChange { .. }),
+            EventType::Motion => motion,
+            EventType::Button => button,
+            EventType::Scroll => scroll,
+            EventType::Touch => matches!(event, InputEvent::Touch { .. }),
+            EventType::Gesture => matches!(event, InputEvent::Gesture { .. }),
+            EventType::Tablet => matches!(event, InputEvent::Tablet | InputEvent::PadButton { .. }
+                | InputEvent::PadRing { .. } | InputEvent::PadStrip { .. }),
+            EventType::Switch => matches!(event, InputEvent::Switch),
+        }
+    }
+}
+
+// --filter-config file, e.g.
+//
+//   types = ["keyboard", "pointer"]
+//
+//   [device_filters."keyboard"]
+//   types = ["keyboard"]
+//
+//   [device_filters."wacom"]
+//   types = ["tablet"]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilterFile {
+    types: Option<Vec<String>>,
+    #[serde(default)]
+    device_filters: BTreeMap<String, FilterTable>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilterTable {
+    types: Vec<String>,
+}
+
+// The event types to keep; None keeps everything
+#[derive(Debug, Default)]
+pub struct Filter {
+    types: Option<Vec<EventType>>,
+}
+
+impl Filter {
+    fn parse(names: &[String]) -> Result<Self, String> {
+        let types = names.iter()
+            .map(|name| EventType::parse(name).ok_or_else(|| format!("unknown event type '{}' (expected {})", name, EventType::NAMES)))
+            .collect::<Result<_, _>>()?;
+        Ok(Filter { types: Some(types) })
+    }
+
+    pub fn allows(&self, event: &InputEvent) -> bool {
+        self.types.as_ref().is_none_or(|types| types.iter().any(|each| each.matches(event)))
+    }
+}
+
+// A Filter per device, picked by a case-insensitive substring of the device
+// name (the longest one that matches, if several do), and the global filter
+// for every other device. Device added and removed events always pass, and
+// are what tie an event's sysname to its device's name.
+#[derive(Debug, Default)]
+pub struct PerDeviceFilter {
+    global: Filter,
+    // Lower-cased name substrings
+    devices: Vec<(String, Filter)>,
+    // sysname -> index into `devices`, or None for the global filter
+    assigned: HashMap<String, Option<usize>>,
+}
+
+impl PerDeviceFilter {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let file: FilterFile = toml::from_str(&text).map_err(|err| err.to_string())?;
+        let global = match &file.types {
+            Some(types) => Filter::parse(types)?,
+            None => Filter::default(),
+        };
+        let devices = file.device_filters.into_iter()
+            .map(|(name, table)| Ok((name.to_lowercase(), Filter::parse(&table.types)?)))
+            .collect::<Result<_, String>>()?;
+        Ok(PerDeviceFilter { global, devices, assigned: HashMap::new() })
+    }
+
+    pub fn allows(&mut self, timed: &TimedEvent) -> bool {
+        match &timed.event {
+            InputEvent::DeviceAdded { device, .. } => {
+                if let Some(sysname) = &timed.sysname {
+                    let index = self.device_filter(device);
+                    self.assigned.insert(sysname.clone(), index);
+                }
+                true
+            },
+            InputEvent::DeviceRemoved { .. } => true,
+            event => {
+                let index = timed.sysname.as_ref().and_then(|sysname| self.assigned.get(sysname).copied().flatten());
+                index.map_or(&self.global, |index| &self.devices[index].1).allows(event)
+            },
+        }
+    }
+
+    fn device_filter(&self, name: &str) -> Option<usize> {
+        let name = name.to_lowercase();
+        self.devices.iter().enumerate()
+            .filter(|(_, (pattern, _))| name.contains(pattern.as_str()))
+            .max_by_key(|(_, (pattern, _))| pattern.len())
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(event: InputEvent, sysname: &str) -> TimedEvent {
+        TimedEvent { time_usec: 0, event, fingers: None, sysname: Some(sysname.to_string()), seat: None }
+    }
+
+    #[test]
+    fn devices_get_their_own_filter() {
+        let file: FilterFile = toml::from_str(r#"
+            types = ["keyboard"]
+            [device_filters."mouse"]
+            types = ["motion"]
+            [device_filters."gaming mouse"]
+            types = ["button"]
+        "#).unwrap();
+        let mut filter = PerDeviceFilter {
+            global: Filter::parse(file.types.as_ref().unwrap()).unwrap(),
+            devices: file.device_filters.into_iter().map(|(name, table)| (name, Filter::parse(&table.types).unwrap())).collect(),
+            assigned: HashMap::new(),
+        };
+        for (name, sysname) in [("Logitech USB Mouse", "event1"), ("Gaming Mouse", "event2"), ("AT Keyboard", "event3")] {
+            assert!(filter.allows(&timed(InputEvent::DeviceAdded { device: name.to_string(), info: None }, sysname)));
+        }
+
+        let motion = || InputEvent::PointerMotion { dx: 1.0, dy: 0.0 };
+        let click = || InputEvent::PointerButton { button: 272, pressed: true };
+        let key = || InputEvent::Key { code: 30, pressed: true };
+        assert!(filter.allows(&timed(motion(), "event1")));
+        assert!(!filter.allows(&timed(click(), "event1")));
+        assert!(filter.allows(&timed(click(), "event2")));
+        assert!(!filter.allows(&timed(motion(), "event2")));
+        assert!(filter.allows(&timed(key(), "event3")));
+        assert!(!filter.allows(&timed(motion(), "event3")));
+        // Devices added before the filter saw them get the global one
+        assert!(!filter.allows(&timed(motion(), "event9")));
+
+        assert!(Filter::parse(&["wheel".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use crate::button_names::button_name;
+use crate::event::{InputEvent, ScrollSource, TimedEvent};
+use crate::key_names::kernel_key_name;
+use crate::keys::key_name;
+
+// libinput reports wheel scrolling as 15 pointer units per notch alongside
+// the v120 value
+const UNITS_PER_NOTCH: f64 = 15.0;
+const V120_PER_NOTCH: f64 = 120.0;
+
+// From <linux/input-event-codes.h>
+const EV_KEY: u32 = 1;
+const EV_REL: u32 = 2;
+const REL_HWHEEL: u32 = 0x06;
+const REL_WHEEL: u32 = 0x08;
+const REL_WHEEL_HI_RES: u32 = 0x0b;
+const REL_HWHEEL_HI_RES: u32 = 0x0c;
+// evtest lines are padded to this width before the name column
+const EVTEST_WIDTH: usize = 72;
+
+// Capability letters in the order `libinput debug-events` prints them
+const CAPABILITY_LETTERS: [(&str, &str); 7] = [
+    ("keyboard", "k"), ("pointer", "p"), ("touch", "t"), ("gesture", "g"),
+    ("tablet_tool", "T"), ("tablet_pad", "P"), ("switch", "S"),
+];
+
+// The formats that mimic another tool, one line (or a few) per event
+pub enum CompatFormatter {
+    LibinputDebug(LibinputDebugFormatter),
+    Evtest(EvtestFormatter),
+}
+
+impl CompatFormatter {
+    pub fn format(&mut self, timed: &TimedEvent) -> Option<String> {
+        match self {
+            CompatFormatter::LibinputDebug(formatter) => formatter.format(timed),
+            CompatFormatter::Evtest(formatter) => formatter.format(timed),
+        }
+    }
+}
+
+// --format libinput-debug: lines in the layout of `libinput debug-events
+// --show-keycodes`, so the two can be diffed. A header of the device's event
+// node (prefixed with '-' when it differs from the previous line's) and the
+// event type, then the time since the first event and the details:
+//
+//     -event3   KEYBOARD_KEY            +0.000s	KEY_A (30) pressed
+//     -event7   POINTER_MOTION          +0.105s	 -1.23/  0.45 ( -1.23/  0.45)
+//
+// Where rustkey doesn't keep what libinput prints, the line gets as close
+// as it can: motion repeats the accelerated delta as the unaccelerated one,
+// touch positions are only in mm, and device lines have no device group.
+#[derive(Default)]
+pub struct LibinputDebugFormatter {
+    start_usec: Option<u64>,
+    last_device: Option<String>,
+    // How many times each button is held across the seat
+    seat_buttons: HashMap<u32, u32>,
+}
+
+impl LibinputDebugFormatter {
+    // The line for an event; None for events libinput doesn't have
+    // (the touchpad finger count changes rustkey derives)
+    pub fn format(&mut self, timed: &TimedEvent) -> Option<String> {
+        let (kind, details) = self.describe(&timed.event)?;
+
+        let device = match (&timed.sysname, &timed.event) {
+            (Some(sysname), _) => sysname.as_str(),
+            // Recorded device events still have the sysfs path
+            (None, InputEvent::DeviceAdded { info: Some(info), .. } | InputEvent::DeviceRemoved { info: Some(info), .. })
+                if !info.sysfs_path.is_empty() => info.sysfs_path.rsplit('/').next().unwrap_or("unknown"),
+            (None, _) => "unknown",
+        };
+        let prefix = if self.last_device.as_deref() == Some(device) { ' ' } else { '-' };
+        self.last_device = Some(device.to_string());
+        let mut line = format!("{}{:<7}  {:<23} ", prefix, device, kind);
+
+        // Device notifications carry no time
+        if !matches!(timed.event, InputEvent::DeviceAdded { .. } | InputEvent::DeviceRemoved { .. }) {
+            let start_usec = *self.start_usec.get_or_insert(timed.time_usec);
+            let seconds = timed.time_usec.saturating_sub(start_usec) as f64 / 1_000_000.0;
+            line.push_str(&format!("{:+6.3}s\t", seconds));
+        }
+        line.push_str(&details);
+        Some(line.trim_end().to_string())
+    }
+
+    // Event type and details, without the device and time
+    pub fn describe(&mut self, event: &InputEvent) -> Option<(String, String)> {
+        let (kind, details) = match event {
+            InputEvent::DeviceAdded { device, info } | InputEvent::DeviceRemoved { device, info } => {
+                let kind = if matches!(event, InputEvent::DeviceAdded { .. }) { "DEVICE_ADDED" } else { "DEVICE_REMOVED" };
+                let details = match info {
+                    Some(info) => {
+                        let capabilities: String = CAPABILITY_LETTERS.iter()
+                            .filter(|(name, _)| info.capabilities.iter().any(|capability| capability == name))
+                            .map(|(_, letter)| *letter)
+                            .collect();
+                        format!("{:<33} {:>5} {:>7}  cap:{}", device, info.seat, "default", capabilities)
+                    },
+                    None => device.clone(),
+                };
+                (kind.to_string(), details)
+            },
+            InputEvent::Key { code, pressed } => (
+                "KEYBOARD_KEY".to_string(),
+                format!("{} ({}) {}", kernel_key_name(*code).unwrap_or("???"), code, state(*pressed)),
+            ),
+            InputEvent::PointerMotion { dx, dy } => (
+                "POINTER_MOTION".to_string(),
+                format!("{:6.2}/{:6.2} ({:6.2}/{:6.2})", dx, dy, dx, dy),
+            ),
+            InputEvent::PointerMotionAbsolute { x, y } => ("POINTER_MOTION_ABSOLUTE".to_string(), format!("{:6.2}/{:6.2}", x, y)),
+            InputEvent::PointerButton { button, pressed } => {
+                let count = self.seat_buttons.entry(*button).or_insert(0);
+                *count = if *pressed { *count + 1 } else { count.saturating_sub(1) };
+                ("POINTER_BUTTON".to_string(), format!("{} ({}) {}, seat count: {}", button_name(*button), button, state(*pressed), count))
+            },
+            InputEvent::Scroll { source, horizontal, vertical } => scroll(*source, *horizontal, *vertical),
+            InputEvent::Touch { kind, slot, x, y } => {
+                let details = match (slot, x, y) {
+                    (Some(slot), Some(x), Some(y)) => format!("{} ({}) {:5.2}/{:5.2}mm", slot, slot, x, y),
+                    (Some(slot), _, _) => format!("{} ({})", slot, slot),
+                    _ => String::new(),
+                };
+                (format!("TOUCH_{}", kind.to_uppercase()), details)
+            },
+            InputEvent::Gesture { kind, .. } => (gesture_kind(kind), String::new()),
+            InputEvent::TouchpadFingerCountChange { .. } => return None,
+            InputEvent::Tablet => ("TABLET_TOOL".to_string(), String::new()),
+            InputEvent::PadButton { button, pressed, mode, .. } => (
+                "TABLET_PAD_BUTTON".to_string(),
+                format!("{:3} {} (mode {})", button, state(*pressed), mode),
+            ),
+            InputEvent::PadRing { number, position, mode } => (
+                "TABLET_PAD_RING".to_string(),
+                format!("ring {} position {:.2} (source finger) (mode {})", number, position.unwrap_or(-1.0), mode),
+            ),
+            InputEvent::PadStrip { number, position, mode } => (
+                "TABLET_PAD_STRIP".to_string(),
+                format!("strip {} position {:.2} (source finger) (mode {})", number, position.unwrap_or(-1.0), mode),
+            ),
+            InputEvent::Switch => ("SWITCH_TOGGLE".to_string(), String::new()),
+            InputEvent::Other { source, kind, .. } => (format!("{}_{}", source, kind).to_uppercase(), String::new()),
+        };
+        Some((kind, details))
+    }
+}
+
+// --format evtest: lines in the layout of `evtest`, one per kernel event,
+// each frame closed by a SYN_REPORT, with the key name from our table as a
+// trailing comment:
+//
+//     Event: time 5.123456, type 1 (EV_KEY), code 30 (KEY_A), value 1     # A
+//     Event: time 5.123456, -------------- SYN_REPORT ------------
+//
+// Keys, buttons and wheel scrolling map back onto the raw events the kernel
+// sent. libinput has already processed everything else (accelerated
+// motion, touches in mm, gestures), so those get a line with the decoded
+// event in place of a made-up triple.
+#[derive(Default)]
+pub struct EvtestFormatter {
+    decoded: LibinputDebugFormatter,
+}
+
+impl EvtestFormatter {
+    pub fn format(&mut self, timed: &TimedEvent) -> Option<String> {
+        let time = format!("Event: time {}.{:06}", timed.time_usec / 1_000_000, timed.time_usec % 1_000_000);
+        let raw: Vec<(u32, u32, i64, Option<&str>)> = match &timed.event {
+            InputEvent::Key { code, pressed } => vec![(EV_KEY, *code, *pressed as i64, Some(key_name(*code)))],
+            InputEvent::PointerButton { button, pressed } => vec![(EV_KEY, *button, *pressed as i64, None)],
+            // libinput's v120 is positive downwards, REL_WHEEL upwards
+            InputEvent::Scroll { source: ScrollSource::Wheel, horizontal, vertical } => {
+                let mut raw = Vec::new();
+                if *vertical != 0.0 {
+                    raw.push((EV_REL, REL_WHEEL, (-vertical / V120_PER_NOTCH).round() as i64, None));
+                    raw.push((EV_REL, REL_WHEEL_HI_RES, -vertical.round() as i64, None));
+                }
+                if *horizontal != 0.0 {
+                    raw.push((EV_REL, REL_HWHEEL, (horizontal / V120_PER_NOTCH).round() as i64, None));
+                    raw.push((EV_REL, REL_HWHEEL_HI_RES, horizontal.round() as i64, None));
+                }
+                raw
+            },
+            InputEvent::TouchpadFingerCountChange { .. } => return None,
+            event => {
+                let (kind, details) = self.decoded.describe(event)?;
+                return Some(format!("{}, no raw event: {} {}", time, kind, details).trim_end().to_string());
+            },
+        };
+
+        let mut lines: Vec<String> = raw.into_iter().map(|(kind, code, value, name)| {
+            let line = format!("{}, type {} ({}), code {} ({}), value {}", time, kind, type_name(kind), code, code_name(kind, code), value);
+            match name {
+                Some(name) => format!("{:<width$} # {}", line, name, width = EVTEST_WIDTH),
+                None => line,
+            }
+        }).collect();
+        lines.push(format!("{}, -------------- SYN_REPORT ------------", time));
+        Some(lines.join("\n"))
+    }
+}
+
+fn type_name(kind: u32) -> &'static str {
+    match kind {
+        EV_KEY => "EV_KEY",
+        EV_REL => "EV_REL",
+        _ => "?",
+    }
+}
+
+fn code_name(kind: u32, code: u32) -> &'static str {
+    match (kind, code) {
+        (EV_KEY, code) => kernel_key_name(code).unwrap_or_else(|| button_name(code)),
+        (EV_REL, REL_HWHEEL) => "REL_HWHEEL",
+        (EV_REL, REL_WHEEL) => "REL_WHEEL",
+        (EV_REL, REL_WHEEL_HI_RES) => "REL_WHEEL_HI_RES",
+        (EV_REL, REL_HWHEEL_HI_RES) => "REL_HWHEEL_HI_RES",
+        _ => "?",
+    }
+}
+
+fn state(pressed: bool) -> &'static str {
+    if pressed { "pressed" } else { "released" }
+}
+
+// e.g. "vert 15.00/120.0* horiz 0.00/0.0 (wheel)"; the '*' marks an axis
+// the event has
+fn scroll(source: ScrollSource, horizontal: f64, vertical: f64) -> (String, String) {
+    let (kind, name) = match source {
+        ScrollSource::Wheel => ("POINTER_SCROLL_WHEEL", "wheel"),
+        ScrollSource::Finger => ("POINTER_SCROLL_FINGER", "finger"),
+        ScrollSource::Continuous => ("POINTER_SCROLL_CONTINUOUS", "continuous"),
+    };
+    // Wheel events are stored in v120 units, the others in pointer units
+    let axis = |value: f64| {
+        let (units, v120) = match source {
+            ScrollSource::Wheel => (value / V120_PER_NOTCH * UNITS_PER_NOTCH, value),
+            _ => (value, 0.0),
+        };
+        format!("{:.2}/{:.1}{}", units, v120, if value != 0.0 { "*" } else { "" })
+    };
+    (kind.to_string(), format!("vert {} horiz {} ({})", axis(vertical), axis(horizontal), name))
+}
+
+// "SwipeBegin" -> "GESTURE_SWIPE_BEGIN"
+fn gesture_kind(kind: &str) -> String {
+    let mut name = String::from("GESTURE");
+    for c in kind.chars() {
+        if c.is_ascii_uppercase() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(time_usec: u64, sysname: &str, event: InputEvent) -> TimedEvent {
+        TimedEvent { time_usec, event, fingers: None, sysname: Some(sysname.to_string()), seat: None }
+    }
+
+    #[test]
+    fn matches_debug_events_layout() {
+        let mut formatter = LibinputDebugFormatter::default();
+        let lines: Vec<String> = [
+            timed(5_000, "event3", InputEvent::Key { code: 30, pressed: true }),
+            timed(6_000, "event3", InputEvent::Key { code: 30, pressed: false }),
+            timed(7_000, "event7", InputEvent::PointerMotion { dx: -1.23, dy: 0.45 }),
+            timed(8_000, "event7", InputEvent::PointerButton { button: 272, pressed: true }),
+            timed(9_000, "event7", InputEvent::Scroll { source: ScrollSource::Wheel, horizontal: 0.0, vertical: 120.0 }),
+        ].iter().filter_map(|event| formatter.format(event)).collect();
+
+        assert_eq!(lines, [
+            "-event3   KEYBOARD_KEY            +0.000s\tKEY_A (30) pressed",
+            " event3   KEYBOARD_KEY            +0.001s\tKEY_A (30) released",
+            "-event7   POINTER_MOTION          +0.002s\t -1.23/  0.45 ( -1.23/  0.45)",
+            " event7   POINTER_BUTTON          +0.003s\tBTN_LEFT (272) pressed, seat count: 1",
+            " event7   POINTER_SCROLL_WHEEL    +0.004s\tvert 15.00/120.0* horiz 0.00/0.0 (wheel)",
+        ]);
+    }
+
+    #[test]
+    fn evtest_lines_carry_raw_events() {
+        let mut formatter = EvtestFormatter::default();
+        let key = formatter.format(&timed(5_123_456, "event3", InputEvent::Key { code: 30, pressed: true })).unwrap();
+        assert_eq!(key, format!("{:<72} # A\nEvent: time 5.123456, -------------- SYN_REPORT ------------",
+            "Event: time 5.123456, type 1 (EV_KEY), code 30 (KEY_A), value 1"));
+
+        let wheel = InputEvent::Scroll { source: ScrollSource::Wheel, horizontal: 0.0, vertical: 120.0 };
+        let lines = formatter.format(&timed(6_000_000, "event7", wheel)).unwrap();
+        assert!(lines.starts_with("Event: time 6.000000, type 2 (EV_REL), code 8 (REL_WHEEL), value -1\n"));
+        assert!(lines.contains("code 11 (REL_WHEEL_HI_RES), value -120"));
+
+        let motion = formatter.format(&timed(7_000_000, "event7", InputEvent::PointerMotion { dx: 3.0, dy: 4.0 })).unwrap();
+        assert_eq!(motion, "Event: time 7.000000, no raw event: POINTER_MOTION   3.00/  4.00 (  3.00/  4.00)");
+    }
+
+    #[test]
+    fn derived_events_are_skipped() {
+        let mut formatter = LibinputDebugFormatter::default();
+        let event = timed(0, "event5", InputEvent::TouchpadFingerCountChange { previous: 0, count: 2 });
+        assert_eq!(formatter.format(&event), None);
+        assert_eq!(gesture_kind("HoldBegin"), "GESTURE_HOLD_BEGIN");
+    }
+}
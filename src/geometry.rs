@@ -0,0 +1,67 @@
+// Ramer-Douglas-Peucker: keep only the points that deviate more than
+// `epsilon` from the line between their neighbours. The first and last
+// points always stay. Iterative, so long sessions can't overflow the stack.
+pub fn simplify_path(points: &[(f64, f64)], epsilon: f64) -> Vec<(f64, f64)> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut ranges = vec![(0, points.len() - 1)];
+
+    while let Some((first, last)) = ranges.pop() {
+        let mut farthest = (0.0, first);
+        for index in first + 1..last {
+            let distance = distance_to_line(points[index], points[first], points[last]);
+            if distance > farthest.0 {
+                farthest = (distance, index);
+            }
+        }
+        if farthest.0 > epsilon {
+            keep[farthest.1] = true;
+            ranges.push((first, farthest.1));
+            ranges.push((farthest.1, last));
+        }
+    }
+
+    points.iter().zip(keep).filter(|(_, kept)| *kept).map(|(point, _)| *point).collect()
+}
+
+// From `point` to the line through `start` and `end`, or to `start` when
+// they're the same point
+fn distance_to_line(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        return (point.0 - start.0).hypot(point.1 - start.1);
+    }
+    (dy * point.0 - dx * point.1 + end.0 * start.1 - end.1 * start.0).abs() / length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn drops_points_close_to_the_line() {
+        let points = [(0.0, 0.0), (1.0, 0.1), (2.0, -0.1), (3.0, 5.0), (4.0, 0.0)];
+        assert_eq!(simplify_path(&points, 1.0), vec![(0.0, 0.0), (2.0, -0.1), (3.0, 5.0), (4.0, 0.0)]);
+        assert_eq!(simplify_path(&points, 10.0), vec![(0.0, 0.0), (4.0, 0.0)]);
+    }
+
+    proptest! {
+        #[test]
+        fn never_adds_points_and_keeps_the_ends(
+            points in prop::collection::vec((-1000.0..1000.0f64, -1000.0..1000.0f64), 0..200),
+            epsilon in 0.0..50.0f64,
+        ) {
+            let simplified = simplify_path(&points, epsilon);
+            prop_assert!(simplified.len() <= points.len());
+            prop_assert_eq!(simplified.first(), points.first());
+            prop_assert_eq!(simplified.last(), points.last());
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::event::InputEvent;
+
+// Centroid travel (libinput's normalized units, about 1/40 mm on a
+// touchpad) a swipe needs before it has a direction
+const MIN_SWIPE_TRAVEL: f64 = 100.0;
+// Spread change that makes a pinch (0.8 = 20% narrower)
+const PINCH_IN_SCALE: f64 = 0.8;
+const PINCH_OUT_SCALE: f64 = 1.25;
+// Rotation in degrees that makes a pinch a rotation
+const MIN_ROTATION: f64 = 15.0;
+
+const DEFAULT_ACTIONS: [(&str, &str); 6] = [
+    ("SWIPE_3_LEFT", "browser_back"),
+    ("SWIPE_3_RIGHT", "browser_forward"),
+    ("SWIPE_4_UP", "show_desktop"),
+    ("PINCH_IN", "zoom_out"),
+    ("PINCH_OUT", "zoom_in"),
+    ("ROTATE_CW", "rotate_clockwise"),
+];
+
+// --gesture-map file: `[actions]` maps gesture names to action names,
+// replacing the built-in mapping of the same gesture, e.g.
+//
+//   [actions]
+//   SWIPE_3_LEFT = "previous_tab"
+//   SWIPE_4_DOWN = "show_windows"
+//   PINCH_IN = ""    # no action
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GestureMapFile {
+    #[serde(default)]
+    actions: HashMap<String, String>,
+}
+
+// A finished gesture: its name, e.g. "SWIPE_3_LEFT", "PINCH_OUT",
+// "ROTATE_CCW" or "HOLD_2", and the action mapped to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct GestureMatch {
+    pub gesture: String,
+    pub action: Option<String>,
+}
+
+#[derive(Default)]
+struct Progress {
+    fingers: u32,
+    dx: f64,
+    dy: f64,
+    scale: f64,
+    angle: f64,
+}
+
+// Follows libinput's gesture sequences and names each one once it ends
+// (cancelled ones are dropped), then looks up the action for that name
+pub struct GestureClassifier {
+    actions: HashMap<String, String>,
+    progress: Progress,
+}
+
+impl GestureClassifier {
+    pub fn new(actions: HashMap<String, String>) -> Self {
+        GestureClassifier { actions, progress: Progress::default() }
+    }
+
+    pub fn default_actions() -> HashMap<String, String> {
+        DEFAULT_ACTIONS.iter().map(|(gesture, action)| (gesture.to_string(), action.to_string())).collect()
+    }
+
+    // The built-in mapping with the file's entries on top; an empty action
+    // removes a mapping
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let file: GestureMapFile = toml::from_str(&text).map_err(|err| err.to_string())?;
+        let mut actions = Self::default_actions();
+        for (gesture, action) in file.actions {
+            if action.is_empty() {
+                actions.remove(&gesture.to_uppercase());
+            } else {
+                actions.insert(gesture.to_uppercase(), action);
+            }
+        }
+        Ok(Self::new(actions))
+    }
+
+    // Returns the classified gesture when `event` ends one
+    pub fn observe(&mut self, event: &InputEvent) -> Option<GestureMatch> {
+        let InputEvent::Gesture { kind, finger_count, dx, dy, scale, angle, cancelled } = event else {
+            return None;
+        };
+        let progress = &mut self.progress;
+        if kind.ends_with("Begin") {
+            *progress = Progress { fingers: finger_count.unwrap_or(0), scale: 1.0, ..Progress::default() };
+        }
+        progress.dx += dx.unwrap_or(0.0);
+        progress.dy += dy.unwrap_or(0.0);
+        progress.angle += angle.unwrap_or(0.0);
+        if let Some(scale) = scale {
+            progress.scale = *scale;
+        }
+        if !kind.ends_with("End") || *cancelled == Some(true) {
+            return None;
+        }
+
+        let gesture = match kind.as_str() {
+            "SwipeEnd" => swipe_name(progress)?,
+            "PinchEnd" => pinch_name(progress)?,
+            "HoldEnd" => format!("HOLD_{}", progress.fingers),
+            _ => return None,
+        };
+        let action = self.actions.get(&gesture).cloned();
+        Some(GestureMatch { gesture, action })
+    }
+}
+
+fn swipe_name(progress: &Progress) -> Option<String> {
+    let (dx, dy) = (progress.dx, progress.dy);
+    if dx.hypot(dy) < MIN_SWIPE_TRAVEL {
+        return None;
+    }
+    let direction = match (dx.abs() >= dy.abs(), dx < 0.0, dy < 0.0) {
+        (true, true, _) => "LEFT",
+        (true, false, _) => "RIGHT",
+        (false, _, true) => "UP",
+        (false, _, false) => "DOWN",
+    };
+    Some(format!("SWIPE_{}_{}", progress.fingers, direction))
+}
+
+fn pinch_name(progress: &Progress) -> Option<String> {
+    if progress.scale <= PINCH_IN_SCALE {
+        Some("PINCH_IN".to_string())
+    } else if progress.scale >= PINCH_OUT_SCALE {
+        Some("PINCH_OUT".to_string())
+    } else if progress.angle >= MIN_ROTATION {
+        Some("ROTATE_CW".to_string())
+    } else if progress.angle <= -MIN_ROTATION {
+        Some("ROTATE_CCW".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gesture(kind: &str, fingers: u32, delta: Option<(f64, f64)>, scale: Option<f64>, angle: Option<f64>) -> InputEvent {
+        InputEvent::Gesture {
+            kind: kind.to_string(),
+            finger_count: Some(fingers),
+            dx: delta.map(|(dx, _)| dx),
+            dy: delta.map(|(_, dy)| dy),
+            scale,
+            angle,
+            cancelled: kind.ends_with("End").then_some(false),
+        }
+    }
+
+    #[test]
+    fn names_swipes_and_pinches() {
+        let mut classifier = GestureClassifier::new(GestureClassifier::default_actions());
+        assert_eq!(classifier.observe(&gesture("SwipeBegin", 3, None, None, None)), None);
+        for _ in 0..5 {
+            classifier.observe(&gesture("SwipeUpdate", 3, Some((-40.0, 3.0)), None, None));
+        }
+        let swipe = classifier.observe(&gesture("SwipeEnd", 3, None, None, None)).unwrap();
+        assert_eq!(swipe, GestureMatch { gesture: "SWIPE_3_LEFT".to_string(), action: Some("browser_back".to_string()) });
+
+        classifier.observe(&gesture("PinchBegin", 2, None, Some(1.0), None));
+        classifier.observe(&gesture("PinchUpdate", 2, Some((0.0, 0.0)), Some(1.02), Some(10.0)));
+        classifier.observe(&gesture("PinchUpdate", 2, Some((0.0, 0.0)), Some(1.05), Some(10.0)));
+        let rotation = classifier.observe(&gesture("PinchEnd", 2, None, Some(1.05), None)).unwrap();
+        assert_eq!(rotation.gesture, "ROTATE_CW");
+
+        // Too short to have a direction
+        classifier.observe(&gesture("SwipeBegin", 4, None, None, None));
+        classifier.observe(&gesture("SwipeUpdate", 4, Some((0.0, -10.0)), None, None));
+        assert_eq!(classifier.observe(&gesture("SwipeEnd", 4, None, None, None)), None);
+    }
+}
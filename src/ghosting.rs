@@ -0,0 +1,129 @@
+// Keys released this close together went up in the same scan of the matrix
+const SAME_SCAN_USEC: u64 = 2_000;
+// Fewer held keys than this is ordinary typing overlap, not a rollover limit
+const MIN_BLOCKING_LIMIT: usize = 3;
+
+// A key that went down while two or more others were held: the third corner
+// of a rectangle in the keyboard matrix can make a fourth one read as pressed
+struct Suspect {
+    code: u32,
+    // The keys held when it appeared
+    triggers: Vec<u32>,
+    released_usec: Option<u64>,
+}
+
+// A key press that was most likely a phantom of the keyboard matrix
+pub struct Ghost {
+    pub code: u32,
+    pub triggers: Vec<u32>,
+}
+
+// A key press that most likely came late: the keyboard was already sending
+// as many keys as it can, and only reported this one once a slot freed up
+pub struct DroppedPress {
+    pub code: u32,
+    // The key whose release let it through
+    pub released: u32,
+    // Keys held when the keyboard stopped taking more
+    pub limit: usize,
+}
+
+pub enum KeyAnomaly {
+    Ghost(Ghost),
+    Dropped(DroppedPress),
+}
+
+// --detect-ghosting: tracks the held keys and spots phantom presses. A ghost
+// only exists while the keys that cause it are held, so it's released in
+// the same scan as one of them; a key the user really pressed is released
+// on its own. The opposite, key blocking, shows up as a press that arrives
+// in the same scan as a release while the most keys of the session were
+// held: the new key was down all along, but had no room in the report.
+#[derive(Default)]
+pub struct GhostingDetector {
+    held: Vec<u32>,
+    // The latest release, and how many keys were held before it
+    last_release: Option<(u32, u64, usize)>,
+    suspects: Vec<Suspect>,
+    // Releases within the last SAME_SCAN_USEC
+    recent_releases: Vec<(u32, u64)>,
+    // Most keys held at once, i.e. the rollover the keyboard managed
+    max_held: usize,
+    ghosts: u64,
+    dropped: u64,
+}
+
+impl GhostingDetector {
+    pub fn key(&mut self, code: u32, pressed: bool, time_usec: u64) -> Option<KeyAnomaly> {
+        if pressed {
+            let mut dropped = None;
+            if !self.held.contains(&code) {
+                if let Some((released, released_usec, limit)) = self.last_release {
+                    if limit >= MIN_BLOCKING_LIMIT && limit == self.max_held && time_usec.saturating_sub(released_usec) <= SAME_SCAN_USEC {
+                        self.dropped += 1;
+                        dropped = Some(KeyAnomaly::Dropped(DroppedPress { code, released, limit }));
+                    }
+                }
+                if self.held.len() >= 2 {
+                    self.suspects.retain(|suspect| suspect.code != code);
+                    self.suspects.push(Suspect { code, triggers: self.held.clone(), released_usec: None });
+                }
+                self.held.push(code);
+                self.max_held = self.max_held.max(self.held.len());
+            }
+            return dropped;
+        }
+
+        if self.held.contains(&code) {
+            self.last_release = Some((code, time_usec, self.held.len()));
+        }
+        self.held.retain(|held| *held != code);
+        self.recent_releases.retain(|(_, released)| time_usec.saturating_sub(*released) <= SAME_SCAN_USEC);
+        self.recent_releases.push((code, time_usec));
+
+        // Either the suspect goes up right after one of its triggers, or the
+        // other way round
+        let recent_releases = &self.recent_releases;
+        let ghost = self.suspects.iter().position(|suspect| if suspect.code == code {
+            recent_releases.iter().any(|(released, _)| suspect.triggers.contains(released))
+        } else {
+            suspect.triggers.contains(&code)
+                && suspect.released_usec.is_some_and(|released| time_usec.saturating_sub(released) <= SAME_SCAN_USEC)
+        });
+        if let Some(index) = ghost {
+            let suspect = self.suspects.remove(index);
+            self.ghosts += 1;
+            return Some(KeyAnomaly::Ghost(Ghost { code: suspect.code, triggers: suspect.triggers }));
+        }
+
+        for suspect in self.suspects.iter_mut().filter(|suspect| suspect.code == code) {
+            suspect.released_usec = Some(time_usec);
+        }
+        // A suspect that outlasts all of its triggers was a real key
+        let held = &self.held;
+        self.suspects.retain(|suspect| match suspect.released_usec {
+            Some(released) => time_usec.saturating_sub(released) <= SAME_SCAN_USEC,
+            None => suspect.triggers.iter().any(|trigger| {
+                held.contains(trigger) || recent_releases.iter().any(|(released, _)| released == trigger)
+            }),
+        });
+        None
+    }
+
+    pub fn max_held(&self) -> usize {
+        self.max_held
+    }
+
+    pub fn ghosts(&self) -> u64 {
+        self.ghosts
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    // The keys down right now, in the order they went down
+    pub fn held(&self) -> &[u32] {
+        &self.held
+    }
+}
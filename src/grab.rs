@@ -0,0 +1,38 @@
+use std::io;
+use std::os::unix::io::{AsRawFd, OwnedFd};
+use std::path::Path;
+
+use input::LibinputInterface;
+
+use crate::{Colors, Interface};
+
+// From <linux/input.h>: EVIOCGRAB takes an int, 1 to grab and 0 to release
+const EVIOCGRAB: libc::c_ulong = 0x4004_4590;
+const GRAB: libc::c_ulong = 1;
+const RELEASE: libc::c_ulong = 0;
+
+// --grab: opens the --device-path devices like Interface does, then takes
+// them exclusively, so their events reach this process and nothing else.
+// The kernel drops a grab when its fd is closed, which happens on every way
+// out of the process, a crash or SIGKILL included; closing a device through
+// libinput also releases it explicitly.
+pub struct GrabbingInterface;
+
+impl LibinputInterface for GrabbingInterface {
+    fn open_restricted(&mut self, path: &Path, flags: i32) -> Result<OwnedFd, i32> {
+        let fd = Interface.open_restricted(path, flags)?;
+        if unsafe { libc::ioctl(fd.as_raw_fd(), EVIOCGRAB, GRAB) } < 0 {
+            let err = io::Error::last_os_error();
+            let reason = if err.raw_os_error() == Some(libc::EBUSY) { "another program has grabbed it".to_string() } else { err.to_string() };
+            eprintln!("{}⚠️ Cannot grab {}: {}{}", Colors::RED, path.display(), reason, Colors::RESET);
+            return Err(err.raw_os_error().unwrap_or(libc::EIO));
+        }
+        Ok(fd)
+    }
+
+    fn close_restricted(&mut self, fd: OwnedFd) {
+        unsafe {
+            libc::ioctl(fd.as_raw_fd(), EVIOCGRAB, RELEASE);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::strokes::{StrokeCategory, StrokeCounter};
+use crate::timefmt;
+
+// Per-minute activity CSV in the `timestamp,activity_type,value,unit` layout
+// understood by spreadsheet imports and health dashboards (Apple Health / Google Fit style)
+pub struct HealthExporter {
+    writer: BufWriter<File>,
+    minute_start: u64,
+    key_presses: u64,
+    mouse_distance: f64,
+    scroll_events: u64,
+    strokes: StrokeCounter,
+}
+
+impl HealthExporter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "timestamp,activity_type,value,unit")?;
+        writer.flush()?;
+
+        Ok(HealthExporter {
+            writer,
+            minute_start: minute_of(SystemTime::now()),
+            key_presses: 0,
+            mouse_distance: 0.0,
+            scroll_events: 0,
+            strokes: StrokeCounter::default(),
+        })
+    }
+
+    pub fn record_key_press(&mut self) {
+        self.key_presses += 1;
+    }
+
+    pub fn record_stroke(&mut self, category: StrokeCategory) {
+        self.strokes.add(category);
+    }
+
+    pub fn record_motion(&mut self, dx: f64, dy: f64) {
+        self.mouse_distance += (dx * dx + dy * dy).sqrt();
+    }
+
+    pub fn record_scroll(&mut self) {
+        self.scroll_events += 1;
+    }
+
+    // Write rows for every minute that has ended. Minutes without activity still get
+    // zero-valued rows so the series has no gaps.
+    pub fn tick(&mut self, now: SystemTime) -> io::Result<()> {
+        let current = minute_of(now);
+        while self.minute_start < current {
+            self.write_minute()?;
+            self.minute_start += 60;
+        }
+        Ok(())
+    }
+
+    // Flush the partially completed minute on exit
+    pub fn finish(mut self) -> io::Result<()> {
+        self.tick(SystemTime::now())?;
+        self.write_minute()?;
+        self.writer.flush()
+    }
+
+    fn write_minute(&mut self) -> io::Result<()> {
+        let timestamp = timefmt::iso8601(UNIX_EPOCH + Duration::from_secs(self.minute_start));
+
+        writeln!(self.writer, "{},typing,{},keypresses/min", timestamp, self.key_presses)?;
+        writeln!(self.writer, "{},mouse_movement,{:.2},px/min", timestamp, self.mouse_distance)?;
+        writeln!(self.writer, "{},scrolling,{},events/min", timestamp, self.scroll_events)?;
+        for category in StrokeCategory::ALL {
+            writeln!(self.writer, "{},keystrokes_{},{},keypresses/min",
+                timestamp, category.name().to_lowercase(), self.strokes.get(category))?;
+        }
+        // Left empty in minutes without typing or deleting
+        let productivity = self.strokes.productivity().map_or(String::new(), |ratio| format!("{:.3}", ratio));
+        writeln!(self.writer, "{},productivity_ratio,{},ratio", timestamp, productivity)?;
+        self.writer.flush()?;
+
+        self.key_presses = 0;
+        self.mouse_distance = 0.0;
+        self.scroll_events = 0;
+        self.strokes = StrokeCounter::default();
+        Ok(())
+    }
+}
+
+// Seconds since the epoch, rounded down to the start of the minute
+fn minute_of(time: SystemTime) -> u64 {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    secs - secs % 60
+}
@@ -0,0 +1,182 @@
+// Key press heatmap drawn over a keyboard outline. evdev keycodes name
+// physical positions, so every layout shares the same geometry; layouts only
+// differ in the labels printed on the keys.
+
+// Grid width in half-key units (a regular key spans 2)
+const GRID_COLUMNS: usize = 30;
+const GRID_ROWS: usize = 5;
+
+// 256-color palette entries from blue (few presses) to red (most)
+const SPECTRUM: &[u8] = &[21, 27, 33, 39, 45, 50, 48, 82, 118, 154, 190, 226, 220, 214, 208, 202, 196];
+// Keys that were never pressed
+const UNUSED_COLOR: u8 = 238;
+
+// (keycode, row, column, span, QWERTY label); columns and spans in half-key units
+const KEY_POSITIONS: &[(u32, usize, usize, usize, &str)] = &[
+    (41, 0, 0, 2, "`"), (2, 0, 2, 2, "1"), (3, 0, 4, 2, "2"), (4, 0, 6, 2, "3"), (5, 0, 8, 2, "4"),
+    (6, 0, 10, 2, "5"), (7, 0, 12, 2, "6"), (8, 0, 14, 2, "7"), (9, 0, 16, 2, "8"), (10, 0, 18, 2, "9"),
+    (11, 0, 20, 2, "0"), (12, 0, 22, 2, "-"), (13, 0, 24, 2, "="), (14, 0, 26, 4, "BKSP"),
+    (15, 1, 0, 3, "TAB"), (16, 1, 3, 2, "Q"), (17, 1, 5, 2, "W"), (18, 1, 7, 2, "E"), (19, 1, 9, 2, "R"),
+    (20, 1, 11, 2, "T"), (21, 1, 13, 2, "Y"), (22, 1, 15, 2, "U"), (23, 1, 17, 2, "I"), (24, 1, 19, 2, "O"),
+    (25, 1, 21, 2, "P"), (26, 1, 23, 2, "["), (27, 1, 25, 2, "]"), (43, 1, 27, 3, "\\"),
+    (58, 2, 0, 4, "CAPS"), (30, 2, 4, 2, "A"), (31, 2, 6, 2, "S"), (32, 2, 8, 2, "D"), (33, 2, 10, 2, "F"),
+    (34, 2, 12, 2, "G"), (35, 2, 14, 2, "H"), (36, 2, 16, 2, "J"), (37, 2, 18, 2, "K"), (38, 2, 20, 2, "L"),
+    (39, 2, 22, 2, ";"), (40, 2, 24, 2, "'"), (28, 2, 26, 4, "ENTER"),
+    (42, 3, 0, 5, "SHIFT"), (44, 3, 5, 2, "Z"), (45, 3, 7, 2, "X"), (46, 3, 9, 2, "C"), (47, 3, 11, 2, "V"),
+    (48, 3, 13, 2, "B"), (49, 3, 15, 2, "N"), (50, 3, 17, 2, "M"), (51, 3, 19, 2, ","), (52, 3, 21, 2, "."),
+    (53, 3, 23, 2, "/"), (54, 3, 25, 5, "SHIFT"),
+    (29, 4, 0, 3, "CTRL"), (125, 4, 3, 3, "SUPER"), (56, 4, 6, 3, "ALT"), (57, 4, 9, 12, "SPACE"),
+    (100, 4, 21, 3, "ALTGR"), (126, 4, 24, 3, "SUPER"), (97, 4, 27, 3, "CTRL"),
+];
+
+// A keyboard layout: the shared key positions plus the labels that differ from QWERTY
+pub struct KeyboardLayout {
+    pub name: &'static str,
+    labels: &'static [(u32, &'static str)],
+}
+
+pub static QWERTY: KeyboardLayout = KeyboardLayout { name: "qwerty", labels: &[] };
+
+pub static QWERTZ: KeyboardLayout = KeyboardLayout {
+    name: "qwertz",
+    labels: &[
+        (21, "Z"), (44, "Y"), (12, "ß"), (13, "´"), (26, "Ü"), (27, "+"), (39, "Ö"), (40, "Ä"),
+        (41, "^"), (43, "#"), (53, "-"),
+    ],
+};
+
+pub static AZERTY: KeyboardLayout = KeyboardLayout {
+    name: "azerty",
+    labels: &[
+        (16, "A"), (17, "Z"), (30, "Q"), (39, "M"), (44, "W"), (50, ","), (51, ";"), (52, ":"), (53, "!"),
+        (2, "&"), (3, "é"), (4, "\""), (5, "'"), (6, "("), (7, "-"), (8, "è"), (9, "_"), (10, "ç"),
+        (11, "à"), (12, ")"), (13, "="), (26, "^"), (27, "$"), (40, "ù"), (41, "²"), (43, "*"),
+    ],
+};
+
+impl KeyboardLayout {
+    pub fn by_name(name: &str) -> Option<&'static KeyboardLayout> {
+        [&QWERTY, &QWERTZ, &AZERTY].into_iter().find(|layout| layout.name.eq_ignore_ascii_case(name))
+    }
+
+    // Where a key sits: (row, column, span)
+    pub fn position(&self, code: u32) -> Option<(usize, usize, usize)> {
+        KEY_POSITIONS.iter()
+            .find(|(key, ..)| *key == code)
+            .map(|&(_, row, col, span, _)| (row, col, span))
+    }
+
+    // Every key on the grid: (keycode, row, column, span)
+    pub fn keys(&self) -> impl Iterator<Item = (u32, usize, usize, usize)> {
+        KEY_POSITIONS.iter().map(|&(code, row, col, span, _)| (code, row, col, span))
+    }
+
+    // The label this layout prints where QWERTY's differs
+    pub fn relabel(&self, code: u32) -> Option<&'static str> {
+        self.labels.iter().find(|(key, _)| *key == code).map(|(_, label)| *label)
+    }
+
+    pub fn label(&self, code: u32) -> &'static str {
+        self.labels.iter()
+            .find(|(key, _)| *key == code)
+            .map(|(_, label)| *label)
+            .or_else(|| KEY_POSITIONS.iter().find(|(key, ..)| *key == code).map(|(.., label)| *label))
+            .unwrap_or("")
+    }
+}
+
+// Press counts laid out like the keyboard. A key's count lives in the cell
+// where it starts; keys off the main block (F-keys, arrows, numpad) aren't drawn.
+pub struct HeatmapGrid {
+    cells: Vec<Vec<u64>>,
+    layout: &'static KeyboardLayout,
+}
+
+impl HeatmapGrid {
+    pub fn new(layout: &'static KeyboardLayout) -> Self {
+        HeatmapGrid { cells: vec![vec![0; GRID_COLUMNS]; GRID_ROWS], layout }
+    }
+
+    pub fn add_press(&mut self, code: u32) {
+        if let Some((row, col, _)) = self.layout.position(code) {
+            self.cells[row][col] += 1;
+        }
+    }
+
+    // Counts scaled to [0, 1] on a log scale, so a few very busy keys (space,
+    // E) don't wash out the rest
+    pub fn normalize(&self) -> Vec<Vec<f64>> {
+        let max = self.cells.iter().flatten().copied().max().unwrap_or(0);
+        let scale = (1.0 + max as f64).ln();
+        self.cells.iter()
+            .map(|row| row.iter()
+                .map(|&count| if max == 0 { 0.0 } else { (1.0 + count as f64).ln() / scale })
+                .collect())
+            .collect()
+    }
+
+    // One line per keyboard row, each key on a 256-color background
+    pub fn render_ansi(&self) -> String {
+        let levels = self.normalize();
+        let mut output = String::new();
+        for (row, (counts, levels)) in self.cells.iter().zip(&levels).enumerate() {
+            let mut keys: Vec<_> = KEY_POSITIONS.iter().filter(|(_, key_row, ..)| *key_row == row).collect();
+            keys.sort_by_key(|(_, _, col, ..)| *col);
+
+            for &&(code, _, col, span, _) in &keys {
+                let color = if counts[col] == 0 {
+                    UNUSED_COLOR
+                } else {
+                    let index = (levels[col] * (SPECTRUM.len() - 1) as f64).round() as usize;
+                    SPECTRUM[index.min(SPECTRUM.len() - 1)]
+                };
+                let width = span * 2 - 1;
+                output.push_str(&format!("\x1b[48;5;{}m\x1b[38;5;16m{:^width$}\x1b[0m ",
+                    color, self.layout.label(code), width = width));
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_is_logarithmic() {
+        let mut grid = HeatmapGrid::new(&QWERTY);
+        for _ in 0..99 {
+            grid.add_press(57);
+        }
+        grid.add_press(30);
+        grid.add_press(59); // F1 isn't on the grid
+
+        let levels = grid.normalize();
+        assert_eq!(levels[4][9], 1.0);
+        // ln(2) / ln(100): one press is far more visible than 1/99th
+        assert!((levels[2][4] - 2f64.ln() / 100f64.ln()).abs() < 1e-9);
+        assert_eq!(levels[0][0], 0.0);
+    }
+
+    #[test]
+    fn layouts_relabel_the_same_positions() {
+        assert_eq!(QWERTY.label(21), "Y");
+        assert_eq!(QWERTZ.label(21), "Z");
+        assert_eq!(AZERTY.label(16), "A");
+        assert_eq!(AZERTY.position(16), QWERTY.position(16));
+        assert!(KeyboardLayout::by_name("AZERTY").is_some());
+    }
+
+    #[test]
+    fn render_uses_the_spectrum() {
+        let mut grid = HeatmapGrid::new(&QWERTY);
+        grid.add_press(35);
+        let rendered = grid.render_ansi();
+
+        assert_eq!(rendered.lines().count(), GRID_ROWS);
+        assert!(rendered.contains("\x1b[48;5;196m\x1b[38;5;16m H \x1b[0m"));
+        assert!(rendered.contains("\x1b[48;5;238m"));
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+
+use regex::Regex;
+
+use crate::keys::key_name;
+
+// Key presses kept for matching; patterns longer than this never match
+const BUFFER_KEYS: usize = 16;
+
+// A --highlight-pattern, matched against the names of the latest key presses
+#[derive(Clone)]
+pub struct HighlightPattern {
+    pub source: String,
+    // Anchored to the end of the buffer, so only matches that include the
+    // newest key count and an old match isn't reported again, and to the
+    // start of a key name, so "T" doesn't match the end of SHIFT
+    regex: Regex,
+}
+
+impl HighlightPattern {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let regex = Regex::new(&format!("(?:^| )(?:{})$", source)).map_err(|err| err.to_string())?;
+        Ok(HighlightPattern { source: source.to_string(), regex })
+    }
+}
+
+// A pattern that matched, by its position among the --highlight-pattern flags
+pub struct PatternMatch<'a> {
+    pub index: usize,
+    pub pattern: &'a str,
+    pub text: String,
+}
+
+// The latest key presses as space-separated names, e.g. "CTRL A" for Ctrl+A.
+// Modifiers use their combo names (CTRL, not LEFT CTRL) and multi-word names
+// are joined with underscores (CAPS_LOCK), so every key is one word.
+pub struct PatternHighlighter {
+    patterns: Vec<HighlightPattern>,
+    keys: VecDeque<String>,
+}
+
+impl PatternHighlighter {
+    pub fn new(patterns: Vec<HighlightPattern>) -> Self {
+        PatternHighlighter { patterns, keys: VecDeque::new() }
+    }
+
+    // Add a key press and return the patterns it completed
+    pub fn key_pressed(&mut self, code: u32) -> Vec<PatternMatch<'_>> {
+        if self.keys.len() == BUFFER_KEYS {
+            self.keys.pop_front();
+        }
+        self.keys.push_back(buffer_name(code));
+
+        let buffer = self.keys.iter().map(String::as_str).collect::<Vec<_>>().join(" ");
+        self.patterns.iter()
+            .enumerate()
+            .filter_map(|(index, pattern)| pattern.regex.find(&buffer).map(|found| PatternMatch {
+                index,
+                pattern: &pattern.source,
+                text: found.as_str().trim().to_string(),
+            }))
+            .collect()
+    }
+}
+
+fn buffer_name(code: u32) -> String {
+    match key_name(code) {
+        "LEFT CTRL" | "RIGHT CTRL" => "CTRL".to_string(),
+        "LEFT SHIFT" | "RIGHT SHIFT" => "SHIFT".to_string(),
+        "LEFT ALT" | "RIGHT ALT" => "ALT".to_string(),
+        "LEFT SUPER" | "RIGHT SUPER" => "SUPER".to_string(),
+        "UNKNOWN KEY" => format!("KEY_{}", code),
+        name => name.replace(' ', "_"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlighter(patterns: &[&str]) -> PatternHighlighter {
+        PatternHighlighter::new(patterns.iter().map(|pattern| HighlightPattern::parse(pattern).unwrap()).collect())
+    }
+
+    #[test]
+    fn combos_match_as_key_sequences() {
+        let mut highlighter = highlighter(&["CTRL A", "H I"]);
+        assert!(highlighter.key_pressed(97).is_empty());
+        let matches = highlighter.key_pressed(30);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].index, matches[0].pattern, matches[0].text.as_str()), (0, "CTRL A", "CTRL A"));
+
+        highlighter.key_pressed(35);
+        let matches = highlighter.key_pressed(23);
+        assert_eq!(matches.iter().map(|found| found.index).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn a_match_is_reported_once() {
+        let mut highlighter = highlighter(&["(CTRL )?A"]);
+        assert_eq!(highlighter.key_pressed(30).len(), 1);
+        assert!(highlighter.key_pressed(58).is_empty());
+        assert!(HighlightPattern::parse("(").is_err());
+    }
+
+    #[test]
+    fn patterns_match_whole_key_names() {
+        let mut highlighter = highlighter(&["T"]);
+        assert!(highlighter.key_pressed(42).is_empty());
+        let matches = highlighter.key_pressed(20);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "T");
+    }
+}
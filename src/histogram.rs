@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+// Log-linear buckets, like an HDR histogram: durations under 128 ns are
+// exact, and above that every power of two is split into 64 buckets, so any
+// recorded value is off by less than 1.6%. Everything up to 2^40 ns (about
+// 18 minutes) fits in 2240 buckets, however many events are recorded.
+const SUB_BUCKET_BITS: u32 = 6;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+const MAX_EXPONENT: u32 = 40;
+const BUCKETS: usize = ((MAX_EXPONENT - SUB_BUCKET_BITS + 1) as u64 * SUB_BUCKETS) as usize;
+
+// Per-event processing times, for the --benchmark readout and the summary
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    total_ns: u64,
+    max_ns: u64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        LatencyHistogram { buckets: vec![0; BUCKETS], count: 0, total_ns: 0, max_ns: 0 }
+    }
+
+    pub fn record(&mut self, elapsed: Duration) {
+        let ns = elapsed.as_nanos().min(u64::MAX as u128) as u64;
+        self.buckets[bucket_index(ns)] += 1;
+        self.count += 1;
+        self.total_ns = self.total_ns.saturating_add(ns);
+        self.max_ns = self.max_ns.max(ns);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean_us(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.total_ns as f64 / self.count as f64 / 1000.0 }
+    }
+
+    pub fn max_us(&self) -> f64 {
+        self.max_ns as f64 / 1000.0
+    }
+
+    // Upper bound of the bucket containing the given percentile (but never
+    // more than the maximum), in microseconds
+    pub fn percentile_us(&self, percentile: f64) -> f64 {
+        let target = ((self.count as f64 * percentile / 100.0).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= target {
+                return bucket_end(index).min(self.max_ns) as f64 / 1000.0;
+            }
+        }
+        self.max_us()
+    }
+}
+
+fn bucket_index(ns: u64) -> usize {
+    if ns < 2 * SUB_BUCKETS {
+        return ns as usize;
+    }
+    // Keep the top SUB_BUCKET_BITS + 1 bits; longer durations share the last bucket
+    let exponent = (63 - ns.leading_zeros()).min(MAX_EXPONENT - 1);
+    let shift = exponent - SUB_BUCKET_BITS;
+    let sub_bucket = (ns >> shift).min(2 * SUB_BUCKETS - 1);
+    (shift as u64 * SUB_BUCKETS + sub_bucket) as usize
+}
+
+// First duration above the bucket, in nanoseconds
+fn bucket_end(index: usize) -> u64 {
+    let index = index as u64;
+    if index < 2 * SUB_BUCKETS {
+        return index + 1;
+    }
+    let shift = index / SUB_BUCKETS - 1;
+    let sub_bucket = index % SUB_BUCKETS + SUB_BUCKETS;
+    (sub_bucket + 1) << shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_are_contiguous_and_tight() {
+        let mut previous_end = 0;
+        for index in 0..BUCKETS {
+            let end = bucket_end(index);
+            assert!(end > previous_end);
+            // The bucket starts where the previous one ended
+            assert_eq!(bucket_index(previous_end), index);
+            assert_eq!(bucket_index(end - 1), index);
+            assert!((end - previous_end) as f64 <= end as f64 / 64.0 + 1.0);
+            previous_end = end;
+        }
+        assert_eq!(bucket_index(u64::MAX), BUCKETS - 1);
+    }
+
+    #[test]
+    fn percentiles_and_max() {
+        let mut histogram = LatencyHistogram::new();
+        for us in 1..=1000 {
+            histogram.record(Duration::from_micros(us));
+        }
+        histogram.record(Duration::from_secs(2));
+
+        assert_eq!(histogram.count(), 1001);
+        assert_eq!(histogram.max_us(), 2_000_000.0);
+        for (percentile, expected) in [(50.0, 501.0), (95.0, 951.0), (99.0, 991.0)] {
+            let actual = histogram.percentile_us(percentile);
+            assert!(actual >= expected && actual <= expected * 1.02, "p{} = {}", percentile, actual);
+        }
+        assert_eq!(histogram.percentile_us(100.0), 2_000_000.0);
+    }
+}
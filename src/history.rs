@@ -0,0 +1,35 @@
+use std::collections::VecDeque;
+
+use crate::event::TimedEvent;
+
+// Fixed-size buffer of the most recent decoded events, kept so something that
+// scrolled past can be re-rendered on demand
+pub struct EventHistory {
+    capacity: usize,
+    events: VecDeque<TimedEvent>,
+}
+
+impl EventHistory {
+    pub fn new(capacity: usize) -> Self {
+        EventHistory { capacity, events: VecDeque::with_capacity(capacity) }
+    }
+
+    pub fn push(&mut self, event: &TimedEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event.clone());
+    }
+
+    // Oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &TimedEvent> {
+        self.events.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+}
@@ -0,0 +1,102 @@
+use input::{Device, DeviceCapability};
+use serde::{Deserialize, Serialize};
+
+use crate::event::{InputEvent, TimedEvent};
+
+// Capabilities in the order they're listed
+const CAPABILITIES: [(DeviceCapability, &str); 7] = [
+    (DeviceCapability::Keyboard, "keyboard"),
+    (DeviceCapability::Pointer, "pointer"),
+    (DeviceCapability::Touch, "touch"),
+    (DeviceCapability::TabletTool, "tablet_tool"),
+    (DeviceCapability::TabletPad, "tablet_pad"),
+    (DeviceCapability::Gesture, "gesture"),
+    (DeviceCapability::Switch, "switch"),
+];
+
+// What libinput tells us about a device when it's added or removed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub name: String,
+    // e.g. /sys/class/input/event5
+    pub sysfs_path: String,
+    pub seat: String,
+    pub capabilities: Vec<String>,
+}
+
+impl DeviceInfo {
+    pub fn of(device: &Device) -> Self {
+        DeviceInfo {
+            name: device.name().to_string(),
+            sysfs_path: format!("/sys/class/input/{}", device.sysname()),
+            seat: device.seat().physical_name().to_string(),
+            capabilities: CAPABILITIES.iter()
+                .filter(|(capability, _)| device.has_capability(*capability))
+                .map(|(_, name)| name.to_string())
+                .collect(),
+        }
+    }
+
+    // Device events from binary recordings and older .rkey files only have a name
+    fn from_name(name: &str) -> Self {
+        DeviceInfo { name: name.to_string(), sysfs_path: String::new(), seat: String::new(), capabilities: Vec::new() }
+    }
+
+    fn same_device(&self, other: &DeviceInfo) -> bool {
+        if self.sysfs_path.is_empty() || other.sysfs_path.is_empty() {
+            self.name == other.name
+        } else {
+            self.sysfs_path == other.sysfs_path
+        }
+    }
+}
+
+type AddedCallback = Box<dyn FnMut(&str, &[String])>;
+type RemovedCallback = Box<dyn FnMut(&str)>;
+
+// Keeps the list of connected devices up to date from device added/removed
+// events, across dispatch() calls, and tells registered callbacks about changes
+#[derive(Default)]
+pub struct DeviceHotplugMonitor {
+    devices: Vec<DeviceInfo>,
+    added_callbacks: Vec<AddedCallback>,
+    removed_callbacks: Vec<RemovedCallback>,
+}
+
+impl DeviceHotplugMonitor {
+    // Called with the device name and capabilities
+    pub fn on_device_added(&mut self, callback: impl FnMut(&str, &[String]) + 'static) {
+        self.added_callbacks.push(Box::new(callback));
+    }
+
+    pub fn on_device_removed(&mut self, callback: impl FnMut(&str) + 'static) {
+        self.removed_callbacks.push(Box::new(callback));
+    }
+
+    pub fn observe(&mut self, timed: &TimedEvent) {
+        match &timed.event {
+            InputEvent::DeviceAdded { device, info } => {
+                let info = info.clone().unwrap_or_else(|| DeviceInfo::from_name(device));
+                for callback in &mut self.added_callbacks {
+                    callback(&info.name, &info.capabilities);
+                }
+                self.devices.push(info);
+            },
+            InputEvent::DeviceRemoved { device, info } => {
+                let info = info.clone().unwrap_or_else(|| DeviceInfo::from_name(device));
+                if let Some(index) = self.devices.iter().position(|known| known.same_device(&info)) {
+                    self.devices.remove(index);
+                }
+                for callback in &mut self.removed_callbacks {
+                    callback(&info.name);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    // Connected devices, in the order they were added
+    pub fn devices(&self) -> &[DeviceInfo] {
+        &self.devices
+    }
+}
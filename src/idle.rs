@@ -0,0 +1,139 @@
+use std::time::{Duration, SystemTime};
+
+use crate::priority::EventClass;
+
+// How long each kind of event keeps the session "active". Motion is cheap
+// (a nudged desk moves the mouse), so by default it counts for a quarter
+// of the time a key press does.
+#[derive(Debug, Clone, Copy)]
+pub struct IdleTimeouts {
+    keyboard: Duration,
+    button: Duration,
+    scroll: Duration,
+    motion: Duration,
+    other: Duration,
+}
+
+impl IdleTimeouts {
+    pub fn new(base: Duration) -> Self {
+        IdleTimeouts { keyboard: base, button: base, scroll: base, motion: base / 4, other: base }
+    }
+
+    pub fn get(&self, class: EventClass) -> Duration {
+        match class {
+            EventClass::Keyboard => self.keyboard,
+            EventClass::Button => self.button,
+            EventClass::Scroll => self.scroll,
+            EventClass::Motion => self.motion,
+            EventClass::Other => self.other,
+        }
+    }
+
+    pub fn set(&mut self, class: EventClass, timeout: Duration) {
+        match class {
+            EventClass::Keyboard => self.keyboard = timeout,
+            EventClass::Button => self.button = timeout,
+            EventClass::Scroll => self.scroll = timeout,
+            EventClass::Motion => self.motion = timeout,
+            EventClass::Other => self.other = timeout,
+        }
+    }
+}
+
+// Parse a per-type override written as `<type>=<seconds>`, e.g. `motion=5`
+pub fn parse_override(spec: &str) -> Result<(EventClass, Duration), String> {
+    let (name, value) = spec.split_once('=')
+        .ok_or_else(|| format!("expected <type>=<seconds>, got '{}'", spec))?;
+    let class = EventClass::parse(name.trim()).ok_or_else(|| {
+        format!("unknown event type '{}' (expected keyboard, button, scroll, motion or other)", name)
+    })?;
+    let seconds: f64 = value.trim().parse()
+        .ok()
+        .filter(|seconds: &f64| seconds.is_finite() && *seconds >= 0.0)
+        .ok_or_else(|| format!("invalid timeout '{}' for '{}'", value, name))?;
+    Ok((class, Duration::from_secs_f64(seconds)))
+}
+
+// Decides when to stop polling libinput. Every event pushes the end of the
+// active period out by its type's timeout; past that, the session is idle.
+pub struct IdleMonitor {
+    timeouts: IdleTimeouts,
+    active_until_usec: u64,
+    // Monotonic and wall-clock time the pause started
+    paused_since: Option<(u64, SystemTime)>,
+}
+
+impl IdleMonitor {
+    pub fn new(timeouts: IdleTimeouts, now_usec: u64) -> Self {
+        let startup_grace = timeouts.get(EventClass::Keyboard).as_micros() as u64;
+        IdleMonitor { timeouts, active_until_usec: now_usec + startup_grace, paused_since: None }
+    }
+
+    pub fn observe(&mut self, class: EventClass, time_usec: u64) {
+        let until = time_usec + self.timeouts.get(class).as_micros() as u64;
+        self.active_until_usec = self.active_until_usec.max(until);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    // Start a pause if the active period is over; returns true when it did
+    pub fn pause_if_idle(&mut self, now_usec: u64) -> bool {
+        if self.paused_since.is_some() || now_usec <= self.active_until_usec {
+            return false;
+        }
+        self.paused_since = Some((now_usec, SystemTime::now()));
+        true
+    }
+
+    // End the pause; returns when it started and how long it lasted
+    pub fn resume(&mut self, now_usec: u64) -> Option<(SystemTime, Duration)> {
+        self.paused_since.take().map(|(since_usec, since)| {
+            (since, Duration::from_micros(now_usec.saturating_sub(since_usec)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn motion_counts_a_quarter_unless_overridden() {
+        let mut timeouts = IdleTimeouts::new(Duration::from_secs(60));
+        assert_eq!(timeouts.get(EventClass::Keyboard), Duration::from_secs(60));
+        assert_eq!(timeouts.get(EventClass::Motion), Duration::from_secs(15));
+
+        let (class, timeout) = parse_override("motion = 5").unwrap();
+        timeouts.set(class, timeout);
+        assert_eq!(timeouts.get(EventClass::Motion), Duration::from_secs(5));
+        assert_eq!(parse_override("scroll=0.5").unwrap(), (EventClass::Scroll, Duration::from_millis(500)));
+        assert!(parse_override("motion").is_err());
+        assert!(parse_override("wheel=5").is_err());
+        assert!(parse_override("motion=-1").is_err());
+    }
+
+    #[test]
+    fn pauses_once_idle_and_resumes() {
+        let mut timeouts = IdleTimeouts::new(Duration::from_secs(10));
+        timeouts.set(EventClass::Button, Duration::from_secs(30));
+        let mut idle = IdleMonitor::new(timeouts, 0);
+
+        // Motion at 5 s keeps it active only until 7.5 s; the startup grace lasts to 10 s
+        idle.observe(EventClass::Motion, 5_000_000);
+        assert!(!idle.pause_if_idle(10_000_000));
+        assert!(idle.pause_if_idle(10_000_001));
+        assert!(idle.is_paused());
+        assert!(!idle.pause_if_idle(20_000_000));
+
+        let (_, paused_for) = idle.resume(12_000_001).unwrap();
+        assert_eq!(paused_for, Duration::from_secs(2));
+        assert!(!idle.is_paused());
+        assert!(idle.resume(13_000_000).is_none());
+
+        idle.observe(EventClass::Button, 12_000_001);
+        assert!(!idle.pause_if_idle(42_000_001));
+        assert!(idle.pause_if_idle(42_000_002));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::heatmap::KeyboardLayout;
+use crate::keys::{self, key_name};
+use crate::Colors;
+
+// Terminal columns per half-key unit of the layout grid
+const UNIT_WIDTH: usize = 3;
+// Terminal rows per keyboard row: top border, label, bottom border
+const ROW_HEIGHT: usize = 3;
+const GRID_ROWS: usize = 5;
+// How long a pressed key stays inverted
+const FLASH: Duration = Duration::from_millis(200);
+const INVERSE: &str = "\x1b[7m";
+
+// --kbd-view: a keyboard drawn with box characters that lights up as keys
+// are pressed, for checking that every key on a keyboard registers. Keys
+// pressed at least once stay green. Drawn at the top of the terminal and
+// updated in place with cursor addressing.
+pub struct KeyboardView {
+    layout: &'static KeyboardLayout,
+    // Keys inverted right now, and when they go back to normal
+    flashing: Vec<(u32, Instant)>,
+    seen: HashSet<u32>,
+    last_key: Option<u32>,
+}
+
+impl KeyboardView {
+    pub fn new(layout: &'static KeyboardLayout) -> Self {
+        KeyboardView { layout, flashing: Vec::new(), seen: HashSet::new(), last_key: None }
+    }
+
+    // Clear the screen and draw every key
+    pub fn draw(&self) -> String {
+        let mut screen = String::from("\x1b[2J");
+        for (code, ..) in self.layout.keys() {
+            screen.push_str(&self.draw_key(code));
+        }
+        screen.push_str(&self.draw_footer());
+        screen
+    }
+
+    // Invert the key and update the footer
+    pub fn key_pressed(&mut self, code: u32, now: Instant) -> String {
+        self.seen.insert(code);
+        self.last_key = Some(code);
+        self.flashing.retain(|(key, _)| *key != code);
+        self.flashing.push((code, now + FLASH));
+        self.draw_key(code) + &self.draw_footer()
+    }
+
+    // Redraw the keys whose flash is over
+    pub fn tick(&mut self, now: Instant) -> String {
+        let (expired, flashing) = self.flashing.iter().partition::<Vec<_>, _>(|(_, until)| *until <= now);
+        self.flashing = flashing;
+        expired.iter().map(|(code, _)| self.draw_key(*code)).collect()
+    }
+
+    // Put the cursor below the keyboard, so what's printed next doesn't
+    // overwrite it
+    pub fn leave(&self) -> String {
+        format!("\x1b[{};1H\n", GRID_ROWS * ROW_HEIGHT + 2)
+    }
+
+    fn draw_key(&self, code: u32) -> String {
+        let Some((row, col, span)) = self.layout.position(code) else {
+            return String::new();
+        };
+        let width = span * UNIT_WIDTH - 2;
+        let style = if self.flashing.iter().any(|(key, _)| *key == code) {
+            INVERSE
+        } else if self.seen.contains(&code) {
+            Colors::GREEN
+        } else {
+            ""
+        };
+        let label = keys::alias(code).or_else(|| self.layout.relabel(code)).unwrap_or_else(|| key_name(code));
+        let label: String = label.chars().take(width).collect();
+
+        let top = 1 + row * ROW_HEIGHT;
+        let left = 1 + col * UNIT_WIDTH;
+        format!("\x1b[{};{}H{}┌{}┐\x1b[{};{}H│{:^width$}│\x1b[{};{}H└{}┘{}",
+            top, left, style, "─".repeat(width),
+            top + 1, left, label,
+            top + 2, left, "─".repeat(width), Colors::RESET,
+            width = width)
+    }
+
+    fn draw_footer(&self) -> String {
+        let on_grid = self.layout.keys().count();
+        let seen_on_grid = self.layout.keys().filter(|(code, ..)| self.seen.contains(code)).count();
+        let last = self.last_key.map_or("-".to_string(), |code| format!("{} (code {})", key_name(code), code));
+        // Keys off the drawn block (F-keys, arrows, numpad) are counted separately
+        let elsewhere = match self.seen.len() - seen_on_grid {
+            0 => String::new(),
+            count => format!(" (+{} elsewhere)", count),
+        };
+        format!("\x1b[{};1H\x1b[KLast key: {}{}{}   Keys registered: {}/{}{}",
+            GRID_ROWS * ROW_HEIGHT + 1, Colors::YELLOW, last, Colors::RESET, seen_on_grid, on_grid, elsewhere)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heatmap::QWERTY;
+
+    // KEY_A sits on the third row, two half-keys wide
+    const KEY_A_TOP: &str = "\x1b[7;13H";
+
+    #[test]
+    fn draws_each_key_as_a_box() {
+        let view = KeyboardView::new(&QWERTY);
+        let screen = view.draw();
+        assert!(screen.starts_with("\x1b[2J"));
+        assert!(screen.contains("\x1b[7;13H┌────┐\x1b[8;13H│ A  │\x1b[9;13H└────┘\x1b[0m"));
+        assert!(screen.contains(&format!("Keys registered: 0/{}", QWERTY.keys().count())));
+    }
+
+    #[test]
+    fn pressed_keys_flash_then_stay_lit() {
+        let mut view = KeyboardView::new(&QWERTY);
+        let now = Instant::now();
+        let drawn = view.key_pressed(30, now);
+        assert!(drawn.starts_with(&format!("{}{}┌", KEY_A_TOP, INVERSE)));
+        assert!(drawn.contains("Last key: \x1b[33mA (code 30)"));
+
+        assert!(view.tick(now + FLASH / 2).is_empty());
+        let drawn = view.tick(now + FLASH);
+        assert!(drawn.starts_with(&format!("{}{}┌", KEY_A_TOP, Colors::GREEN)));
+        assert!(view.draw().contains(&format!("{}{}┌", KEY_A_TOP, Colors::GREEN)));
+
+        // F1 isn't on the drawn block
+        let drawn = view.key_pressed(59, now);
+        assert!(drawn.ends_with(&format!("Keys registered: 1/{} (+1 elsewhere)", QWERTY.keys().count())));
+    }
+}
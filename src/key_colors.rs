@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::modifiers::is_modifier_key;
+
+// Groups of keys that --key-colors can give a color each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyCategory {
+    Letters,
+    // The number row and the keypad digits
+    Digits,
+    Modifiers,
+    // Arrows, Home, End, Page Up, Page Down, Insert
+    Navigation,
+    // F1 to F24
+    Function,
+    // Volume and playback keys
+    Media,
+}
+
+impl KeyCategory {
+    pub const NAMES: &'static str = "letters, digits, modifiers, navigation, function, media";
+
+    pub fn of(code: u32) -> Option<KeyCategory> {
+        match code {
+            16..=25 | 30..=38 | 44..=50 => Some(KeyCategory::Letters),
+            2..=11 | 71..=73 | 75..=77 | 79..=82 => Some(KeyCategory::Digits),
+            code if is_modifier_key(code) => Some(KeyCategory::Modifiers),
+            102..=110 => Some(KeyCategory::Navigation),
+            59..=68 | 87 | 88 | 183..=194 => Some(KeyCategory::Function),
+            113..=115 | 163..=166 | 200 | 201 => Some(KeyCategory::Media),
+            _ => None,
+        }
+    }
+
+    fn parse(name: &str) -> Option<KeyCategory> {
+        match name.to_ascii_lowercase().as_str() {
+            "letters" => Some(KeyCategory::Letters),
+            "digits" => Some(KeyCategory::Digits),
+            "modifiers" => Some(KeyCategory::Modifiers),
+            "navigation" => Some(KeyCategory::Navigation),
+            "function" => Some(KeyCategory::Function),
+            "media" => Some(KeyCategory::Media),
+            _ => None,
+        }
+    }
+}
+
+// --key-colors file, e.g.
+//
+//   [colors]
+//   letters = "#a3be8c"
+//   modifiers = "magenta"
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyColorsFile {
+    #[serde(default)]
+    colors: HashMap<String, String>,
+}
+
+// The color of each category's key press lines; categories left out keep
+// the theme's key press color
+#[derive(Debug, Default)]
+pub struct KeyColors {
+    colors: Vec<(KeyCategory, String)>,
+}
+
+impl KeyColors {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let file: KeyColorsFile = toml::from_str(&text).map_err(|err| err.to_string())?;
+        let mut colors = Vec::new();
+        for (name, value) in file.colors {
+            let category = KeyCategory::parse(&name)
+                .ok_or_else(|| format!("unknown key category '{}' (expected {})", name, KeyCategory::NAMES))?;
+            let sequence = parse_color(&value).ok_or_else(|| format!("invalid color '{}' for '{}'", value, name))?;
+            colors.push((category, sequence));
+        }
+        Ok(KeyColors { colors })
+    }
+
+    pub fn color(&self, code: u32) -> Option<&str> {
+        let category = KeyCategory::of(code)?;
+        self.colors.iter().find(|(each, _)| *each == category).map(|(_, sequence)| sequence.as_str())
+    }
+}
+
+// "#rrggbb" as a 24-bit color, or one of the basic color names from the
+// 256-color palette. Neither is one of the `Colors` constants, so
+// --color-theme leaves them alone.
+fn parse_color(value: &str) -> Option<String> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        return Some(format!("\x1b[38;2;{};{};{}m", channel(0)?, channel(2)?, channel(4)?));
+    }
+    let index = match value.to_ascii_lowercase().as_str() {
+        "black" => 0,
+        "red" => 1,
+        "green" => 2,
+        "yellow" => 3,
+        "blue" => 4,
+        "magenta" => 5,
+        "cyan" => 6,
+        "white" => 7,
+        "gray" | "grey" => 8,
+        _ => return None,
+    };
+    Some(format!("\x1b[38;5;{}m", index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_and_colors() {
+        assert_eq!(KeyCategory::of(30), Some(KeyCategory::Letters));
+        assert_eq!(KeyCategory::of(2), Some(KeyCategory::Digits));
+        assert_eq!(KeyCategory::of(125), Some(KeyCategory::Modifiers));
+        assert_eq!(KeyCategory::of(105), Some(KeyCategory::Navigation));
+        assert_eq!(KeyCategory::of(88), Some(KeyCategory::Function));
+        assert_eq!(KeyCategory::of(164), Some(KeyCategory::Media));
+        assert_eq!(KeyCategory::of(28), None);
+
+        assert_eq!(parse_color("#ff8000").as_deref(), Some("\x1b[38;2;255;128;0m"));
+        assert_eq!(parse_color("Magenta").as_deref(), Some("\x1b[38;5;5m"));
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("orange"), None);
+
+        let colors = KeyColors { colors: vec![(KeyCategory::Digits, "\x1b[38;5;2m".to_string())] };
+        assert_eq!(colors.color(3), Some("\x1b[38;5;2m"));
+        assert_eq!(colors.color(30), None);
+    }
+}
@@ -0,0 +1,97 @@
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::keys::{self, key_name};
+
+// A binding fires at most this often, whatever the key does
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+// An --on-key KEY=COMMAND pair
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBinding {
+    pub code: u32,
+    pub command: String,
+}
+
+impl KeyBinding {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        // Key names are never empty, so "==cmd" binds the = key
+        let split = value.char_indices().skip(1).find(|(_, c)| *c == '=').map(|(at, _)| at);
+        let (key, command) = split.map(|at| (&value[..at], &value[at + 1..])).ok_or("expected KEY=COMMAND")?;
+        let code = keys::key_code(key).ok_or_else(|| format!("unknown key '{}'", key.trim()))?;
+        if command.trim().is_empty() {
+            return Err("the command is empty".to_string());
+        }
+        Ok(KeyBinding { code, command: command.to_string() })
+    }
+}
+
+struct Runner {
+    binding: KeyBinding,
+    last_started: Option<Instant>,
+    running: Option<Child>,
+}
+
+// Runs shell commands bound to keys with --on-key. Commands start in the
+// background; a binding is skipped while its previous command still runs or
+// fired less than MIN_INTERVAL ago, so a held or bouncing key can't pile up
+// processes.
+pub struct KeyCommands {
+    runners: Vec<Runner>,
+}
+
+impl KeyCommands {
+    pub fn new(bindings: Vec<KeyBinding>) -> Self {
+        let runners = bindings.into_iter().map(|binding| Runner { binding, last_started: None, running: None }).collect();
+        KeyCommands { runners }
+    }
+
+    // Start the commands bound to `code`. Returns the commands started, or
+    // the first error.
+    pub fn key_pressed(&mut self, code: u32, time_usec: u64) -> Result<Vec<&str>, String> {
+        let now = Instant::now();
+        let mut started = Vec::new();
+        for runner in self.runners.iter_mut().filter(|runner| runner.binding.code == code) {
+            // Reap the previous run, or leave it be if it's still going
+            if let Some(child) = runner.running.as_mut() {
+                match child.try_wait() {
+                    Ok(None) => continue,
+                    _ => runner.running = None,
+                }
+            }
+            if runner.last_started.is_some_and(|last| now.duration_since(last) < MIN_INTERVAL) {
+                continue;
+            }
+
+            let child = Command::new("sh")
+                .arg("-c")
+                .arg(&runner.binding.command)
+                .env("RUSTKEY_KEY", key_name(code))
+                .env("RUSTKEY_TIME_USEC", time_usec.to_string())
+                // Keep the event stream on stdout clean
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .spawn()
+                .map_err(|err| format!("cannot run --on-key command '{}': {}", runner.binding.command, err))?;
+            runner.running = Some(child);
+            runner.last_started = Some(now);
+            started.push(runner.binding.command.as_str());
+        }
+        Ok(started)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_may_contain_and_keys_may_be_equals() {
+        assert_eq!(KeyBinding::parse("F12=A=b notify-send hi").unwrap(),
+            KeyBinding { code: 88, command: "A=b notify-send hi".to_string() });
+        assert_eq!(KeyBinding::parse("==make").unwrap(), KeyBinding { code: 13, command: "make".to_string() });
+        assert_eq!(KeyBinding::parse("13=make").unwrap().code, 13);
+        assert!(KeyBinding::parse("F12=").is_err());
+        assert!(KeyBinding::parse("=").is_err());
+    }
+}
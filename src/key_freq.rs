@@ -0,0 +1,81 @@
+// Linux keycodes fit in [0, KEY_MAX], KEY_MAX = 0x2ff
+pub const KEYCODE_SLOTS: usize = 768;
+
+// Per-keycode press counts in a fixed 6 KB table: O(1) lookups with no hashing,
+// which matters at high typing speeds
+#[derive(Debug, PartialEq)]
+pub struct KeyFrequencyMap {
+    counts: Box<[u64; KEYCODE_SLOTS]>,
+}
+
+impl KeyFrequencyMap {
+    pub fn new() -> Self {
+        KeyFrequencyMap { counts: Box::new([0; KEYCODE_SLOTS]) }
+    }
+
+    // Codes outside the kernel's range are ignored
+    pub fn increment(&mut self, code: u32) {
+        if let Some(count) = self.counts.get_mut(code as usize) {
+            *count += 1;
+        }
+    }
+
+    // Add `count` presses at once, e.g. from an imported stats file. Imported
+    // counts can be anything, so merging saturates rather than overflowing.
+    pub fn add(&mut self, code: u32, count: u64) {
+        if let Some(total) = self.counts.get_mut(code as usize) {
+            *total = total.saturating_add(count);
+        }
+    }
+
+    // Presses recorded for `code`; 0 for codes outside the kernel's range
+    #[cfg_attr(not(test), allow(dead_code))] // the binary only reads top_n
+    pub fn count(&self, code: u32) -> u64 {
+        self.counts.get(code as usize).copied().unwrap_or(0)
+    }
+
+    // The `n` most pressed keys, most frequent first (ties by lower keycode)
+    pub fn top_n(&self, n: usize) -> Vec<(u32, u64)> {
+        let mut pressed: Vec<(u32, u64)> = self.counts.iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(code, &count)| (code as u32, count))
+            .collect();
+        pressed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        pressed.truncate(n);
+        pressed
+    }
+}
+
+impl Default for KeyFrequencyMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_reads_back_increments_and_adds() {
+        let mut map = KeyFrequencyMap::new();
+        map.increment(30);
+        map.increment(30);
+        map.add(30, 5);
+        map.add(KEYCODE_SLOTS as u32 - 1, 1);
+        assert_eq!(map.count(30), 7);
+        assert_eq!(map.count(KEYCODE_SLOTS as u32 - 1), 1);
+        assert_eq!(map.count(31), 0);
+    }
+
+    #[test]
+    fn out_of_range_codes_are_ignored_and_count_zero() {
+        let mut map = KeyFrequencyMap::new();
+        map.increment(KEYCODE_SLOTS as u32);
+        map.add(u32::MAX, 3);
+        assert_eq!(map.count(KEYCODE_SLOTS as u32), 0);
+        assert_eq!(map.count(u32::MAX), 0);
+        assert_eq!(map, KeyFrequencyMap::new());
+    }
+}
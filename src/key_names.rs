@@ -0,0 +1,424 @@
+use phf::phf_map;
+
+// Kernel KEY_* names from linux/input-event-codes.h, keyed by key code, for
+// output that has to read like the kernel's (--format libinput-debug). The
+// display names in `keys` are friendlier but don't round-trip.
+static KEY_NAMES: phf::Map<u32, &'static str> = phf_map! {
+    0x0u32 => "KEY_RESERVED",
+    0x1u32 => "KEY_ESC",
+    0x2u32 => "KEY_1",
+    0x3u32 => "KEY_2",
+    0x4u32 => "KEY_3",
+    0x5u32 => "KEY_4",
+    0x6u32 => "KEY_5",
+    0x7u32 => "KEY_6",
+    0x8u32 => "KEY_7",
+    0x9u32 => "KEY_8",
+    0xau32 => "KEY_9",
+    0xbu32 => "KEY_0",
+    0xcu32 => "KEY_MINUS",
+    0xdu32 => "KEY_EQUAL",
+    0xeu32 => "KEY_BACKSPACE",
+    0xfu32 => "KEY_TAB",
+    0x10u32 => "KEY_Q",
+    0x11u32 => "KEY_W",
+    0x12u32 => "KEY_E",
+    0x13u32 => "KEY_R",
+    0x14u32 => "KEY_T",
+    0x15u32 => "KEY_Y",
+    0x16u32 => "KEY_U",
+    0x17u32 => "KEY_I",
+    0x18u32 => "KEY_O",
+    0x19u32 => "KEY_P",
+    0x1au32 => "KEY_LEFTBRACE",
+    0x1bu32 => "KEY_RIGHTBRACE",
+    0x1cu32 => "KEY_ENTER",
+    0x1du32 => "KEY_LEFTCTRL",
+    0x1eu32 => "KEY_A",
+    0x1fu32 => "KEY_S",
+    0x20u32 => "KEY_D",
+    0x21u32 => "KEY_F",
+    0x22u32 => "KEY_G",
+    0x23u32 => "KEY_H",
+    0x24u32 => "KEY_J",
+    0x25u32 => "KEY_K",
+    0x26u32 => "KEY_L",
+    0x27u32 => "KEY_SEMICOLON",
+    0x28u32 => "KEY_APOSTROPHE",
+    0x29u32 => "KEY_GRAVE",
+    0x2au32 => "KEY_LEFTSHIFT",
+    0x2bu32 => "KEY_BACKSLASH",
+    0x2cu32 => "KEY_Z",
+    0x2du32 => "KEY_X",
+    0x2eu32 => "KEY_C",
+    0x2fu32 => "KEY_V",
+    0x30u32 => "KEY_B",
+    0x31u32 => "KEY_N",
+    0x32u32 => "KEY_M",
+    0x33u32 => "KEY_COMMA",
+    0x34u32 => "KEY_DOT",
+    0x35u32 => "KEY_SLASH",
+    0x36u32 => "KEY_RIGHTSHIFT",
+    0x37u32 => "KEY_KPASTERISK",
+    0x38u32 => "KEY_LEFTALT",
+    0x39u32 => "KEY_SPACE",
+    0x3au32 => "KEY_CAPSLOCK",
+    0x3bu32 => "KEY_F1",
+    0x3cu32 => "KEY_F2",
+    0x3du32 => "KEY_F3",
+    0x3eu32 => "KEY_F4",
+    0x3fu32 => "KEY_F5",
+    0x40u32 => "KEY_F6",
+    0x41u32 => "KEY_F7",
+    0x42u32 => "KEY_F8",
+    0x43u32 => "KEY_F9",
+    0x44u32 => "KEY_F10",
+    0x45u32 => "KEY_NUMLOCK",
+    0x46u32 => "KEY_SCROLLLOCK",
+    0x47u32 => "KEY_KP7",
+    0x48u32 => "KEY_KP8",
+    0x49u32 => "KEY_KP9",
+    0x4au32 => "KEY_KPMINUS",
+    0x4bu32 => "KEY_KP4",
+    0x4cu32 => "KEY_KP5",
+    0x4du32 => "KEY_KP6",
+    0x4eu32 => "KEY_KPPLUS",
+    0x4fu32 => "KEY_KP1",
+    0x50u32 => "KEY_KP2",
+    0x51u32 => "KEY_KP3",
+    0x52u32 => "KEY_KP0",
+    0x53u32 => "KEY_KPDOT",
+    0x55u32 => "KEY_ZENKAKUHANKAKU",
+    0x56u32 => "KEY_102ND",
+    0x57u32 => "KEY_F11",
+    0x58u32 => "KEY_F12",
+    0x59u32 => "KEY_RO",
+    0x5au32 => "KEY_KATAKANA",
+    0x5bu32 => "KEY_HIRAGANA",
+    0x5cu32 => "KEY_HENKAN",
+    0x5du32 => "KEY_KATAKANAHIRAGANA",
+    0x5eu32 => "KEY_MUHENKAN",
+    0x5fu32 => "KEY_KPJPCOMMA",
+    0x60u32 => "KEY_KPENTER",
+    0x61u32 => "KEY_RIGHTCTRL",
+    0x62u32 => "KEY_KPSLASH",
+    0x63u32 => "KEY_SYSRQ",
+    0x64u32 => "KEY_RIGHTALT",
+    0x65u32 => "KEY_LINEFEED",
+    0x66u32 => "KEY_HOME",
+    0x67u32 => "KEY_UP",
+    0x68u32 => "KEY_PAGEUP",
+    0x69u32 => "KEY_LEFT",
+    0x6au32 => "KEY_RIGHT",
+    0x6bu32 => "KEY_END",
+    0x6cu32 => "KEY_DOWN",
+    0x6du32 => "KEY_PAGEDOWN",
+    0x6eu32 => "KEY_INSERT",
+    0x6fu32 => "KEY_DELETE",
+    0x70u32 => "KEY_MACRO",
+    0x71u32 => "KEY_MUTE",
+    0x72u32 => "KEY_VOLUMEDOWN",
+    0x73u32 => "KEY_VOLUMEUP",
+    0x74u32 => "KEY_POWER",
+    0x75u32 => "KEY_KPEQUAL",
+    0x76u32 => "KEY_KPPLUSMINUS",
+    0x77u32 => "KEY_PAUSE",
+    0x78u32 => "KEY_SCALE",
+    0x79u32 => "KEY_KPCOMMA",
+    0x7au32 => "KEY_HANGEUL",
+    0x7bu32 => "KEY_HANJA",
+    0x7cu32 => "KEY_YEN",
+    0x7du32 => "KEY_LEFTMETA",
+    0x7eu32 => "KEY_RIGHTMETA",
+    0x7fu32 => "KEY_COMPOSE",
+    0x80u32 => "KEY_STOP",
+    0x81u32 => "KEY_AGAIN",
+    0x82u32 => "KEY_PROPS",
+    0x83u32 => "KEY_UNDO",
+    0x84u32 => "KEY_FRONT",
+    0x85u32 => "KEY_COPY",
+    0x86u32 => "KEY_OPEN",
+    0x87u32 => "KEY_PASTE",
+    0x88u32 => "KEY_FIND",
+    0x89u32 => "KEY_CUT",
+    0x8au32 => "KEY_HELP",
+    0x8bu32 => "KEY_MENU",
+    0x8cu32 => "KEY_CALC",
+    0x8du32 => "KEY_SETUP",
+    0x8eu32 => "KEY_SLEEP",
+    0x8fu32 => "KEY_WAKEUP",
+    0x90u32 => "KEY_FILE",
+    0x91u32 => "KEY_SENDFILE",
+    0x92u32 => "KEY_DELETEFILE",
+    0x93u32 => "KEY_XFER",
+    0x94u32 => "KEY_PROG1",
+    0x95u32 => "KEY_PROG2",
+    0x96u32 => "KEY_WWW",
+    0x97u32 => "KEY_MSDOS",
+    0x98u32 => "KEY_COFFEE",
+    0x99u32 => "KEY_DIRECTION",
+    0x9au32 => "KEY_CYCLEWINDOWS",
+    0x9bu32 => "KEY_MAIL",
+    0x9cu32 => "KEY_BOOKMARKS",
+    0x9du32 => "KEY_COMPUTER",
+    0x9eu32 => "KEY_BACK",
+    0x9fu32 => "KEY_FORWARD",
+    0xa0u32 => "KEY_CLOSECD",
+    0xa1u32 => "KEY_EJECTCD",
+    0xa2u32 => "KEY_EJECTCLOSECD",
+    0xa3u32 => "KEY_NEXTSONG",
+    0xa4u32 => "KEY_PLAYPAUSE",
+    0xa5u32 => "KEY_PREVIOUSSONG",
+    0xa6u32 => "KEY_STOPCD",
+    0xa7u32 => "KEY_RECORD",
+    0xa8u32 => "KEY_REWIND",
+    0xa9u32 => "KEY_PHONE",
+    0xaau32 => "KEY_ISO",
+    0xabu32 => "KEY_CONFIG",
+    0xacu32 => "KEY_HOMEPAGE",
+    0xadu32 => "KEY_REFRESH",
+    0xaeu32 => "KEY_EXIT",
+    0xafu32 => "KEY_MOVE",
+    0xb0u32 => "KEY_EDIT",
+    0xb1u32 => "KEY_SCROLLUP",
+    0xb2u32 => "KEY_SCROLLDOWN",
+    0xb3u32 => "KEY_KPLEFTPAREN",
+    0xb4u32 => "KEY_KPRIGHTPAREN",
+    0xb5u32 => "KEY_NEW",
+    0xb6u32 => "KEY_REDO",
+    0xb7u32 => "KEY_F13",
+    0xb8u32 => "KEY_F14",
+    0xb9u32 => "KEY_F15",
+    0xbau32 => "KEY_F16",
+    0xbbu32 => "KEY_F17",
+    0xbcu32 => "KEY_F18",
+    0xbdu32 => "KEY_F19",
+    0xbeu32 => "KEY_F20",
+    0xbfu32 => "KEY_F21",
+    0xc0u32 => "KEY_F22",
+    0xc1u32 => "KEY_F23",
+    0xc2u32 => "KEY_F24",
+    0xc8u32 => "KEY_PLAYCD",
+    0xc9u32 => "KEY_PAUSECD",
+    0xcau32 => "KEY_PROG3",
+    0xcbu32 => "KEY_PROG4",
+    0xccu32 => "KEY_DASHBOARD",
+    0xcdu32 => "KEY_SUSPEND",
+    0xceu32 => "KEY_CLOSE",
+    0xcfu32 => "KEY_PLAY",
+    0xd0u32 => "KEY_FASTFORWARD",
+    0xd1u32 => "KEY_BASSBOOST",
+    0xd2u32 => "KEY_PRINT",
+    0xd3u32 => "KEY_HP",
+    0xd4u32 => "KEY_CAMERA",
+    0xd5u32 => "KEY_SOUND",
+    0xd6u32 => "KEY_QUESTION",
+    0xd7u32 => "KEY_EMAIL",
+    0xd8u32 => "KEY_CHAT",
+    0xd9u32 => "KEY_SEARCH",
+    0xdau32 => "KEY_CONNECT",
+    0xdbu32 => "KEY_FINANCE",
+    0xdcu32 => "KEY_SPORT",
+    0xddu32 => "KEY_SHOP",
+    0xdeu32 => "KEY_ALTERASE",
+    0xdfu32 => "KEY_CANCEL",
+    0xe0u32 => "KEY_BRIGHTNESSDOWN",
+    0xe1u32 => "KEY_BRIGHTNESSUP",
+    0xe2u32 => "KEY_MEDIA",
+    0xe3u32 => "KEY_SWITCHVIDEOMODE",
+    0xe4u32 => "KEY_KBDILLUMTOGGLE",
+    0xe5u32 => "KEY_KBDILLUMDOWN",
+    0xe6u32 => "KEY_KBDILLUMUP",
+    0xe7u32 => "KEY_SEND",
+    0xe8u32 => "KEY_REPLY",
+    0xe9u32 => "KEY_FORWARDMAIL",
+    0xeau32 => "KEY_SAVE",
+    0xebu32 => "KEY_DOCUMENTS",
+    0xecu32 => "KEY_BATTERY",
+    0xedu32 => "KEY_BLUETOOTH",
+    0xeeu32 => "KEY_WLAN",
+    0xefu32 => "KEY_UWB",
+    0xf0u32 => "KEY_UNKNOWN",
+    0xf1u32 => "KEY_VIDEO_NEXT",
+    0xf2u32 => "KEY_VIDEO_PREV",
+    0xf3u32 => "KEY_BRIGHTNESS_CYCLE",
+    0xf4u32 => "KEY_BRIGHTNESS_AUTO",
+    0xf5u32 => "KEY_DISPLAY_OFF",
+    0xf6u32 => "KEY_WWAN",
+    0xf7u32 => "KEY_RFKILL",
+    0xf8u32 => "KEY_MICMUTE",
+    0x160u32 => "KEY_OK",
+    0x161u32 => "KEY_SELECT",
+    0x162u32 => "KEY_GOTO",
+    0x163u32 => "KEY_CLEAR",
+    0x164u32 => "KEY_POWER2",
+    0x165u32 => "KEY_OPTION",
+    0x166u32 => "KEY_INFO",
+    0x167u32 => "KEY_TIME",
+    0x168u32 => "KEY_VENDOR",
+    0x169u32 => "KEY_ARCHIVE",
+    0x16au32 => "KEY_PROGRAM",
+    0x16bu32 => "KEY_CHANNEL",
+    0x16cu32 => "KEY_FAVORITES",
+    0x16du32 => "KEY_EPG",
+    0x16eu32 => "KEY_PVR",
+    0x16fu32 => "KEY_MHP",
+    0x170u32 => "KEY_LANGUAGE",
+    0x171u32 => "KEY_TITLE",
+    0x172u32 => "KEY_SUBTITLE",
+    0x173u32 => "KEY_ANGLE",
+    0x174u32 => "KEY_ZOOM",
+    0x175u32 => "KEY_MODE",
+    0x176u32 => "KEY_KEYBOARD",
+    0x177u32 => "KEY_SCREEN",
+    0x178u32 => "KEY_PC",
+    0x179u32 => "KEY_TV",
+    0x17au32 => "KEY_TV2",
+    0x17bu32 => "KEY_VCR",
+    0x17cu32 => "KEY_VCR2",
+    0x17du32 => "KEY_SAT",
+    0x17eu32 => "KEY_SAT2",
+    0x17fu32 => "KEY_CD",
+    0x180u32 => "KEY_TAPE",
+    0x181u32 => "KEY_RADIO",
+    0x182u32 => "KEY_TUNER",
+    0x183u32 => "KEY_PLAYER",
+    0x184u32 => "KEY_TEXT",
+    0x185u32 => "KEY_DVD",
+    0x186u32 => "KEY_AUX",
+    0x187u32 => "KEY_MP3",
+    0x188u32 => "KEY_AUDIO",
+    0x189u32 => "KEY_VIDEO",
+    0x18au32 => "KEY_DIRECTORY",
+    0x18bu32 => "KEY_LIST",
+    0x18cu32 => "KEY_MEMO",
+    0x18du32 => "KEY_CALENDAR",
+    0x18eu32 => "KEY_RED",
+    0x18fu32 => "KEY_GREEN",
+    0x190u32 => "KEY_YELLOW",
+    0x191u32 => "KEY_BLUE",
+    0x192u32 => "KEY_CHANNELUP",
+    0x193u32 => "KEY_CHANNELDOWN",
+    0x194u32 => "KEY_FIRST",
+    0x195u32 => "KEY_LAST",
+    0x196u32 => "KEY_AB",
+    0x197u32 => "KEY_NEXT",
+    0x198u32 => "KEY_RESTART",
+    0x199u32 => "KEY_SLOW",
+    0x19au32 => "KEY_SHUFFLE",
+    0x19bu32 => "KEY_BREAK",
+    0x19cu32 => "KEY_PREVIOUS",
+    0x19du32 => "KEY_DIGITS",
+    0x19eu32 => "KEY_TEEN",
+    0x19fu32 => "KEY_TWEN",
+    0x1a0u32 => "KEY_VIDEOPHONE",
+    0x1a1u32 => "KEY_GAMES",
+    0x1a2u32 => "KEY_ZOOMIN",
+    0x1a3u32 => "KEY_ZOOMOUT",
+    0x1a4u32 => "KEY_ZOOMRESET",
+    0x1a5u32 => "KEY_WORDPROCESSOR",
+    0x1a6u32 => "KEY_EDITOR",
+    0x1a7u32 => "KEY_SPREADSHEET",
+    0x1a8u32 => "KEY_GRAPHICSEDITOR",
+    0x1a9u32 => "KEY_PRESENTATION",
+    0x1aau32 => "KEY_DATABASE",
+    0x1abu32 => "KEY_NEWS",
+    0x1acu32 => "KEY_VOICEMAIL",
+    0x1adu32 => "KEY_ADDRESSBOOK",
+    0x1aeu32 => "KEY_MESSENGER",
+    0x1afu32 => "KEY_DISPLAYTOGGLE",
+    0x1b0u32 => "KEY_SPELLCHECK",
+    0x1b1u32 => "KEY_LOGOFF",
+    0x1b2u32 => "KEY_DOLLAR",
+    0x1b3u32 => "KEY_EURO",
+    0x1b4u32 => "KEY_FRAMEBACK",
+    0x1b5u32 => "KEY_FRAMEFORWARD",
+    0x1b6u32 => "KEY_CONTEXT_MENU",
+    0x1b7u32 => "KEY_MEDIA_REPEAT",
+    0x1b8u32 => "KEY_10CHANNELSUP",
+    0x1b9u32 => "KEY_10CHANNELSDOWN",
+    0x1bau32 => "KEY_IMAGES",
+    0x1c0u32 => "KEY_DEL_EOL",
+    0x1c1u32 => "KEY_DEL_EOS",
+    0x1c2u32 => "KEY_INS_LINE",
+    0x1c3u32 => "KEY_DEL_LINE",
+    0x1d0u32 => "KEY_FN",
+    0x1d1u32 => "KEY_FN_ESC",
+    0x1d2u32 => "KEY_FN_F1",
+    0x1d3u32 => "KEY_FN_F2",
+    0x1d4u32 => "KEY_FN_F3",
+    0x1d5u32 => "KEY_FN_F4",
+    0x1d6u32 => "KEY_FN_F5",
+    0x1d7u32 => "KEY_FN_F6",
+    0x1d8u32 => "KEY_FN_F7",
+    0x1d9u32 => "KEY_FN_F8",
+    0x1dau32 => "KEY_FN_F9",
+    0x1dbu32 => "KEY_FN_F10",
+    0x1dcu32 => "KEY_FN_F11",
+    0x1ddu32 => "KEY_FN_F12",
+    0x1deu32 => "KEY_FN_1",
+    0x1dfu32 => "KEY_FN_2",
+    0x1e0u32 => "KEY_FN_D",
+    0x1e1u32 => "KEY_FN_E",
+    0x1e2u32 => "KEY_FN_F",
+    0x1e3u32 => "KEY_FN_S",
+    0x1e4u32 => "KEY_FN_B",
+    0x1f1u32 => "KEY_BRL_DOT1",
+    0x1f2u32 => "KEY_BRL_DOT2",
+    0x1f3u32 => "KEY_BRL_DOT3",
+    0x1f4u32 => "KEY_BRL_DOT4",
+    0x1f5u32 => "KEY_BRL_DOT5",
+    0x1f6u32 => "KEY_BRL_DOT6",
+    0x1f7u32 => "KEY_BRL_DOT7",
+    0x1f8u32 => "KEY_BRL_DOT8",
+    0x1f9u32 => "KEY_BRL_DOT9",
+    0x1fau32 => "KEY_BRL_DOT10",
+    0x200u32 => "KEY_NUMERIC_0",
+    0x201u32 => "KEY_NUMERIC_1",
+    0x202u32 => "KEY_NUMERIC_2",
+    0x203u32 => "KEY_NUMERIC_3",
+    0x204u32 => "KEY_NUMERIC_4",
+    0x205u32 => "KEY_NUMERIC_5",
+    0x206u32 => "KEY_NUMERIC_6",
+    0x207u32 => "KEY_NUMERIC_7",
+    0x208u32 => "KEY_NUMERIC_8",
+    0x209u32 => "KEY_NUMERIC_9",
+    0x20au32 => "KEY_NUMERIC_STAR",
+    0x20bu32 => "KEY_NUMERIC_POUND",
+    0x210u32 => "KEY_CAMERA_FOCUS",
+    0x211u32 => "KEY_WPS_BUTTON",
+    0x212u32 => "KEY_TOUCHPAD_TOGGLE",
+    0x213u32 => "KEY_TOUCHPAD_ON",
+    0x214u32 => "KEY_TOUCHPAD_OFF",
+    0x215u32 => "KEY_CAMERA_ZOOMIN",
+    0x216u32 => "KEY_CAMERA_ZOOMOUT",
+    0x217u32 => "KEY_CAMERA_UP",
+    0x218u32 => "KEY_CAMERA_DOWN",
+    0x219u32 => "KEY_CAMERA_LEFT",
+    0x21au32 => "KEY_CAMERA_RIGHT",
+    0x21bu32 => "KEY_ATTENDANT_ON",
+    0x21cu32 => "KEY_ATTENDANT_OFF",
+    0x21du32 => "KEY_ATTENDANT_TOGGLE",
+    0x21eu32 => "KEY_LIGHTS_TOGGLE",
+    0x230u32 => "KEY_ALS_TOGGLE",
+    0x240u32 => "KEY_BUTTONCONFIG",
+    0x241u32 => "KEY_TASKMANAGER",
+    0x242u32 => "KEY_JOURNAL",
+    0x243u32 => "KEY_CONTROLPANEL",
+    0x244u32 => "KEY_APPSELECT",
+    0x245u32 => "KEY_SCREENSAVER",
+    0x246u32 => "KEY_VOICECOMMAND",
+    0x250u32 => "KEY_BRIGHTNESS_MIN",
+    0x251u32 => "KEY_BRIGHTNESS_MAX",
+    0x260u32 => "KEY_KBDINPUTASSIST_PREV",
+    0x261u32 => "KEY_KBDINPUTASSIST_NEXT",
+    0x262u32 => "KEY_KBDINPUTASSIST_PREVGROUP",
+    0x263u32 => "KEY_KBDINPUTASSIST_NEXTGROUP",
+    0x264u32 => "KEY_KBDINPUTASSIST_ACCEPT",
+    0x265u32 => "KEY_KBDINPUTASSIST_CANCEL",
+};
+
+// Kernel name for a key code, e.g. 30 -> "KEY_A"
+pub fn kernel_key_name(key_code: u32) -> Option<&'static str> {
+    KEY_NAMES.get(&key_code).copied()
+}
@@ -0,0 +1,185 @@
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::process::Command;
+
+use crate::Colors;
+
+const INPUT_CLASS: &str = "/sys/class/input";
+// From <linux/input.h>: EVIOCGREP / EVIOCSREP, unsigned int[2] of delay and period
+const EVIOCGREP: libc::c_ulong = 0x8008_4503;
+const EVIOCSREP: libc::c_ulong = 0x4008_4503;
+// Devices the kernel autorepeats for have this event type
+const EV_REP: u32 = 0x14;
+
+pub struct SetRepeatOptions {
+    pub device: String,
+    pub delay_ms: u32,
+    // Keys per second
+    pub rate: u32,
+}
+
+// The kernel's autorepeat for one device: the wait before a held key starts
+// repeating and the time between repeats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyboardRepeatConfig {
+    pub delay_ms: u32,
+    pub period_ms: u32,
+}
+
+impl KeyboardRepeatConfig {
+    // Repeats per second
+    pub fn rate(&self) -> f64 {
+        if self.period_ms == 0 { 0.0 } else { 1000.0 / self.period_ms as f64 }
+    }
+
+    fn read(device: &RepeatDevice) -> io::Result<Self> {
+        let file = File::open(device.node())?;
+        let mut values: [libc::c_uint; 2] = [0; 2];
+        if unsafe { libc::ioctl(file.as_raw_fd(), EVIOCGREP, values.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(KeyboardRepeatConfig { delay_ms: values[0], period_ms: values[1] })
+    }
+
+    fn write(&self, device: &RepeatDevice) -> io::Result<()> {
+        let file = OpenOptions::new().read(true).write(true).open(device.node())?;
+        let values: [libc::c_uint; 2] = [self.delay_ms, self.period_ms];
+        if unsafe { libc::ioctl(file.as_raw_fd(), EVIOCSREP, values.as_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+// An event node with autorepeat, found through sysfs
+struct RepeatDevice {
+    // e.g. "event3"
+    sysname: String,
+    name: String,
+}
+
+impl RepeatDevice {
+    fn node(&self) -> String {
+        format!("/dev/input/{}", self.sysname)
+    }
+}
+
+fn repeat_devices() -> Vec<RepeatDevice> {
+    let Ok(entries) = fs::read_dir(INPUT_CLASS) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<RepeatDevice> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|sysname| sysname.starts_with("event"))
+        .filter_map(|sysname| {
+            let device = Path::new(INPUT_CLASS).join(&sysname).join("device");
+            let ev = fs::read_to_string(device.join("capabilities/ev")).ok()?;
+            if u64::from_str_radix(ev.trim(), 16).ok()? & (1 << EV_REP) == 0 {
+                return None;
+            }
+            let name = fs::read_to_string(device.join("name")).map(|name| name.trim().to_string()).unwrap_or_default();
+            Some(RepeatDevice { sysname, name })
+        })
+        .collect();
+    // event10 after event9
+    devices.sort_by_key(|device| device.sysname["event".len()..].parse::<u32>().unwrap_or(u32::MAX));
+    devices
+}
+
+// `rustkey show-repeat-config`: the autorepeat of every keyboard, or the X
+// server's if no event node can be read; returns the process exit code
+pub fn run_show() -> i32 {
+    let mut shown = Vec::new();
+    let mut denied = None;
+    for device in repeat_devices() {
+        match KeyboardRepeatConfig::read(&device) {
+            Ok(config) => shown.push((format!("{} ({})", device.name, device.sysname), config)),
+            Err(err) => denied = Some(err),
+        }
+    }
+    // Without access to the event nodes, X11 still knows its own setting
+    if shown.is_empty() {
+        if let Some(config) = command_output("xset", &["q"]).as_deref().and_then(parse_xset) {
+            shown.push(("X server (xset q)".to_string(), config));
+        }
+    }
+
+    if !shown.is_empty() {
+        println!("{}{}⌨️  Key repeat{}", Colors::CYAN, Colors::BOLD, Colors::RESET);
+        for (device, config) in shown {
+            println!("  {}: delay {} ms, rate {:.1} keys/s", device, config.delay_ms, config.rate());
+        }
+        return 0;
+    }
+
+    match denied {
+        Some(err) => eprintln!("{}error: cannot read the keyboards' repeat settings: {} (run as root or join the input group){}",
+            Colors::RED, err, Colors::RESET),
+        None => eprintln!("{}error: no keyboard with key repeat found in {}{}", Colors::RED, INPUT_CLASS, Colors::RESET),
+    }
+    2
+}
+
+// `rustkey set-repeat`: change one keyboard's autorepeat; returns the
+// process exit code
+pub fn run_set(options: &SetRepeatOptions) -> i32 {
+    // Match the kernel name exactly ("event3") or the product name by substring
+    let wanted_lower = options.device.to_lowercase();
+    let mut matches: Vec<RepeatDevice> = repeat_devices().into_iter()
+        .filter(|device| device.sysname == options.device || device.name.to_lowercase().contains(&wanted_lower))
+        .collect();
+    let device = match matches.len() {
+        0 => {
+            eprintln!("{}error: no keyboard with key repeat matches '{}'{}", Colors::RED, options.device, Colors::RESET);
+            return 2;
+        },
+        1 => matches.remove(0),
+        _ => {
+            eprintln!("{}error: '{}' matches several keyboards, be more specific:{}", Colors::RED, options.device, Colors::RESET);
+            for device in &matches {
+                eprintln!("  {} ({})", device.name, device.sysname);
+            }
+            return 2;
+        },
+    };
+
+    let config = KeyboardRepeatConfig { delay_ms: options.delay_ms, period_ms: (1000 + options.rate / 2) / options.rate };
+    if let Err(err) = config.write(&device) {
+        eprintln!("{}error: cannot set key repeat on {}: {}{}", Colors::RED, device.node(), err, Colors::RESET);
+        return 1;
+    }
+    println!("{}✅ {} ({}): delay {} ms, rate {:.1} keys/s{}",
+        Colors::GREEN, device.name, device.sysname, config.delay_ms, config.rate(), Colors::RESET);
+    // The compositor or X server may apply its own repeat on top
+    println!("Note: desktops usually repeat keys themselves; this changes the kernel's repeat, which consoles and raw evdev readers see.");
+    0
+}
+
+// "auto repeat delay:  660    repeat rate:  25" from `xset q`
+fn parse_xset(output: &str) -> Option<KeyboardRepeatConfig> {
+    let line = output.lines().find(|line| line.contains("auto repeat delay:"))?;
+    let mut words = line.split_whitespace();
+    let delay_ms = words.by_ref().skip_while(|word| *word != "delay:").nth(1)?.parse().ok()?;
+    let rate: u32 = words.skip_while(|word| *word != "rate:").nth(1)?.parse().ok()?;
+    (rate > 0).then(|| KeyboardRepeatConfig { delay_ms, period_ms: 1000 / rate })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_xset_repeat_line() {
+        let output = "Keyboard Control:\n  auto repeat:  on    key click percent:  0    LED mask:  00000000\n  \
+            XKB indicators:\n  auto repeat delay:  660    repeat rate:  25\n";
+        assert_eq!(parse_xset(output), Some(KeyboardRepeatConfig { delay_ms: 660, period_ms: 40 }));
+        assert_eq!(parse_xset("Pointer Control:\n"), None);
+    }
+}
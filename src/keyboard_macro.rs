@@ -0,0 +1,367 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::Duration;
+
+use input::Libinput;
+
+use crate::event::{self, InputEvent};
+use crate::keys::{self, key_name};
+use crate::modifiers::Modifiers;
+use crate::recording;
+use crate::signals;
+use crate::{Colors, Interface};
+
+// Gaps shorter than this aren't worth an `xdotool sleep`
+const MIN_SLEEP_USEC: u64 = 20_000;
+// A pause this long ends a typed word, so the pause itself is kept
+const WORD_BREAK_USEC: u64 = 500_000;
+
+// US layout: the characters a key types without and with Shift
+const SHIFTED: &[(char, char)] = &[
+    ('1', '!'), ('2', '@'), ('3', '#'), ('4', '$'), ('5', '%'), ('6', '^'), ('7', '&'),
+    ('8', '*'), ('9', '('), ('0', ')'), ('-', '_'), ('=', '+'), ('[', '{'), (']', '}'),
+    (';', ':'), ('\'', '"'), ('`', '~'), ('\\', '|'), (',', '<'), ('.', '>'), ('/', '?'),
+];
+
+// Options for `rustkey record-macro`
+pub struct MacroOptions {
+    pub output: PathBuf,
+    pub trigger: Hotkey,
+    pub stop: Hotkey,
+    pub ignore_timing: bool,
+    // Build the macro from a recording instead of live input
+    pub replay: Option<PathBuf>,
+}
+
+// A key plus the modifiers that must be held with it, e.g. Ctrl+F9
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hotkey {
+    // Collapsed modifier label as produced by `Modifiers::label`, e.g. "CTRL+SHIFT"
+    modifiers: String,
+    code: u32,
+    spec: String,
+}
+
+impl Hotkey {
+    pub fn parse(spec: &str) -> Result<Hotkey, String> {
+        let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty())
+            .ok_or_else(|| format!("invalid hotkey '{}'", spec))?;
+        let code = keys::key_code(key).ok_or_else(|| format!("unknown key '{}' in hotkey '{}'", key, spec))?;
+
+        let mut held = [false; 4];
+        for part in parts {
+            let index = match part.to_lowercase().as_str() {
+                "ctrl" | "control" => 0,
+                "shift" => 1,
+                "alt" => 2,
+                "super" | "meta" | "win" => 3,
+                _ => return Err(format!("unknown modifier '{}' in hotkey '{}'", part, spec)),
+            };
+            held[index] = true;
+        }
+        // Same order as Modifiers::label
+        let modifiers = ["CTRL", "SHIFT", "ALT", "SUPER"].iter().zip(held)
+            .filter(|(_, held)| *held)
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join("+");
+
+        Ok(Hotkey { modifiers, code, spec: spec.to_string() })
+    }
+
+    fn matches(&self, held: &Modifiers, code: u32) -> bool {
+        code == self.code && held.label() == self.modifiers
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum MacroStep {
+    Type(String),
+    Key(String),
+    Sleep(u64),
+}
+
+// Collects the key presses between the trigger and stop hotkeys
+pub struct KeyboardMacroRecorder {
+    trigger: Hotkey,
+    stop: Hotkey,
+    ignore_timing: bool,
+    modifiers: Modifiers,
+    recording: bool,
+    finished: bool,
+    last_usec: u64,
+    steps: Vec<MacroStep>,
+    // Presses of keys xdotool has no name for here
+    skipped: u32,
+}
+
+impl KeyboardMacroRecorder {
+    pub fn new(trigger: Hotkey, stop: Hotkey, ignore_timing: bool) -> Self {
+        KeyboardMacroRecorder {
+            trigger,
+            stop,
+            ignore_timing,
+            modifiers: Modifiers::default(),
+            recording: false,
+            finished: false,
+            last_usec: 0,
+            steps: Vec::new(),
+            skipped: 0,
+        }
+    }
+
+    pub fn key(&mut self, code: u32, pressed: bool, time_usec: u64) {
+        let held = self.modifiers;
+        // Modifiers only count as part of the key they're held for
+        if self.modifiers.update(code, pressed) || !pressed || self.finished {
+            return;
+        }
+
+        if !self.recording {
+            if self.trigger.matches(&held, code) {
+                self.recording = true;
+                self.last_usec = time_usec;
+            }
+            return;
+        }
+        if self.stop.matches(&held, code) {
+            self.recording = false;
+            self.finished = true;
+            return;
+        }
+
+        let gap = time_usec.saturating_sub(self.last_usec);
+        self.last_usec = time_usec;
+        let label = held.label();
+
+        // Plain or shifted characters are typed; everything else is a key stroke
+        let typed = match label.as_str() {
+            "" => typed_char(code, false),
+            "SHIFT" => typed_char(code, true),
+            _ => None,
+        };
+        if let Some(ch) = typed {
+            if let Some(MacroStep::Type(text)) = self.steps.last_mut() {
+                if gap < WORD_BREAK_USEC || self.ignore_timing {
+                    text.push(ch);
+                    return;
+                }
+            }
+            self.push_sleep(gap);
+            self.steps.push(MacroStep::Type(ch.to_string()));
+            return;
+        }
+
+        let Some(keysym) = keysym(code) else {
+            self.skipped += 1;
+            return;
+        };
+        let mut combo: Vec<String> = label.split('+')
+            .filter(|name| !name.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        combo.push(keysym);
+        self.push_sleep(gap);
+        self.steps.push(MacroStep::Key(combo.join("+")));
+    }
+
+    fn push_sleep(&mut self, gap_usec: u64) {
+        if !self.ignore_timing && !self.steps.is_empty() && gap_usec >= MIN_SLEEP_USEC {
+            self.steps.push(MacroStep::Sleep(gap_usec));
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // The recorded steps as a shell script
+    pub fn script(&self) -> String {
+        let mut script = String::from("#!/bin/sh\n# Recorded with `rustkey record-macro`\nset -e\n\n");
+        for step in &self.steps {
+            match step {
+                MacroStep::Type(text) => script.push_str(&format!("xdotool type -- \"{}\"\n", shell_escape(text))),
+                MacroStep::Key(combo) => script.push_str(&format!("xdotool key {}\n", combo)),
+                MacroStep::Sleep(usec) => script.push_str(&format!("xdotool sleep {:.3}\n", *usec as f64 / 1_000_000.0)),
+            }
+        }
+        script
+    }
+
+    // Write an executable script
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.script())?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+    }
+}
+
+fn typed_char(code: u32, shifted: bool) -> Option<char> {
+    if !keys::is_character_key(code) {
+        return None;
+    }
+    if code == 57 {
+        return Some(' ');
+    }
+    let ch = key_name(code).chars().next()?;
+    if !shifted {
+        return Some(ch.to_ascii_lowercase());
+    }
+    if ch.is_ascii_alphabetic() {
+        return Some(ch);
+    }
+    SHIFTED.iter().find(|(plain, _)| *plain == ch).map(|(_, shifted)| *shifted)
+}
+
+// X keysym names for the keys `key_name` knows
+fn keysym(code: u32) -> Option<String> {
+    let name = key_name(code);
+    if keys::is_character_key(code) {
+        let sym = match name {
+            "SPACE" => "space", "-" => "minus", "=" => "equal", "[" => "bracketleft",
+            "]" => "bracketright", ";" => "semicolon", "'" => "apostrophe", "`" => "grave",
+            "\\" => "backslash", "," => "comma", "." => "period", "/" => "slash",
+            letter_or_digit => return Some(letter_or_digit.to_lowercase()),
+        };
+        return Some(sym.to_string());
+    }
+    if name.starts_with('F') && name[1..].parse::<u32>().is_ok() {
+        return Some(name.to_string());
+    }
+    let sym = match name {
+        "ESC" => "Escape",
+        "ENTER" => "Return",
+        "BACKSPACE" => "BackSpace",
+        "TAB" => "Tab",
+        "UP" => "Up",
+        "DOWN" => "Down",
+        "LEFT" => "Left",
+        "RIGHT" => "Right",
+        "HOME" => "Home",
+        "END" => "End",
+        "PAGE UP" => "Prior",
+        "PAGE DOWN" => "Next",
+        "DELETE" => "Delete",
+        "INSERT" => "Insert",
+        "CAPS LOCK" => "Caps_Lock",
+        "NUM LOCK" => "Num_Lock",
+        "SCROLL LOCK" => "Scroll_Lock",
+        "PRINT SCREEN" => "Print",
+        "PAUSE" => "Pause",
+        "NUM ENTER" => "KP_Enter",
+        "NUM +" => "KP_Add",
+        "NUM -" => "KP_Subtract",
+        "NUM *" => "KP_Multiply",
+        "NUM /" => "KP_Divide",
+        "NUM ." => "KP_Decimal",
+        "MUTE" => "XF86AudioMute",
+        "VOLUME DOWN" => "XF86AudioLowerVolume",
+        "VOLUME UP" => "XF86AudioRaiseVolume",
+        "PLAY/PAUSE" => "XF86AudioPlay",
+        "NEXT TRACK" => "XF86AudioNext",
+        "PREV TRACK" => "XF86AudioPrev",
+        "STOP TRACK" => "XF86AudioStop",
+        "<>" => "less",
+        "STOP" => "Cancel",
+        "COMPOSE" | "MENU" => "Menu",
+        "POWER" => "XF86PowerOff",
+        "SLEEP" => "XF86Sleep",
+        "WAKE UP" => "XF86WakeUp",
+        "CALCULATOR" => "XF86Calculator",
+        "WWW" => "XF86WWW",
+        "SCREEN LOCK" => "XF86ScreenSaver",
+        "MAIL" => "XF86Mail",
+        "BOOKMARKS" => "XF86Favorites",
+        "COMPUTER" => "XF86MyComputer",
+        "BACK" => "XF86Back",
+        "FORWARD" => "XF86Forward",
+        "EJECT" => "XF86Eject",
+        "BRIGHTNESS DOWN" => "XF86MonBrightnessDown",
+        "BRIGHTNESS UP" => "XF86MonBrightnessUp",
+        numpad if numpad.starts_with("NUM ") => return Some(format!("KP_{}", &numpad[4..])),
+        _ => return None,
+    };
+    Some(sym.to_string())
+}
+
+// Inside double quotes, the shell still expands these
+fn shell_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '"' | '\\' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+// Record a macro from live input (or a recording); returns the process exit code
+pub fn run(options: &MacroOptions) -> i32 {
+    let mut recorder = KeyboardMacroRecorder::new(options.trigger.clone(), options.stop.clone(), options.ignore_timing);
+
+    if let Some(path) = &options.replay {
+        let events = match recording::read_recording(path) {
+            Ok(events) => events,
+            Err(err) => {
+                eprintln!("{}error: cannot replay {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                return 1;
+            },
+        };
+        for recorded in events {
+            if let InputEvent::Key { code, pressed } = recorded.event.event {
+                recorder.key(code, pressed, recorded.event.time_usec);
+            }
+        }
+    } else {
+        let mut input = Libinput::new_with_udev(Interface);
+        if input.udev_assign_seat("seat0").is_err() {
+            eprintln!("{}error: cannot assign seat0{}", Colors::RED, Colors::RESET);
+            return 2;
+        }
+        signals::install_exit_handlers();
+
+        println!("Press {}{}{} to start recording and {}{}{} to stop.",
+            Colors::YELLOW, options.trigger.spec, Colors::RESET,
+            Colors::YELLOW, options.stop.spec, Colors::RESET);
+        let mut was_recording = false;
+        while !recorder.is_finished() && !signals::exit_requested() {
+            if input.dispatch().is_err() {
+                break;
+            }
+            for event in &mut input {
+                let timed = event::decode(&event, event::monotonic_usec());
+                if let InputEvent::Key { code, pressed } = timed.event {
+                    recorder.key(code, pressed, timed.time_usec);
+                }
+            }
+            if recorder.is_recording() && !was_recording {
+                println!("{}⏺️  Recording...{}", Colors::RED, Colors::RESET);
+                was_recording = true;
+            }
+            sleep(Duration::from_millis(5));
+        }
+    }
+
+    if !recorder.is_finished() {
+        eprintln!("{}warning: the stop hotkey {} was never pressed; saving what was recorded{}",
+            Colors::YELLOW, options.stop.spec, Colors::RESET);
+    }
+    if recorder.skipped > 0 {
+        eprintln!("{}warning: skipped {} key press(es) xdotool has no name for{}",
+            Colors::YELLOW, recorder.skipped, Colors::RESET);
+    }
+    if let Err(err) = recorder.save(&options.output) {
+        eprintln!("{}error: cannot write {}: {}{}", Colors::RED, options.output.display(), err, Colors::RESET);
+        return 2;
+    }
+    println!("Macro written to {} ({} steps)", options.output.display(), recorder.steps.len());
+    0
+}
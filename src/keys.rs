@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+// Display labels from --alias and --alias-file, by key code
+static ALIASES: OnceLock<HashMap<u32, &'static str>> = OnceLock::new();
+
+// Helper function to convert key codes to more readable names. Codes are the
+// KEY_* values from linux/input-event-codes.h; --alias labels take precedence.
+pub fn key_name(key_code: u32) -> &'static str {
+    alias(key_code).unwrap_or_else(|| table_name(key_code))
+}
+
+// The label set with --alias, if any
+pub fn alias(key_code: u32) -> Option<&'static str> {
+    ALIASES.get()?.get(&key_code).copied()
+}
+
+// Set the --alias labels; later entries win. Only the first call counts.
+pub fn set_aliases(aliases: Vec<(u32, String)>) {
+    // Labels live as long as the process, like the table's
+    let aliases = aliases.into_iter().map(|(code, label)| (code, &*Box::leak(label.into_boxed_str()))).collect();
+    let _ = ALIASES.set(aliases);
+}
+
+// An --alias KEY=LABEL, e.g. "58=CTRL" or "CAPS_LOCK=CTRL"
+pub fn parse_alias(value: &str) -> Result<(u32, String), String> {
+    let (key, label) = value.split_once('=').ok_or("expected KEY=LABEL")?;
+    let code = key_code(key.trim()).ok_or_else(|| format!("unknown key '{}'", key.trim()))?;
+    let label = label.trim();
+    if label.is_empty() {
+        return Err("the label is empty".to_string());
+    }
+    Ok((code, label.to_string()))
+}
+
+// An --alias-file: one KEY=LABEL per line; blank lines and lines starting
+// with '#' are skipped
+pub fn load_aliases(path: &Path) -> Result<Vec<(u32, String)>, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| parse_alias(line).map_err(|err| format!("line {}: {}", index + 1, err)))
+        .collect()
+}
+
+// The built-in names
+fn table_name(key_code: u32) -> &'static str {
+    match key_code {
+        1 => "ESC",
+        28 => "ENTER",
+        14 => "BACKSPACE",
+        15 => "TAB",
+        57 => "SPACE",
+        // Letters
+        16 => "Q", 17 => "W", 18 => "E", 19 => "R", 20 => "T", 
+        21 => "Y", 22 => "U", 23 => "I", 24 => "O", 25 => "P",
+        30 => "A", 31 => "S", 32 => "D", 33 => "F", 34 => "G", 
+        35 => "H", 36 => "J", 37 => "K", 38 => "L",
+        44 => "Z", 45 => "X", 46 => "C", 47 => "V", 48 => "B", 
+        49 => "N", 50 => "M",
+        // Arrow keys
+        103 => "UP", 105 => "LEFT", 106 => "RIGHT", 108 => "DOWN",
+        // Function keys
+        59 => "F1", 60 => "F2", 61 => "F3", 62 => "F4",
+        63 => "F5", 64 => "F6", 65 => "F7", 66 => "F8",
+        67 => "F9", 68 => "F10", 87 => "F11", 88 => "F12",
+        // Modifiers
+        29 => "LEFT CTRL", 97 => "RIGHT CTRL",
+        42 => "LEFT SHIFT", 54 => "RIGHT SHIFT",
+        56 => "LEFT ALT", 100 => "ALT GR",
+        // KEY_LEFTMETA / KEY_RIGHTMETA
+        125 => "LEFT SUPER", 126 => "RIGHT SUPER",
+        // Other common keys
+        2 => "1", 3 => "2", 4 => "3", 5 => "4", 6 => "5",
+        7 => "6", 8 => "7", 9 => "8", 10 => "9", 11 => "0",
+        12 => "-", 13 => "=", 26 => "[", 27 => "]", 39 => ";",
+        40 => "'", 41 => "`", 43 => "\\", 51 => ",", 52 => ".",
+        53 => "/", 58 => "CAPS LOCK", 69 => "NUM LOCK", 70 => "SCROLL LOCK",
+        // KEY_102ND, the extra key next to left shift on ISO keyboards
+        86 => "<>",
+        // Numpad
+        71 => "NUM 7", 72 => "NUM 8", 73 => "NUM 9", 74 => "NUM -",
+        75 => "NUM 4", 76 => "NUM 5", 77 => "NUM 6", 78 => "NUM +",
+        79 => "NUM 1", 80 => "NUM 2", 81 => "NUM 3", 82 => "NUM 0",
+        83 => "NUM .", 96 => "NUM ENTER", 98 => "NUM /", 55 => "NUM *",
+        // Media keys
+        113 => "MUTE", 114 => "VOLUME DOWN", 115 => "VOLUME UP",
+        // System keys
+        99 => "PRINT SCREEN", 119 => "PAUSE", 110 => "HOME", 102 => "PAGE UP",
+        107 => "END", 109 => "PAGE DOWN", 111 => "DELETE", 118 => "INSERT",
+        // KEY_COMPOSE is the context menu key on PC keyboards; KEY_MENU is
+        // what some laptops send instead
+        127 => "COMPOSE", 139 => "MENU", 128 => "STOP",
+        // Power management
+        116 => "POWER", 142 => "SLEEP", 143 => "WAKE UP",
+        // Browser and media cluster
+        140 => "CALCULATOR", 150 => "WWW", 152 => "SCREEN LOCK", 154 => "CYCLE WINDOWS",
+        155 => "MAIL", 156 => "BOOKMARKS", 157 => "COMPUTER", 158 => "BACK", 159 => "FORWARD",
+        160 => "CLOSE CD", 161 => "EJECT", 162 => "EJECT/CLOSE CD",
+        163 => "NEXT TRACK", 164 => "PLAY/PAUSE", 165 => "PREV TRACK", 166 => "STOP TRACK",
+        // Laptop keys
+        224 => "BRIGHTNESS DOWN", 225 => "BRIGHTNESS UP",
+        // Others
+        _ => "UNKNOWN KEY",
+    }
+}
+
+// Highest keycode the kernel defines (KEY_MAX in input-event-codes.h)
+pub const KEY_MAX: u32 = 0x2ff;
+
+// Resolve a key given on the command line to its code. Accepts a name from
+// the table above (not an alias), case-insensitive with '_' standing in for
+// spaces ("CAPS_LOCK", "caps lock", "F12"), or a raw code ("58", "KEY_58").
+// Names come first, so "1" is the 1 key; its code is KEY_2.
+pub fn key_code(name: &str) -> Option<u32> {
+    key_code_by_name(name)
+        .or_else(|| name.trim().strip_prefix("KEY_").unwrap_or(name.trim()).parse().ok())
+}
+
+// Like `key_code`, without the raw code forms
+pub fn key_code_by_name(name: &str) -> Option<u32> {
+    let wanted = normalize(name);
+    (0..=KEY_MAX).find(|&code| {
+        let known = table_name(code);
+        known != "UNKNOWN KEY" && normalize(known) == wanted
+    })
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_uppercase().replace('_', " ")
+}
+
+// Keys that type a character: digits, letters, punctuation and space
+pub fn is_character_key(key_code: u32) -> bool {
+    matches!(key_code, 2..=13 | 16..=27 | 30..=41 | 43..=53 | 57)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_win_over_raw_codes() {
+        assert_eq!(key_code("1"), Some(2));
+        assert_eq!(key_code("caps_lock"), Some(58));
+        assert_eq!(key_code("58"), Some(58));
+        assert_eq!(key_code("KEY_1"), Some(1));
+        assert_eq!(key_code("NO SUCH KEY"), None);
+    }
+}
@@ -0,0 +1,302 @@
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::event::{InputEvent, TimedEvent};
+
+// KEY_UNKNOWN: a real code that no physical keyboard sends
+pub const PROBE_KEY: u32 = 240;
+// Mean gap between injections; the gaps are exponentially distributed, so
+// injections form a Poisson process and don't beat against periodic work
+const MEAN_INTERVAL_USEC: f64 = 2_000_000.0;
+// Give libinput time to pick up the new virtual device before the first probe
+const WARMUP_USEC: u64 = 2_000_000;
+// A probe that hasn't come back by then is counted as lost
+const PROBE_TIMEOUT_USEC: u64 = 1_000_000;
+
+// What `LatencyMeasurer::observe` made of an event
+pub enum Observed {
+    // Not a probe: real input, handled as usual
+    Input,
+    ProbeReleased,
+    // A probe came back after this many microseconds
+    ProbeArrived(u64),
+}
+
+// Round-trip statistics over the completed probes, in microseconds
+pub struct LatencySummary {
+    pub samples: usize,
+    pub mean_usec: u64,
+    pub min_usec: u64,
+    pub max_usec: u64,
+    pub p99_usec: u64,
+}
+
+// Injects the probes: a uinput keyboard, or a stand-in in tests
+pub trait ProbeKey {
+    // Press and release the probe key
+    fn tap(&mut self) -> io::Result<()>;
+}
+
+// Estimates how long an input event takes to get from the kernel through
+// libinput to this program, by injecting key presses through a uinput device
+// and timing how long they take to arrive. This is the kernel input stack
+// plus our own event loop, not the time until anything reaches the screen.
+pub struct LatencyMeasurer<K = uinput::VirtualKey> {
+    device: K,
+    rng_state: u64,
+    next_probe_usec: u64,
+    // Injection time of the probe on its way
+    in_flight: Option<u64>,
+    // The probe's release, still to be swallowed
+    release_pending: bool,
+    samples: Vec<u64>,
+    lost: u64,
+}
+
+impl LatencyMeasurer {
+    pub fn new(now_usec: u64) -> Result<Self, String> {
+        let device = uinput::VirtualKey::create("rustkey latency probe", PROBE_KEY as u16)?;
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |elapsed| elapsed.as_nanos() as u64) | 1;
+        Ok(LatencyMeasurer::with_key(device, seed, now_usec))
+    }
+}
+
+impl<K: ProbeKey> LatencyMeasurer<K> {
+    fn with_key(device: K, seed: u64, now_usec: u64) -> Self {
+        LatencyMeasurer {
+            device,
+            rng_state: seed,
+            next_probe_usec: now_usec + WARMUP_USEC,
+            in_flight: None,
+            release_pending: false,
+            samples: Vec::new(),
+            lost: 0,
+        }
+    }
+
+    // Inject the next probe once it's due; called from the event loop
+    pub fn tick(&mut self, now_usec: u64) -> Result<(), String> {
+        if let Some(sent) = self.in_flight {
+            if now_usec.saturating_sub(sent) < PROBE_TIMEOUT_USEC {
+                return Ok(());
+            }
+            self.in_flight = None;
+            self.lost += 1;
+        }
+        if now_usec < self.next_probe_usec {
+            return Ok(());
+        }
+
+        self.device.tap().map_err(|err| format!("cannot inject a latency probe: {}", err))?;
+        self.in_flight = Some(now_usec);
+        self.release_pending = true;
+        self.next_probe_usec = now_usec + self.next_interval();
+        Ok(())
+    }
+
+    // Pick out the probe's press and release, so they aren't shown as
+    // typing. `now_usec` is when the event came out of libinput.
+    pub fn observe(&mut self, timed: &TimedEvent, now_usec: u64) -> Observed {
+        match timed.event {
+            InputEvent::Key { code: PROBE_KEY, pressed: true } => match self.in_flight.take() {
+                Some(sent) => {
+                    let latency = now_usec.saturating_sub(sent);
+                    self.samples.push(latency);
+                    Observed::ProbeArrived(latency)
+                },
+                None => Observed::Input,
+            },
+            InputEvent::Key { code: PROBE_KEY, pressed: false } if self.release_pending => {
+                self.release_pending = false;
+                Observed::ProbeReleased
+            },
+            _ => Observed::Input,
+        }
+    }
+
+    pub fn summary(&self) -> Option<LatencySummary> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        Some(LatencySummary {
+            samples: sorted.len(),
+            mean_usec: sorted.iter().sum::<u64>() / sorted.len() as u64,
+            min_usec: sorted[0],
+            max_usec: sorted[sorted.len() - 1],
+            // Nearest-rank percentile
+            p99_usec: sorted[(sorted.len() * 99).div_ceil(100) - 1],
+        })
+    }
+
+    pub fn lost(&self) -> u64 {
+        self.lost
+    }
+
+    // Exponentially distributed, by inverting the CDF of a uniform sample
+    fn next_interval(&mut self) -> u64 {
+        // xorshift64, so no extra crates are needed
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+        let uniform = (self.rng_state >> 11) as f64 / (1u64 << 53) as f64;
+        (-MEAN_INTERVAL_USEC * (1.0 - uniform).ln()) as u64
+    }
+}
+
+#[cfg(feature = "uinput")]
+mod uinput {
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Write};
+    use std::mem;
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::slice;
+
+    // From <linux/uinput.h> and <linux/input-event-codes.h>
+    const UI_DEV_CREATE: libc::c_ulong = 0x5501;
+    const UI_DEV_DESTROY: libc::c_ulong = 0x5502;
+    const UI_DEV_SETUP: libc::c_ulong = 0x405c_5503;
+    const UI_SET_EVBIT: libc::c_ulong = 0x4004_5564;
+    const UI_SET_KEYBIT: libc::c_ulong = 0x4004_5565;
+    const EV_SYN: u16 = 0;
+    const EV_KEY: u16 = 1;
+    const SYN_REPORT: u16 = 0;
+    const BUS_VIRTUAL: u16 = 0x06;
+
+    // A uinput keyboard with a single key, removed again when dropped
+    pub struct VirtualKey {
+        file: File,
+        code: u16,
+    }
+
+    impl VirtualKey {
+        pub fn create(name: &str, code: u16) -> Result<Self, String> {
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open("/dev/uinput")
+                .map_err(|err| format!("cannot open /dev/uinput: {}", err))?;
+            let fd = file.as_raw_fd();
+
+            let mut setup: libc::uinput_setup = unsafe { mem::zeroed() };
+            setup.id.bustype = BUS_VIRTUAL;
+            for (slot, byte) in setup.name.iter_mut().zip(name.bytes().take(libc::UINPUT_MAX_NAME_SIZE - 1)) {
+                *slot = byte as libc::c_char;
+            }
+            let created = unsafe {
+                libc::ioctl(fd, UI_SET_EVBIT, libc::c_int::from(EV_KEY)) >= 0
+                    && libc::ioctl(fd, UI_SET_KEYBIT, libc::c_int::from(code)) >= 0
+                    && libc::ioctl(fd, UI_DEV_SETUP, &setup) >= 0
+                    && libc::ioctl(fd, UI_DEV_CREATE) >= 0
+            };
+            if !created {
+                return Err(format!("cannot create a uinput device: {}", io::Error::last_os_error()));
+            }
+            Ok(VirtualKey { file, code })
+        }
+    }
+
+    impl super::ProbeKey for VirtualKey {
+        fn tap(&mut self) -> io::Result<()> {
+            for (kind, code, value) in [(EV_KEY, self.code, 1), (EV_SYN, SYN_REPORT, 0), (EV_KEY, self.code, 0), (EV_SYN, SYN_REPORT, 0)] {
+                let mut event: libc::input_event = unsafe { mem::zeroed() };
+                event.type_ = kind;
+                event.code = code;
+                event.value = value;
+                let bytes = unsafe {
+                    slice::from_raw_parts(&event as *const libc::input_event as *const u8, mem::size_of::<libc::input_event>())
+                };
+                self.file.write_all(bytes)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for VirtualKey {
+        fn drop(&mut self) {
+            unsafe {
+                libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "uinput"))]
+mod uinput {
+    use std::io;
+
+    pub struct VirtualKey;
+
+    impl VirtualKey {
+        pub fn create(_name: &str, _code: u16) -> Result<Self, String> {
+            Err("uinput support is not compiled in (rebuild with `--features uinput`)".to_string())
+        }
+    }
+
+    impl super::ProbeKey for VirtualKey {
+        fn tap(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingKey {
+        taps: u32,
+    }
+
+    impl ProbeKey for CountingKey {
+        fn tap(&mut self) -> io::Result<()> {
+            self.taps += 1;
+            Ok(())
+        }
+    }
+
+    fn key(code: u32, pressed: bool) -> TimedEvent {
+        TimedEvent { time_usec: 0, event: InputEvent::Key { code, pressed }, fingers: None, sysname: None, seat: None }
+    }
+
+    #[test]
+    fn probes_are_timed_and_swallowed() {
+        let mut measurer = LatencyMeasurer::with_key(CountingKey::default(), 1, 0);
+        measurer.tick(WARMUP_USEC - 1).unwrap();
+        assert_eq!(measurer.device.taps, 0);
+        measurer.tick(WARMUP_USEC).unwrap();
+        assert_eq!(measurer.device.taps, 1);
+
+        assert!(matches!(measurer.observe(&key(30, true), WARMUP_USEC + 100), Observed::Input));
+        assert!(matches!(measurer.observe(&key(PROBE_KEY, true), WARMUP_USEC + 350), Observed::ProbeArrived(350)));
+        assert!(matches!(measurer.observe(&key(PROBE_KEY, false), WARMUP_USEC + 400), Observed::ProbeReleased));
+        // Nothing in flight: a real KEY_UNKNOWN
+        assert!(matches!(measurer.observe(&key(PROBE_KEY, true), WARMUP_USEC + 500), Observed::Input));
+        assert!(matches!(measurer.observe(&key(PROBE_KEY, false), WARMUP_USEC + 600), Observed::Input));
+        assert_eq!(measurer.summary().unwrap().samples, 1);
+    }
+
+    #[test]
+    fn unanswered_probes_are_lost_after_the_timeout() {
+        let mut measurer = LatencyMeasurer::with_key(CountingKey::default(), 1, 0);
+        measurer.tick(WARMUP_USEC).unwrap();
+        measurer.tick(WARMUP_USEC + PROBE_TIMEOUT_USEC - 1).unwrap();
+        assert_eq!(measurer.lost(), 0);
+        measurer.tick(WARMUP_USEC + PROBE_TIMEOUT_USEC).unwrap();
+        assert_eq!(measurer.lost(), 1);
+        assert!(measurer.summary().is_none());
+    }
+
+    #[test]
+    fn summary_uses_the_nearest_rank_p99() {
+        let mut measurer = LatencyMeasurer::with_key(CountingKey::default(), 1, 0);
+        measurer.samples = (1..=200).rev().collect();
+        let summary = measurer.summary().unwrap();
+        assert_eq!((summary.samples, summary.min_usec, summary.max_usec), (200, 1, 200));
+        assert_eq!(summary.mean_usec, 100);
+        assert_eq!(summary.p99_usec, 198);
+    }
+}
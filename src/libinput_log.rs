@@ -0,0 +1,100 @@
+use std::os::raw::c_uint;
+use std::sync::Mutex;
+
+use input::{ffi, AsRaw, Libinput};
+
+// libinput's own log levels, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum LogPriority {
+    Debug,
+    Info,
+    Error,
+}
+
+impl LogPriority {
+    // Errors are always shown, -v adds info messages, -vv debug ones
+    pub fn from_verbosity(verbosity: u8) -> Self {
+        match verbosity {
+            0 => LogPriority::Error,
+            1 => LogPriority::Info,
+            _ => LogPriority::Debug,
+        }
+    }
+
+    #[cfg_attr(not(any(target_arch = "x86_64", target_arch = "aarch64")), allow(dead_code))]
+    fn from_raw(raw: c_uint) -> Self {
+        match raw {
+            ffi::libinput_log_priority_LIBINPUT_LOG_PRIORITY_DEBUG => LogPriority::Debug,
+            ffi::libinput_log_priority_LIBINPUT_LOG_PRIORITY_INFO => LogPriority::Info,
+            _ => LogPriority::Error,
+        }
+    }
+
+    fn raw(self) -> ffi::libinput_log_priority {
+        match self {
+            LogPriority::Debug => ffi::libinput_log_priority_LIBINPUT_LOG_PRIORITY_DEBUG,
+            LogPriority::Info => ffi::libinput_log_priority_LIBINPUT_LOG_PRIORITY_INFO,
+            LogPriority::Error => ffi::libinput_log_priority_LIBINPUT_LOG_PRIORITY_ERROR,
+        }
+    }
+}
+
+// The handler runs inside libinput calls, where the monitor's outputs aren't
+// reachable; messages wait here until the event loop prints them
+static PENDING: Mutex<Vec<(LogPriority, String)>> = Mutex::new(Vec::new());
+
+// The input crate doesn't wrap the log handler, since it takes a va_list.
+// On x86-64 and AArch64 a va_list argument arrives as a pointer, so the
+// handler takes it as one and hands it straight on to vsnprintf. Other
+// architectures pass it differently; there libinput keeps printing its
+// messages to stderr itself.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+mod handler {
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_int, c_uint, c_void};
+
+    use input::ffi;
+
+    use super::{LogPriority, PENDING};
+
+    // Longest message kept; libinput's are a line or two
+    const MAX_MESSAGE_LEN: usize = 1024;
+
+    type LogHandler = unsafe extern "C" fn(*mut ffi::libinput, c_uint, *const c_char, *mut c_void);
+
+    extern "C" {
+        fn libinput_log_set_handler(libinput: *mut ffi::libinput, handler: Option<LogHandler>);
+        fn vsnprintf(buffer: *mut c_char, size: usize, format: *const c_char, args: *mut c_void) -> c_int;
+    }
+
+    unsafe extern "C" fn handle_log(_libinput: *mut ffi::libinput, priority: c_uint, format: *const c_char, args: *mut c_void) {
+        let mut buffer = [0 as c_char; MAX_MESSAGE_LEN];
+        if vsnprintf(buffer.as_mut_ptr(), buffer.len(), format, args) < 0 {
+            return;
+        }
+        let message = CStr::from_ptr(buffer.as_ptr()).to_string_lossy().trim_end().to_string();
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.push((LogPriority::from_raw(priority), message));
+        }
+    }
+
+    pub unsafe fn set(libinput: *mut ffi::libinput) {
+        libinput_log_set_handler(libinput, Some(handle_log));
+    }
+}
+
+// Capture libinput's messages at `priority` and above instead of letting it
+// print them to stderr itself. Install before assigning a seat or adding
+// devices, which is when most device quirks are reported.
+pub fn install(input: &Libinput, priority: LogPriority) {
+    unsafe {
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        handler::set(input.as_raw_mut());
+        ffi::libinput_log_set_priority(input.as_raw_mut(), priority.raw());
+    }
+}
+
+// Messages logged since the last call, oldest first
+pub fn take_pending() -> Vec<(LogPriority, String)> {
+    PENDING.lock().map(|mut pending| std::mem::take(&mut *pending)).unwrap_or_default()
+}
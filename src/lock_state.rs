@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+
+use crate::Colors;
+
+// Keyboard LEDs as the kernel's LED class names them, e.g. input3::capslock
+const LEDS_CLASS: &str = "/sys/class/leds";
+
+const KEY_CAPSLOCK: u32 = 58;
+const KEY_NUMLOCK: u32 = 69;
+const KEY_SCROLLLOCK: u32 = 70;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lock {
+    Caps,
+    Num,
+    Scroll,
+}
+
+impl Lock {
+    const ALL: [Lock; 3] = [Lock::Caps, Lock::Num, Lock::Scroll];
+
+    pub fn of_key(code: u32) -> Option<Lock> {
+        match code {
+            KEY_CAPSLOCK => Some(Lock::Caps),
+            KEY_NUMLOCK => Some(Lock::Num),
+            KEY_SCROLLLOCK => Some(Lock::Scroll),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lock::Caps => "CAPS LOCK",
+            Lock::Num => "NUM LOCK",
+            Lock::Scroll => "SCROLL LOCK",
+        }
+    }
+
+    fn short_name(self) -> &'static str {
+        match self {
+            Lock::Caps => "CAPS",
+            Lock::Num => "NUM",
+            Lock::Scroll => "SCROLL",
+        }
+    }
+
+    fn led_suffix(self) -> &'static str {
+        match self {
+            Lock::Caps => "::capslock",
+            Lock::Num => "::numlock",
+            Lock::Scroll => "::scrolllock",
+        }
+    }
+}
+
+// Caps, Num and Scroll Lock, toggled on each press of their key. Events
+// only say a key was pressed, so a lock is unknown until its LED could be
+// read or it was first toggled; from then on it's counted from off.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LockState {
+    states: [Option<bool>; 3],
+}
+
+impl LockState {
+    // The keyboard LEDs right now, where sysfs has them. With several
+    // keyboards a lock counts as on if any of them shows it.
+    pub fn from_leds() -> Self {
+        let mut locks = LockState::default();
+        let Ok(entries) = fs::read_dir(LEDS_CLASS) else {
+            return locks;
+        };
+        for name in entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()) {
+            let Some(lock) = Lock::ALL.into_iter().find(|lock| name.ends_with(lock.led_suffix())) else {
+                continue;
+            };
+            let Some(brightness) = fs::read_to_string(Path::new(LEDS_CLASS).join(&name).join("brightness")).ok()
+                .and_then(|value| value.trim().parse::<u32>().ok()) else {
+                continue;
+            };
+            let state = &mut locks.states[lock as usize];
+            *state = Some(state.unwrap_or(false) || brightness > 0);
+        }
+        locks
+    }
+
+    // Flip the lock; returns the new state
+    pub fn toggle(&mut self, lock: Lock) -> bool {
+        let state = &mut self.states[lock as usize];
+        let on = !state.unwrap_or(false);
+        *state = Some(on);
+        on
+    }
+
+    pub fn get(&self, lock: Lock) -> Option<bool> {
+        self.states[lock as usize]
+    }
+
+    // e.g. "[CAPS] num scroll?": locks that are on highlighted, unknown
+    // ones with a question mark
+    pub fn indicator(&self) -> String {
+        Lock::ALL.iter()
+            .map(|lock| match self.get(*lock) {
+                Some(true) => format!("{}[{}]{}", Colors::GREEN, lock.short_name(), Colors::RESET),
+                Some(false) => format!("{}{}{}", Colors::GRAY, lock.short_name().to_lowercase(), Colors::RESET),
+                None => format!("{}{}?{}", Colors::GRAY, lock.short_name().to_lowercase(), Colors::RESET),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_from_unknown() {
+        let mut locks = LockState::default();
+        assert_eq!(locks.get(Lock::Caps), None);
+        assert!(locks.toggle(Lock::Caps));
+        assert!(!locks.toggle(Lock::Caps));
+        assert_eq!(locks.get(Lock::Caps), Some(false));
+        assert_eq!(locks.get(Lock::Num), None);
+
+        assert_eq!(Lock::of_key(69), Some(Lock::Num));
+        assert_eq!(Lock::of_key(30), None);
+    }
+}
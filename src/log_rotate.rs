@@ -0,0 +1,207 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::output::BLOCK_BUFFER_SIZE;
+use crate::Colors;
+
+pub const DEFAULT_MAX_SIZE_MB: u64 = 100;
+pub const DEFAULT_KEEP_FILES: usize = 5;
+
+// When --log-file starts a new file, and what happens to the old ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    pub max_bytes: u64,
+    // Also at the first write of each new (UTC) day
+    pub daily: bool,
+    // gzip rotated files into <path>.N.gz
+    pub compress: bool,
+    pub keep: usize,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation { max_bytes: DEFAULT_MAX_SIZE_MB * 1024 * 1024, daily: false, compress: false, keep: DEFAULT_KEEP_FILES }
+    }
+}
+
+// A --log-file that is rotated like logrotate would: once it grows past the
+// size limit (checked before each line, so a file is never left empty) or
+// the day changes, it becomes <path>.1 (or <path>.1.gz), older files move
+// up one number, and anything past --log-keep-files is deleted. Readers
+// never see a missing or half-written file: a compressed copy is renamed
+// into place once complete, and the fresh file replaces the old one with a
+// single rename.
+pub struct LogFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    written: u64,
+    day: u64,
+    rotation: Option<Rotation>,
+}
+
+impl LogFile {
+    pub fn open(path: &Path, rotation: Rotation) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+        Ok(LogFile {
+            path: path.to_path_buf(),
+            writer: BufWriter::with_capacity(BLOCK_BUFFER_SIZE, file),
+            written,
+            day: today(),
+            rotation: Some(rotation),
+        })
+    }
+
+    // A file that is never rotated, emptied first unless `append`
+    pub fn create(path: &Path, append: bool) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).write(true).append(append).truncate(!append).open(path)?;
+        Ok(LogFile {
+            path: path.to_path_buf(),
+            writer: BufWriter::with_capacity(BLOCK_BUFFER_SIZE, file),
+            written: 0,
+            day: today(),
+            rotation: None,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.rotation_due() {
+            if let Err(err) = self.rotate() {
+                eprintln!("{}⚠️ Log rotation of {} failed, rotation is off from now on: {}{}",
+                    Colors::RED, self.path.display(), err, Colors::RESET);
+                self.rotation = None;
+            }
+        }
+        writeln!(self.writer, "{}", line)?;
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn rotation_due(&self) -> bool {
+        let Some(rotation) = self.rotation else {
+            return false;
+        };
+        self.written > 0 && (self.written >= rotation.max_bytes || (rotation.daily && today() != self.day))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let Some(rotation) = self.rotation else {
+            return Ok(());
+        };
+        self.writer.flush()?;
+
+        // Make room: the oldest goes, the rest move up one
+        for number in (1..=rotation.keep).rev() {
+            for suffix in ["", ".gz"] {
+                let from = self.rotated(number, suffix);
+                if !from.exists() {
+                    continue;
+                }
+                if number == rotation.keep {
+                    fs::remove_file(&from)?;
+                } else {
+                    fs::rename(&from, self.rotated(number + 1, suffix))?;
+                }
+            }
+        }
+
+        if rotation.keep > 0 {
+            if rotation.compress {
+                let finished = self.rotated(1, ".gz");
+                let partial = self.sibling(".1.gz.tmp");
+                let mut encoder = GzEncoder::new(File::create(&partial)?, Compression::default());
+                io::copy(&mut File::open(&self.path)?, &mut encoder)?;
+                encoder.finish()?.sync_all()?;
+                fs::rename(&partial, &finished)?;
+            } else {
+                // A second name for the current file, which then gets replaced
+                let rotated = self.rotated(1, "");
+                if fs::hard_link(&self.path, &rotated).is_err() {
+                    fs::copy(&self.path, &rotated)?;
+                }
+            }
+        }
+
+        let fresh = self.sibling(".tmp");
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(&fresh)?;
+        fs::rename(&fresh, &self.path)?;
+        self.writer = BufWriter::with_capacity(BLOCK_BUFFER_SIZE, file);
+        self.written = 0;
+        self.day = today();
+        Ok(())
+    }
+
+    // e.g. events.log.2 or events.log.2.gz
+    fn rotated(&self, number: usize, suffix: &str) -> PathBuf {
+        self.sibling(&format!(".{}{}", number, suffix))
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+}
+
+// Days since the epoch, in UTC
+fn today() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() / 86_400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rustkey-log-rotate-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rotates_at_a_one_byte_limit() {
+        let dir = scratch("plain");
+        let path = dir.join("events.log");
+        let mut log = LogFile::open(&path, Rotation { max_bytes: 1, keep: 2, ..Rotation::default() }).unwrap();
+        for line in ["one", "two", "three", "four"] {
+            log.write_line(line).unwrap();
+        }
+        log.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "four\n");
+        assert_eq!(fs::read_to_string(dir.join("events.log.1")).unwrap(), "three\n");
+        assert_eq!(fs::read_to_string(dir.join("events.log.2")).unwrap(), "two\n");
+        assert!(!dir.join("events.log.3").exists());
+        assert!(!dir.join("events.log.tmp").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn compresses_rotated_files() {
+        let dir = scratch("gzip");
+        let path = dir.join("events.log");
+        let mut log = LogFile::open(&path, Rotation { max_bytes: 1, compress: true, ..Rotation::default() }).unwrap();
+        log.write_line("first").unwrap();
+        log.write_line("second").unwrap();
+        log.flush().unwrap();
+
+        let mut text = String::new();
+        GzDecoder::new(File::open(dir.join("events.log.1.gz")).unwrap()).read_to_string(&mut text).unwrap();
+        assert_eq!(text, "first\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+        assert!(!dir.join("events.log.1").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
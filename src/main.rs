@@ -1,19 +1,179 @@
 
+mod accel_curve;
+mod active_window;
+mod alert;
+mod bench;
+mod bigrams;
+mod binary;
+mod button_names;
+mod calibrate;
+mod cli;
+mod coalesce;
+mod combo_stats;
+mod configure;
+mod daemon;
+mod dedup;
+mod diff;
+mod doctor;
+mod event;
+mod export;
+mod fifo;
+mod geometry;
+mod filter;
+mod format_compat;
+mod gesture_classifier;
+mod grab;
+mod ghosting;
+mod health;
+mod heatmap;
+mod highlight;
+mod histogram;
+mod history;
+mod hotplug;
+mod idle;
+mod key_freq;
+mod key_colors;
+mod key_command;
+mod keyboard_macro;
+mod kbd_view;
+mod key_names;
+mod lock_state;
+mod key_repeat;
+mod keys;
+mod latency;
+mod libinput_log;
+mod log_rotate;
+mod modifiers;
+mod mouse_path;
+mod msgpack;
+mod multi_seat;
+mod one_shot;
+mod output;
+mod pointer_constraint;
+mod press_duration;
+mod priority;
+mod raw_evdev;
+mod recording;
+mod replay;
+mod rhythm;
+mod sanitize;
+mod ring_buffer;
+mod scroll_gesture;
+mod scroll_smoothing;
+mod screen_grid;
+mod screen_size;
+mod selftest;
+mod signals;
+mod simulation;
+mod speech;
+mod status_line;
+mod throttle;
+mod strokes;
+mod sqlite_log;
+mod stats;
+mod tablet_pad;
+mod template;
+mod theme;
+mod timefmt;
+mod touch_gesture;
+mod touchpad;
+mod uinput_replay;
+mod warmup;
+mod xkb_layout;
+
 use input::{Libinput, LibinputInterface};
-use input::event::pointer::Axis;
-use input::event::keyboard::KeyboardEventTrait;
-use input::event::pointer::PointerScrollEvent;
+use std::collections::{BTreeMap, VecDeque};
+use std::env;
 use std::fs::OpenOptions;
-use std::os::unix::{fs::OpenOptionsExt, io::OwnedFd};
-use std::path::Path;
-use std::time::Duration;
+use std::os::unix::{fs::OpenOptionsExt, io::{AsRawFd, OwnedFd, RawFd}};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 use std::thread::sleep;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal};
+use std::process;
+
+use accel_curve::AccelCurveVisualizer;
+use active_window::{WindowBreakdown, WindowSampler, UNKNOWN_WINDOW};
+use alert::KeyAlert;
+use binary::BinaryWriter;
+use msgpack::MsgpackWriter;
+use multi_seat::MultiSeatMonitor;
+use one_shot::OneShot;
+use button_names::button_name;
+use calibrate::Calibration;
+use coalesce::Coalescer;
+use dedup::Deduplicator;
+use gesture_classifier::{GestureClassifier, GestureMatch};
+use cli::{Command, Options};
+use event::{InputEvent, JsonEvent, ScrollSource, TimedEvent};
+use fifo::Fifo;
+use filter::PerDeviceFilter;
+use warmup::{Warmup, WarmupStart};
+use format_compat::CompatFormatter;
+use health::HealthExporter;
+use heatmap::HeatmapGrid;
+use history::EventHistory;
+use hotplug::DeviceHotplugMonitor;
+use idle::{IdleMonitor, IdleTimeouts};
+use kbd_view::KeyboardView;
+use grab::GrabbingInterface;
+use ghosting::{DroppedPress, Ghost, GhostingDetector, KeyAnomaly};
+use key_colors::KeyColors;
+use key_command::KeyCommands;
+use libinput_log::LogPriority;
+use log_rotate::LogFile;
+use modifiers::Modifiers;
+use mouse_path::MousePathRecorder;
+use output::{emit, outln, FlushPolicy, Output, OutputFormat, Severity};
+use pointer_constraint::PointerConstraint;
+use press_duration::{PressClassifier, PressKind, PressSource};
+use priority::{EventClass, PriorityEventQueue};
+use recording::Recorder;
+use rhythm::{TypingMode, IKI_BUCKET_LABELS};
+use sanitize::Sanitizer;
+use scroll_gesture::{ScrollCoalescer, ScrollGesture};
+use scroll_smoothing::ScrollSmoothingFilter;
+use screen_grid::ScreenGrid;
+use sqlite_log::SqliteLog;
+use stats::{EventRateHistogram, SessionStats, RATE_BUCKET_LABELS};
+use speech::Speaker;
+use highlight::PatternHighlighter;
+use histogram::LatencyHistogram;
+use latency::{LatencyMeasurer, Observed};
+use simulation::ScenarioRunner;
+use status_line::StatusLine;
+use lock_state::{Lock, LockState};
+use raw_evdev::RawEventDumper;
+use template::TemplateEngine;
+use throttle::LineThrottle;
+use strokes::StrokeCategory;
+use touch_gesture::{TouchGesture, TouchGestureKind, TouchTracker};
+use touchpad::TouchpadMonitor;
+
+// Nominal scroll value of one line for touchpad / continuous scrolling
+// (libinput's legacy axis value per wheel click) when no calibration is loaded
+const SCROLL_UNITS_PER_LINE: f64 = 15.0;
+// High-resolution wheel values come in 120ths of a notch
+const V120_PER_NOTCH: f64 = 120.0;
+// The udev seat whose devices are monitored
+const SEAT: &str = "seat0";
+// How long to wait for the seat's first device before warning
+const DEVICE_WAIT_USEC: u64 = 2_000_000;
+// How long each sleep on the libinput fd lasts while idle
+const IDLE_POLL_MS: i32 = 500;
+// Presses held at least this long are long presses (--classify-presses)
+const DEFAULT_LONG_PRESS: Duration = Duration::from_millis(500);
+// --smooth-scroll defaults
+const DEFAULT_SCROLL_FRICTION: f64 = 0.85;
+const DEFAULT_SMOOTH_SCROLL_RATE: u32 = 60;
+// How often --correlate-window checks the focused window
+const DEFAULT_WINDOW_SAMPLE: Duration = Duration::from_millis(500);
 
 // Use constants directly instead of importing from libc
 const O_RDONLY: i32 = 0;
 const O_WRONLY: i32 = 1;
 const O_RDWR: i32 = 2;
+const O_ACCMODE: i32 = 3;
 
 // Track current mouse position
 struct MouseState {
@@ -21,6 +181,45 @@ struct MouseState {
     y: f64,
     dx: f64,
     dy: f64,
+    // Bit n is set while button BTN_MOUSE + n is down
+    buttons_held: u32,
+}
+
+// First mouse button code (BTN_LEFT); buttons up to BTN_MOUSE + 31 are tracked
+const BTN_MOUSE: u32 = 0x110;
+
+impl MouseState {
+    // Returns false if the button was already down, i.e. its release was lost
+    fn press_button(&mut self, button: u32) -> bool {
+        let Some(bit) = button_bit(button) else {
+            return true;
+        };
+        let was_held = self.buttons_held & bit != 0;
+        self.buttons_held |= bit;
+        !was_held
+    }
+
+    fn release_button(&mut self, button: u32) {
+        if let Some(bit) = button_bit(button) {
+            self.buttons_held &= !bit;
+        }
+    }
+
+    fn held_buttons(&self) -> Vec<u32> {
+        (0..32).filter(|n| self.buttons_held & (1 << n) != 0).map(|n| BTN_MOUSE + n).collect()
+    }
+
+    // e.g. "LEFT+MIDDLE"
+    fn held_label(&self) -> String {
+        self.held_buttons().iter()
+            .map(|&button| button_name(button).trim_start_matches("BTN_"))
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
+fn button_bit(button: u32) -> Option<u32> {
+    button.checked_sub(BTN_MOUSE).filter(|n| *n < 32).map(|n| 1 << n)
 }
 
 // Colors for terminal output (ANSI color codes)
@@ -33,17 +232,82 @@ impl Colors {
     const BLUE: &'static str = "\x1b[34m";
     const MAGENTA: &'static str = "\x1b[35m";
     const CYAN: &'static str = "\x1b[36m";
+    const GRAY: &'static str = "\x1b[90m";
     const BOLD: &'static str = "\x1b[1m";
 }
 
+// Ctrl+C on a --grab keyboard ends the monitor
+const KEY_C: u32 = 46;
+
+// Width of the longest bar in the summary's histograms
+const HISTOGRAM_BAR_WIDTH: u64 = 20;
+// Shortcuts listed in the summary; --format json gets all of them
+const TOP_COMBOS: usize = 20;
+
+// The summary's session table keeps to the most recent ones
+const MAX_SESSION_ROWS: usize = 20;
+
+// Seconds per events-per-second bucket as a bar chart, then each event
+// category's share of its active seconds per bucket
+fn emit_rate_histogram(out: &mut Output, rates: &EventRateHistogram) {
+    let seconds = rates.seconds();
+    let Some(most) = seconds.iter().copied().max().filter(|&most| most > 0) else {
+        return;
+    };
+    let total: u64 = seconds.iter().sum();
+    emit!(out, Severity::Notice, "{}Events per second ({} s):{}", Colors::GREEN, total, Colors::RESET);
+    for (label, count) in RATE_BUCKET_LABELS.iter().zip(seconds) {
+        let bar = "█".repeat((count * HISTOGRAM_BAR_WIDTH / most) as usize);
+        emit!(out, Severity::Notice, "  {:<10}  {:<20} {} ({:.0}%)",
+            format!("{}/s", label), bar, count, count as f64 * 100.0 / total as f64);
+    }
+    for (class, seconds) in rates.class_seconds() {
+        let active: u64 = seconds.iter().sum();
+        let shares: Vec<String> = RATE_BUCKET_LABELS.iter().zip(seconds)
+            .filter(|(_, count)| *count > 0)
+            .map(|(label, count)| format!("{}/s {:.0}%", label, count as f64 * 100.0 / active as f64))
+            .collect();
+        emit!(out, Severity::Notice, "  {:<10}  {}", format!("{}:", class.name()), shares.join(", "));
+    }
+}
+
+// Compact duration for the summary, e.g. "45s", "12m 30s", "2h 05m"
+fn format_duration(usec: u64) -> String {
+    let secs = usec / 1_000_000;
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs / 60 % 60),
+    }
+}
+
+// What each color means in the event stream, for --legend. Keep in step with
+// the colors render_event uses.
+const COLOR_LEGEND: &[(&str, &str)] = &[
+    (Colors::YELLOW, "key press"),
+    (Colors::BLUE, "key / button release"),
+    (Colors::MAGENTA, "button press, touch, gesture"),
+    (Colors::CYAN, "motion, scroll"),
+    (Colors::GREEN, "device added"),
+    (Colors::RED, "device removed, warnings"),
+    (Colors::GRAY, "libinput's own messages"),
+];
+
+fn display_legend(out: &mut Output) {
+    let entries: Vec<String> = COLOR_LEGEND.iter()
+        .map(|(color, meaning)| format!("{}■ {}{}", color, meaning, Colors::RESET))
+        .collect();
+    emit!(out, Severity::Notice, "🎨 Colors: {}", entries.join("  "));
+}
+
 struct Interface;
 
 impl LibinputInterface for Interface {
     fn open_restricted(&mut self, path: &Path, flags: i32) -> Result<OwnedFd, i32> {
         OpenOptions::new()
             .custom_flags(flags)
-            .read((flags & O_RDONLY != 0) | (flags & O_RDWR != 0))
-            .write((flags & O_WRONLY != 0) | (flags & O_RDWR != 0))
+            .read(matches!(flags & O_ACCMODE, O_RDONLY | O_RDWR))
+            .write(matches!(flags & O_ACCMODE, O_WRONLY | O_RDWR))
             .open(path)
             .map(|file| file.into())
             .map_err(|err| err.raw_os_error().unwrap())
@@ -53,270 +317,2269 @@ impl LibinputInterface for Interface {
     }
 }
 
-// Helper function to convert key codes to more readable names
-fn key_name(key_code: u32) -> &'static str {
-    match key_code {
-        1 => "ESC",
-        28 => "ENTER",
-        14 => "BACKSPACE",
-        15 => "TAB",
-        57 => "SPACE",
-        // Letters
-        16 => "Q", 17 => "W", 18 => "E", 19 => "R", 20 => "T", 
-        21 => "Y", 22 => "U", 23 => "I", 24 => "O", 25 => "P",
-        30 => "A", 31 => "S", 32 => "D", 33 => "F", 34 => "G", 
-        35 => "H", 36 => "J", 37 => "K", 38 => "L",
-        44 => "Z", 45 => "X", 46 => "C", 47 => "V", 48 => "B", 
-        49 => "N", 50 => "M",
-        // Arrow keys
-        103 => "UP", 105 => "LEFT", 106 => "RIGHT", 108 => "DOWN",
-        // Function keys
-        59 => "F1", 60 => "F2", 61 => "F3", 62 => "F4",
-        63 => "F5", 64 => "F6", 65 => "F7", 66 => "F8",
-        67 => "F9", 68 => "F10", 87 => "F11", 88 => "F12",
-        // Modifiers
-        29 => "CTRL", 42 => "SHIFT (LEFT)", 54 => "SHIFT (RIGHT)",
-        56 => "ALT", 100 => "ALT GR", 125 => "SUPER/WIN",
-        // Other common keys
-        2 => "1", 3 => "2", 4 => "3", 5 => "4", 6 => "5",
-        7 => "6", 8 => "7", 9 => "8", 10 => "9", 11 => "0",
-        12 => "-", 13 => "=", 26 => "[", 27 => "]", 39 => ";",
-        40 => "'", 41 => "`", 43 => "\\", 51 => ",", 52 => ".",
-        53 => "/", 58 => "CAPS LOCK", 69 => "NUM LOCK", 70 => "SCROLL LOCK",
-        // Numpad
-        71 => "NUM 7", 72 => "NUM 8", 73 => "NUM 9", 74 => "NUM -",
-        75 => "NUM 4", 76 => "NUM 5", 77 => "NUM 6", 78 => "NUM +",
-        79 => "NUM 1", 80 => "NUM 2", 81 => "NUM 3", 82 => "NUM 0",
-        83 => "NUM .", 96 => "NUM ENTER", 98 => "NUM /", 55 => "NUM *",
-        // Media keys
-        113 => "MUTE", 114 => "VOLUME DOWN", 115 => "VOLUME UP",
-        // System keys
-        99 => "PRINT SCREEN", 119 => "PAUSE", 110 => "HOME", 102 => "PAGE UP",
-        107 => "END", 109 => "PAGE DOWN", 111 => "DELETE", 118 => "INSERT",
-        // Modern keys
-        127 => "PAUSE", 128 => "PREV TRACK", 129 => "NEXT TRACK", 
-        130 => "STOP", 131 => "PLAY/PAUSE",
-        // Others
-        _ => "UNKNOWN KEY",
-    }
-}
-
-fn display_welcome_message() {
+// `title` replaces the default "RUSTKEY INPUT MONITOR" heading
+fn display_welcome_message(title: Option<&str>) {
     // Clear screen
-    print!("\x1c");
+    output::write_stdout("\x1c");
     
-    println!("{}══════════════════════════════════════════════════════{}",
+    outln!("{}══════════════════════════════════════════════════════{}",
         Colors::CYAN, Colors::RESET);
-    println!("{}{}           🎮 RUSTKEY INPUT MONITOR 🎮           {}{}",
-        Colors::CYAN, Colors::BOLD, Colors::RESET, Colors::CYAN);
-    println!("{}══════════════════════════════════════════════════════{}",
+    let title = match title {
+        Some(text) => format!("           {}", text),
+        None => "           🎮 RUSTKEY INPUT MONITOR 🎮           ".to_string(),
+    };
+    outln!("{}{}{}{}{}", Colors::CYAN, Colors::BOLD,
+        output::decorate(&title), Colors::RESET, Colors::CYAN);
+    outln!("{}══════════════════════════════════════════════════════{}",
         Colors::CYAN, Colors::RESET);
-    println!();
-    println!("{}A beautiful way to visualize your input events in real-time{}", 
+    outln!();
+    outln!("{}A beautiful way to visualize your input events in real-time{}", 
         Colors::GREEN, Colors::RESET);
-    println!();
-    println!("{}Waiting for input events...{} (press {}Ctrl+C{} to exit)", 
+    outln!();
+    outln!("{}Waiting for input events...{} (press {}Ctrl+C{} to exit)", 
         Colors::YELLOW, Colors::RESET, Colors::RED, Colors::RESET);
-    println!();
-    println!("{}------------------------------------------{}", 
+    outln!();
+    outln!("{}------------------------------------------{}", 
         Colors::CYAN, Colors::RESET);
     
     // Force flush to ensure everything is displayed
-    io::stdout().flush().unwrap();
+    output::flush_stdout();
 }
 
-fn main() {
-    // Initialize libinput
-    let mut input = Libinput::new_with_udev(Interface);
-    input.udev_assign_seat("seat0").unwrap();
-    
-    // Show our fancy welcome message
-    display_welcome_message();
-
-    // Track mouse state
-    let mut mouse_state = MouseState {
-        x: 0.0,
-        y: 0.0,
-        dx: 0.0,
-        dy: 0.0,
-    };
+// Everything the event loop keeps track of between events
+struct Monitor {
+    mouse_state: MouseState,
+    modifiers: Modifiers,
+    stats: SessionStats,
+    heatmap: Option<HeatmapGrid>,
+    health_export: Option<HealthExporter>,
+    recorder: Option<Recorder>,
+    sqlite_log: Option<SqliteLog>,
+    key_alert: KeyAlert,
+    history: EventHistory,
+    calibration: Calibration,
+    scroll_gestures: ScrollCoalescer,
+    // Replaces the event lines with an acceleration sparkline (--show-accel-curve)
+    accel_curve: Option<AccelCurveVisualizer>,
+    // Per-category key press colors (--key-colors)
+    key_colors: Option<KeyColors>,
+    // Turns wheel notches into glides (--smooth-scroll)
+    scroll_smoothing: Option<ScrollSmoothingFilter>,
+    // Slot positions for inferring gestures from raw touches
+    touches: TouchTracker,
+    raw_scroll: bool,
+    show_codes: bool,
+    dedup: Deduplicator,
+    // Pauses libinput polling during inactivity (--timeout-idle)
+    idle: Option<IdleMonitor>,
+    screen_grid: Option<ScreenGrid>,
+    // Trajectory drawn to an SVG on exit (--record-mouse-path)
+    mouse_path: Option<MousePathRecorder>,
+    // Positions recorded and drawn, once the mouse path is written
+    mouse_path_points: Option<(usize, usize)>,
+    // Human-readable lines (events in text mode, alerts, summary)
+    out: Output,
+    // Machine-readable events for --format json
+    json_out: Option<Output>,
+    // Fixed-size records for --format binary
+    binary_out: Option<BinaryWriter>,
+    // Length-prefixed MessagePack frames for --format msgpack
+    msgpack_out: Option<MsgpackWriter>,
+    // Lines in another tool's format for --format libinput-debug / evtest
+    compat_out: Option<(Output, CompatFormatter)>,
+    last_time_usec: Option<u64>,
+    // Time since the event before the latest one
+    delta_usec: u64,
+    // Monotonic baseline for elapsed_usec: startup for live input, the first
+    // event for recordings (whose clock was another boot's)
+    clock_start_usec: Option<u64>,
+    // Time from the baseline to the latest event
+    elapsed_usec: u64,
+    // Stop after this many events (--max-events)
+    max_events: Option<u64>,
+    events_seen: u64,
+    // Focused window title, sampled in the background (--correlate-window)
+    window_sampler: Option<WindowSampler>,
+    window_breakdown: WindowBreakdown,
+    // Running totals in place of event lines (--status-line, terminal only)
+    status_line: Option<StatusLine>,
+    // Spoken key announcements (--screen-reader)
+    speaker: Option<Speaker>,
+    // Key sequences to point out (--highlight-pattern)
+    highlighter: Option<PatternHighlighter>,
+    // Injected probe key presses (--measure-latency); started by run_monitor
+    latency: Option<LatencyMeasurer>,
+    // Devices connected right now
+    hotplug: DeviceHotplugMonitor,
+    // Masks typed characters and device names (--sanitize)
+    sanitizer: Option<Sanitizer>,
+    // Simulated confinement region (--constrain-pointer)
+    pointer_constraint: Option<PointerConstraint>,
+    // Show which modifiers are held whenever that changes (--show-modifiers)
+    show_modifiers: bool,
+    // Commands bound to keys (--on-key)
+    key_commands: Option<KeyCommands>,
+    // Live keyboard drawing in place of event lines (--kbd-view, terminal only)
+    kbd_view: Option<KeyboardView>,
+    // Time from dispatch() returning to each event being written, when
+    // monitoring live input
+    processing_latency: LatencyHistogram,
+    // Phantom key presses and rollover (--detect-ghosting)
+    ghosting: Option<GhostingDetector>,
+    // --rollover-test: the held keys on the open line instead of event lines
+    rollover_test: bool,
+    // --histogram-interval, and when the events-per-second histogram was last printed
+    rate_snapshots: Option<(Duration, Instant)>,
+    // Decimals of coordinates, deltas and scroll values (--precision)
+    precision: usize,
+    // --summary-out, and whether each summary is added to its end
+    summary_out: Option<PathBuf>,
+    summary_append: bool,
+    // Events per seat with --all-seats, seats without any included
+    seat_counts: Option<BTreeMap<String, u64>>,
+    // Tap / long press labels (--classify-presses)
+    presses: Option<PressClassifier>,
+    // Drops text lines over --max-lines-per-sec
+    throttle: Option<LineThrottle>,
+    // Counts runs of identical events instead of printing them (--coalesce)
+    coalescer: Option<Coalescer>,
+    // Names finished touchpad gestures and their actions (--gesture-map)
+    gestures: GestureClassifier,
+    // What each gesture end seen by observe_event completed, oldest first,
+    // until it is displayed
+    gesture_matches: VecDeque<Option<GestureMatch>>,
+    // --grab: modifiers held on the grabbed devices, for the Ctrl+C that the
+    // terminal can no longer see
+    grab_escape: Option<Modifiers>,
+    // Event types to keep, per device (--filter-config)
+    filter: Option<PerDeviceFilter>,
+    // The first seconds of events to leave out of the statistics (--warmup),
+    // and whether they're still printed
+    warmup: Option<Warmup>,
+    warmup_show: bool,
+    warmup_stats: Option<SessionStats>,
+    // Lets only the first matching event through (--one-shot)
+    one_shot: Option<OneShot>,
+    // Replaces the event lines (--output-template)
+    template: Option<TemplateEngine>,
+    // Caps/Num/Scroll Lock, seeded from the keyboard LEDs for live input
+    locks: LockState,
+    // Kernel events of one device next to libinput's (--raw-evdev)
+    raw_evdev: Option<RawEventDumper>,
+}
 
-    // Track basic statistics
-    let mut key_press_count = 0;
-    let mut mouse_click_count = 0;
-    
-    // Main event loop
-    loop {
-        input.dispatch().unwrap();
-        
-        for event in &mut input {
-            match event {
-                input::Event::Device(device_event) => {
-                    match device_event {
-                        input::event::DeviceEvent::Added(_device) => {
-                            println!("{}➕ Device Added{}", Colors::GREEN, Colors::RESET);
-                        },
-                        input::event::DeviceEvent::Removed(_device) => {
-                            println!("{}➖ Device Removed{}", Colors::RED, Colors::RESET);
-                        },
-                        _ => println!("{}📱 Other Device Event{}", Colors::BLUE, Colors::RESET),
+impl Monitor {
+    fn new(options: &Options) -> Self {
+        // Optional per-minute activity export
+        let health_export = options.health_export.as_ref().map(|path| {
+            HealthExporter::create(path).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot create {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            })
+        });
+
+        // Optional recording of every decoded event
+        let recorder = options.record.as_ref().map(|path| {
+            Recorder::create(path).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot create {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            })
+        });
+
+        // Optional searchable event database
+        let sqlite_log = options.log_sqlite.as_ref().map(|path| {
+            SqliteLog::open(path).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot open {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            })
+        });
+
+        // Optional scroll / pointer normalization from `calibrate`
+        let calibration = match &options.calibration {
+            Some(path) => Calibration::load(path).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot load {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            }),
+            None => Calibration::default(),
+        };
+
+        // Event lines go to the terminal unless syslog, the journal, a log file or a FIFO was requested
+        let events = if options.syslog || options.journal {
+            let connected = if options.syslog {
+                Output::syslog(&options.syslog_ident)
+            } else {
+                Output::journal(&options.syslog_ident)
+            };
+            connected.unwrap_or_else(|err| {
+                eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+                process::exit(1);
+            })
+        } else if let Some(path) = &options.log_file {
+            Output::log_file(path, options.log_rotation()).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot open {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            })
+        } else if let Some(path) = &options.output_fifo {
+            if options.fifo_wait_for_reader {
+                eprintln!("Waiting for a reader on {}...", path.display());
+            }
+            Fifo::create(path, options.fifo_wait_for_reader).map(Output::Fifo).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot create FIFO {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            })
+        } else {
+            Output::Terminal
+        };
+        // With JSON, binary or another tool's events on stdout, the summary and alerts move to stderr
+        let (out, json_out, binary_out, msgpack_out, compat_out) = match options.format {
+            OutputFormat::Text => (events, None, None, None, None),
+            OutputFormat::Json => (Output::Stderr, Some(events), None, None, None),
+            OutputFormat::LibinputDebug => (Output::Stderr, None, None, None, Some((events, CompatFormatter::LibinputDebug(Default::default())))),
+            OutputFormat::Evtest => (Output::Stderr, None, None, None, Some((events, CompatFormatter::Evtest(Default::default())))),
+            OutputFormat::Msgpack => {
+                let writer = match &options.output {
+                    Some(path) => MsgpackWriter::to_file(path).unwrap_or_else(|err| {
+                        eprintln!("{}error: cannot create {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                        process::exit(1);
+                    }),
+                    None if io::stdout().is_terminal() => {
+                        eprintln!("{}error: refusing to write MessagePack frames to a terminal; use --output <file> or a pipe{}",
+                            Colors::RED, Colors::RESET);
+                        process::exit(1);
+                    },
+                    None => MsgpackWriter::to_stdout(),
+                };
+                (Output::Stderr, None, None, Some(writer), None)
+            },
+            OutputFormat::Binary => {
+                let writer = match &options.output {
+                    Some(path) => BinaryWriter::to_file(path, SEAT),
+                    None if io::stdout().is_terminal() => {
+                        eprintln!("{}error: refusing to write binary events to a terminal; use --output <file> or a pipe{}",
+                            Colors::RED, Colors::RESET);
+                        process::exit(1);
+                    },
+                    None => BinaryWriter::to_stdout(SEAT),
+                };
+                let writer = writer.unwrap_or_else(|err| {
+                    eprintln!("{}error: cannot write binary output: {}{}", Colors::RED, err, Colors::RESET);
+                    process::exit(1);
+                });
+                (Output::Stderr, None, Some(writer), None, None)
+            },
+        };
+
+        // Carriage returns only work on a terminal; elsewhere events are printed as usual
+        let status_line = options.status_line && out.is_terminal() && io::stdout().is_terminal();
+        let kbd_view = options.kbd_view && out.is_terminal() && io::stdout().is_terminal();
+        // A recording says nothing about the LEDs when it was made
+        let locks = if options.benchmark || options.replay.is_some() || options.simulate.is_some() {
+            LockState::default()
+        } else {
+            LockState::from_leds()
+        };
+
+        Monitor {
+            // Track mouse state
+            mouse_state: MouseState {
+                x: 0.0,
+                y: 0.0,
+                dx: 0.0,
+                dy: 0.0,
+                buttons_held: 0,
+            },
+            modifiers: Modifiers::default(),
+            // Track basic statistics
+            stats: SessionStats::new(options.idle_threshold.as_micros() as u64, options.session_gap.as_micros() as u64),
+            heatmap: options.heatmap.map(HeatmapGrid::new),
+            health_export,
+            recorder,
+            sqlite_log,
+            key_alert: KeyAlert::new(options.alert_keys.clone(), options.alert_style),
+            history: EventHistory::new(options.history),
+            calibration,
+            scroll_gestures: ScrollCoalescer::new(options.scroll_debounce.as_micros() as u64),
+            accel_curve: options.show_accel_curve.then(AccelCurveVisualizer::new),
+            key_colors: options.key_colors.as_ref().map(|path| KeyColors::load(path).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot load key colors {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            })),
+            scroll_smoothing: options.smooth_scroll.then(|| ScrollSmoothingFilter::new(
+                options.smooth_scroll_rate.unwrap_or(DEFAULT_SMOOTH_SCROLL_RATE),
+                options.scroll_friction.unwrap_or(DEFAULT_SCROLL_FRICTION),
+            )),
+            touches: TouchTracker::default(),
+            raw_scroll: options.raw_scroll,
+            show_codes: options.show_codes,
+            // --coalesce folds repeated motion and scrolling itself
+            dedup: Deduplicator::new(if options.coalesce { 0 } else { options.dedup_window.as_micros() as u64 }),
+            idle: options.timeout_idle.map(|base| {
+                let mut timeouts = IdleTimeouts::new(base);
+                for (class, timeout) in &options.timeout_idle_overrides {
+                    timeouts.set(*class, *timeout);
+                }
+                IdleMonitor::new(timeouts, event::monotonic_usec())
+            }),
+            screen_grid: options.follow_cursor
+                .then(|| {
+                    let (width, height) = options.screen.unwrap_or(screen_size::FALLBACK);
+                    ScreenGrid::new(width, height, options.grid_size)
+                }),
+            mouse_path: options.record_mouse_path.as_ref()
+                .map(|path| MousePathRecorder::new(path, options.screen.unwrap_or(screen_size::FALLBACK),
+                    options.path_simplify_epsilon.unwrap_or(mouse_path::DEFAULT_SIMPLIFY_EPSILON))),
+            mouse_path_points: None,
+            out,
+            json_out,
+            binary_out,
+            msgpack_out,
+            compat_out,
+            last_time_usec: None,
+            delta_usec: 0,
+            clock_start_usec: options.replay.is_none().then(event::monotonic_usec),
+            elapsed_usec: 0,
+            max_events: options.max_events,
+            events_seen: 0,
+            // Started by run_monitor, after a --daemon fork
+            window_sampler: None,
+            window_breakdown: WindowBreakdown::default(),
+            status_line: status_line.then(|| {
+                let mut status_line = StatusLine::default();
+                if options.show_modifiers {
+                    status_line.set_modifiers(Modifiers::default());
+                }
+                status_line.set_locks(locks);
+                status_line
+            }),
+            speaker: options.screen_reader
+                .then(|| Speaker::new(options.speech_rate, options.speak_mouse_buttons)),
+            highlighter: (!options.highlight_patterns.is_empty())
+                .then(|| PatternHighlighter::new(options.highlight_patterns.clone())),
+            latency: None,
+            hotplug: DeviceHotplugMonitor::default(),
+            sanitizer: options.sanitize.then(Sanitizer::default),
+            pointer_constraint: options.constrain_pointer,
+            show_modifiers: options.show_modifiers,
+            kbd_view: kbd_view.then(|| KeyboardView::new(options.layout)),
+            processing_latency: LatencyHistogram::new(),
+            ghosting: (options.detect_ghosting || options.rollover_test).then(GhostingDetector::default),
+            rollover_test: options.rollover_test,
+            rate_snapshots: options.histogram_interval.map(|interval| (interval, Instant::now())),
+            precision: options.precision,
+            summary_out: options.summary_out.clone(),
+            summary_append: options.summary_append,
+            seat_counts: None,
+            presses: options.classify_presses.then(|| {
+                PressClassifier::new(options.long_press_threshold.unwrap_or(DEFAULT_LONG_PRESS).as_micros() as u64)
+            }),
+            throttle: options.max_lines_per_sec.map(LineThrottle::new),
+            coalescer: options.coalesce.then(Coalescer::default),
+            gestures: match &options.gesture_map {
+                Some(path) => GestureClassifier::load(path).unwrap_or_else(|err| {
+                    eprintln!("{}error: cannot load gesture map {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                    process::exit(1);
+                }),
+                None => GestureClassifier::new(GestureClassifier::default_actions()),
+            },
+            gesture_matches: VecDeque::new(),
+            filter: options.filter_config.as_ref().map(|path| PerDeviceFilter::load(path).unwrap_or_else(|err| {
+                eprintln!("{}error: cannot load filter config {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                process::exit(1);
+            })),
+            grab_escape: options.grab.then(Modifiers::default),
+            warmup: options.warmup.map(|duration| Warmup::new(duration, options.warmup_from)),
+            warmup_show: options.warmup_show,
+            warmup_stats: None,
+            one_shot: options.one_shot.then(|| OneShot::new(options.one_shot_class, options.timeout)),
+            template: options.output_template.clone(),
+            locks,
+            raw_evdev: options.raw_evdev_device.as_ref().map(|path| {
+                RawEventDumper::open(path).unwrap_or_else(|err| {
+                    eprintln!("{}error: cannot open {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                    process::exit(1);
+                })
+            }),
+            key_commands: (!options.key_bindings.is_empty()).then(|| KeyCommands::new(options.key_bindings.clone())),
+        }
+    }
+
+    // Periodic work done once per loop iteration
+    fn tick(&mut self) {
+        if signals::take_history_request() {
+            self.display_history();
+        }
+
+        if signals::take_summary_request() {
+            self.export_summary();
+        }
+
+        if let Some(gesture) = self.scroll_gestures.poll(event::monotonic_usec()) {
+            self.display_scroll_gesture(&gesture);
+        }
+
+        self.display_libinput_log();
+
+        if let Some(drawing) = self.kbd_view.as_mut().map(|view| view.tick(Instant::now())) {
+            draw_kbd_view(&drawing);
+        }
+
+        // A flood that stopped is still reported after its second is up
+        if let Some(suppressed) = self.throttle.as_mut().and_then(|throttle| throttle.roll(event::monotonic_usec())) {
+            self.report_suppressed(suppressed);
+        }
+
+        if let Some(Err(err)) = self.latency.as_mut().map(|latency| latency.tick(event::monotonic_usec())) {
+            eprintln!("{}⚠️ {}; latency measurement stopped{}", Colors::RED, err, Colors::RESET);
+            self.latency = None;
+        }
+
+        if let Some((interval, last)) = self.rate_snapshots.as_mut() {
+            if last.elapsed() >= *interval {
+                *last = Instant::now();
+                emit_rate_histogram(&mut self.out, &self.stats.rates);
+            }
+        }
+
+        if let Some(export) = self.health_export.as_mut() {
+            if let Err(err) = export.tick(SystemTime::now()) {
+                eprintln!("{}⚠️ Health export write failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+    }
+
+    // Flush everything that writes to files
+    fn finish(&mut self) {
+        // The rest of a glide still under way
+        for smoothed in self.take_smoothed(u64::MAX) {
+            self.unrecorded(|monitor| monitor.handle_one(&smoothed));
+        }
+
+        // Final totals, in case the last redraw was skipped
+        self.draw_status_line();
+        if let Some(view) = &self.kbd_view {
+            draw_kbd_view(&view.leave());
+        }
+        if let Some(curve) = self.accel_curve.as_ref().map(AccelCurveVisualizer::curve) {
+            emit!(self.out, Severity::Notice, "{}{}📈 Acceleration by raw speed (reported / unaccelerated motion):{}",
+                Colors::CYAN, Colors::BOLD, Colors::RESET);
+            if curve.is_empty() {
+                emit!(self.out, Severity::Notice, "  no pointer motion");
+            }
+            for row in curve {
+                emit!(self.out, Severity::Notice, "{}", row);
+            }
+        }
+
+        if let Some(suppressed) = self.throttle.as_mut().and_then(LineThrottle::take_suppressed) {
+            self.report_suppressed(suppressed);
+        }
+        if let Some((event, count)) = self.coalescer.as_mut().and_then(Coalescer::finish) {
+            self.report_run(&event, count);
+        }
+
+        // --one-shot prints its event and nothing else
+        if self.one_shot.is_none() {
+            let now = event::monotonic_usec();
+            for button in self.mouse_state.held_buttons() {
+                self.report_lost_release(now, button, "held_at_exit");
+            }
+            self.report_incomplete_presses();
+        }
+
+        // No summary follows with --one-shot
+        self.out.flush();
+        if let Some(json_out) = self.json_out.as_mut() {
+            json_out.flush();
+        }
+        if let Some((compat_out, _)) = self.compat_out.as_mut() {
+            compat_out.flush();
+        }
+
+        if let Some(binary_out) = self.binary_out.take() {
+            if let Err(err) = binary_out.finish() {
+                eprintln!("{}⚠️ Binary output write failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+        if let Some(msgpack_out) = self.msgpack_out.take() {
+            if let Err(err) = msgpack_out.finish() {
+                eprintln!("{}⚠️ MessagePack output write failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+
+        if let Some(gesture) = self.scroll_gestures.flush().filter(|_| self.one_shot.is_none()) {
+            self.display_scroll_gesture(&gesture);
+        }
+
+        if let Some(export) = self.health_export.take() {
+            if let Err(err) = export.finish() {
+                eprintln!("{}⚠️ Health export write failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+
+        if let Some(recorder) = self.recorder.take() {
+            if let Err(err) = recorder.finish() {
+                eprintln!("{}⚠️ Recording write failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+
+        if let Some(sqlite_log) = self.sqlite_log.take() {
+            if let Err(err) = sqlite_log.finish() {
+                eprintln!("{}⚠️ SQLite commit failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+
+        if let Some(mouse_path) = self.mouse_path.take() {
+            match mouse_path.finish() {
+                Ok(points) => self.mouse_path_points = Some(points),
+                Err(err) => eprintln!("{}⚠️ Mouse path write failed: {}{}", Colors::RED, err, Colors::RESET),
+            }
+        }
+    }
+
+    // One line for a whole burst of scrolling, e.g. "Scrolled down ~3.0 lines"
+    fn display_scroll_gesture(&mut self, gesture: &ScrollGesture) {
+        // Machine-readable output already carries every scroll event
+        if self.machine_output() {
+            return;
+        }
+
+        let to_lines = |raw: f64| match gesture.source {
+            ScrollSource::Wheel => raw / V120_PER_NOTCH,
+            _ => self.calibration.scroll_notches(raw).unwrap_or(raw / SCROLL_UNITS_PER_LINE),
+        };
+
+        let mut parts = Vec::new();
+        let vertical = to_lines(gesture.vertical);
+        let horizontal = to_lines(gesture.horizontal);
+        if vertical.abs() >= 0.05 {
+            parts.push(format!("{} ~{:.1} lines", if vertical > 0.0 { "down" } else { "up" }, vertical.abs()));
+        }
+        if horizontal.abs() >= 0.05 {
+            parts.push(format!("{} ~{:.1} lines", if horizontal > 0.0 { "right" } else { "left" }, horizontal.abs()));
+        }
+        if parts.is_empty() {
+            parts.push("back to where it started".to_string());
+        }
+
+        emit!(self.out, Severity::Info, "{}🌀 Scrolled {}{} ({} events over {} ms)",
+            Colors::CYAN, parts.join(" and "), Colors::RESET,
+            gesture.events, gesture.duration_usec / 1000);
+    }
+
+    // e.g. "Inferred 2-finger scroll down 12.0 mm" for touches without gesture events
+    fn display_touch_gesture(&mut self, gesture: &TouchGesture) {
+        let motion = match gesture.kind {
+            TouchGestureKind::PinchIn | TouchGestureKind::PinchOut => {
+                let direction = if gesture.kind == TouchGestureKind::PinchIn { "in" } else { "out" };
+                format!("pinch {} (×{:.2})", direction, gesture.scale)
+            },
+            TouchGestureKind::Scroll | TouchGestureKind::Swipe => {
+                let name = if gesture.kind == TouchGestureKind::Scroll { "scroll" } else { "swipe" };
+                let (direction, distance) = if gesture.dy.abs() >= gesture.dx.abs() {
+                    (if gesture.dy > 0.0 { "down" } else { "up" }, gesture.dy.abs())
+                } else {
+                    (if gesture.dx > 0.0 { "right" } else { "left" }, gesture.dx.abs())
+                };
+                format!("{} {} {:.1} mm", name, direction, distance)
+            },
+        };
+        emit!(self.out, Severity::Info, "{}🤲 Inferred {}-finger {}{} ({} ms)",
+            Colors::MAGENTA, gesture.fingers, motion, Colors::RESET, gesture.duration_usec / 1000);
+    }
+
+    fn display_raw_scroll(&mut self, time_usec: u64, source: ScrollSource, horizontal: f64, vertical: f64) {
+        // Calibrated devices also show the scroll in wheel notches
+        let notches = self.calibration.scroll_notches(vertical)
+            .map(|notches| format!(" (≈ {:.1} notches)", notches))
+            .unwrap_or_default();
+        let (kind, line) = match source {
+            ScrollSource::Wheel => ("wheel", format!("{}🖱️  Scroll wheel: horizontal: {:.p$}, vertical: {:.p$}{}{}",
+                Colors::CYAN, horizontal, vertical, notches, Colors::RESET, p = self.precision)),
+            ScrollSource::Finger => ("finger", format!("{}🖱️  Scroll finger event{}{}", Colors::CYAN, notches, Colors::RESET)),
+            ScrollSource::Continuous => ("continuous", format!("{}🖱️  Scroll continuous event{}{}", Colors::CYAN, notches, Colors::RESET)),
+        };
+        let count = self.dedup.observe(kind, [horizontal, vertical], time_usec);
+        self.out.emit_repeatable(Severity::Info, &line, count);
+    }
+
+    // --on-key commands bound to this key
+    fn run_key_commands(&mut self, code: u32, time_usec: u64) {
+        let Some(key_commands) = self.key_commands.as_mut() else {
+            return;
+        };
+        match key_commands.key_pressed(code, time_usec) {
+            Ok(started) => {
+                for command in started {
+                    emit!(self.out, Severity::Notice, "{}Running --on-key command: {}{}", Colors::GRAY, command, Colors::RESET);
+                }
+            },
+            Err(err) => eprintln!("{}⚠️ {}{}", Colors::RED, err, Colors::RESET),
+        }
+    }
+
+    // After a motion line: where the pointer would have gone if the
+    // --constrain-pointer region hadn't stopped it
+    fn display_constrained(&mut self, unconfined: Option<(f64, f64)>) {
+        if let Some((x, y)) = unconfined {
+            emit!(self.out, Severity::Info, "{}[CONSTRAINED: would be ({:.0}, {:.0})]{}", Colors::YELLOW, x, y, Colors::RESET);
+        }
+    }
+
+    fn record_mouse_position(&mut self) {
+        if let Some(mouse_path) = self.mouse_path.as_mut() {
+            mouse_path.record_position(self.mouse_state.x, self.mouse_state.y);
+        }
+    }
+
+    // Show the cursor's grid cell, only when it moves to a different one
+    fn follow_cursor(&mut self) {
+        let Some(grid) = self.screen_grid.as_mut() else {
+            return;
+        };
+        if let Some(cell) = grid.update(self.mouse_state.x, self.mouse_state.y) {
+            emit!(self.out, Severity::Info, "{}🧭 {} {}{}{}",
+                Colors::BLUE, grid.render(), Colors::BOLD, grid.label(cell), Colors::RESET);
+        }
+    }
+
+    // " (code: 30)" with --show-codes, and always for codes without a name
+    fn code_suffix(&self, code: u32, name: &str) -> String {
+        if self.show_codes || name.starts_with("UNKNOWN") {
+            format!(" (code: {})", code)
+        } else {
+            String::new()
+        }
+    }
+
+    // Enter the idle pause once every event type's timeout has run out
+    fn pause_if_idle(&mut self, now_usec: u64) {
+        if self.idle.as_mut().is_some_and(|idle| idle.pause_if_idle(now_usec)) {
+            emit!(self.out, Severity::Notice, "{}[IDLE - monitoring paused] at {}{}",
+                Colors::YELLOW, timefmt::iso8601(SystemTime::now()), Colors::RESET);
+        }
+    }
+
+    fn resume_from_idle(&mut self, now_usec: u64) {
+        if let Some((since, duration)) = self.idle.as_mut().and_then(|idle| idle.resume(now_usec)) {
+            emit!(self.out, Severity::Notice, "{}[ACTIVE - monitoring resumed] idle from {} to {} ({} s){}",
+                Colors::GREEN, timefmt::iso8601(since), timefmt::iso8601(SystemTime::now()),
+                duration.as_secs(), Colors::RESET);
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.idle.as_ref().is_some_and(IdleMonitor::is_paused)
+    }
+
+    // Buttons still down after this event, e.g. " [held: 🔘 LEFT+RIGHT]"
+    fn held_suffix(&self) -> String {
+        if self.mouse_state.buttons_held == 0 {
+            String::new()
+        } else {
+            format!(" [held: 🔘 {}]", self.mouse_state.held_label())
+        }
+    }
+
+    // A button was pressed twice without a release in between, or is still
+    // down at exit: the release event was lost (suspend/resume, flaky hardware)
+    fn report_lost_release(&mut self, time_usec: u64, button: u32, reason: &str) {
+        emit!(self.out, Severity::Warning, "{}⚠️ Lost button release: {} ({}) {}{}",
+            Colors::RED, button_name(button), button,
+            if reason == "held_at_exit" { "was still held at exit" } else { "was pressed again without a release" },
+            Colors::RESET);
+
+        if let Some(json_out) = self.json_out.as_mut() {
+            let line = serde_json::json!({
+                "time_usec": time_usec,
+                "type": "lost_button_release",
+                "button": button,
+                "button_name": button_name(button),
+                "reason": reason,
+            });
+            emit!(json_out, Severity::Warning, "{}", line);
+        }
+    }
+
+    // --detect-ghosting and --rollover-test: ghosts and dropped presses are
+    // reported whatever stands in for the event lines
+    fn observe_rollover(&mut self, timed: &TimedEvent) {
+        let InputEvent::Key { code, pressed } = timed.event else {
+            return;
+        };
+        let Some(ghosting) = self.ghosting.as_mut() else {
+            return;
+        };
+        match ghosting.key(code, pressed, timed.time_usec) {
+            Some(KeyAnomaly::Ghost(ghost)) => self.report_ghost(timed.time_usec, &ghost),
+            Some(KeyAnomaly::Dropped(dropped)) => self.report_dropped_press(timed.time_usec, &dropped),
+            None => {},
+        }
+        if self.rollover_test {
+            self.draw_rollover();
+        }
+    }
+
+    // e.g. "⌨️ Held: 3 (max 4)  A + S + D"
+    fn draw_rollover(&mut self) {
+        let Some(ghosting) = &self.ghosting else {
+            return;
+        };
+        let sanitizing = self.sanitizer.is_some();
+        let keys: Vec<&str> = ghosting.held().iter().map(|code| sanitize::key_label(*code, sanitizing)).collect();
+        let line = format!("⌨️ Held: {} (max {})  {}", keys.len(), ghosting.max_held(), keys.join(" + "));
+        self.out.status(&line);
+    }
+
+    // A key press came late, once another key made room for it
+    fn report_dropped_press(&mut self, time_usec: u64, dropped: &DroppedPress) {
+        let sanitizing = self.sanitizer.is_some();
+        emit!(self.out, Severity::Warning, "{}⚠️ DROPPED PRESS: {} only arrived as {} went up, with {} keys held; the keyboard likely blocks keys past {}{}",
+            Colors::RED, sanitize::key_label(dropped.code, sanitizing), sanitize::key_label(dropped.released, sanitizing),
+            dropped.limit, dropped.limit, Colors::RESET);
+
+        if let Some(json_out) = self.json_out.as_mut() {
+            let line = serde_json::json!({
+                "time_usec": time_usec,
+                "type": "dropped_press",
+                "code": dropped.code,
+                "key": sanitize::key_label(dropped.code, sanitizing),
+                "released": dropped.released,
+                "limit": dropped.limit,
+            });
+            emit!(json_out, Severity::Warning, "{}", line);
+        }
+    }
+
+    // A key press turned out to be a phantom of the keyboard matrix
+    fn report_ghost(&mut self, time_usec: u64, ghost: &Ghost) {
+        let sanitizing = self.sanitizer.is_some();
+        let triggers: Vec<&str> = ghost.triggers.iter().map(|code| sanitize::key_label(*code, sanitizing)).collect();
+        emit!(self.out, Severity::Warning, "{}👻 GHOST: {} was reported while {} were held and went up with them (keyboard matrix ghosting){}",
+            Colors::RED, sanitize::key_label(ghost.code, sanitizing), triggers.join(" + "), Colors::RESET);
+
+        if let Some(json_out) = self.json_out.as_mut() {
+            let line = serde_json::json!({
+                "time_usec": time_usec,
+                "type": "ghost_key",
+                "code": ghost.code,
+                "key": sanitize::key_label(ghost.code, sanitizing),
+                "triggers": ghost.triggers,
+            });
+            emit!(json_out, Severity::Warning, "{}", line);
+        }
+    }
+
+    // --max-lines-per-sec: report what the last second dropped, then
+    // decide on this event
+    fn admit_lines(&mut self, timed: &TimedEvent) -> bool {
+        let Some(throttle) = self.throttle.as_mut() else {
+            return true;
+        };
+        let suppressed = throttle.roll(timed.time_usec);
+        let admitted = throttle.admit(timed.time_usec);
+        if let Some(suppressed) = suppressed {
+            self.report_suppressed(suppressed);
+        }
+        admitted
+    }
+
+    // --coalesce: print the event only if it starts a new run, after
+    // reporting the run it ends
+    fn admit_coalesced(&mut self, timed: &TimedEvent) -> bool {
+        let Some(coalescer) = self.coalescer.as_mut() else {
+            return true;
+        };
+        let (admitted, ended) = coalescer.observe(timed);
+        if let Some((event, count)) = ended {
+            self.report_run(&event, count);
+        }
+        admitted
+    }
+
+    fn report_run(&mut self, event: &InputEvent, count: u32) {
+        emit!(self.out, Severity::Info, "{}🔁 {} x{}{}",
+            Colors::GRAY, coalesce::run_label(event, self.sanitizer.is_some(), self.precision), count, Colors::RESET);
+    }
+
+    fn report_suppressed(&mut self, suppressed: u64) {
+        emit!(self.out, Severity::Notice, "{}... {} events suppressed ...{}", Colors::GRAY, suppressed, Colors::RESET);
+    }
+
+    // --classify-presses: start timing a key, button or touch...
+    fn press_started(&mut self, source: PressSource, code: u32, time_usec: u64) {
+        if let Some(presses) = self.presses.as_mut() {
+            presses.press(source, code, time_usec);
+        }
+    }
+
+    // ...and label it once it's released
+    fn press_ended(&mut self, source: PressSource, code: u32, time_usec: u64) {
+        let Some((kind, duration_usec)) = self.presses.as_mut().and_then(|presses| presses.release(source, code, time_usec)) else {
+            return;
+        };
+        emit!(self.out, Severity::Info, "{}⏱️  {}: {} ({} ms){}",
+            if kind == PressKind::LongPress { Colors::YELLOW } else { Colors::CYAN },
+            kind, self.press_label(source, code), duration_usec / 1000, Colors::RESET);
+
+        if let Some(json_out) = self.json_out.as_mut() {
+            let line = serde_json::json!({
+                "time_usec": time_usec,
+                "type": "press_classified",
+                "source": source.name(),
+                "code": code,
+                "kind": if kind == PressKind::LongPress { "long_press" } else { "tap" },
+                "duration_ms": duration_usec / 1000,
+            });
+            emit!(json_out, Severity::Info, "{}", line);
+        }
+    }
+
+    // Presses still held at exit never completed; timed up to the last event
+    fn report_incomplete_presses(&mut self) {
+        let Some(presses) = &self.presses else {
+            return;
+        };
+        let last_usec = self.last_time_usec.unwrap_or(0);
+        for (source, code, duration_usec) in presses.incomplete(last_usec) {
+            emit!(self.out, Severity::Info, "{}⏱️  Incomplete press: {} still held at exit (at least {} ms){}",
+                Colors::GRAY, self.press_label(source, code), duration_usec / 1000, Colors::RESET);
+        }
+    }
+
+    fn press_label(&self, source: PressSource, code: u32) -> String {
+        match source {
+            PressSource::Key => sanitize::key_label(code, self.sanitizer.is_some()).to_string(),
+            PressSource::Button => format!("button {}", button_name(code).trim_start_matches("BTN_")),
+            PressSource::Touch => format!("touch slot {}", code),
+        }
+    }
+
+    // The typing rhythm switched between burst, normal and hunt-and-peck typing
+    fn display_typing_mode(&mut self, time_usec: u64, mode: TypingMode) {
+        let median_ms = self.stats.rhythm.median_usec().unwrap_or(0) / 1000;
+        emit!(self.out, Severity::Info, "{}⌨️  Typing mode: {} (median interval {} ms){}",
+            Colors::GREEN, mode, median_ms, Colors::RESET);
+        if let Some(status_line) = self.status_line.as_mut() {
+            status_line.set_typing_mode(mode);
+        }
+
+        if let Some(json_out) = self.json_out.as_mut() {
+            let line = serde_json::json!({
+                "time_usec": time_usec,
+                "type": "typing_mode_change",
+                "mode": mode.to_string(),
+                "median_iki_ms": median_ms,
+            });
+            emit!(json_out, Severity::Info, "{}", line);
+        }
+    }
+
+    // --measure-latency probes are taken out of the event stream; true if
+    // `timed` was one
+    fn claim_latency_probe(&mut self, timed: &TimedEvent) -> bool {
+        let Some(latency) = self.latency.as_mut() else {
+            return false;
+        };
+        match latency.observe(timed, event::monotonic_usec()) {
+            Observed::Input => false,
+            Observed::ProbeReleased => true,
+            Observed::ProbeArrived(latency_usec) => {
+                emit!(self.out, Severity::Info, "{}Input latency: {} µs{}", Colors::GRAY, latency_usec, Colors::RESET);
+                if let Some(json_out) = self.json_out.as_mut() {
+                    let line = serde_json::json!({
+                        "time_usec": timed.time_usec,
+                        "type": "latency_sample",
+                        "latency_usec": latency_usec,
+                    });
+                    emit!(json_out, Severity::Info, "{}", line);
+                }
+                true
+            },
+        }
+    }
+
+    // --highlight-pattern sequences completed by this key press
+    fn display_pattern_matches(&mut self, time_usec: u64, code: u32) {
+        let Some(highlighter) = self.highlighter.as_mut() else {
+            return;
+        };
+        for found in highlighter.key_pressed(code) {
+            // Bright yellow background, so the match stands out like an alert
+            emit!(self.out, Severity::Notice, "{}\x1b[30;103m [MATCH: {}] {} {}",
+                Colors::BOLD, found.pattern, found.text, Colors::RESET);
+
+            if let Some(json_out) = self.json_out.as_mut() {
+                let line = serde_json::json!({
+                    "time_usec": time_usec,
+                    "type": "pattern_match",
+                    "pattern_index": found.index,
+                    "pattern": found.pattern,
+                    "text": found.text,
+                });
+                emit!(json_out, Severity::Notice, "{}", line);
+            }
+        }
+    }
+
+    // Messages libinput logged since the last tick (device quirks, config
+    // problems). They're diagnostics, so they stay with the summary and alerts
+    // rather than joining the JSON or binary event stream.
+    fn display_libinput_log(&mut self) {
+        for (priority, message) in libinput_log::take_pending() {
+            let severity = match priority {
+                LogPriority::Error => Severity::Warning,
+                LogPriority::Info => Severity::Notice,
+                LogPriority::Debug => Severity::Info,
+            };
+            let message = match &self.sanitizer {
+                Some(sanitizer) => sanitizer.mask_text(&message),
+                None => message,
+            };
+            emit!(self.out, severity, "{}[libinput] {}{}", Colors::GRAY, message, Colors::RESET);
+        }
+    }
+
+    // Re-render the buffered recent events, one JSON object per line
+    fn display_history(&mut self) {
+        emit!(self.out, Severity::Notice, "{}{}🕘 LAST {} EVENTS{}",
+            Colors::CYAN, Colors::BOLD, self.history.len(), Colors::RESET);
+        for timed in self.history.iter() {
+            let line = serde_json::to_string(timed).unwrap_or_default();
+            emit!(self.out, Severity::Notice, "  {}", line);
+        }
+        self.out.flush();
+    }
+
+    fn display_summary(&mut self) {
+        let mut out = std::mem::replace(&mut self.out, Output::Discard);
+        self.write_summary(&mut out);
+        out.flush();
+        self.out = out;
+        if let Some(json_out) = self.json_out.as_mut() {
+            json_out.flush();
+        }
+        self.export_summary();
+    }
+
+    // --summary-out: the summary on its own, as JSON lines with --format json
+    fn export_summary(&self) {
+        let Some(path) = &self.summary_out else {
+            return;
+        };
+        let file = match LogFile::create(path, self.summary_append) {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("{}⚠️ Cannot write the summary to {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+                return;
+            },
+        };
+        let mut out = Output::File(file, FlushPolicy::Block);
+        if self.json_out.is_some() {
+            for line in self.summary_json() {
+                emit!(out, Severity::Notice, "{}", line);
+            }
+        } else {
+            self.write_summary(&mut out);
+        }
+        out.flush();
+    }
+
+    // The summary's figures for machines: the totals, then the histograms
+    fn summary_json(&self) -> Vec<serde_json::Value> {
+        let stats = &self.stats;
+        let sanitizing = self.sanitizer.is_some();
+        let most_pressed: Vec<serde_json::Value> = stats.key_frequency.top_n(5).into_iter()
+            .map(|(code, count)| serde_json::json!({ "key": sanitize::key_label(code, sanitizing), "count": count }))
+            .collect();
+        let mut lines = vec![serde_json::json!({
+            "type": "session_summary",
+            "key_presses": stats.key_presses,
+            "mouse_clicks": stats.mouse_clicks,
+            "mouse_distance": stats.mouse_distance,
+            "wpm": stats.typing.wpm(),
+            "peak_wpm": stats.typing.peak_wpm(),
+            "average_wpm": stats.typing.average_wpm(),
+            "most_pressed": most_pressed,
+            "longest_pause_ms": stats.typing.longest_gap_ms(),
+            "mean_interval_ms": stats.typing.mean_gap_ms(),
+        })];
+
+        let categories: serde_json::Map<String, serde_json::Value> = stats.rates.class_seconds().into_iter()
+            .map(|(class, seconds)| (class.name().to_string(), serde_json::json!(seconds)))
+            .collect();
+        lines.push(serde_json::json!({
+            "type": "event_rate_histogram",
+            "buckets": RATE_BUCKET_LABELS,
+            "seconds": stats.rates.seconds(),
+            "categories": categories,
+        }));
+        if !stats.combos.is_empty() {
+            let combos: serde_json::Map<String, serde_json::Value> = stats.combos.top_n(usize::MAX).into_iter()
+                .map(|(combo, count)| (combo.to_string(), serde_json::json!(count)))
+                .collect();
+            lines.push(serde_json::json!({ "type": "combo_frequency", "combos": combos }));
+        }
+        lines
+    }
+
+    fn write_summary(&self, out: &mut Output) {
+        let sanitizing = self.sanitizer.is_some();
+        let stats = &self.stats;
+        emit!(out, Severity::Notice, "");
+        emit!(out, Severity::Notice, "{}------------------------------------------{}",
+            Colors::CYAN, Colors::RESET);
+        emit!(out, Severity::Notice, "{}{}📊 SESSION SUMMARY{}", Colors::CYAN, Colors::BOLD, Colors::RESET);
+        emit!(out, Severity::Notice, "{}Key presses:{} {}", Colors::GREEN, Colors::RESET, stats.key_presses);
+        emit!(out, Severity::Notice, "{}Mouse clicks:{} {}", Colors::GREEN, Colors::RESET, stats.mouse_clicks);
+        if let Some(counts) = &self.seat_counts {
+            let per_seat: Vec<String> = counts.iter().map(|(seat, count)| format!("{} {}", seat, count)).collect();
+            emit!(out, Severity::Notice, "{}Events per seat:{} {}", Colors::GREEN, Colors::RESET, per_seat.join(", "));
+        }
+        if stats.mouse_clicks > 0 {
+            let per_button: Vec<String> = stats.clicks.button_peaks().iter()
+                .map(|(button, peak)| format!("{} {}", button_name(*button).trim_start_matches("BTN_"), peak))
+                .collect();
+            emit!(out, Severity::Notice, "{}Peak click rate:{} {} CPS ({})",
+                Colors::GREEN, Colors::RESET, stats.clicks.peak_cps(), per_button.join(", "));
+        }
+        match self.calibration.pointer_cm(stats.mouse_distance) {
+            Some(cm) => emit!(out, Severity::Notice, "{}Mouse distance:{} {:.0} counts (≈ {:.1} cm)",
+                Colors::GREEN, Colors::RESET, stats.mouse_distance, cm),
+            None => emit!(out, Severity::Notice, "{}Mouse distance:{} {:.0} counts",
+                Colors::GREEN, Colors::RESET, stats.mouse_distance),
+        }
+        emit!(out, Severity::Notice, "{}Typing speed (last minute of typing):{} {:.1} WPM",
+            Colors::GREEN, Colors::RESET, stats.typing.wpm());
+        if let Some(average) = stats.typing.average_wpm() {
+            emit!(out, Severity::Notice, "{}Peak typing speed:{} {:.1} WPM, {}average over active time:{} {:.1} WPM",
+                Colors::GREEN, Colors::RESET, stats.typing.peak_wpm(), Colors::GREEN, Colors::RESET, average);
+        }
+
+        let top_keys = stats.key_frequency.top_n(5);
+        if !top_keys.is_empty() {
+            let listing: Vec<String> = top_keys.iter()
+                .map(|(code, count)| format!("{} ×{}", sanitize::key_label(*code, sanitizing), count))
+                .collect();
+            emit!(out, Severity::Notice, "{}Most pressed keys:{} {}", Colors::GREEN, Colors::RESET, listing.join(", "));
+        }
+
+        if !stats.combos.is_empty() {
+            emit!(out, Severity::Notice, "{}Most used shortcuts:{}", Colors::GREEN, Colors::RESET);
+            for (combo, count) in stats.combos.top_n(TOP_COMBOS) {
+                emit!(out, Severity::Notice, "  {:<20} ×{}", combo, count);
+            }
+        }
+
+        let top_bigrams = stats.bigrams.top_n(5);
+        if !top_bigrams.is_empty() {
+            let listing: Vec<String> = top_bigrams.iter()
+                .map(|((first, second), count)| format!("{}→{} ×{}",
+                    sanitize::key_label(*first, sanitizing), sanitize::key_label(*second, sanitizing), count))
+                .collect();
+            emit!(out, Severity::Notice, "{}Most typed key pairs:{} {}", Colors::GREEN, Colors::RESET, listing.join(", "));
+        }
+
+        if stats.strokes.total() > 0 {
+            let counts: Vec<String> = StrokeCategory::ALL.iter()
+                .map(|category| format!("{} {}", stats.strokes.get(*category), category.name().to_lowercase().replace('_', " ")))
+                .collect();
+            let productivity = stats.strokes.productivity()
+                .map_or(String::new(), |ratio| format!(" (productivity {:.0}%)", ratio * 100.0));
+            emit!(out, Severity::Notice, "{}Keystrokes:{} {}{}", Colors::GREEN, Colors::RESET, counts.join(", "), productivity);
+        }
+
+        match (stats.typing.longest_gap_ms(), stats.typing.mean_gap_ms()) {
+            (Some(longest), Some(mean)) => {
+                emit!(out, Severity::Notice, "{}Longest pause between keys:{} {:.0} ms", Colors::GREEN, Colors::RESET, longest);
+                emit!(out, Severity::Notice, "{}Average inter-key interval:{} {:.0} ms", Colors::GREEN, Colors::RESET, mean);
+            },
+            _ => emit!(out, Severity::Notice, "{}Inter-key timing:{} not enough key presses", Colors::GREEN, Colors::RESET),
+        }
+
+        let histogram = stats.rhythm.histogram();
+        if let Some(most) = histogram.iter().copied().max().filter(|&most| most > 0) {
+            let total: u64 = histogram.iter().sum();
+            emit!(out, Severity::Notice, "{}Inter-key intervals:{}", Colors::GREEN, Colors::RESET);
+            for (label, count) in IKI_BUCKET_LABELS.iter().zip(histogram) {
+                let bar = "█".repeat((count * HISTOGRAM_BAR_WIDTH / most) as usize);
+                emit!(out, Severity::Notice, "  {:<10}  {:<20} {} ({:.0}%)",
+                    label, bar, count, *count as f64 * 100.0 / total as f64);
+            }
+        }
+        emit_rate_histogram(out, &stats.rates);
+        // The same as JSON for --format json, next to the rest of the summary
+        // rather than among the events
+        if self.json_out.is_some() {
+            for line in self.summary_json() {
+                emit!(out, Severity::Notice, "{}", line);
+            }
+        }
+
+        // The heatmap is all background color, so only a terminal can show it
+        if let Some(heatmap) = self.heatmap.as_ref().filter(|_| out.is_terminal() && io::stdout().is_terminal()) {
+            emit!(out, Severity::Notice, "{}Key heatmap:{}", Colors::GREEN, Colors::RESET);
+            for line in heatmap.render_ansi().lines() {
+                emit!(out, Severity::Notice, "  {}", line);
+            }
+        }
+
+        let devices = self.hotplug.devices();
+        if !devices.is_empty() {
+            let names: Vec<&str> = devices.iter().map(|device| device.name.as_str()).filter(|name| !name.is_empty()).collect();
+            emit!(out, Severity::Notice, "{}Devices connected at exit:{} {}{}", Colors::GREEN, Colors::RESET, devices.len(),
+                if names.is_empty() { String::new() } else { format!(" ({})", names.join(", ")) });
+        }
+
+        if let Some((recorded, drawn)) = self.mouse_path_points {
+            emit!(out, Severity::Notice, "{}Mouse path:{} {} points simplified to {} ({:.1}x smaller)",
+                Colors::GREEN, Colors::RESET, recorded, drawn, recorded as f64 / drawn.max(1) as f64);
+        }
+
+        if let Some(warmup) = &self.warmup {
+            let from = match warmup.start() {
+                WarmupStart::Startup => "startup",
+                WarmupStart::FirstInput => "the first event",
+            };
+            emit!(out, Severity::Notice, "{}Warmup:{} {} events in the first {}s after {} left out of the statistics",
+                Colors::GREEN, Colors::RESET, warmup.discarded(), warmup.duration_usec() as f64 / 1_000_000.0, from);
+        }
+
+        if let Some(throttle) = self.throttle.as_ref().filter(|throttle| throttle.total_suppressed() > 0) {
+            emit!(out, Severity::Notice, "{}Output throttled:{} {} events not printed (over {} per second)",
+                Colors::GREEN, Colors::RESET, throttle.total_suppressed(), throttle.max_per_sec());
+        }
+
+        if let Some(presses) = &self.presses {
+            emit!(out, Severity::Notice, "{}Presses:{} {} taps, {} long presses ({} ms or more), {} incomplete",
+                Colors::GREEN, Colors::RESET, presses.taps(), presses.long_presses(), presses.threshold_usec() / 1000,
+                presses.incomplete(0).len());
+        }
+
+        if let Some(ghosting) = &self.ghosting {
+            emit!(out, Severity::Notice, "{}Key rollover:{} up to {} keys held at once, {} ghost key presses, {} dropped presses",
+                Colors::GREEN, Colors::RESET, ghosting.max_held(), ghosting.ghosts(), ghosting.dropped());
+        }
+
+        if let Some(latency) = &self.latency {
+            match latency.summary() {
+                Some(summary) => emit!(out, Severity::Notice,
+                    "{}Input latency ({} probes):{} mean {} µs, min {} µs, max {} µs, p99 {} µs, {} lost",
+                    Colors::GREEN, summary.samples, Colors::RESET,
+                    summary.mean_usec, summary.min_usec, summary.max_usec, summary.p99_usec, latency.lost()),
+                None => emit!(out, Severity::Notice, "{}Input latency:{} no probes came back ({} lost)",
+                    Colors::GREEN, Colors::RESET, latency.lost()),
+            }
+        }
+
+        let processing = &self.processing_latency;
+        if processing.count() > 0 {
+            emit!(out, Severity::Notice,
+                "{}Event processing latency ({} events):{} p50 {:.0} µs, p95 {:.0} µs, p99 {:.0} µs, max {:.0} µs",
+                Colors::GREEN, processing.count(), Colors::RESET, processing.percentile_us(50.0),
+                processing.percentile_us(95.0), processing.percentile_us(99.0), processing.max_us());
+        }
+
+        let windows = self.window_breakdown.top_n(5);
+        if !windows.is_empty() {
+            let listing: Vec<String> = windows.iter()
+                .map(|(title, share)| format!("{} {:.0}%", title, share * 100.0))
+                .collect();
+            emit!(out, Severity::Notice, "{}Active windows:{} {}", Colors::GREEN, Colors::RESET, listing.join(", "));
+        }
+
+        let sessions = stats.sessions.sessions();
+        if let Some(first) = sessions.first() {
+            emit!(out, Severity::Notice, "{}Activity sessions{} (split at gaps over {}):",
+                Colors::GREEN, Colors::RESET, format_duration(stats.sessions.gap_usec()));
+            emit!(out, Severity::Notice, "  {:>3}  {:>9}  {:>9}  {:>6}  {:>6}", "#", "start", "duration", "keys", "WPM");
+            let hidden = sessions.len().saturating_sub(MAX_SESSION_ROWS);
+            if hidden > 0 {
+                emit!(out, Severity::Notice, "  ({} earlier sessions not shown)", hidden);
+            }
+            for (index, session) in sessions.iter().enumerate().skip(hidden) {
+                let offset = (session.start_usec - first.start_usec) / 1_000_000;
+                let wpm = session.wpm().map_or("-".to_string(), |wpm| format!("{:.1}", wpm));
+                emit!(out, Severity::Notice, "  {:>3}  {:>9}  {:>9}  {:>6}  {:>6}",
+                    index + 1,
+                    format!("+{}:{:02}:{:02}", offset / 3600, offset / 60 % 60, offset % 60),
+                    format_duration(session.duration_usec()),
+                    session.key_presses,
+                    wpm);
+            }
+        }
+    }
+
+    // Full pipeline for one event: keep it, then show it
+    fn handle_event(&mut self, timed: &TimedEvent) {
+        for smoothed in self.take_smoothed(timed.time_usec) {
+            self.unrecorded(|monitor| monitor.handle_one(&smoothed));
+        }
+        if !self.absorb_wheel(timed) {
+            self.handle_one(timed);
+        }
+    }
+
+    fn handle_one(&mut self, timed: &TimedEvent) {
+        if !self.admit(timed) || self.one_shot_skips(timed) {
+            return;
+        }
+        let sanitized = self.sanitize(timed);
+        let timed = sanitized.as_ref().unwrap_or(timed);
+        self.observe_event(timed);
+        self.display_event(timed);
+    }
+
+    // The first step for every event, live or not, before --sanitize changes
+    // it; false when the event goes no further
+    fn admit(&mut self, timed: &TimedEvent) -> bool {
+        self.watch_grab_escape(timed);
+        // Device names are matched before --sanitize renames the devices
+        self.filter.as_mut().is_none_or(|filter| filter.allows(timed)) && !self.held_by_warmup(timed)
+    }
+
+    // --warmup: true for events inside the window, which --warmup-show
+    // prints right away without counting them
+    fn held_by_warmup(&mut self, timed: &TimedEvent) -> bool {
+        if !self.warmup.as_mut().is_some_and(|warmup| warmup.discards(timed)) {
+            return false;
+        }
+        if self.warmup_show {
+            self.display_uncounted(timed);
+        }
+        true
+    }
+
+    // --grab: Ctrl+C typed on a grabbed keyboard never reaches the terminal,
+    // so it ends the monitor from here
+    fn watch_grab_escape(&mut self, timed: &TimedEvent) {
+        if let (Some(held), InputEvent::Key { code, pressed }) = (self.grab_escape.as_mut(), &timed.event) {
+            if *pressed && *code == KEY_C && held.ctrl_held() {
+                signals::request_exit();
+            }
+            held.update(*code, *pressed);
+        }
+    }
+
+    // One dispatch() batch of live events, each with the touchpad finger
+    // change it caused: kept and queued for display. Returns true if a
+    // device was added.
+    fn ingest(&mut self, events: Vec<(TimedEvent, Option<TimedEvent>)>, queue: &mut PriorityEventQueue) -> bool {
+        let mut devices_added = false;
+        for (mut timed, finger_change) in events {
+            if self.limit_reached() {
+                break;
+            }
+            if self.claim_latency_probe(&timed) {
+                continue;
+            }
+            devices_added |= matches!(timed.event, InputEvent::DeviceAdded { .. });
+            if self.admit(&timed) {
+                if let Some(sanitized) = self.sanitize(&timed) {
+                    timed = sanitized;
+                }
+                if !self.absorb_wheel(&timed) && !self.one_shot_skips(&timed) {
+                    self.observe_event(&timed);
+                    queue.push(timed);
+                }
+            }
+            if let Some(change) = finger_change.filter(|change| !self.held_by_warmup(change) && !self.one_shot_skips(change)) {
+                self.observe_event(&change);
+                queue.push(change);
+            }
+        }
+
+        for smoothed in self.take_smoothed(event::monotonic_usec()) {
+            if !self.one_shot_skips(&smoothed) {
+                self.unrecorded(|monitor| monitor.observe_event(&smoothed));
+                queue.push(smoothed);
+            }
+        }
+        devices_added
+    }
+
+    // --warmup-show: print the event with the session statistics set aside,
+    // so the warmup's own running totals are shown
+    fn display_uncounted(&mut self, timed: &TimedEvent) {
+        let warmup_stats = self.warmup_stats.take().unwrap_or_else(|| self.stats.fresh());
+        let stats = std::mem::replace(&mut self.stats, warmup_stats);
+        let sanitized = self.sanitize(timed);
+        let timed = sanitized.as_ref().unwrap_or(timed);
+        self.observe_event(timed);
+        self.display_event(timed);
+        self.warmup_stats = Some(std::mem::replace(&mut self.stats, stats));
+    }
+
+    // With --sanitize, the event as every output should see it
+    fn sanitize(&mut self, timed: &TimedEvent) -> Option<TimedEvent> {
+        self.sanitizer.as_mut().map(|sanitizer| sanitizer.sanitize(timed))
+    }
+
+    // With --smooth-scroll, wheel events are taken over by the filter. The
+    // --record recording keeps the wheel events rather than their glides, so
+    // it replays like the real device.
+    fn absorb_wheel(&mut self, timed: &TimedEvent) -> bool {
+        let (Some(filter), InputEvent::Scroll { source: ScrollSource::Wheel, horizontal, vertical }) =
+            (self.scroll_smoothing.as_mut(), &timed.event) else {
+            return false;
+        };
+        // Glides are continuous scrolling, so in its units rather than v120
+        let scale = SCROLL_UNITS_PER_LINE / V120_PER_NOTCH;
+        filter.push(timed.time_usec, horizontal * scale, vertical * scale);
+        self.record(timed);
+        true
+    }
+
+    // Run `step` with --record set aside, for the glide steps
+    fn unrecorded(&mut self, step: impl FnOnce(&mut Self)) {
+        let recorder = self.recorder.take();
+        step(self);
+        self.recorder = recorder;
+    }
+
+    // The glide steps due by `now_usec`, as continuous scroll events
+    fn take_smoothed(&mut self, now_usec: u64) -> Vec<TimedEvent> {
+        let Some(filter) = self.scroll_smoothing.as_mut() else {
+            return Vec::new();
+        };
+        filter.due(now_usec).into_iter().map(|(time_usec, horizontal, vertical)| TimedEvent {
+            time_usec,
+            event: InputEvent::Scroll { source: ScrollSource::Continuous, horizontal, vertical },
+            fingers: None,
+            sysname: None,
+            seat: None,
+        }).collect()
+    }
+
+    // True once --max-events events have been processed
+    fn limit_reached(&self) -> bool {
+        self.max_events.is_some_and(|max| self.events_seen >= max)
+            || self.one_shot.as_ref().is_some_and(OneShot::captured)
+    }
+
+    // With --one-shot, true for every event but the one to print
+    fn one_shot_skips(&mut self, timed: &TimedEvent) -> bool {
+        self.one_shot.as_mut().is_some_and(|one_shot| !one_shot.admit(&timed.event))
+    }
+
+    fn one_shot_timed_out(&self) -> bool {
+        self.one_shot.as_ref().is_some_and(OneShot::timed_out)
+    }
+
+    // With --one-shot: 0 if the event was printed, 1 if none matched
+    fn one_shot_exit_code(&self) -> Option<i32> {
+        self.one_shot.as_ref().map(|one_shot| if one_shot.captured() { 0 } else { 1 })
+    }
+
+    // JSON, binary, MessagePack or another tool's lines replace the per-event text lines
+    fn machine_output(&self) -> bool {
+        self.json_out.is_some() || self.binary_out.is_some() || self.msgpack_out.is_some() || self.compat_out.is_some()
+    }
+
+    // History, recording and JSON/binary output always see events in kernel order
+    fn observe_event(&mut self, timed: &TimedEvent) {
+        self.events_seen += 1;
+        if let (Some(counts), Some(seat)) = (self.seat_counts.as_mut(), &timed.seat) {
+            *counts.entry(seat.clone()).or_insert(0) += 1;
+        }
+        self.history.push(timed);
+        self.hotplug.observe(timed);
+
+        self.delta_usec = self.last_time_usec.map_or(0, |last| timed.time_usec.saturating_sub(last));
+        self.elapsed_usec = timed.time_usec.saturating_sub(*self.clock_start_usec.get_or_insert(timed.time_usec));
+        let gesture_match = self.gestures.observe(&timed.event);
+        if self.json_out.is_some() || self.msgpack_out.is_some() {
+            let window = self.window_sampler.as_ref().and_then(WindowSampler::current);
+            let line = JsonEvent {
+                time_usec: timed.time_usec,
+                delta_usec: self.delta_usec,
+                elapsed_usec: self.elapsed_usec,
+                event: &timed.event,
+                fingers: timed.fingers,
+                seat: timed.seat.as_deref(),
+                window: window.as_deref(),
+                mapped_action: gesture_match.as_ref().and_then(|gesture| gesture.action.as_deref()),
+            };
+            if let Some(json_out) = self.json_out.as_mut() {
+                emit!(json_out, Severity::Info, "{}", serde_json::to_string(&line).unwrap_or_default());
+            }
+            if let Some(Err(err)) = self.msgpack_out.as_mut().map(|msgpack_out| msgpack_out.write(&line)) {
+                eprintln!("{}⚠️ MessagePack output write failed: {}{}", Colors::RED, err, Colors::RESET);
+                self.msgpack_out = None;
+            }
+        }
+        self.last_time_usec = Some(timed.time_usec);
+        if matches!(&timed.event, InputEvent::Gesture { kind, .. } if kind.ends_with("End")) {
+            self.gesture_matches.push_back(gesture_match);
+        }
+
+        if let Some((compat_out, formatter)) = self.compat_out.as_mut() {
+            if let Some(line) = formatter.format(timed) {
+                emit!(compat_out, Severity::Info, "{}", line);
+            }
+        }
+
+        if let Some(idle) = self.idle.as_mut() {
+            idle.observe(EventClass::of(&timed.event), timed.time_usec);
+        }
+
+        if let Some(binary_out) = self.binary_out.as_mut() {
+            if let Err(err) = binary_out.write(timed) {
+                eprintln!("{}⚠️ Binary output write failed: {}{}", Colors::RED, err, Colors::RESET);
+                self.binary_out = None;
+            }
+        }
+
+        self.record(timed);
+
+        if let Some(sqlite_log) = self.sqlite_log.as_mut() {
+            if let Err(err) = sqlite_log.insert(timed) {
+                eprintln!("{}⚠️ SQLite insert failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+    }
+
+    fn record(&mut self, timed: &TimedEvent) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(err) = recorder.record(timed) {
+                eprintln!("{}⚠️ Recording write failed: {}{}", Colors::RED, err, Colors::RESET);
+            }
+        }
+    }
+
+    // Update statistics and print the event
+    fn display_event(&mut self, timed: &TimedEvent) {
+        self.observe_rollover(timed);
+        let text_lines = !self.machine_output() && self.status_line.is_none() && self.kbd_view.is_none()
+            && self.accel_curve.is_none() && !self.rollover_test;
+        if text_lines && self.template.is_none() && self.admit_coalesced(timed) && self.admit_lines(timed) {
+            if let Some(seat) = &timed.seat {
+                output::set_line_prefix(&format!("[{}] ", seat));
+                self.render_event(timed);
+                output::set_line_prefix("");
+            } else {
+                self.render_event(timed);
+            }
+            return;
+        }
+
+        // The JSON line or binary record was written by observe_event, the
+        // status line, keyboard view or template stands in for the event, or
+        // --coalesce or --max-lines-per-sec dropped it; keep the statistics up to date but
+        // drop the text lines
+        let modifiers_before = self.modifiers;
+        let locks_before = self.locks;
+        let out = std::mem::replace(&mut self.out, Output::Discard);
+        self.render_event(timed);
+        self.out = out;
+
+        if text_lines && self.template.is_some() && self.admit_lines(timed) {
+            self.render_template(timed);
+        }
+
+        if let Some(status_line) = self.status_line.as_mut() {
+            if let InputEvent::Key { code, pressed: true } = timed.event {
+                status_line.key_pressed(code);
+            }
+            // Modifier changes are shown right away, so a quick tap isn't missed
+            let modifiers_changed = self.show_modifiers && self.modifiers != modifiers_before;
+            if modifiers_changed {
+                status_line.set_modifiers(self.modifiers);
+            }
+            let locks_changed = self.locks != locks_before;
+            if locks_changed {
+                status_line.set_locks(self.locks);
+            }
+            if modifiers_changed || locks_changed || status_line.due() {
+                self.draw_status_line();
+            }
+        }
+
+        if let (Some(view), InputEvent::Key { code, pressed: true }) = (self.kbd_view.as_mut(), &timed.event) {
+            draw_kbd_view(&view.key_pressed(*code, Instant::now()));
+        }
+    }
+
+    // --output-template: the event's JSON fields plus the computed ones
+    fn render_template(&mut self, timed: &TimedEvent) {
+        let Some(template) = &self.template else {
+            return;
+        };
+        let line = JsonEvent {
+            time_usec: timed.time_usec,
+            delta_usec: self.delta_usec,
+            elapsed_usec: self.elapsed_usec,
+            event: &timed.event,
+            fingers: timed.fingers,
+            seat: timed.seat.as_deref(),
+            window: None,
+            mapped_action: None,
+        };
+        let mut fields = match serde_json::to_value(&line) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => return,
+        };
+        fields.insert("sysname".to_string(), timed.sysname.clone().into());
+        fields.insert("timestamp_us".to_string(), timed.time_usec.into());
+        match &timed.event {
+            InputEvent::Key { code, .. } => {
+                fields.insert("key_name".to_string(), sanitize::key_label(*code, self.sanitizer.is_some()).into());
+            },
+            InputEvent::PointerButton { button, .. } => {
+                fields.insert("button_name".to_string(), button_name(*button).into());
+            },
+            _ => {},
+        }
+        fields.insert("wpm".to_string(), format!("{:.1}", self.stats.typing.wpm()).into());
+        let session_start = self.stats.sessions.sessions().first().map_or(timed.time_usec, |session| session.start_usec);
+        fields.insert("session_duration_s".to_string(), (timed.time_usec.saturating_sub(session_start) / 1_000_000).into());
+        fields.insert("modifier_state".to_string(), self.modifiers.label().into());
+
+        let line = template.render(&fields);
+        emit!(self.out, Severity::Info, "{}", line);
+    }
+
+    // The LED follows each press of its lock key
+    fn toggle_lock(&mut self, lock: Lock) {
+        let known = self.locks.get(lock).is_some();
+        let on = self.locks.toggle(lock);
+        emit!(self.out, Severity::Info, "{}💡 {}: {}{}{}",
+            Colors::CYAN, lock.name(), if on { "ON" } else { "OFF" },
+            if known { "" } else { " (state before was unknown, taken as off)" }, Colors::RESET);
+    }
+
+    // --raw-evdev: what the kernel queued since the last call, ahead of
+    // libinput's view of the same events
+    fn dump_raw_events(&mut self) {
+        let Some(dumper) = self.raw_evdev.as_mut() else {
+            return;
+        };
+        match dumper.read() {
+            Ok(events) => {
+                for event in events {
+                    emit!(self.out, Severity::Info, "{}[evdev] {}{}", Colors::GRAY, raw_evdev::describe(&event), Colors::RESET);
+                }
+            },
+            Err(err) => {
+                eprintln!("{}⚠️ Stopped dumping raw events of {}: {}{}", Colors::RED, dumper.path().display(), err, Colors::RESET);
+                self.raw_evdev = None;
+            },
+        }
+    }
+
+    // --show-accel-curve: device settings as pointers appear, then the
+    // sparkline on the open line
+    fn observe_accel(&mut self, event: &input::Event) {
+        let Some(curve) = self.accel_curve.as_mut() else {
+            return;
+        };
+        let line = curve.observe(event);
+        // With --sanitize, the alias the device's added event will get
+        let sanitizer = self.sanitizer.as_mut();
+        let name = |name: &str| sanitizer.map_or_else(|| name.to_string(), |sanitizer| sanitizer.alias(name));
+        if let Some(device) = accel_curve::describe_device(event, name) {
+            emit!(self.out, Severity::Notice, "{}🐁 {}{}", Colors::CYAN, device, Colors::RESET);
+        }
+        if let Some(line) = line {
+            self.out.status(&line);
+        }
+    }
+
+    fn draw_status_line(&mut self) {
+        if let Some(status_line) = &self.status_line {
+            let line = status_line.render(self.stats.key_presses, self.stats.mouse_clicks, self.stats.typing.wpm(), &self.stats.strokes);
+            self.out.status(&line);
+        }
+    }
+
+    fn render_event(&mut self, timed: &TimedEvent) {
+        if !matches!(timed.event, InputEvent::DeviceAdded { .. } | InputEvent::DeviceRemoved { .. } | InputEvent::Other { .. }) {
+            let key_press = matches!(timed.event, InputEvent::Key { pressed: true, .. });
+            self.stats.sessions.record(timed.time_usec, key_press);
+            self.stats.rates.record(timed.time_usec, EventClass::of(&timed.event));
+            if let Some(sampler) = &self.window_sampler {
+                self.window_breakdown.record(sampler.current().as_deref().unwrap_or(UNKNOWN_WINDOW));
+            }
+        }
+
+        // Only motion and scroll lines are folded into repeat counters
+        if !matches!(timed.event, InputEvent::PointerMotion { .. } | InputEvent::PointerMotionAbsolute { .. } | InputEvent::Scroll { .. }) {
+            self.dedup.reset();
+        }
+
+        match &timed.event {
+            InputEvent::DeviceAdded { device, info } => {
+                let capabilities = info.as_ref()
+                    .filter(|info| !info.capabilities.is_empty())
+                    .map(|info| format!(" ({})", info.capabilities.join(", ")))
+                    .unwrap_or_default();
+                emit!(self.out, Severity::Notice, "{}➕ Device Added{}{}{}",
+                    Colors::GREEN, device_label(device), capabilities, Colors::RESET);
+            },
+            InputEvent::DeviceRemoved { device, .. } => {
+                emit!(self.out, Severity::Notice, "{}➖ Device Removed{}{}",
+                    Colors::RED, device_label(device), Colors::RESET);
+            },
+            InputEvent::Key { code, pressed } => {
+                let key_text = sanitize::key_label(*code, self.sanitizer.is_some());
+                // Modifiers held before this key, for combo display
+                let held = self.modifiers;
+                let is_modifier = self.modifiers.update(*code, *pressed);
+                xkb_layout::update(*code, *pressed);
+                if let Some(category) = self.stats.strokes.record(*code, *pressed, held) {
+                    if let Some(export) = self.health_export.as_mut() {
+                        export.record_stroke(category);
+                    }
+                }
+
+                if *pressed {
+                    self.stats.key_presses += 1;
+                    self.press_started(PressSource::Key, *code, timed.time_usec);
+                    self.stats.typing.record_press(timed.time_usec);
+                    self.stats.key_frequency.increment(*code);
+                    self.stats.bigrams.record_press(*code, timed.time_usec);
+                    if let Some(heatmap) = self.heatmap.as_mut() {
+                        heatmap.add_press(*code);
+                    }
+                    if let Some(export) = self.health_export.as_mut() {
+                        export.record_key_press();
+                    }
+                    let color = self.key_colors.as_ref().and_then(|colors| colors.color(*code)).unwrap_or(Colors::YELLOW);
+                    emit!(self.out, Severity::Info, "{}⌨️  KEY PRESS DETECTED --> {}{}{}{}{} <--{}{}",
+                        color, 
+                        Colors::MAGENTA, Colors::BOLD, 
+                        key_text, 
+                        Colors::RESET, color,
+                        self.code_suffix(*code, key_text),
+                        Colors::RESET);
+                    emit!(self.out, Severity::Info, "{}🔠 YOU PRESSED: [ {} ]{} (Total key presses: {})",
+                        Colors::GREEN, key_text, Colors::RESET, self.stats.key_presses);
+                    if !is_modifier && held.shortcut_held() {
+                        self.stats.combos.increment(&format!("{}+{}", held.label(), key_text));
+                    }
+                    if !is_modifier && !held.is_empty() {
+                        emit!(self.out, Severity::Info, "{}{}🎹 COMBO: {}+{}{}",
+                            Colors::MAGENTA, Colors::BOLD, held.label(), key_text, Colors::RESET);
                     }
-                },
-                input::Event::Keyboard(keyboard_event) => {
-                    if let input::event::KeyboardEvent::Key(key_event) = keyboard_event {
-                        // Get the key code directly from key_event method
-                        let key_code = key_event.key();
-                        let key_text = key_name(key_code);
-                        
-                        // Use key_state instead of state
-                        if key_event.key_state() == input::event::keyboard::KeyState::Pressed {
-                            key_press_count += 1;
-                            println!("{}⌨️  KEY PRESS DETECTED --> {}{}{} {}{} {}<-- (code: {}){}",
-                                Colors::YELLOW, 
-                                Colors::MAGENTA, Colors::BOLD, 
-                                key_text, 
-                                Colors::RESET, Colors::YELLOW,
-                                Colors::BOLD,
-                                key_code,
-                                Colors::RESET);
-                            println!("{}🔠 YOU PRESSED: [ {} ]{} (Total key presses: {})",
-                                Colors::GREEN, key_text, Colors::RESET, key_press_count);
-                        } else {
-                            println!("{}⌨️  KEY RELEASE DETECTED --> {} {} <-- (code: {}){}",
-                                Colors::BLUE, key_text, Colors::RESET, key_code, Colors::RESET);
+                    if let Some(lock) = Lock::of_key(*code) {
+                        self.toggle_lock(lock);
+                    }
+                    self.key_alert.on_key_press(*code, &mut self.out);
+                    self.run_key_commands(*code, timed.time_usec);
+                    self.display_pattern_matches(timed.time_usec, *code);
+                    if let Some(Err(err)) = self.speaker.as_mut().map(|speaker| speaker.key_pressed(*code, &held, is_modifier)) {
+                        eprintln!("{}⚠️ {}{}", Colors::RED, err, Colors::RESET);
+                    }
+                    if !is_modifier {
+                        if let Some(mode) = self.stats.rhythm.record_press(timed.time_usec) {
+                            self.display_typing_mode(timed.time_usec, mode);
                         }
-                    } else {
-                        println!("{}⌨️  Other Keyboard Event{}", Colors::CYAN, Colors::RESET);
                     }
-                },
-                input::Event::Pointer(pointer_event) => {
-                    match pointer_event {
-                        input::event::PointerEvent::Motion(motion) => {
-                            // Update mouse state
-                            mouse_state.dx = motion.dx();
-                            mouse_state.dy = motion.dy();
-                            mouse_state.x += motion.dx();
-                            mouse_state.y += motion.dy();
-                            
-                            println!("{}🖱️  Mouse motion - Position: ({:.2}, {:.2}), Delta: ({:.2}, {:.2}){}",
-                                Colors::CYAN, 
-                                mouse_state.x, mouse_state.y, 
-                                mouse_state.dx, mouse_state.dy, 
-                                Colors::RESET);
-                        },
-                        input::event::PointerEvent::MotionAbsolute(abs_motion) => {
-                            // Update absolute mouse position
-                            mouse_state.x = abs_motion.absolute_x();
-                            mouse_state.y = abs_motion.absolute_y();
-                            
-                            println!("{}🖱️  Mouse absolute position: ({:.2}, {:.2}){}",
-                                Colors::CYAN, mouse_state.x, mouse_state.y, Colors::RESET);
-                        },
-                        input::event::PointerEvent::Button(button) => {
-                            // Extract button number from debug representation
-                            let debug_str = format!("{:?}", button);
-                            let mut description = String::from("🖱️  Mouse button");
-                            let mut button_num = 0;
-                            
-                            if debug_str.contains("button: ") {
-                                if let Some(btn_start) = debug_str.find("button: ") {
-                                    if let Some(btn_end) = debug_str[btn_start..].find(",") {
-                                        let btn_str = &debug_str[btn_start+8..btn_start+btn_end];
-                                        if let Ok(btn) = btn_str.parse::<u32>() {
-                                            button_num = btn;
-                                            // Map button numbers to common names
-                                            let button_name = match btn {
-                                                272 => "LEFT",
-                                                273 => "RIGHT",
-                                                274 => "MIDDLE",
-                                                275 => "SIDE",
-                                                276 => "EXTRA",
-                                                _ => btn_str,
-                                            };
-                                            description = format!("🖱️  Mouse button {} ({})", button_name, btn);
-                                        } else {
-                                            description = format!("🖱️  Mouse button {}", btn_str);
-                                        }
-                                    }
-                                }
-                            }
-                            
-                            if debug_str.contains("Pressed") {
-                                mouse_click_count += 1;
-                                description.push_str(" - PRESSED");
-                                println!("{}{} at position: ({:.2}, {:.2}){} (Total clicks: {})",
-                                    Colors::MAGENTA, description, 
-                                    mouse_state.x, mouse_state.y, 
-                                    Colors::RESET, mouse_click_count);
-                            } else if debug_str.contains("Released") {
-                                description.push_str(" - RELEASED");
-                                println!("{}{} at position: ({:.2}, {:.2}){}",
-                                    Colors::BLUE, description, 
-                                    mouse_state.x, mouse_state.y, 
-                                    Colors::RESET);
-                            }
-                        },
-                        input::event::PointerEvent::ScrollWheel(scroll) => {
-                            // Get scroll information using the correct Axis enum
-                            // Fix the error by checking if axis is set first
-                            let scroll_y = if scroll.has_axis(Axis::Vertical) {
-                                scroll.scroll_value_v120(Axis::Vertical)
-                            } else {
-                                0.0
-                            };
-                            
-                            let scroll_x = if scroll.has_axis(Axis::Horizontal) {
-                                scroll.scroll_value_v120(Axis::Horizontal)
-                            } else {
-                                0.0
-                            };
-                            
-                            println!("{}🖱️  Scroll wheel: horizontal: {:.2}, vertical: {:.2}{}",
-                                Colors::CYAN, scroll_x, scroll_y, Colors::RESET);
-                        },
-                        input::event::PointerEvent::ScrollFinger(_) => {
-                            println!("{}🖱️  Scroll finger event{}", Colors::CYAN, Colors::RESET);
-                        },
-                        input::event::PointerEvent::ScrollContinuous(_) => {
-                            println!("{}🖱️  Scroll continuous event{}", Colors::CYAN, Colors::RESET);
-                        },
-                        other => {
-                            // Show the event type to help diagnose
-                            let debug_str = format!("{:?}", other);
-                            // Extract the variant name
-                            if let Some(end) = debug_str.find('(') {
-                                let variant = &debug_str[..end];
-                                println!("{}🖱️  Pointer Event: {}{}", Colors::CYAN, variant, Colors::RESET);
-                            } else {
-                                println!("{}🖱️  Other Pointer Event: {:?}{}", Colors::CYAN, other, Colors::RESET);
-                            }
-                        },
+                } else {
+                    if let Some(speaker) = self.speaker.as_mut() {
+                        speaker.key_released(*code);
                     }
-                },
-                input::Event::Touch(touch_event) => {
-                    let debug_str = format!("{:?}", touch_event);
-                    if let Some(end) = debug_str.find('(') {
-                        let variant = &debug_str[..end];
-                        println!("{}👆 Touch Event: {}{}", Colors::MAGENTA, variant, Colors::RESET);
-                    } else {
-                        println!("{}👆 Touch Event: {:?}{}", Colors::MAGENTA, touch_event, Colors::RESET);
+                    emit!(self.out, Severity::Info, "{}⌨️  KEY RELEASE DETECTED --> {} <--{}{}",
+                        Colors::BLUE, key_text, self.code_suffix(*code, key_text), Colors::RESET);
+                    self.press_ended(PressSource::Key, *code, timed.time_usec);
+                }
+                if self.show_modifiers && self.modifiers != held {
+                    emit!(self.out, Severity::Info, "MODIFIERS: {}", self.modifiers.indicator());
+                }
+            },
+            InputEvent::PointerMotion { dx, dy } => {
+                // Update mouse state
+                self.mouse_state.dx = *dx;
+                self.mouse_state.dy = *dy;
+                self.mouse_state.x += dx;
+                self.mouse_state.y += dy;
+                self.stats.mouse_distance += (dx * dx + dy * dy).sqrt();
+                if let Some(export) = self.health_export.as_mut() {
+                    export.record_motion(*dx, *dy);
+                }
+                
+                let count = self.dedup.observe("motion", [*dx, *dy], timed.time_usec);
+                let line = format!("{}🖱️  Mouse motion - Position: ({:.p$}, {:.p$}), Delta: ({:.p$}, {:.p$}){}",
+                    Colors::CYAN, 
+                    self.mouse_state.x, self.mouse_state.y, 
+                    self.mouse_state.dx, self.mouse_state.dy, 
+                    Colors::RESET, p = self.precision);
+                self.out.emit_repeatable(Severity::Info, &line, count);
+                let constrained = self.pointer_constraint.as_mut().and_then(|constraint| constraint.motion(*dx, *dy));
+                self.display_constrained(constrained);
+                self.record_mouse_position();
+                self.follow_cursor();
+            },
+            InputEvent::PointerMotionAbsolute { x, y } => {
+                // Update absolute mouse position
+                self.mouse_state.x = *x;
+                self.mouse_state.y = *y;
+                
+                let count = self.dedup.observe("absolute", [*x, *y], timed.time_usec);
+                let line = format!("{}🖱️  Mouse absolute position: ({:.p$}, {:.p$}){}",
+                    Colors::CYAN, self.mouse_state.x, self.mouse_state.y, Colors::RESET, p = self.precision);
+                self.out.emit_repeatable(Severity::Info, &line, count);
+                let constrained = self.pointer_constraint.as_mut().and_then(|constraint| constraint.move_to(*x, *y));
+                self.display_constrained(constrained);
+                self.record_mouse_position();
+                self.follow_cursor();
+            },
+            InputEvent::PointerButton { button, pressed } => {
+                // BTN_LEFT -> LEFT, BTN_TRIGGER_HAPPY3 -> TRIGGER_HAPPY3
+                let name = button_name(*button);
+                let mut description = format!("🖱️  Mouse button {}{}",
+                    name.trim_start_matches("BTN_"), self.code_suffix(*button, name));
+                
+                if *pressed {
+                    if !self.mouse_state.press_button(*button) {
+                        self.report_lost_release(timed.time_usec, *button, "pressed_again");
                     }
-                },
-                input::Event::Gesture(gesture_event) => {
-                    let debug_str = format!("{:?}", gesture_event);
-                    if let Some(end) = debug_str.find('(') {
-                        let variant = &debug_str[..end];
-                        println!("{}🤲 Gesture Event: {}{}", Colors::MAGENTA, variant, Colors::RESET);
-                    } else {
-                        println!("{}🤲 Gesture Event: {:?}{}", Colors::MAGENTA, gesture_event, Colors::RESET);
+                    self.stats.mouse_clicks += 1;
+                    self.stats.clicks.record_press(*button, timed.time_usec);
+                    self.press_started(PressSource::Button, *button, timed.time_usec);
+                    if let Some(Err(err)) = self.speaker.as_mut().map(|speaker| speaker.button_pressed(*button)) {
+                        eprintln!("{}⚠️ {}{}", Colors::RED, err, Colors::RESET);
                     }
-                },
-                input::Event::Tablet(_) => println!("{}✏️ Tablet Event{}", Colors::YELLOW, Colors::RESET),
-                input::Event::Switch(_) => println!("{}🔄 Switch Event{}", Colors::YELLOW, Colors::RESET),
-                _ => println!("{}⚠️ Other Event{}", Colors::RED, Colors::RESET),
+                    if let Some(mouse_path) = self.mouse_path.as_mut() {
+                        mouse_path.record_click(self.mouse_state.x, self.mouse_state.y, *button);
+                    }
+                    description.push_str(" - PRESSED");
+                    emit!(self.out, Severity::Info, "{}{} at position: ({:.p$}, {:.p$}){} (Total clicks: {}){}",
+                        Colors::MAGENTA, description, 
+                        self.mouse_state.x, self.mouse_state.y, 
+                        Colors::RESET, self.stats.mouse_clicks, self.held_suffix(), p = self.precision);
+                } else {
+                    self.mouse_state.release_button(*button);
+                    description.push_str(" - RELEASED");
+                    emit!(self.out, Severity::Info, "{}{} at position: ({:.p$}, {:.p$}){}{}",
+                        Colors::BLUE, description, 
+                        self.mouse_state.x, self.mouse_state.y, 
+                        Colors::RESET, self.held_suffix(), p = self.precision);
+                    self.press_ended(PressSource::Button, *button, timed.time_usec);
+                }
+            },
+            InputEvent::Scroll { source, horizontal, vertical } => {
+                if let Some(export) = self.health_export.as_mut() {
+                    export.record_scroll();
+                }
+                if let Some(gesture) = self.scroll_gestures.push(timed.time_usec, *source, *horizontal, *vertical) {
+                    self.display_scroll_gesture(&gesture);
+                }
+                // Individual events only on request; gestures are easier to read
+                if self.raw_scroll {
+                    self.display_raw_scroll(timed.time_usec, *source, *horizontal, *vertical);
+                }
+            },
+            InputEvent::Touch { kind, slot, x, y } => {
+                let detail = match (slot, x, y) {
+                    (Some(slot), Some(x), Some(y)) => format!(" (slot {} at {:.1}, {:.1} mm)", slot, x, y),
+                    (Some(slot), _, _) => format!(" (slot {})", slot),
+                    _ => String::new(),
+                };
+                emit!(self.out, Severity::Info, "{}👆 Touch Event: {}{}{}", Colors::MAGENTA, kind, detail, Colors::RESET);
+
+                match (kind.as_str(), slot, x, y) {
+                    ("Down", Some(slot), Some(x), Some(y)) => {
+                        self.touches.down(*slot, *x, *y);
+                        self.press_started(PressSource::Touch, *slot, timed.time_usec);
+                    },
+                    ("Motion", Some(slot), Some(x), Some(y)) => self.touches.motion(*slot, *x, *y),
+                    ("Up", Some(slot), _, _) => {
+                        self.touches.up(*slot);
+                        self.press_ended(PressSource::Touch, *slot, timed.time_usec);
+                    },
+                    ("Cancel", _, _, _) => {
+                        self.touches.cancel();
+                        if let Some(presses) = self.presses.as_mut() {
+                            presses.cancel_touches();
+                        }
+                    },
+                    ("Frame", _, _, _) => {
+                        if let Some(gesture) = self.touches.frame(timed.time_usec) {
+                            self.display_touch_gesture(&gesture);
+                        }
+                    },
+                    _ => {},
+                }
+            },
+            InputEvent::Gesture { kind, finger_count, dx, dy, scale, angle, cancelled } => {
+                let mut details = Vec::new();
+                if let Some(fingers) = finger_count {
+                    details.push(format!("{} fingers", fingers));
+                }
+                if let (Some(dx), Some(dy)) = (dx, dy) {
+                    details.push(format!("dx {:.p$}, dy {:.p$}", dx, dy, p = self.precision));
+                }
+                if let Some(scale) = scale {
+                    details.push(format!("scale {:.2}", scale));
+                }
+                if let Some(angle) = angle {
+                    details.push(format!("angle {:+.1}°", angle));
+                }
+                if *cancelled == Some(true) {
+                    details.push("cancelled".to_string());
+                }
+                let details = if details.is_empty() { String::new() } else { format!(" ({})", details.join(", ")) };
+                let mapped = match kind.ends_with("End").then(|| self.gesture_matches.pop_front()).flatten().flatten() {
+                    Some(GestureMatch { gesture, action: Some(action) }) => format!(" → {}: {}{}{}", gesture, Colors::BOLD, action, Colors::RESET),
+                    Some(GestureMatch { gesture, action: None }) => format!(" → {} (no action)", gesture),
+                    None => String::new(),
+                };
+                emit!(self.out, Severity::Info, "{}🤲 Gesture Event: {}{}{}{}", Colors::MAGENTA, kind, details, mapped, Colors::RESET);
+            },
+            InputEvent::TouchpadFingerCountChange { previous, count } => {
+                emit!(self.out, Severity::Info, "{}👆 Touchpad fingers: {} → {}{}", Colors::MAGENTA, previous, count, Colors::RESET);
+                if let Some(status_line) = self.status_line.as_mut() {
+                    status_line.set_fingers(*count);
+                }
+            },
+            InputEvent::Tablet => emit!(self.out, Severity::Info, "{}✏️ Tablet Event{}", Colors::YELLOW, Colors::RESET),
+            InputEvent::PadButton { button, pressed, mode, group } => {
+                let (color, state) = if *pressed { (Colors::MAGENTA, "pressed") } else { (Colors::BLUE, "released") };
+                emit!(self.out, Severity::Info, "{}🎛️ Pad button {} {} (mode {}, group {}){}",
+                    color, button, state, mode, group, Colors::RESET);
+            },
+            InputEvent::PadRing { number, position, mode } => {
+                emit!(self.out, Severity::Info, "{}🎛️ {} (mode {}){}",
+                    Colors::CYAN, tablet_pad::ring_gauge(*number, *position), mode, Colors::RESET);
+            },
+            InputEvent::PadStrip { number, position, mode } => {
+                emit!(self.out, Severity::Info, "{}🎛️ {} (mode {}){}",
+                    Colors::CYAN, tablet_pad::strip_gauge(*number, *position), mode, Colors::RESET);
+            },
+            InputEvent::Switch => emit!(self.out, Severity::Info, "{}🔄 Switch Event{}", Colors::YELLOW, Colors::RESET),
+            InputEvent::Other { source, kind, debug } => {
+                match source.as_str() {
+                    "device" => emit!(self.out, Severity::Info, "{}📱 Other Device Event{}", Colors::BLUE, Colors::RESET),
+                    "keyboard" => emit!(self.out, Severity::Info, "{}⌨️  Other Keyboard Event{}", Colors::CYAN, Colors::RESET),
+                    "pointer" => emit!(self.out, Severity::Info, "{}🖱️  Pointer Event: {}{}", Colors::CYAN, kind, Colors::RESET),
+                    _ => emit!(self.out, Severity::Warning, "{}⚠️ Other Event{}", Colors::RED, Colors::RESET),
+                }
+                if let Some(debug) = debug {
+                    emit!(self.out, Severity::Info, "   {}", debug);
+                }
+            },
+        }
+    }
+}
+
+fn main() {
+    let options = match cli::command_from_args() {
+        Ok(Command::Monitor(options)) => *options,
+        Ok(Command::Diff(diff_options)) => process::exit(diff::run(&diff_options)),
+        Ok(Command::Calibrate(calibrate_options)) => process::exit(calibrate::run(&calibrate_options)),
+        Ok(Command::ConfigureDevice(configure_options)) => process::exit(configure::run(&configure_options)),
+        Ok(Command::Decode(decode_options)) => process::exit(binary::run_decode(&decode_options)),
+        Ok(Command::Query(query_options)) => process::exit(sqlite_log::run_query(&query_options)),
+        Ok(Command::Export(export_options)) => process::exit(export::run(&export_options)),
+        Ok(Command::RecordMacro(macro_options)) => process::exit(keyboard_macro::run(&macro_options)),
+        Ok(Command::ShowRepeatConfig) => process::exit(key_repeat::run_show()),
+        Ok(Command::SetRepeat(repeat_options)) => process::exit(key_repeat::run_set(&repeat_options)),
+        Err(err) => {
+            eprintln!("{}error: {}{} (see --help)", Colors::RED, err, Colors::RESET);
+            process::exit(2);
+        }
+    };
+
+    process::exit(run_monitor(options));
+}
+
+// --kbd-view updates go straight to the terminal, unbuffered
+fn draw_kbd_view(drawing: &str) {
+    if !drawing.is_empty() {
+        output::write_stdout(drawing);
+        output::flush_stdout();
+    }
+}
+
+// ": name" for device lines; binary recordings don't keep device names
+fn device_label(device: &str) -> String {
+    if device.is_empty() { String::new() } else { format!(": {}", device) }
+}
+
+fn warn_no_devices(out: &mut Output) {
+    emit!(out, Severity::Warning, "{}⚠️ No input devices found on seat '{}'{}", Colors::YELLOW, SEAT, Colors::RESET);
+    emit!(out, Severity::Warning, "   Pick devices with --device-path /dev/input/eventN, or check access with --probe-permissions.");
+}
+
+// Block until `fd` is readable or `timeout_ms` passes; true if readable
+fn wait_readable(fd: RawFd, timeout_ms: i32) -> bool {
+    let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+    unsafe { libc::poll(&mut pollfd, 1, timeout_ms) > 0 }
+}
+
+// Path backend: add each device on its own, so one bad path doesn't stop the
+// rest. Returns the context and a "Watching <path>: <name> (<sysname>)" line
+// per device; the kernel name tells identical models apart.
+fn open_device_paths(paths: &[PathBuf], grab: bool, log_priority: LogPriority, mut sanitizer: Option<&mut Sanitizer>) -> (Libinput, Vec<String>) {
+    let mut input = if grab { Libinput::new_from_path(GrabbingInterface) } else { Libinput::new_from_path(Interface) };
+    libinput_log::install(&input, log_priority);
+    let mut watched = Vec::new();
+
+    for path in paths {
+        let device = path.to_str().and_then(|path_str| input.path_add_device(path_str));
+        match device {
+            Some(device) => {
+                let name = match sanitizer.as_deref_mut() {
+                    Some(sanitizer) => sanitizer.alias(device.name()),
+                    None => device.name().to_string(),
+                };
+                watched.push(format!("Watching {}: {} ({})", path.display(), name, device.sysname()));
+            },
+            None if grab => eprintln!("{}⚠️ Cannot monitor {}: not an input device, not readable or not grabbed{}",
+                Colors::RED, path.display(), Colors::RESET),
+            None => eprintln!("{}⚠️ Cannot monitor {}: not an input device, or not readable{}",
+                Colors::RED, path.display(), Colors::RESET),
+        }
+    }
+
+    if watched.is_empty() {
+        eprintln!("{}error: none of the --device-path devices could be opened{}", Colors::RED, Colors::RESET);
+        process::exit(1);
+    }
+    (input, watched)
+}
+
+// Where the live loop's events come from: one libinput context, or a
+// context per seat with --all-seats
+enum EventSource {
+    Libinput(Libinput),
+    AllSeats(MultiSeatMonitor),
+}
+
+impl EventSource {
+    // Block until there are events or `timeout_ms` passes; true if there are
+    fn wait(&mut self, timeout_ms: i32) -> bool {
+        match self {
+            EventSource::Libinput(input) => wait_readable(input.as_raw_fd(), timeout_ms),
+            EventSource::AllSeats(seats) => seats.wait(timeout_ms),
+        }
+    }
+
+    // The events that came in since the last call, each with the finger
+    // count change it caused. Pointer acceleration is observed on the way,
+    // while the libinput event is still at hand.
+    fn read(&mut self, monitor: &mut Monitor, touchpad: &mut TouchpadMonitor) -> Vec<(TimedEvent, Option<TimedEvent>)> {
+        match self {
+            EventSource::Libinput(input) => {
+                input.dispatch().unwrap();
+                let mut events = Vec::new();
+                for event in &mut *input {
+                    let mut timed = event::decode(&event, event::monotonic_usec());
+                    monitor.observe_accel(&event);
+                    let finger_change = touchpad.observe(&event, &mut timed);
+                    events.push((timed, finger_change));
+                }
+                events
+            },
+            // The seat threads decode touchpad fingers themselves
+            EventSource::AllSeats(seats) => seats.drain().into_iter().map(|timed| (timed, None)).collect(),
+        }
+    }
+}
+
+// Returns the process exit code
+fn run_monitor(mut options: Options) -> i32 {
+    output::set_emoji(options.emoji.unwrap_or_else(output::emoji_supported));
+    let no_color = options.no_color || env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    theme::set(options.color_theme, no_color);
+    event::set_debug_unknown(options.debug_unknown);
+    let mut aliases = match &options.alias_file {
+        Some(path) => keys::load_aliases(path).unwrap_or_else(|err| {
+            eprintln!("{}error: cannot load {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+            process::exit(1);
+        }),
+        None => Vec::new(),
+    };
+    aliases.extend(options.key_aliases.iter().cloned());
+    keys::set_aliases(aliases);
+    if options.xkb {
+        if let Err(err) = xkb_layout::init() {
+            eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+            process::exit(1);
+        }
+    }
+    output::set_flush_policy(options.flush);
+
+    if options.probe_permissions {
+        let readable = doctor::report(&doctor::probe());
+        process::exit(if readable { 0 } else { 1 });
+    }
+    if options.selftest {
+        process::exit(selftest::run());
+    }
+    if let Some(path) = &options.replay_into_uinput {
+        process::exit(uinput_replay::run(path));
+    }
+    // Without --screen, absolute positions stay in millimeters unless the
+    // resolution can be found out
+    if options.screen.is_none() {
+        options.screen = screen_size::detect();
+    }
+    event::set_screen(options.screen);
+
+    // Catch the most common first-run failure before libinput hits it,
+    // while stderr is still attached to the terminal. Explicit
+    // --device-path devices are checked one by one instead.
+    if !options.benchmark && options.replay.is_none() && options.simulate.is_none() && options.device_paths.is_empty() {
+        let probe = doctor::probe();
+        if let doctor::Probe::Denied { .. } = probe {
+            doctor::report(&probe);
+            process::exit(1);
+        }
+    }
+
+    if let Some(template) = &options.output_template {
+        let unknown = template.unknown_fields();
+        if !unknown.is_empty() {
+            eprintln!("{}⚠️ Unknown field{} in --output-template, shown as {{?field?}}: {}{}",
+                Colors::YELLOW, if unknown.len() == 1 { "" } else { "s" }, unknown.join(", "), Colors::RESET);
+        }
+    }
+
+    let mut monitor = Monitor::new(&options);
+
+    // Detach once every output file is open, so relative paths still work.
+    // Dropping the PID file on return removes it again.
+    let _pid_file = if options.daemon {
+        daemon::daemonize(options.pid_file.as_deref()).unwrap_or_else(|err| {
+            eprintln!("{}error: cannot daemonize: {}{}", Colors::RED, err, Colors::RESET);
+            process::exit(1);
+        })
+    } else {
+        None
+    };
+
+    // Threads don't survive the fork, so sampling starts only now
+    if options.correlate_window {
+        monitor.window_sampler = Some(WindowSampler::start(options.window_sample.unwrap_or(DEFAULT_WINDOW_SAMPLE)));
+    }
+    if options.measure_latency {
+        monitor.latency = Some(LatencyMeasurer::new(event::monotonic_usec()).unwrap_or_else(|err| {
+            eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+            process::exit(1);
+        }));
+    }
+
+    // Let Ctrl+C / SIGTERM fall out of the loop so exporters can flush
+    signals::install_exit_handlers();
+    signals::install_history_handler();
+    if options.summary_out.is_some() {
+        signals::install_summary_handler();
+    }
+
+    if options.benchmark {
+        bench::run(&mut monitor, options.benchmark_duration);
+        monitor.finish();
+        return 0;
+    }
+
+    if let Some(path) = &options.simulate {
+        let scenario = ScenarioRunner::load(path).unwrap_or_else(|err| {
+            eprintln!("{}error: cannot load scenario {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+            process::exit(1);
+        });
+        scenario.run(&mut monitor);
+        monitor.finish();
+        if let Some(code) = monitor.one_shot_exit_code() {
+            return code;
+        }
+        monitor.display_summary();
+        return 0;
+    }
+
+    if let Some(path) = &options.replay {
+        let replayed = if options.interactive {
+            replay::run_interactive(&mut monitor, path)
+        } else {
+            replay::run(&mut monitor, path)
+        };
+        if let Err(err) = replayed {
+            eprintln!("{}error: cannot replay {}: {}{}", Colors::RED, path.display(), err, Colors::RESET);
+            process::exit(1);
+        }
+        monitor.finish();
+        if let Some(code) = monitor.one_shot_exit_code() {
+            return code;
+        }
+        // Stepping back counts events twice, so the totals would mislead
+        if !options.interactive {
+            monitor.display_summary();
+        }
+        return 0;
+    }
+
+    // Initialize libinput: every device on the seat (or on every seat), or
+    // just the given paths
+    let log_priority = LogPriority::from_verbosity(options.verbosity);
+    let (mut source, watched) = if options.all_seats {
+        let seats = MultiSeatMonitor::start(log_priority).unwrap_or_else(|err| {
+            eprintln!("{}error: cannot enumerate seats: {}{}", Colors::RED, err, Colors::RESET);
+            process::exit(1);
+        });
+        let watched = vec![format!("Watching seats: {}", seats.seats().join(", "))];
+        monitor.seat_counts = Some(seats.seats().iter().map(|seat| (seat.clone(), 0)).collect());
+        (EventSource::AllSeats(seats), watched)
+    } else if options.device_paths.is_empty() {
+        let mut input = Libinput::new_with_udev(Interface);
+        libinput_log::install(&input, log_priority);
+        input.udev_assign_seat(SEAT).unwrap();
+        (EventSource::Libinput(input), Vec::new())
+    } else {
+        let (input, watched) = open_device_paths(&options.device_paths, options.grab, log_priority, monitor.sanitizer.as_mut());
+        (EventSource::Libinput(input), watched)
+    };
+    
+    // Show our fancy welcome message, but only to a person: not into files,
+    // pipes or machine-readable output
+    if !options.no_banner && !options.one_shot && monitor.out.is_terminal() && io::stdout().is_terminal() {
+        display_welcome_message(options.banner_text.as_deref());
+    }
+    // Only where the colors are actually shown
+    if options.legend && !options.one_shot && !theme::no_color() && monitor.out.is_terminal() && io::stdout().is_terminal() {
+        display_legend(&mut monitor.out);
+    }
+    for line in watched.iter().filter(|_| !options.one_shot) {
+        emit!(monitor.out, Severity::Notice, "{}📱 {}{}", Colors::CYAN, line, Colors::RESET);
+    }
+    if options.grab {
+        emit!(monitor.out, Severity::Warning, "{}⚠️ Grabbed: nothing else sees input from these devices. Ctrl+C on a grabbed keyboard exits{}",
+            Colors::RED, Colors::RESET);
+    }
+    if let Some(view) = &monitor.kbd_view {
+        draw_kbd_view(&view.draw());
+    }
+    
+    // Each dispatch() batch is displayed in priority order
+    let mut queue = PriorityEventQueue::new(options.event_priorities);
+    // Finger counts of touchpads that expose their touch slots
+    let mut touchpad = TouchpadMonitor::default();
+
+    // udev adds every device as soon as the seat is assigned; if none show up
+    // (VMs, containers), say so instead of waiting silently
+    let started_usec = event::monotonic_usec();
+    let mut devices_found = !options.device_paths.is_empty();
+    let mut empty_warned = false;
+    let mut exit_code = 0;
+
+    // Main event loop
+    while !signals::exit_requested() {
+        // While idle, don't dispatch: sleep on the libinput fd in long steps
+        // and resume as soon as it has something to read
+        if monitor.is_idle() {
+            if !source.wait(IDLE_POLL_MS) {
+                monitor.tick();
+                continue;
             }
+            monitor.resume_from_idle(event::monotonic_usec());
+        }
+
+        monitor.dump_raw_events();
+        let events = source.read(&mut monitor, &mut touchpad);
+        let dispatched = Instant::now();
+        
+        devices_found |= monitor.ingest(events, &mut queue);
+
+        for timed in queue.drain_sorted() {
+            monitor.display_event(&timed);
+            monitor.processing_latency.record(dispatched.elapsed());
+        }
+
+        monitor.tick();
+        if monitor.limit_reached() {
+            break;
+        }
+        if monitor.one_shot_timed_out() {
+            exit_code = 1;
+            break;
+        }
+        monitor.pause_if_idle(event::monotonic_usec());
+
+        if !devices_found && !empty_warned && event::monotonic_usec() - started_usec >= DEVICE_WAIT_USEC {
+            warn_no_devices(&mut monitor.out);
+            if options.exit_if_empty {
+                exit_code = 1;
+                break;
+            }
+            empty_warned = true;
         }
         
         sleep(Duration::from_millis(5)); // Small sleep to reduce CPU usage
     }
+
+    monitor.finish();
+    if let Some(code) = monitor.one_shot_exit_code() {
+        return code.max(exit_code);
+    }
+    monitor.display_summary();
+    exit_code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn live(args: &[&str]) -> (Monitor, PriorityEventQueue) {
+        let options = Options::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+        (Monitor::new(&options), PriorityEventQueue::new(options.event_priorities))
+    }
+
+    fn key(code: u32, pressed: bool) -> (TimedEvent, Option<TimedEvent>) {
+        at(1_000_000, InputEvent::Key { code, pressed }, None)
+    }
+
+    fn at(time_usec: u64, event: InputEvent, sysname: Option<&str>) -> (TimedEvent, Option<TimedEvent>) {
+        (TimedEvent { time_usec, event, fingers: None, sysname: sysname.map(str::to_string), seat: None }, None)
+    }
+
+    fn queued(queue: &mut PriorityEventQueue) -> Vec<InputEvent> {
+        queue.drain_sorted().map(|timed| timed.event).collect()
+    }
+
+    #[test]
+    fn ctrl_c_on_a_grabbed_keyboard_exits() {
+        let (mut monitor, mut queue) = live(&["--device-path", "/dev/input/event0", "--grab", "--confirm-grab"]);
+        monitor.ingest(vec![key(46, true), key(46, false), key(29, true)], &mut queue);
+        assert!(!signals::exit_requested());
+        monitor.ingest(vec![key(46, true)], &mut queue);
+        assert!(signals::exit_requested());
+    }
+
+    #[test]
+    fn live_events_are_filtered_by_their_real_device_name() {
+        let config = std::env::temp_dir().join(format!("rustkey-live-filters-{}.toml", process::id()));
+        std::fs::write(&config, "types = [\"keyboard\"]\n[device_filters.\"mouse\"]\ntypes = [\"motion\"]\n").unwrap();
+        let (mut monitor, mut queue) = live(&["--filter-config", config.to_str().unwrap(), "--sanitize"]);
+        std::fs::remove_file(&config).unwrap();
+
+        let added = |name: &str| InputEvent::DeviceAdded { device: name.to_string(), info: None };
+        let motion = InputEvent::PointerMotion { dx: 1.0, dy: 0.0 };
+        monitor.ingest(vec![
+            at(1_000_000, added("Test Mouse"), Some("event4")),
+            at(1_000_000, added("Test Keyboard"), Some("event3")),
+            at(1_100_000, motion.clone(), Some("event4")),
+            at(1_200_000, motion, Some("event3")),
+            at(1_300_000, InputEvent::PointerButton { button: 272, pressed: true }, Some("event4")),
+        ], &mut queue);
+        let kept: Vec<&str> = queued(&mut queue).iter().map(|event| match event {
+            InputEvent::DeviceAdded { .. } => "added",
+            InputEvent::PointerMotion { .. } => "motion",
+            _ => "other",
+        }).collect();
+        assert_eq!(kept, ["added", "added", "motion"]);
+    }
+
+    #[test]
+    fn live_warmup_keeps_early_events_out_of_the_stats() {
+        let (mut monitor, mut queue) = live(&["--warmup", "2"]);
+        let press = |time_usec| at(time_usec, InputEvent::Key { code: 30, pressed: true }, None);
+        monitor.ingest(vec![
+            at(1_000_000, InputEvent::DeviceAdded { device: "Test Keyboard".to_string(), info: None }, None),
+            press(1_500_000),
+            press(2_500_000),
+            press(3_500_000),
+        ], &mut queue);
+        assert_eq!(queued(&mut queue).len(), 2);
+        assert_eq!(monitor.events_seen, 2);
+        assert_eq!(monitor.warmup.as_ref().unwrap().discarded(), 2);
+    }
 }
@@ -0,0 +1,95 @@
+use crate::Colors;
+
+// Black on bright green, for held modifiers in the indicator
+const HELD_STYLE: &str = "\x1b[30;102m";
+
+// Modifier keys, with left and right tracked separately so that releasing one
+// side doesn't drop a modifier that's still held on the other
+const LEFT_CTRL: u8 = 1 << 0;
+const RIGHT_CTRL: u8 = 1 << 1;
+const LEFT_SHIFT: u8 = 1 << 2;
+const RIGHT_SHIFT: u8 = 1 << 3;
+const LEFT_ALT: u8 = 1 << 4;
+const RIGHT_ALT: u8 = 1 << 5;
+const LEFT_SUPER: u8 = 1 << 6;
+const RIGHT_SUPER: u8 = 1 << 7;
+
+// Combo labels in display order, each covering both sides
+const COLLAPSED: [(u8, &str); 4] = [
+    (LEFT_CTRL | RIGHT_CTRL, "CTRL"),
+    (LEFT_SHIFT | RIGHT_SHIFT, "SHIFT"),
+    (LEFT_ALT | RIGHT_ALT, "ALT"),
+    (LEFT_SUPER | RIGHT_SUPER, "SUPER"),
+];
+
+fn modifier_bit(code: u32) -> Option<u8> {
+    match code {
+        29 => Some(LEFT_CTRL),
+        97 => Some(RIGHT_CTRL),
+        42 => Some(LEFT_SHIFT),
+        54 => Some(RIGHT_SHIFT),
+        56 => Some(LEFT_ALT),
+        100 => Some(RIGHT_ALT),
+        125 => Some(LEFT_SUPER),
+        126 => Some(RIGHT_SUPER),
+        _ => None,
+    }
+}
+
+pub fn is_modifier_key(code: u32) -> bool {
+    modifier_bit(code).is_some()
+}
+
+// Bitset of the modifier keys currently held down
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Modifiers {
+    held: u8,
+}
+
+impl Modifiers {
+    // Returns true if the key was a modifier
+    pub fn update(&mut self, code: u32, pressed: bool) -> bool {
+        match modifier_bit(code) {
+            Some(bit) if pressed => self.held |= bit,
+            Some(bit) => self.held &= !bit,
+            None => return false,
+        }
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.held == 0
+    }
+
+    pub fn ctrl_held(&self) -> bool {
+        self.held & (LEFT_CTRL | RIGHT_CTRL) != 0
+    }
+
+    // Ctrl, Alt or Super held. Shift only changes what a key types, and so
+    // does right Alt, which is AltGr on most non-US layouts.
+    pub fn shortcut_held(&self) -> bool {
+        self.held & (LEFT_CTRL | RIGHT_CTRL | LEFT_ALT | LEFT_SUPER | RIGHT_SUPER) != 0
+    }
+
+    // Held modifiers with left/right collapsed, e.g. "CTRL+SHIFT"
+    pub fn label(&self) -> String {
+        COLLAPSED.iter()
+            .filter(|(mask, _)| self.held & mask != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    // All four modifiers, held ones highlighted and in brackets (so they
+    // stand out without color too), e.g. "[CTRL] shift alt super"
+    pub fn indicator(&self) -> String {
+        COLLAPSED.iter()
+            .map(|(mask, name)| if self.held & mask != 0 {
+                format!("{}{}[{}]{}", HELD_STYLE, Colors::BOLD, name, Colors::RESET)
+            } else {
+                format!("{}{}{}", Colors::GRAY, name.to_lowercase(), Colors::RESET)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::geometry::simplify_path;
+
+pub const DEFAULT_SIMPLIFY_EPSILON: f64 = 1.0;
+// Upper bound on buffered trajectory points; beyond it the path is
+// simplified early, with a tolerance that doubles until there is room
+const MAX_POINTS: usize = 1_000_000;
+// The trajectory is drawn as this many polylines, each one colored by time
+const COLOR_SEGMENTS: usize = 64;
+// Blank border around the drawing, in pixels
+const MARGIN: f64 = 20.0;
+
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+
+type Point = (f64, f64);
+
+// Collects the pointer trajectory and clicks of a session and writes them
+// out as an SVG drawing on exit (--record-mouse-path). The trajectory is
+// simplified with --path-simplify-epsilon just before it's drawn.
+pub struct MousePathRecorder {
+    output: PathBuf,
+    width: f64,
+    height: f64,
+    points: Vec<Point>,
+    clicks: Vec<(Point, u32)>,
+    epsilon: f64,
+    // Positions recorded, before any simplification
+    recorded: usize,
+}
+
+impl MousePathRecorder {
+    pub fn new(output: &Path, (width, height): (u32, u32), epsilon: f64) -> Self {
+        MousePathRecorder {
+            output: output.to_path_buf(),
+            width: width as f64,
+            height: height as f64,
+            points: Vec::new(),
+            clicks: Vec::new(),
+            epsilon,
+            recorded: 0,
+        }
+    }
+
+    pub fn record_position(&mut self, x: f64, y: f64) {
+        self.points.push((x, y));
+        self.recorded += 1;
+        if self.points.len() >= MAX_POINTS {
+            let mut epsilon = self.epsilon.max(0.5);
+            while self.points.len() > MAX_POINTS / 2 {
+                self.points = simplify_path(&self.points, epsilon);
+                epsilon *= 2.0;
+            }
+        }
+    }
+
+    pub fn record_click(&mut self, x: f64, y: f64, button: u32) {
+        self.clicks.push(((x, y), button));
+    }
+
+    // Returns the number of positions recorded and drawn
+    pub fn finish(mut self) -> io::Result<(usize, usize)> {
+        self.points = simplify_path(&self.points, self.epsilon);
+        fs::write(&self.output, self.to_svg())?;
+        Ok((self.recorded, self.points.len()))
+    }
+
+    fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width, h = self.height);
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+        let project = self.projection();
+
+        // Consecutive chunks share their end point so the line stays continuous
+        let chunk_len = self.points.len().div_ceil(COLOR_SEGMENTS).max(1);
+        let chunks = self.points.len().div_ceil(chunk_len);
+        for index in 0..chunks {
+            let start = index * chunk_len;
+            let end = ((index + 1) * chunk_len + 1).min(self.points.len());
+            if end - start < 2 {
+                continue;
+            }
+            // Early = blue (hue 240), late = red (hue 0)
+            let progress = if chunks > 1 { index as f64 / (chunks - 1) as f64 } else { 0.0 };
+            let hue = 240.0 * (1.0 - progress);
+
+            let _ = write!(svg, r#"<polyline fill="none" stroke="hsl({:.0}, 90%, 45%)" stroke-width="2" points=""#, hue);
+            for point in &self.points[start..end] {
+                let (x, y) = project(*point);
+                let _ = write!(svg, "{:.1},{:.1} ", x, y);
+            }
+            let _ = writeln!(svg, r#""/>"#);
+        }
+
+        for (point, button) in &self.clicks {
+            let (x, y) = project(*point);
+            let color = match *button {
+                BTN_LEFT => "green",
+                BTN_RIGHT => "red",
+                BTN_MIDDLE => "blue",
+                _ => "gray",
+            };
+            let _ = writeln!(svg, r#"<circle cx="{:.1}" cy="{:.1}" r="6" fill="{}" fill-opacity="0.7"/>"#, x, y, color);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // Fit everything that was recorded into the screen, keeping the aspect ratio
+    fn projection(&self) -> impl Fn(Point) -> (f64, f64) {
+        let all = self.points.iter().chain(self.clicks.iter().map(|(point, _)| point));
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for &(x, y) in all {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        if min_x > max_x {
+            (min_x, min_y, max_x, max_y) = (0.0, 0.0, 0.0, 0.0);
+        }
+
+        let span_x = (max_x - min_x).max(1.0);
+        let span_y = (max_y - min_y).max(1.0);
+        let scale = ((self.width - 2.0 * MARGIN) / span_x).min((self.height - 2.0 * MARGIN) / span_y);
+        move |(x, y)| (MARGIN + (x - min_x) * scale, MARGIN + (y - min_y) * scale)
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::event::JsonEvent;
+use crate::output::{self, FlushPolicy};
+
+// Layout of `--format msgpack`: a stream of frames, each
+//
+//   u32 length (big-endian), then that many bytes of MessagePack
+//
+// holding one event as a map with the same keys and values as its
+// `--format json` line. No header, so frames can be read as they arrive.
+pub const LENGTH_SIZE: usize = 4;
+
+pub struct MsgpackWriter {
+    writer: BufWriter<Box<dyn Write>>,
+    // Flush after every frame unless --flush asked for block buffering
+    flush_each: bool,
+}
+
+impl MsgpackWriter {
+    pub fn to_file(path: &std::path::Path) -> io::Result<Self> {
+        Ok(Self::start(Box::new(File::create(path)?)))
+    }
+
+    pub fn to_stdout() -> Self {
+        Self::start(Box::new(io::stdout()))
+    }
+
+    fn start(writer: Box<dyn Write>) -> Self {
+        let flush_each = output::flush_policy(false) != FlushPolicy::Block;
+        MsgpackWriter { writer: BufWriter::new(writer), flush_each }
+    }
+
+    pub fn write(&mut self, event: &JsonEvent) -> io::Result<()> {
+        self.writer.write_all(&encode_frame(event)?)?;
+        if self.flush_each {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn encode_frame(event: &JsonEvent) -> io::Result<Vec<u8>> {
+    // Named fields, so readers see a map like the JSON object
+    let body = rmp_serde::to_vec_named(event).map_err(io::Error::other)?;
+    let length = u32::try_from(body.len()).map_err(io::Error::other)?;
+    let mut frame = Vec::with_capacity(LENGTH_SIZE + body.len());
+    frame.extend_from_slice(&length.to_be_bytes());
+    frame.extend_from_slice(&body);
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::InputEvent;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn frames_hold_the_json_fields() {
+        let event = InputEvent::Key { code: 30, pressed: true };
+        let line = JsonEvent { time_usec: 1_000_000, delta_usec: 250, elapsed_usec: 1_000, event: &event, fingers: None, seat: None, window: None, mapped_action: None };
+        let frame = encode_frame(&line).unwrap();
+
+        let length = u32::from_be_bytes(frame[..LENGTH_SIZE].try_into().unwrap()) as usize;
+        assert_eq!(length, frame.len() - LENGTH_SIZE);
+        let decoded: Value = rmp_serde::from_slice(&frame[LENGTH_SIZE..]).unwrap();
+        assert_eq!(decoded, serde_json::to_value(&line).unwrap());
+        assert_eq!(decoded["type"], json!("key"));
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use input::Libinput;
+
+use crate::event::{self, TimedEvent};
+use crate::libinput_log::{self, LogPriority};
+use crate::touchpad::TouchpadMonitor;
+use crate::{signals, wait_readable, Colors, Interface, SEAT};
+
+// How long a seat thread sleeps on its libinput fd before checking for exit
+const SEAT_POLL_MS: i32 = 100;
+
+// --all-seats: one libinput context per udev seat, each dispatched on a
+// thread of its own (a context can't leave the thread that created it), with
+// the decoded events merged onto one channel tagged with their seat.
+// Touchpad finger count changes are decoded next to the events they come
+// from and arrive as events of their own.
+pub struct MultiSeatMonitor {
+    seats: Vec<String>,
+    receiver: Receiver<(String, TimedEvent)>,
+    // Received by wait() and not handed out yet
+    pending: VecDeque<(String, TimedEvent)>,
+}
+
+impl MultiSeatMonitor {
+    pub fn start(log_priority: LogPriority) -> io::Result<Self> {
+        let seats = list_seats()?;
+        let (sender, receiver) = mpsc::channel();
+        for seat in &seats {
+            let (seat, sender) = (seat.clone(), sender.clone());
+            thread::Builder::new()
+                .name(format!("seat-{}", seat))
+                .spawn(move || run_seat(&seat, log_priority, &sender))?;
+        }
+        Ok(MultiSeatMonitor { seats, receiver, pending: VecDeque::new() })
+    }
+
+    pub fn seats(&self) -> &[String] {
+        &self.seats
+    }
+
+    // Block until an event arrives or `timeout_ms` passes; true if one did
+    pub fn wait(&mut self, timeout_ms: i32) -> bool {
+        if !self.pending.is_empty() {
+            return true;
+        }
+        match self.receiver.recv_timeout(Duration::from_millis(timeout_ms.max(0) as u64)) {
+            Ok(received) => {
+                self.pending.push_back(received);
+                true
+            },
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => false,
+        }
+    }
+
+    // Everything received so far, with the seat filled in
+    pub fn drain(&mut self) -> Vec<TimedEvent> {
+        let received: Vec<_> = self.pending.drain(..).chain(self.receiver.try_iter()).collect();
+        received.into_iter().map(|(seat, mut timed)| {
+            timed.seat = Some(seat);
+            timed
+        }).collect()
+    }
+}
+
+// Seats are named by the ID_SEAT property of the devices udev tags "seat";
+// devices without one belong to seat0
+fn list_seats() -> io::Result<Vec<String>> {
+    let mut enumerator = udev::Enumerator::new()?;
+    enumerator.match_tag("seat")?;
+    let mut seats = BTreeSet::from([SEAT.to_string()]);
+    for device in enumerator.scan_devices()? {
+        if let Some(seat) = device.property_value("ID_SEAT").and_then(|seat| seat.to_str()) {
+            seats.insert(seat.to_string());
+        }
+    }
+    Ok(seats.into_iter().collect())
+}
+
+// Runs until exit is requested or the monitor stops listening
+fn run_seat(seat: &str, log_priority: LogPriority, sender: &Sender<(String, TimedEvent)>) {
+    let mut input = Libinput::new_with_udev(Interface);
+    libinput_log::install(&input, log_priority);
+    if input.udev_assign_seat(seat).is_err() {
+        eprintln!("{}⚠️ Cannot assign seat '{}', not monitoring it{}", Colors::RED, seat, Colors::RESET);
+        return;
+    }
+    let mut touchpad = TouchpadMonitor::default();
+
+    while !signals::exit_requested() {
+        if !wait_readable(input.as_raw_fd(), SEAT_POLL_MS) {
+            continue;
+        }
+        if let Err(err) = input.dispatch() {
+            eprintln!("{}⚠️ libinput dispatch failed on seat '{}': {}{}", Colors::RED, seat, err, Colors::RESET);
+            return;
+        }
+        for event in &mut input {
+            let mut timed = event::decode(&event, event::monotonic_usec());
+            let finger_change = touchpad.observe(&event, &mut timed);
+            for timed in std::iter::once(timed).chain(finger_change) {
+                if sender.send((seat.to_string(), timed)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+use crate::event::InputEvent;
+use crate::priority::EventClass;
+
+// --one-shot [type]: lets exactly one event through, the first of the given
+// class (or of any class), then ends the session. Device additions and
+// removals never count, and neither do key or button releases: the key
+// that started the command is usually still being released.
+pub struct OneShot {
+    class: Option<EventClass>,
+    deadline: Option<Instant>,
+    captured: bool,
+}
+
+impl OneShot {
+    pub fn new(class: Option<EventClass>, timeout: Option<Duration>) -> Self {
+        OneShot { class, deadline: timeout.map(|timeout| Instant::now() + timeout), captured: false }
+    }
+
+    // True for the first matching event only
+    pub fn admit(&mut self, event: &InputEvent) -> bool {
+        if self.captured || !self.matches(event) {
+            return false;
+        }
+        self.captured = true;
+        true
+    }
+
+    fn matches(&self, event: &InputEvent) -> bool {
+        let input = match event {
+            InputEvent::DeviceAdded { .. } | InputEvent::DeviceRemoved { .. } => false,
+            InputEvent::Key { pressed, .. }
+            | InputEvent::PointerButton { pressed, .. }
+            | InputEvent::PadButton { pressed, .. } => *pressed,
+            _ => true,
+        };
+        input && self.class.is_none_or(|class| EventClass::of(event) == class)
+    }
+
+    pub fn captured(&self) -> bool {
+        self.captured
+    }
+
+    // Nothing matched within --timeout
+    pub fn timed_out(&self) -> bool {
+        !self.captured && self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admits_the_first_matching_press_only() {
+        let mut one_shot = OneShot::new(Some(EventClass::Keyboard), None);
+        assert!(!one_shot.admit(&InputEvent::DeviceAdded { device: "kbd".to_string(), info: None }));
+        assert!(!one_shot.admit(&InputEvent::PointerButton { button: 0x110, pressed: true }));
+        assert!(!one_shot.admit(&InputEvent::Key { code: 28, pressed: false }));
+        assert!(one_shot.admit(&InputEvent::Key { code: 30, pressed: true }));
+        assert!(!one_shot.admit(&InputEvent::Key { code: 48, pressed: true }));
+        assert!(one_shot.captured());
+
+        let mut any = OneShot::new(None, Some(Duration::ZERO));
+        assert!(any.timed_out());
+        assert!(any.admit(&InputEvent::PointerMotion { dx: 1.0, dy: 0.0 }));
+        assert!(!any.timed_out());
+    }
+}
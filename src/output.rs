@@ -0,0 +1,445 @@
+use std::borrow::Cow;
+use std::env;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use crate::fifo::Fifo;
+use crate::log_rotate::{LogFile, Rotation};
+use crate::theme;
+
+// Cleared by --no-emoji, or when the terminal can't be expected to render emoji
+static EMOJI: AtomicBool = AtomicBool::new(true);
+
+// Set while the terminal's last line is a repeatable one left without a
+// newline, so its (xN) counter can still be updated in place
+static LINE_OPEN: AtomicBool = AtomicBool::new(false);
+
+// --flush, as a FlushPolicy discriminant plus one; 0 picks per destination
+static FLUSH_POLICY: AtomicU8 = AtomicU8::new(0);
+
+// Everything the monitor prints to stdout goes through this one writer, so
+// --flush applies to all of it
+static STDOUT: Mutex<Option<BufWriter<io::Stdout>>> = Mutex::new(None);
+
+// --all-seats: the seat of the event whose text lines are being printed
+static LINE_PREFIX: Mutex<String> = Mutex::new(String::new());
+
+// Buffer size for block-buffered output
+pub const BLOCK_BUFFER_SIZE: usize = 64 * 1024;
+
+// Plain ASCII stand-ins for the emoji prefixes, for terminals and log pipelines
+// that can't render them. Variation-selector forms come before the bare ones.
+const EMOJI_TAGS: &[(&str, &str)] = &[
+    ("⌨\u{fe0f}", "[KEY]"),
+    ("⌨", "[KEY]"),
+    ("🔠", "[KEY]"),
+    ("🎹", "[COMBO]"),
+    ("🖱\u{fe0f}", "[MOUSE]"),
+    ("🖱", "[MOUSE]"),
+    ("🔘", "[BUTTON]"),
+    ("🌀", "[SCROLL]"),
+    ("🧭", "[CURSOR]"),
+    ("👆", "[TOUCH]"),
+    ("🤲", "[GESTURE]"),
+    ("✏\u{fe0f}", "[TABLET]"),
+    ("🎛\u{fe0f}", "[PAD]"),
+    ("🎛", "[PAD]"),
+    ("✏", "[TABLET]"),
+    ("🔄", "[SWITCH]"),
+    ("📱", "[DEVICE]"),
+    ("➕", "[+]"),
+    ("➖", "[-]"),
+    ("⚠\u{fe0f}", "[WARN]"),
+    ("⚠", "[WARN]"),
+    ("🔔", "[ALERT]"),
+    ("🕘", "[HISTORY]"),
+    ("📊", "[SUMMARY]"),
+    ("🎨", "[LEGEND]"),
+    ("🎮", "*"),
+];
+
+pub fn set_emoji(enabled: bool) {
+    EMOJI.store(enabled, Ordering::Relaxed);
+}
+
+// Put e.g. "[seat1] " in front of every line until cleared with ""
+pub fn set_line_prefix(prefix: &str) {
+    *LINE_PREFIX.lock().unwrap_or_else(PoisonError::into_inner) = prefix.to_string();
+}
+
+// Emoji need a UTF-8 locale, and the Linux virtual console has no glyphs for them
+pub fn emoji_supported() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+        .to_lowercase();
+    let utf8 = locale.contains("utf-8") || locale.contains("utf8");
+    utf8 && env::var("TERM").map_or(true, |term| term != "linux")
+}
+
+// The line as it should be shown, with emoji swapped for tags if they're off
+pub fn decorate(line: &str) -> Cow<'_, str> {
+    if EMOJI.load(Ordering::Relaxed) {
+        return Cow::Borrowed(line);
+    }
+    let mut plain = line.to_string();
+    for (emoji, tag) in EMOJI_TAGS {
+        if plain.contains(emoji) {
+            plain = plain.replace(emoji, tag);
+        }
+    }
+    Cow::Owned(plain)
+}
+
+// When buffered output is handed on to its destination
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlushPolicy {
+    // After every write, even a partial line
+    Immediate,
+    // After every complete line
+    Line,
+    // Only when the buffer fills up, and at exit
+    Block,
+}
+
+impl FlushPolicy {
+    pub fn parse(value: &str) -> Option<FlushPolicy> {
+        match value {
+            "immediate" => Some(FlushPolicy::Immediate),
+            "line" => Some(FlushPolicy::Line),
+            "block" => Some(FlushPolicy::Block),
+            _ => None,
+        }
+    }
+}
+
+// None keeps the defaults: line buffering on a terminal, block buffering
+// to files and pipes
+pub fn set_flush_policy(policy: Option<FlushPolicy>) {
+    FLUSH_POLICY.store(policy.map_or(0, |policy| policy as u8 + 1), Ordering::Relaxed);
+}
+
+// The policy for a destination, depending on whether it's a terminal
+pub fn flush_policy(terminal: bool) -> FlushPolicy {
+    match FLUSH_POLICY.load(Ordering::Relaxed) {
+        1 => FlushPolicy::Immediate,
+        2 => FlushPolicy::Line,
+        3 => FlushPolicy::Block,
+        _ if terminal => FlushPolicy::Line,
+        _ => FlushPolicy::Block,
+    }
+}
+
+// Write to stdout through the shared writer, flushing as --flush says
+pub fn write_stdout(text: &str) {
+    let mut stdout = STDOUT.lock().unwrap_or_else(PoisonError::into_inner);
+    let writer = stdout.get_or_insert_with(|| BufWriter::with_capacity(BLOCK_BUFFER_SIZE, io::stdout()));
+    let _ = writer.write_all(theme::apply(text).as_bytes());
+    let flush = match flush_policy(io::stdout().is_terminal()) {
+        FlushPolicy::Immediate => true,
+        FlushPolicy::Line => text.contains('\n'),
+        FlushPolicy::Block => false,
+    };
+    if flush {
+        let _ = writer.flush();
+    }
+}
+
+pub fn flush_stdout() {
+    if let Some(writer) = STDOUT.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+        let _ = writer.flush();
+    }
+}
+
+// RFC 3164 relays commonly cut messages at 1024 bytes; trim ourselves so
+// the cut never lands inside a character
+#[cfg(feature = "syslog")]
+const SYSLOG_MAX_LINE: usize = 1024;
+
+// How important an output line is; maps onto syslog severities
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Notice,
+    Warning,
+}
+
+// How events are written: colored text lines, one JSON object per line,
+// fixed-size binary records (see `binary`) or MessagePack frames (see `msgpack`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Binary,
+    Msgpack,
+    // `libinput debug-events` lines (format_compat)
+    LibinputDebug,
+    // `evtest` lines (format_compat)
+    Evtest,
+}
+
+impl OutputFormat {
+    pub fn parse(value: &str) -> Option<OutputFormat> {
+        match value {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "binary" => Some(OutputFormat::Binary),
+            "msgpack" => Some(OutputFormat::Msgpack),
+            "libinput-debug" => Some(OutputFormat::LibinputDebug),
+            "evtest" => Some(OutputFormat::Evtest),
+            _ => None,
+        }
+    }
+}
+
+// Where event and summary lines end up
+pub enum Output {
+    Terminal,
+    // Human-readable lines when stdout carries machine-readable events
+    Stderr,
+    // Lines that nobody asked for
+    Discard,
+    // Plain-text log file, appended to, rotated and flushed as --flush says
+    File(LogFile, FlushPolicy),
+    // Named pipe from --output-fifo; lines are dropped while nobody reads
+    Fifo(Fifo),
+    #[cfg(feature = "syslog")]
+    Syslog(syslog::Logger<syslog::LoggerBackend, syslog::Formatter3164>),
+    // Native journal entries through the global tracing-journald subscriber
+    #[cfg(feature = "journald")]
+    Journal,
+}
+
+impl Output {
+    // Connect to the local syslog daemon (or the journal's /dev/log socket)
+    #[cfg(feature = "syslog")]
+    pub fn syslog(ident: &str) -> Result<Output, String> {
+        let formatter = syslog::Formatter3164 {
+            facility: syslog::Facility::LOG_USER,
+            hostname: None,
+            process: ident.to_string(),
+            pid: std::process::id(),
+        };
+        syslog::unix(formatter)
+            .map(Output::Syslog)
+            .map_err(|err| format!("cannot connect to syslog: {}", err))
+    }
+
+    #[cfg(not(feature = "syslog"))]
+    pub fn syslog(_ident: &str) -> Result<Output, String> {
+        Err("syslog support is not compiled in (rebuild with `--features syslog`)".to_string())
+    }
+
+    // Write structured entries to the systemd journal
+    #[cfg(feature = "journald")]
+    pub fn journal(ident: &str) -> Result<Output, String> {
+        use tracing_journald::{Priority, PriorityMappings};
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let layer = tracing_journald::layer()
+            .map_err(|err| format!("cannot connect to the journal: {}", err))?
+            .with_syslog_identifier(ident.to_string())
+            // tracing's INFO is "notice" by default; event lines are plain info
+            .with_priority_mappings(PriorityMappings { info: Priority::Informational, ..PriorityMappings::new() });
+        tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer))
+            .map_err(|err| format!("cannot install the journal logger: {}", err))?;
+        Ok(Output::Journal)
+    }
+
+    #[cfg(not(feature = "journald"))]
+    pub fn journal(_ident: &str) -> Result<Output, String> {
+        Err("journal support is not compiled in (rebuild with `--features journald`)".to_string())
+    }
+
+    pub fn log_file(path: &Path, rotation: Rotation) -> io::Result<Output> {
+        Ok(Output::File(LogFile::open(path, rotation)?, flush_policy(false)))
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Output::Terminal)
+    }
+
+    // Print a line that may repeat (`count` > 1). On the terminal, repeats
+    // rewrite the open line with an (xN) suffix; everywhere else each one is a
+    // line of its own.
+    pub fn emit_repeatable(&mut self, severity: Severity, line: &str, count: u32) {
+        if !self.is_terminal() || !io::stdout().is_terminal() {
+            self.emit(severity, line);
+            return;
+        }
+
+        let line_open = LINE_OPEN.swap(true, Ordering::Relaxed);
+        write_stdout(&repeatable_text(&decorate(line), count, line_open));
+        // The open line is only useful if it's seen, whatever --flush says
+        flush_stdout();
+    }
+
+    // Replace the terminal's open line with `line` (--status-line). Anything
+    // emitted afterwards starts on a fresh line below it.
+    pub fn status(&mut self, line: &str) {
+        if !self.is_terminal() {
+            return;
+        }
+        write_stdout(&format!("\r\x1b[K{}", decorate(line)));
+        LINE_OPEN.store(true, Ordering::Relaxed);
+        flush_stdout();
+    }
+
+    #[cfg_attr(not(any(feature = "syslog", feature = "journald")), allow(unused_variables))]
+    pub fn emit(&mut self, severity: Severity, line: &str) {
+        let line = decorate(line);
+        let prefix = LINE_PREFIX.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        let line = if prefix.is_empty() { line } else { Cow::Owned(format!("{}{}", prefix, line)) };
+        match self {
+            Output::Terminal => {
+                close_open_line();
+                write_stdout(&format!("{}\n", line));
+            },
+            Output::Stderr => eprintln!("{}", theme::apply(&line)),
+            Output::Discard => {},
+            Output::File(writer, policy) => {
+                let mut result = writer.write_line(&strip_ansi(&line));
+                if *policy != FlushPolicy::Block {
+                    result = result.and_then(|()| writer.flush());
+                }
+                if let Err(err) = result {
+                    eprintln!("log file write failed: {}", err);
+                }
+            },
+            Output::Fifo(fifo) => fifo.write_line(&strip_ansi(&line)),
+            #[cfg(feature = "syslog")]
+            Output::Syslog(logger) => {
+                let message = strip_ansi(&line);
+                if message.trim().is_empty() {
+                    return;
+                }
+                let message = truncate_chars(&message, SYSLOG_MAX_LINE);
+                let result = match severity {
+                    Severity::Info => logger.info(message),
+                    Severity::Notice => logger.notice(message),
+                    Severity::Warning => logger.warning(message),
+                };
+                if let Err(err) = result {
+                    eprintln!("syslog write failed: {}", err);
+                }
+            },
+            #[cfg(feature = "journald")]
+            Output::Journal => {
+                let message = strip_ansi(&line);
+                if message.trim().is_empty() {
+                    return;
+                }
+                match severity {
+                    Severity::Info | Severity::Notice => tracing::info!("{}", message),
+                    Severity::Warning => tracing::warn!("{}", message),
+                }
+            },
+        }
+    }
+
+    pub fn flush(&mut self) {
+        match self {
+            Output::Terminal => {
+                close_open_line();
+                flush_stdout();
+            },
+            Output::Stderr => io::stderr().flush().unwrap(),
+            Output::Discard | Output::Fifo(_) => {},
+            Output::File(writer, _) => {
+                let _ = writer.flush();
+            },
+            #[cfg(feature = "syslog")]
+            Output::Syslog(_) => {},
+            #[cfg(feature = "journald")]
+            Output::Journal => {},
+        }
+    }
+}
+
+// The first `max` bytes of `line`, backed off to a character boundary
+#[cfg(feature = "syslog")]
+fn truncate_chars(line: &str, max: usize) -> &str {
+    if line.len() <= max {
+        return line;
+    }
+    let mut end = max;
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+// What emit_repeatable writes: a repeat replaces the open line and adds
+// its count, anything else ends the open line and starts a new one
+fn repeatable_text(line: &str, count: u32, line_open: bool) -> String {
+    match (count > 1, line_open) {
+        (true, true) => format!("\r\x1b[K{} (x{})", line, count),
+        (false, true) => format!("\n{}", line),
+        (_, false) => line.to_string(),
+    }
+}
+
+// End a repeatable line before anything else is printed after it
+fn close_open_line() {
+    if LINE_OPEN.swap(false, Ordering::Relaxed) {
+        write_stdout("\n");
+    }
+}
+
+// Remove ANSI color sequences (ESC '[' ... 'm') from a line
+pub fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+// `println!`-style helper that sends a formatted line to an `Output`
+macro_rules! emit {
+    ($out:expr, $severity:expr, $($arg:tt)*) => {
+        $out.emit($severity, &format!($($arg)*))
+    };
+}
+
+// `println!` through the shared stdout writer, for text that isn't an
+// event or summary line (the banner)
+macro_rules! outln {
+    () => {
+        $crate::output::write_stdout("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::output::write_stdout(&format!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+pub(crate) use emit;
+pub(crate) use outln;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_rewrite_the_open_line() {
+        assert_eq!(repeatable_text("Motion (1, 0)", 1, false), "Motion (1, 0)");
+        assert_eq!(repeatable_text("Motion (1, 0)", 2, true), "\r\x1b[KMotion (1, 0) (x2)");
+        assert_eq!(repeatable_text("Motion (1, 0)", 3, true), "\r\x1b[KMotion (1, 0) (x3)");
+        // A new run ends the previous one's line
+        assert_eq!(repeatable_text("Motion (0, 1)", 1, true), "\nMotion (0, 1)");
+        // Nothing to fold into after another line closed it
+        assert_eq!(repeatable_text("Motion (0, 1)", 2, false), "Motion (0, 1)");
+    }
+}
@@ -0,0 +1,43 @@
+// Simulates a pointer confined to a rectangle, the way a Wayland compositor
+// confines it for pointer-constraints clients (a zero-size rectangle is a
+// locked pointer). Nothing is actually confined: motion is replayed against
+// the rectangle so the output can say where the pointer would have gone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerConstraint {
+    min: (f64, f64),
+    max: (f64, f64),
+    // The confined pointer position
+    position: (f64, f64),
+}
+
+impl PointerConstraint {
+    // "x1,y1,x2,y2", corners in either order
+    pub fn parse(value: &str) -> Option<Self> {
+        let coords = value.split(',').map(|part| part.trim().parse::<f64>().ok().filter(|coord| coord.is_finite()))
+            .collect::<Option<Vec<_>>>()?;
+        let [x1, y1, x2, y2] = coords[..] else {
+            return None;
+        };
+        let mut constraint = PointerConstraint { min: (x1.min(x2), y1.min(y2)), max: (x1.max(x2), y1.max(y2)), position: (0.0, 0.0) };
+        // The tracked position starts at (0, 0); the confined pointer starts
+        // at the point of the region nearest to it
+        constraint.position = constraint.clamp(0.0, 0.0);
+        Some(constraint)
+    }
+
+    // Relative motion. Returns where the pointer would be without the
+    // region, if the region stopped it.
+    pub fn motion(&mut self, dx: f64, dy: f64) -> Option<(f64, f64)> {
+        self.move_to(self.position.0 + dx, self.position.1 + dy)
+    }
+
+    // Absolute motion, e.g. from a tablet or touchscreen
+    pub fn move_to(&mut self, x: f64, y: f64) -> Option<(f64, f64)> {
+        self.position = self.clamp(x, y);
+        (self.position != (x, y)).then_some((x, y))
+    }
+
+    fn clamp(&self, x: f64, y: f64) -> (f64, f64) {
+        (x.clamp(self.min.0, self.max.0), y.clamp(self.min.1, self.max.1))
+    }
+}
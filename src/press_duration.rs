@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// What was pressed: a key code, a button code or a touch slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PressSource {
+    Key,
+    Button,
+    Touch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PressKind {
+    Tap,
+    LongPress,
+}
+
+impl PressSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            PressSource::Key => "key",
+            PressSource::Button => "button",
+            PressSource::Touch => "touch",
+        }
+    }
+}
+
+impl fmt::Display for PressKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            PressKind::Tap => "TAP",
+            PressKind::LongPress => "LONG PRESS",
+        })
+    }
+}
+
+// --classify-presses: times each press/release cycle of keys, buttons and
+// touches, and calls it a tap or a long press
+pub struct PressClassifier {
+    threshold_usec: u64,
+    // When each held key, button or touch went down
+    pressed: HashMap<(PressSource, u32), u64>,
+    taps: u64,
+    long_presses: u64,
+}
+
+impl PressClassifier {
+    pub fn new(threshold_usec: u64) -> Self {
+        PressClassifier { threshold_usec, pressed: HashMap::new(), taps: 0, long_presses: 0 }
+    }
+
+    pub fn press(&mut self, source: PressSource, code: u32, time_usec: u64) {
+        // A repeated press (lost release) keeps timing from the first one
+        self.pressed.entry((source, code)).or_insert(time_usec);
+    }
+
+    // The kind of the completed press and how long it was held, in
+    // microseconds; None if the press wasn't seen
+    pub fn release(&mut self, source: PressSource, code: u32, time_usec: u64) -> Option<(PressKind, u64)> {
+        let pressed_usec = self.pressed.remove(&(source, code))?;
+        let duration_usec = time_usec.saturating_sub(pressed_usec);
+        let kind = if duration_usec >= self.threshold_usec {
+            self.long_presses += 1;
+            PressKind::LongPress
+        } else {
+            self.taps += 1;
+            PressKind::Tap
+        };
+        Some((kind, duration_usec))
+    }
+
+    // Touch cancel: every contact is gone, none of them completed
+    pub fn cancel_touches(&mut self) {
+        self.pressed.retain(|(source, _), _| *source != PressSource::Touch);
+    }
+
+    // Presses still held, with how long they had been held at `now_usec`,
+    // longest first
+    pub fn incomplete(&self, now_usec: u64) -> Vec<(PressSource, u32, u64)> {
+        let mut held: Vec<_> = self.pressed.iter()
+            .map(|(&(source, code), &pressed_usec)| (source, code, now_usec.saturating_sub(pressed_usec)))
+            .collect();
+        held.sort_by_key(|&(source, code, duration_usec)| (std::cmp::Reverse(duration_usec), source as u8, code));
+        held
+    }
+
+    pub fn threshold_usec(&self) -> u64 {
+        self.threshold_usec
+    }
+
+    pub fn taps(&self) -> u64 {
+        self.taps
+    }
+
+    pub fn long_presses(&self) -> u64 {
+        self.long_presses
+    }
+}
@@ -0,0 +1,129 @@
+use crate::event::{InputEvent, TimedEvent};
+
+// Coarse event categories that can be given a display priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventClass {
+    Keyboard,
+    Button,
+    Scroll,
+    Motion,
+    Other,
+}
+
+impl EventClass {
+    pub const ALL: [EventClass; 5] = [EventClass::Keyboard, EventClass::Button, EventClass::Scroll, EventClass::Motion, EventClass::Other];
+
+    pub fn of(event: &InputEvent) -> Self {
+        match event {
+            InputEvent::Key { .. } => EventClass::Keyboard,
+            InputEvent::PointerButton { .. } => EventClass::Button,
+            InputEvent::Scroll { .. } => EventClass::Scroll,
+            InputEvent::PointerMotion { .. } | InputEvent::PointerMotionAbsolute { .. } => EventClass::Motion,
+            _ => EventClass::Other,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventClass::Keyboard => "keyboard",
+            EventClass::Button => "button",
+            EventClass::Scroll => "scroll",
+            EventClass::Motion => "motion",
+            EventClass::Other => "other",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "keyboard" | "key" => Some(EventClass::Keyboard),
+            "button" | "click" => Some(EventClass::Button),
+            "scroll" => Some(EventClass::Scroll),
+            "motion" => Some(EventClass::Motion),
+            "other" => Some(EventClass::Other),
+            _ => None,
+        }
+    }
+}
+
+// Priority per event class; higher values are displayed first
+#[derive(Debug, Clone, Copy)]
+pub struct EventPriorities {
+    keyboard: u8,
+    button: u8,
+    scroll: u8,
+    motion: u8,
+    other: u8,
+}
+
+impl EventPriorities {
+    pub fn get(&self, class: EventClass) -> u8 {
+        match class {
+            EventClass::Keyboard => self.keyboard,
+            EventClass::Button => self.button,
+            EventClass::Scroll => self.scroll,
+            EventClass::Motion => self.motion,
+            EventClass::Other => self.other,
+        }
+    }
+
+    pub fn set(&mut self, class: EventClass, priority: u8) {
+        match class {
+            EventClass::Keyboard => self.keyboard = priority,
+            EventClass::Button => self.button = priority,
+            EventClass::Scroll => self.scroll = priority,
+            EventClass::Motion => self.motion = priority,
+            EventClass::Other => self.other = priority,
+        }
+    }
+
+    // Apply an override written as `<type>=<priority>`, e.g. `scroll=3`
+    pub fn apply_override(&mut self, spec: &str) -> Result<(), String> {
+        let (name, value) = spec.split_once('=')
+            .ok_or_else(|| format!("expected <type>=<priority>, got '{}'", spec))?;
+        let class = EventClass::parse(name.trim()).ok_or_else(|| {
+            format!("unknown event type '{}' (expected keyboard, button, scroll, motion or other)", name)
+        })?;
+        let priority = value.trim().parse()
+            .map_err(|_| format!("invalid priority '{}' for '{}'", value, name))?;
+        self.set(class, priority);
+        Ok(())
+    }
+}
+
+impl Default for EventPriorities {
+    // Keyboard events first so they aren't buried under heavy mouse movement
+    fn default() -> Self {
+        EventPriorities {
+            keyboard: 3,
+            button: 2,
+            scroll: 1,
+            motion: 0,
+            other: 0,
+        }
+    }
+}
+
+// Collects one dispatch() batch and hands it back ordered by priority.
+// Only the display order changes; recordings still see kernel order.
+pub struct PriorityEventQueue {
+    priorities: EventPriorities,
+    batch: Vec<TimedEvent>,
+}
+
+impl PriorityEventQueue {
+    pub fn new(priorities: EventPriorities) -> Self {
+        PriorityEventQueue { priorities, batch: Vec::new() }
+    }
+
+    pub fn push(&mut self, timed: TimedEvent) {
+        self.batch.push(timed);
+    }
+
+    // Highest priority first; the sort is stable, so events of the same
+    // priority keep their arrival order
+    pub fn drain_sorted(&mut self) -> std::vec::Drain<'_, TimedEvent> {
+        let priorities = self.priorities;
+        self.batch.sort_by_key(|timed| std::cmp::Reverse(priorities.get(EventClass::of(&timed.event))));
+        self.batch.drain(..)
+    }
+}
@@ -0,0 +1,140 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::button_names::button_name;
+use crate::key_names::kernel_key_name;
+
+// From <linux/input-event-codes.h>
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const EV_MSC: u16 = 0x04;
+const EV_SW: u16 = 0x05;
+const EV_LED: u16 = 0x11;
+const EV_SND: u16 = 0x12;
+const EV_REP: u16 = 0x14;
+const EV_FF: u16 = 0x15;
+
+const SYN_NAMES: [&str; 4] = ["SYN_REPORT", "SYN_CONFIG", "SYN_MT_REPORT", "SYN_DROPPED"];
+const REL_NAMES: [&str; 13] = [
+    "REL_X", "REL_Y", "REL_Z", "REL_RX", "REL_RY", "REL_RZ", "REL_HWHEEL", "REL_DIAL", "REL_WHEEL", "REL_MISC",
+    "REL_RESERVED", "REL_WHEEL_HI_RES", "REL_HWHEEL_HI_RES",
+];
+const ABS_NAMES: [(u16, &str); 32] = [
+    (0x00, "ABS_X"), (0x01, "ABS_Y"), (0x02, "ABS_Z"), (0x03, "ABS_RX"), (0x04, "ABS_RY"), (0x05, "ABS_RZ"),
+    (0x06, "ABS_THROTTLE"), (0x07, "ABS_RUDDER"), (0x08, "ABS_WHEEL"), (0x09, "ABS_GAS"), (0x0a, "ABS_BRAKE"),
+    (0x10, "ABS_HAT0X"), (0x11, "ABS_HAT0Y"), (0x18, "ABS_PRESSURE"), (0x19, "ABS_DISTANCE"),
+    (0x1a, "ABS_TILT_X"), (0x1b, "ABS_TILT_Y"), (0x1c, "ABS_TOOL_WIDTH"), (0x20, "ABS_VOLUME"), (0x28, "ABS_MISC"),
+    (0x2f, "ABS_MT_SLOT"), (0x30, "ABS_MT_TOUCH_MAJOR"), (0x31, "ABS_MT_TOUCH_MINOR"), (0x32, "ABS_MT_WIDTH_MAJOR"),
+    (0x33, "ABS_MT_WIDTH_MINOR"), (0x34, "ABS_MT_ORIENTATION"), (0x35, "ABS_MT_POSITION_X"),
+    (0x36, "ABS_MT_POSITION_Y"), (0x37, "ABS_MT_TOOL_TYPE"), (0x39, "ABS_MT_TRACKING_ID"),
+    (0x3a, "ABS_MT_PRESSURE"), (0x3b, "ABS_MT_DISTANCE"),
+];
+const MSC_NAMES: [&str; 6] = ["MSC_SERIAL", "MSC_PULSELED", "MSC_GESTURE", "MSC_RAW", "MSC_SCAN", "MSC_TIMESTAMP"];
+const SW_NAMES: [&str; 3] = ["SW_LID", "SW_TABLET_MODE", "SW_HEADPHONE_INSERT"];
+const LED_NAMES: [&str; 5] = ["LED_NUML", "LED_CAPSL", "LED_SCROLLL", "LED_COMPOSE", "LED_KANA"];
+
+// --raw-evdev: reads the `input_event` structs of one event node next to
+// libinput, which keeps its own copy, to compare what the kernel reports
+// with what comes out of libinput. The node is opened read-only and not
+// grabbed, so nothing else stops seeing the device.
+pub struct RawEventDumper {
+    path: PathBuf,
+    file: File,
+}
+
+impl RawEventDumper {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(path)?;
+        Ok(RawEventDumper { path: path.to_path_buf(), file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Everything queued on the node, without blocking
+    pub fn read(&mut self) -> io::Result<Vec<libc::input_event>> {
+        let size = mem::size_of::<libc::input_event>();
+        let mut buffer = vec![0u8; size * 64];
+        let mut events = Vec::new();
+        loop {
+            let read = match self.file.read(&mut buffer) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "device went away")),
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(events),
+                Err(err) => return Err(err),
+            };
+            // The kernel only hands out whole structs
+            events.extend(buffer[..read].chunks_exact(size)
+                .map(|chunk| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const libc::input_event) }));
+        }
+    }
+}
+
+// e.g. "type=0x01 (EV_KEY) code=30 (KEY_A) value=1 (press) time=1718000.123456"
+pub fn describe(event: &libc::input_event) -> String {
+    let value = match (event.type_, event.value) {
+        (EV_KEY, 0) => format!("{} (release)", event.value),
+        (EV_KEY, 1) => format!("{} (press)", event.value),
+        (EV_KEY, 2) => format!("{} (repeat)", event.value),
+        _ => event.value.to_string(),
+    };
+    format!("type=0x{:02x} ({}) code={} ({}) value={} time={}.{:06}",
+        event.type_, type_name(event.type_), event.code, code_name(event.type_, event.code), value,
+        event.time.tv_sec, event.time.tv_usec)
+}
+
+fn type_name(kind: u16) -> &'static str {
+    match kind {
+        EV_SYN => "EV_SYN",
+        EV_KEY => "EV_KEY",
+        EV_REL => "EV_REL",
+        EV_ABS => "EV_ABS",
+        EV_MSC => "EV_MSC",
+        EV_SW => "EV_SW",
+        EV_LED => "EV_LED",
+        EV_SND => "EV_SND",
+        EV_REP => "EV_REP",
+        EV_FF => "EV_FF",
+        _ => "?",
+    }
+}
+
+fn code_name(kind: u16, code: u16) -> &'static str {
+    let index = code as usize;
+    let name = match kind {
+        EV_SYN => SYN_NAMES.get(index).copied(),
+        EV_KEY => kernel_key_name(code as u32).or_else(|| Some(button_name(code as u32)).filter(|name| name.starts_with("BTN_"))),
+        EV_REL => REL_NAMES.get(index).copied(),
+        EV_ABS => ABS_NAMES.iter().find(|(abs, _)| *abs == code).map(|(_, name)| *name),
+        EV_MSC => MSC_NAMES.get(index).copied(),
+        EV_SW => SW_NAMES.get(index).copied(),
+        EV_LED => LED_NAMES.get(index).copied(),
+        EV_REP => ["REP_DELAY", "REP_PERIOD"].get(index).copied(),
+        _ => None,
+    };
+    name.unwrap_or("?")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(type_: u16, code: u16, value: i32) -> libc::input_event {
+        libc::input_event { time: libc::timeval { tv_sec: 1718000, tv_usec: 123456 }, type_, code, value }
+    }
+
+    #[test]
+    fn names_types_codes_and_key_values() {
+        assert_eq!(describe(&event(EV_KEY, 30, 1)), "type=0x01 (EV_KEY) code=30 (KEY_A) value=1 (press) time=1718000.123456");
+        assert_eq!(describe(&event(EV_KEY, 0x110, 0)), "type=0x01 (EV_KEY) code=272 (BTN_LEFT) value=0 (release) time=1718000.123456");
+        assert_eq!(describe(&event(EV_REL, 11, -120)), "type=0x02 (EV_REL) code=11 (REL_WHEEL_HI_RES) value=-120 time=1718000.123456");
+        assert_eq!(describe(&event(EV_SYN, 0, 0)), "type=0x00 (EV_SYN) code=0 (SYN_REPORT) value=0 time=1718000.123456");
+        assert_eq!(describe(&event(0x1f, 7, 3)), "type=0x1f (?) code=7 (?) value=3 time=1718000.123456");
+    }
+}
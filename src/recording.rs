@@ -0,0 +1,62 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::TimedEvent;
+
+// One line of an .rkey recording: a sequence number plus the decoded event, as JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: TimedEvent,
+}
+
+// Writes decoded events to an .rkey file (JSON lines, one event per line)
+pub struct Recorder {
+    writer: BufWriter<File>,
+    next_seq: u64,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Recorder {
+            writer: BufWriter::new(File::create(path)?),
+            next_seq: 1,
+        })
+    }
+
+    pub fn record(&mut self, event: &TimedEvent) -> io::Result<()> {
+        let line = RecordedEvent { seq: self.next_seq, event: event.clone() };
+        serde_json::to_writer(&mut self.writer, &line)?;
+        self.writer.write_all(b"\n")?;
+        self.next_seq += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// Load every event from an .rkey file
+pub fn read_recording(path: &Path) -> io::Result<Vec<RecordedEvent>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut events = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str(&line).map_err(|err| {
+            io::Error::new(io::ErrorKind::InvalidData,
+                format!("{}:{}: {}", path.display(), index + 1, err))
+        })?;
+        events.push(event);
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::binary::{self, HEADER_SIZE, RECORD_SIZE};
+use crate::event::{InputEvent, TimedEvent};
+use crate::keys::key_name;
+use crate::recording::{self, RecordedEvent};
+use crate::signals;
+use crate::Monitor;
+
+// How far `s` jumps ahead in --interactive mode
+const SKIP_EVENTS: usize = 10;
+
+// Feed a recording through the same pipeline as live events, as fast as possible.
+// Needs no input devices or root, so it also drives the integration tests.
+pub fn run(monitor: &mut Monitor, path: &Path) -> io::Result<()> {
+    for recorded in recording::read_recording(path)? {
+        if signals::exit_requested() || monitor.limit_reached() {
+            break;
+        }
+        monitor.handle_event(&recorded.event);
+    }
+    Ok(())
+}
+
+// Random access to the events of a recording. `--format binary` files have
+// fixed-size records, so event N is found by arithmetic; .rkey files are
+// indexed by line offset once, up front.
+struct EventFile {
+    reader: BufReader<File>,
+    // None for binary files
+    line_offsets: Option<Vec<u64>>,
+    len: usize,
+}
+
+impl EventFile {
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0u8; HEADER_SIZE];
+        let read = reader.read(&mut header)?;
+
+        if binary::has_magic(&header[..read]) {
+            reader.rewind()?;
+            reader.read_exact(&mut header)?;
+            binary::check_header(&header)?;
+            let size = reader.get_ref().metadata()?.len() as usize;
+            let len = size.saturating_sub(HEADER_SIZE) / RECORD_SIZE;
+            return Ok(EventFile { reader, line_offsets: None, len });
+        }
+
+        reader.rewind()?;
+        let mut offsets = Vec::new();
+        let mut offset = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                break;
+            }
+            if !line.trim().is_empty() {
+                offsets.push(offset);
+            }
+            offset += read as u64;
+        }
+        let len = offsets.len();
+        Ok(EventFile { reader, line_offsets: Some(offsets), len })
+    }
+
+    fn get(&mut self, index: usize) -> io::Result<TimedEvent> {
+        match &self.line_offsets {
+            None => {
+                self.reader.seek(SeekFrom::Start((HEADER_SIZE + index * RECORD_SIZE) as u64))?;
+                let mut record = [0u8; RECORD_SIZE];
+                self.reader.read_exact(&mut record)?;
+                binary::decode_event(&record)
+            },
+            Some(offsets) => {
+                self.reader.seek(SeekFrom::Start(offsets[index]))?;
+                let mut line = String::new();
+                self.reader.read_line(&mut line)?;
+                let recorded: RecordedEvent = serde_json::from_str(&line).map_err(|err| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("event {}: {}", index + 1, err))
+                })?;
+                Ok(recorded.event)
+            },
+        }
+    }
+}
+
+// What was typed at the --interactive prompt
+#[derive(Debug, PartialEq)]
+enum StepCommand {
+    Next,
+    Previous,
+    Skip,
+    // Seconds since the first event
+    Jump(f64),
+    // Key name substring
+    Find(String),
+    Quit,
+}
+
+impl StepCommand {
+    fn parse(line: &str) -> Option<StepCommand> {
+        let (command, argument) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let argument = argument.trim();
+        match command {
+            "" | "n" => Some(StepCommand::Next),
+            "p" => Some(StepCommand::Previous),
+            "s" => Some(StepCommand::Skip),
+            "t" => argument.parse().ok().filter(|secs: &f64| *secs >= 0.0).map(StepCommand::Jump),
+            "f" if !argument.is_empty() => Some(StepCommand::Find(argument.to_uppercase())),
+            "q" => Some(StepCommand::Quit),
+            _ => None,
+        }
+    }
+}
+
+// Step through a recording one event at a time, like `less` for events.
+// Every event shown goes through the pipeline, so stepping back shows (and
+// counts) an event again.
+pub fn run_interactive(monitor: &mut Monitor, path: &Path) -> io::Result<()> {
+    let mut events = EventFile::open(path)?;
+    if events.len == 0 {
+        eprintln!("{} has no events", path.display());
+        return Ok(());
+    }
+    let first_usec = events.get(0)?.time_usec;
+    eprintln!("Enter/n: next  p: previous  s: skip {}  t <secs>: jump to time  f <key>: find key  q: quit", SKIP_EVENTS);
+
+    let stdin = io::stdin();
+    let mut current = 0;
+    let mut show = true;
+    loop {
+        if show {
+            let timed = events.get(current)?;
+            monitor.handle_event(&timed);
+            monitor.out.flush();
+            eprint!("[event {}/{}  +{:.3}s] > ",
+                current + 1, events.len, timed.time_usec.saturating_sub(first_usec) as f64 / 1_000_000.0);
+        } else {
+            eprint!("> ");
+        }
+
+        let mut line = String::new();
+        if signals::exit_requested() || stdin.lock().read_line(&mut line)? == 0 {
+            eprintln!();
+            return Ok(());
+        }
+        let Some(command) = StepCommand::parse(&line) else {
+            eprintln!("unknown command '{}'", line.trim());
+            show = false;
+            continue;
+        };
+
+        let target = match command {
+            StepCommand::Quit => return Ok(()),
+            StepCommand::Next => Some(current + 1).filter(|&next| next < events.len),
+            StepCommand::Previous => current.checked_sub(1),
+            StepCommand::Skip => Some((current + SKIP_EVENTS).min(events.len - 1)).filter(|&next| next != current),
+            StepCommand::Jump(secs) => {
+                let time_usec = first_usec + (secs * 1_000_000.0) as u64;
+                first_at_or_after(&mut events, time_usec)?
+            },
+            StepCommand::Find(pattern) => find_key(&mut events, current + 1, &pattern)?,
+        };
+        match target {
+            Some(index) => {
+                current = index;
+                show = true;
+            },
+            None => {
+                eprintln!("no such event");
+                show = false;
+            },
+        }
+    }
+}
+
+// Binary search; recordings are in kernel order, so timestamps only grow
+fn first_at_or_after(events: &mut EventFile, time_usec: u64) -> io::Result<Option<usize>> {
+    let (mut low, mut high) = (0, events.len);
+    while low < high {
+        let middle = (low + high) / 2;
+        if events.get(middle)?.time_usec < time_usec {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    Ok((low < events.len).then_some(low))
+}
+
+// The next key event from `start` on whose name contains `pattern`
+fn find_key(events: &mut EventFile, start: usize, pattern: &str) -> io::Result<Option<usize>> {
+    for index in start..events.len {
+        if let InputEvent::Key { code, .. } = events.get(index)?.event {
+            if key_name(code).contains(pattern) {
+                return Ok(Some(index));
+            }
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+// Upper bounds (ms) of the inter-keystroke interval buckets; the last bucket
+// takes everything above
+pub const IKI_BUCKETS_MS: [u64; 4] = [50, 100, 200, 500];
+pub const IKI_BUCKET_LABELS: [&str; 5] = ["<50 ms", "50-100 ms", "100-200 ms", "200-500 ms", ">500 ms"];
+// The mode follows the median of this many recent intervals
+const RHYTHM_WINDOW: usize = 20;
+// Too few intervals say nothing about a rhythm yet
+const MIN_INTERVALS: usize = 5;
+const BURST_MEDIAN_USEC: u64 = 100_000;
+const NORMAL_MEDIAN_USEC: u64 = 300_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypingMode {
+    Burst,
+    Normal,
+    HuntAndPeck,
+}
+
+impl fmt::Display for TypingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TypingMode::Burst => "BURST",
+            TypingMode::Normal => "NORMAL",
+            TypingMode::HuntAndPeck => "HUNT_AND_PECK",
+        })
+    }
+}
+
+// Inter-keystroke intervals (IKI) between consecutive non-modifier key
+// presses: a session histogram, plus a typing mode from the recent median
+pub struct TypingRhythmAnalyzer {
+    // Gaps longer than this are breaks, not part of the rhythm
+    idle_threshold_usec: u64,
+    last_press: Option<u64>,
+    recent: VecDeque<u64>,
+    histogram: [u64; 5],
+    mode: Option<TypingMode>,
+}
+
+impl TypingRhythmAnalyzer {
+    pub fn new(idle_threshold_usec: u64) -> Self {
+        TypingRhythmAnalyzer {
+            idle_threshold_usec,
+            last_press: None,
+            recent: VecDeque::with_capacity(RHYTHM_WINDOW),
+            histogram: [0; 5],
+            mode: None,
+        }
+    }
+
+    // Returns the new mode when this press changed it
+    pub fn record_press(&mut self, time_usec: u64) -> Option<TypingMode> {
+        let last = self.last_press.replace(time_usec)?;
+        let interval = time_usec.saturating_sub(last);
+        if interval > self.idle_threshold_usec {
+            return None;
+        }
+
+        let bucket = IKI_BUCKETS_MS.iter().position(|&bound| interval < bound * 1000).unwrap_or(IKI_BUCKETS_MS.len());
+        self.histogram[bucket] += 1;
+        if self.recent.len() == RHYTHM_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(interval);
+
+        let mode = classify(self.median_usec()?);
+        (self.mode.replace(mode) != Some(mode)).then_some(mode)
+    }
+
+    // Median of the recent intervals, once there are enough of them
+    pub fn median_usec(&self) -> Option<u64> {
+        if self.recent.len() < MIN_INTERVALS {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.recent.iter().copied().collect();
+        sorted.sort_unstable();
+        Some(sorted[sorted.len() / 2])
+    }
+
+    pub fn histogram(&self) -> &[u64; 5] {
+        &self.histogram
+    }
+}
+
+fn classify(median_usec: u64) -> TypingMode {
+    if median_usec < BURST_MEDIAN_USEC {
+        TypingMode::Burst
+    } else if median_usec <= NORMAL_MEDIAN_USEC {
+        TypingMode::Normal
+    } else {
+        TypingMode::HuntAndPeck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_follows_the_median_interval() {
+        let mut rhythm = TypingRhythmAnalyzer::new(10_000_000);
+        let mut time = 0;
+        let mut changes = Vec::new();
+        for interval in [80_000; 8].into_iter().chain([600_000; 30]) {
+            time += interval;
+            changes.extend(rhythm.record_press(time));
+        }
+
+        assert_eq!(changes, vec![TypingMode::Burst, TypingMode::HuntAndPeck]);
+        assert_eq!(rhythm.histogram(), &[0, 7, 0, 0, 30]);
+    }
+
+    #[test]
+    fn breaks_are_not_intervals() {
+        let mut rhythm = TypingRhythmAnalyzer::new(1_000_000);
+        rhythm.record_press(0);
+        rhythm.record_press(5_000_000);
+        rhythm.record_press(5_150_000);
+
+        assert_eq!(rhythm.histogram(), &[0, 0, 1, 0, 0]);
+        assert_eq!(rhythm.median_usec(), None);
+    }
+}
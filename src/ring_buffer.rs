@@ -0,0 +1,246 @@
+// Lock-free single-producer/single-consumer queue for handing events from the
+// event loop to a display or broadcast thread without a Mutex on the hot path.
+// `ring_buffer()` returns the two ends; each can be moved to its own thread,
+// and since pushing and popping take `&mut self`, an end can't be used from
+// two threads at once.
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+// Capacity used for inter-thread event passing
+#[cfg_attr(not(test), allow(dead_code))]
+pub const EVENT_QUEUE_CAPACITY: usize = 1024;
+
+// The state both ends share
+struct RingBuffer<T, const N: usize> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    // Both counters only ever grow; the slot index is `counter % N`
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// Items move between threads, so T must be Send; a slot is only touched by
+// the producer before `tail` publishes it, and by the consumer after
+unsafe impl<T: Send, const N: usize> Send for RingBuffer<T, N> {}
+unsafe impl<T: Send, const N: usize> Sync for RingBuffer<T, N> {}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    fn len(&self) -> usize {
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+}
+
+impl<T, const N: usize> Drop for RingBuffer<T, N> {
+    fn drop(&mut self) {
+        // Drop whatever the consumer never picked up
+        let (head, tail) = (*self.head.get_mut(), *self.tail.get_mut());
+        for counter in head..tail {
+            unsafe { self.slots[counter % N].get_mut().assume_init_drop() };
+        }
+    }
+}
+
+// The writing end
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Producer<T, const N: usize> {
+    buffer: Arc<RingBuffer<T, N>>,
+}
+
+// The reading end
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Consumer<T, const N: usize> {
+    buffer: Arc<RingBuffer<T, N>>,
+}
+
+// A queue of capacity N, as its two ends
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn ring_buffer<T, const N: usize>() -> (Producer<T, N>, Consumer<T, N>) {
+    assert!(N > 0, "RingBuffer capacity must be non-zero");
+    let buffer = Arc::new(RingBuffer {
+        slots: (0..N).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (Producer { buffer: Arc::clone(&buffer) }, Consumer { buffer })
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl<T, const N: usize> Producer<T, N> {
+    // Add an item at the tail. If the buffer is full, the item is handed back.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        let buffer = &*self.buffer;
+        let tail = buffer.tail.load(Ordering::Relaxed);
+        let head = buffer.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == N {
+            return Err(item);
+        }
+        // The consumer never reads this slot until `tail` is published below
+        unsafe { (*buffer.slots[tail % N].get()).write(item) };
+        buffer.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl<T, const N: usize> Consumer<T, N> {
+    // Take the item at the head, if any
+    pub fn pop(&mut self) -> Option<T> {
+        let buffer = &*self.buffer;
+        let head = buffer.head.load(Ordering::Relaxed);
+        let tail = buffer.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        // The producer published this slot with the Release store of `tail`
+        let item = unsafe { (*buffer.slots[head % N].get()).assume_init_read() };
+        buffer.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(item)
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn pop_on_empty_returns_none() {
+        let (_producer, mut consumer) = ring_buffer::<u32, 4>();
+        assert!(consumer.is_empty());
+        assert_eq!(consumer.pop(), None);
+    }
+
+    #[test]
+    fn preserves_fifo_order() {
+        let (mut producer, mut consumer) = ring_buffer::<u32, 4>();
+        assert_eq!(producer.push(1), Ok(()));
+        assert_eq!(producer.push(2), Ok(()));
+        assert_eq!(producer.push(3), Ok(()));
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(consumer.pop(), Some(2));
+        assert_eq!(consumer.pop(), Some(3));
+        assert_eq!(consumer.pop(), None);
+    }
+
+    #[test]
+    fn push_fails_when_full() {
+        let (mut producer, mut consumer) = ring_buffer::<u32, 2>();
+        assert_eq!(producer.capacity(), 2);
+        assert_eq!(producer.push(1), Ok(()));
+        assert_eq!(producer.push(2), Ok(()));
+        assert_eq!(producer.push(3), Err(3));
+        assert_eq!(producer.len(), 2);
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(producer.push(3), Ok(()));
+        assert_eq!(consumer.pop(), Some(2));
+        assert_eq!(consumer.pop(), Some(3));
+    }
+
+    #[test]
+    fn wraps_around_many_times() {
+        let (mut producer, mut consumer) = ring_buffer::<usize, 3>();
+        for i in 0..1000 {
+            assert_eq!(producer.push(i), Ok(()));
+            assert_eq!(producer.push(i + 1), Ok(()));
+            assert_eq!(consumer.pop(), Some(i));
+            assert_eq!(consumer.pop(), Some(i + 1));
+        }
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn drops_unconsumed_items() {
+        let item = Arc::new(());
+        {
+            let (mut producer, consumer) = ring_buffer::<Arc<()>, 4>();
+            producer.push(Arc::clone(&item)).unwrap();
+            producer.push(Arc::clone(&item)).unwrap();
+            assert_eq!(Arc::strong_count(&item), 3);
+            // The items outlive whichever end is dropped first
+            drop(consumer);
+            assert_eq!(Arc::strong_count(&item), 3);
+        }
+        assert_eq!(Arc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn concurrent_producer_consumer_delivers_everything_in_order() {
+        const ITEMS: u64 = 200_000;
+        let (mut producer, mut consumer) = ring_buffer::<u64, EVENT_QUEUE_CAPACITY>();
+
+        let producer = thread::spawn(move || {
+            for i in 0..ITEMS {
+                while producer.push(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let consumer = thread::spawn(move || {
+            let mut expected = 0;
+            while expected < ITEMS {
+                match consumer.pop() {
+                    Some(value) => {
+                        assert_eq!(value, expected);
+                        expected += 1;
+                    },
+                    None => thread::yield_now(),
+                }
+            }
+            consumer
+        });
+
+        producer.join().unwrap();
+        let consumer = consumer.join().unwrap();
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn concurrent_stress_with_tiny_capacity() {
+        const ITEMS: usize = 50_000;
+        let (mut producer, mut consumer) = ring_buffer::<String, 2>();
+
+        let producer = thread::spawn(move || {
+            for i in 0..ITEMS {
+                let mut item = i.to_string();
+                while let Err(rejected) = producer.push(item) {
+                    item = rejected;
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut received = Vec::with_capacity(ITEMS);
+        while received.len() < ITEMS {
+            match consumer.pop() {
+                Some(value) => received.push(value),
+                None => thread::yield_now(),
+            }
+        }
+        producer.join().unwrap();
+
+        for (i, value) in received.iter().enumerate() {
+            assert_eq!(value, &i.to_string());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use crate::event::{InputEvent, TimedEvent};
+use crate::keys::{self, key_name};
+use crate::xkb_layout;
+
+// Every key of a class is replaced by one representative, so the event
+// stream keeps its shape and timing but not what was typed
+const LETTER_CODE: u32 = 30; // KEY_A
+const DIGIT_CODE: u32 = 11; // KEY_0
+const SYMBOL_CODE: u32 = 12; // KEY_MINUS
+
+// Key codes from linux/input-event-codes.h
+const LETTERS: [u32; 26] = [
+    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, // Q W E R T Y U I O P
+    30, 31, 32, 33, 34, 35, 36, 37, 38, // A S D F G H J K L
+    44, 45, 46, 47, 48, 49, 50, // Z X C V B N M
+];
+const DIGITS: [u32; 20] = [
+    2, 3, 4, 5, 6, 7, 8, 9, 10, 11, // 1-9, 0
+    71, 72, 73, 75, 76, 77, 79, 80, 81, 82, // keypad 7 8 9 4 5 6 1 2 3 0
+];
+const SYMBOLS: [u32; 19] = [
+    12, 13, 26, 27, 39, 40, 41, 43, 51, 52, 53, // - = [ ] ; ' ` \ , . /
+    55, 74, 78, 83, 86, 98, 117, 121, // keypad * - + . , 102nd key, keypad / = ,
+];
+
+// Rewrites events for --sanitize: character keys become their class and
+// device names become "Device 1", "Device 2", ... in order of appearance.
+// Modifiers, Space, Enter, function and navigation keys are kept, since
+// they're what most input bugs are about.
+#[derive(Default)]
+pub struct Sanitizer {
+    // Real device name -> alias
+    devices: HashMap<String, String>,
+}
+
+impl Sanitizer {
+    pub fn sanitize(&mut self, timed: &TimedEvent) -> TimedEvent {
+        let mut timed = timed.clone();
+        match &mut timed.event {
+            InputEvent::Key { code, .. } => {
+                if LETTERS.contains(code) {
+                    *code = LETTER_CODE;
+                } else if DIGITS.contains(code) {
+                    *code = DIGIT_CODE;
+                } else if SYMBOLS.contains(code) {
+                    *code = SYMBOL_CODE;
+                }
+            },
+            InputEvent::DeviceAdded { device, info } | InputEvent::DeviceRemoved { device, info } => {
+                // Binary recordings have no names to hide
+                if !device.is_empty() {
+                    *device = self.alias(device);
+                }
+                if let Some(info) = info {
+                    info.name = self.alias(&info.name);
+                }
+            },
+            // Debug dumps include device names and more
+            InputEvent::Other { debug, .. } => *debug = None,
+            _ => {},
+        }
+        timed
+    }
+
+    // Replace the device names seen so far, e.g. in libinput log messages
+    pub fn mask_text(&self, text: &str) -> String {
+        self.devices.iter().fold(text.to_string(), |masked, (name, alias)| masked.replace(name.as_str(), alias))
+    }
+
+    // "Device N" for a device name, the same one its events will get
+    pub fn alias(&mut self, name: &str) -> String {
+        let next = self.devices.len() + 1;
+        self.devices.entry(name.to_string()).or_insert_with(|| format!("Device {}", next)).clone()
+    }
+}
+
+// Name of a key in text output; with --sanitize, the representatives are
+// shown as their class. --alias labels come first, then with --xkb what the
+// key types in the user's layout.
+pub fn key_label(code: u32, sanitizing: bool) -> &'static str {
+    match (sanitizing, code) {
+        (true, LETTER_CODE) => "LETTER",
+        (true, DIGIT_CODE) => "DIGIT",
+        (true, SYMBOL_CODE) => "SYMBOL",
+        _ => match keys::alias(code) {
+            Some(alias) => alias,
+            None => xkb_layout::label(code).unwrap_or_else(|| key_name(code)),
+        },
+    }
+}
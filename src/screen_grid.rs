@@ -0,0 +1,58 @@
+// Divides the screen into a coarse grid (3×3 or 5×5) and tracks which cell
+// the cursor is in, so its rough position can be shown at a glance
+pub struct ScreenGrid {
+    width: f64,
+    height: f64,
+    size: u32,
+    active: Option<(u32, u32)>,
+}
+
+impl ScreenGrid {
+    pub fn new(width: u32, height: u32, size: u32) -> Self {
+        ScreenGrid { width: width as f64, height: height as f64, size, active: None }
+    }
+
+    // Move the cursor; returns the new (column, row) only when it entered a different cell
+    pub fn update(&mut self, x: f64, y: f64) -> Option<(u32, u32)> {
+        let cell = (self.index(x, self.width), self.index(y, self.height));
+        if self.active == Some(cell) {
+            return None;
+        }
+        self.active = Some(cell);
+        Some(cell)
+    }
+
+    // Positions off screen are clamped to the edge cells
+    fn index(&self, position: f64, extent: f64) -> u32 {
+        let fraction = (position / extent).clamp(0.0, 1.0);
+        ((fraction * self.size as f64) as u32).min(self.size - 1)
+    }
+
+    // One row per bracket group, the active cell filled: [··■] [···] [···]
+    pub fn render(&self) -> String {
+        (0..self.size)
+            .map(|row| {
+                let cells: String = (0..self.size)
+                    .map(|column| if self.active == Some((column, row)) { '■' } else { '·' })
+                    .collect();
+                format!("[{}]", cells)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // "top-left" style names on the 3×3 grid, row/column numbers otherwise
+    pub fn label(&self, (column, row): (u32, u32)) -> String {
+        if self.size == 3 {
+            let vertical = ["top", "middle", "bottom"][row as usize];
+            let horizontal = ["left", "center", "right"][column as usize];
+            if vertical == "middle" && horizontal == "center" {
+                "center".to_string()
+            } else {
+                format!("{}-{}", vertical, horizontal)
+            }
+        } else {
+            format!("row {}, column {}", row + 1, column + 1)
+        }
+    }
+}
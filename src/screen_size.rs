@@ -0,0 +1,45 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::parse_dimensions;
+
+const DRM_CLASS: &str = "/sys/class/drm";
+// Overrides detection without a command-line flag, e.g. from a desktop file
+const ENV_HINT: &str = "RUSTKEY_SCREEN";
+// For --follow-cursor and --record-mouse-path when nothing was detected
+pub const FALLBACK: (u32, u32) = (1920, 1080);
+
+// Resolution of the primary output, used when --screen isn't given. Reads
+// the connectors the kernel's DRM drivers expose in sysfs, so it works the
+// same under Wayland, X11 or on a bare console, without asking the
+// compositor. The primary output is the built-in panel if one is connected,
+// otherwise the first connected connector; its size is the preferred mode,
+// which is what the output runs at unless it was changed by hand.
+pub fn detect() -> Option<(u32, u32)> {
+    if let Some(hint) = env::var(ENV_HINT).ok().and_then(|value| parse_dimensions(&value)) {
+        return Some(hint);
+    }
+
+    let mut connectors: Vec<String> = fs::read_dir(DRM_CLASS).ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        // card0-eDP-1, card1-HDMI-A-2; plain card0 is the GPU itself
+        .filter(|name| name.contains('-'))
+        .filter(|name| read_attribute(name, "status").as_deref() == Some("connected"))
+        .collect();
+    connectors.sort_by_key(|name| (!is_built_in(name), name.clone()));
+
+    connectors.iter().find_map(|name| {
+        // Modes are listed preferred first, e.g. "2560x1600" (or "1920x1080i")
+        let modes = read_attribute(name, "modes")?;
+        parse_dimensions(modes.lines().next()?.trim_end_matches(|c: char| c.is_ascii_alphabetic()))
+    })
+}
+
+fn is_built_in(connector: &str) -> bool {
+    ["-eDP-", "-LVDS-", "-DSI-"].iter().any(|kind| connector.contains(kind))
+}
+
+fn read_attribute(connector: &str, attribute: &str) -> Option<String> {
+    fs::read_to_string(Path::new(DRM_CLASS).join(connector).join(attribute)).ok().map(|value| value.trim().to_string())
+}
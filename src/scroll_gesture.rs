@@ -0,0 +1,66 @@
+use crate::event::ScrollSource;
+
+// A burst of scroll events, summed up once scrolling pauses
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollGesture {
+    pub source: ScrollSource,
+    // Net raw scroll values; positive is down / right, as in libinput
+    pub horizontal: f64,
+    pub vertical: f64,
+    pub events: u32,
+    pub duration_usec: u64,
+}
+
+struct Burst {
+    gesture: ScrollGesture,
+    start_usec: u64,
+    last_usec: u64,
+}
+
+// Coalesces scroll events into gestures, ended by a pause of `debounce_usec`
+// or by a switch to a different scroll source
+pub struct ScrollCoalescer {
+    debounce_usec: u64,
+    burst: Option<Burst>,
+}
+
+impl ScrollCoalescer {
+    pub fn new(debounce_usec: u64) -> Self {
+        ScrollCoalescer { debounce_usec, burst: None }
+    }
+
+    // Add one scroll event; returns the previous gesture if this event ended it
+    pub fn push(&mut self, time_usec: u64, source: ScrollSource, horizontal: f64, vertical: f64) -> Option<ScrollGesture> {
+        let finished = match &self.burst {
+            Some(burst) if burst.gesture.source != source
+                || time_usec.saturating_sub(burst.last_usec) >= self.debounce_usec => self.flush(),
+            _ => None,
+        };
+
+        let burst = self.burst.get_or_insert(Burst {
+            gesture: ScrollGesture { source, horizontal: 0.0, vertical: 0.0, events: 0, duration_usec: 0 },
+            start_usec: time_usec,
+            last_usec: time_usec,
+        });
+        burst.gesture.horizontal += horizontal;
+        burst.gesture.vertical += vertical;
+        burst.gesture.events += 1;
+        burst.last_usec = time_usec;
+        burst.gesture.duration_usec = time_usec.saturating_sub(burst.start_usec);
+
+        finished
+    }
+
+    // Called periodically; returns the pending gesture once scrolling has paused
+    pub fn poll(&mut self, now_usec: u64) -> Option<ScrollGesture> {
+        match &self.burst {
+            Some(burst) if now_usec.saturating_sub(burst.last_usec) >= self.debounce_usec => self.flush(),
+            _ => None,
+        }
+    }
+
+    // End the pending gesture regardless of timing
+    pub fn flush(&mut self) -> Option<ScrollGesture> {
+        self.burst.take().map(|burst| burst.gesture)
+    }
+}
@@ -0,0 +1,124 @@
+// Steps of a glide slower than this fraction of the first one are dropped
+const MIN_VELOCITY_RATIO: f64 = 0.02;
+// Longest glide, in seconds; with no friction the scroll is spread evenly over it
+const MAX_GLIDE_SECS: f64 = 1.0;
+
+// One scroll split into steps that shrink by the friction each time
+struct Glide {
+    start_usec: u64,
+    // Fraction of the total for each step; they add up to 1
+    weights: Vec<f64>,
+    next: usize,
+    total: (f64, f64),
+    emitted: (f64, f64),
+}
+
+impl Glide {
+    fn remaining(&self) -> (f64, f64) {
+        (self.total.0 - self.emitted.0, self.total.1 - self.emitted.1)
+    }
+}
+
+// --smooth-scroll: turns each discrete scroll into a glide of smaller and
+// smaller steps, `rate` per second, the way browsers animate wheel
+// scrolling. Each step is the previous one times (1 - friction), so 0.0
+// keeps the speed constant and 1.0 stops after the first step. The glide
+// ends once a step would fall below 2% of the first, and the steps always
+// add up to exactly what was scrolled. Scrolling again mid-glide starts a
+// new glide with what was left of the old one added.
+pub struct ScrollSmoothingFilter {
+    period_usec: u64,
+    friction: f64,
+    glide: Option<Glide>,
+}
+
+impl ScrollSmoothingFilter {
+    pub fn new(rate: u32, friction: f64) -> Self {
+        ScrollSmoothingFilter { period_usec: 1_000_000 / u64::from(rate.max(1)), friction, glide: None }
+    }
+
+    pub fn push(&mut self, time_usec: u64, horizontal: f64, vertical: f64) {
+        let (left_h, left_v) = self.glide.as_ref().map_or((0.0, 0.0), Glide::remaining);
+        self.glide = Some(Glide {
+            start_usec: time_usec,
+            weights: self.weights(),
+            next: 0,
+            total: (horizontal + left_h, vertical + left_v),
+            emitted: (0.0, 0.0),
+        });
+    }
+
+    fn weights(&self) -> Vec<f64> {
+        let max_steps = (MAX_GLIDE_SECS * 1_000_000.0 / self.period_usec as f64).max(1.0) as usize;
+        let decay = 1.0 - self.friction;
+        let mut weights = vec![1.0];
+        while weights.len() < max_steps {
+            let next = weights[weights.len() - 1] * decay;
+            if next < MIN_VELOCITY_RATIO {
+                break;
+            }
+            weights.push(next);
+        }
+        let sum: f64 = weights.iter().sum();
+        weights.iter().map(|weight| weight / sum).collect()
+    }
+
+    // The steps due by `now_usec`, as (time, horizontal, vertical)
+    pub fn due(&mut self, now_usec: u64) -> Vec<(u64, f64, f64)> {
+        let mut steps = Vec::new();
+        let Some(glide) = self.glide.as_mut() else {
+            return steps;
+        };
+        while glide.next < glide.weights.len() {
+            let time_usec = glide.start_usec + glide.next as u64 * self.period_usec;
+            if time_usec > now_usec {
+                break;
+            }
+            // The last step takes whatever rounding left over
+            let (horizontal, vertical) = if glide.next + 1 == glide.weights.len() {
+                glide.remaining()
+            } else {
+                let weight = glide.weights[glide.next];
+                (glide.total.0 * weight, glide.total.1 * weight)
+            };
+            glide.emitted.0 += horizontal;
+            glide.emitted.1 += vertical;
+            glide.next += 1;
+            steps.push((time_usec, horizontal, vertical));
+        }
+        if glide.next == glide.weights.len() {
+            self.glide = None;
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glides_add_up_to_the_scroll() {
+        let mut filter = ScrollSmoothingFilter::new(60, 0.5);
+        filter.push(1_000_000, 0.0, 15.0);
+        let first = filter.due(1_000_000);
+        assert_eq!(first.len(), 1);
+        let rest = filter.due(u64::MAX);
+        // 1, 1/2, ... 1/32: six steps, each half the one before
+        assert_eq!(rest.len(), 5);
+        assert!((rest[0].2 * 2.0 - first[0].2).abs() < 1e-9);
+        assert_eq!(rest[0].0, 1_000_000 + 16_666);
+        let total: f64 = first.iter().chain(&rest).map(|step| step.2).sum();
+        assert!((total - 15.0).abs() < 1e-9);
+
+        // Instant stop, then no friction at all
+        let mut filter = ScrollSmoothingFilter::new(60, 1.0);
+        filter.push(0, 15.0, 0.0);
+        assert_eq!(filter.due(u64::MAX), vec![(0, 15.0, 0.0)]);
+        let mut filter = ScrollSmoothingFilter::new(60, 0.0);
+        filter.push(0, 0.0, -60.0);
+        let steps = filter.due(u64::MAX);
+        assert_eq!(steps.len(), 60);
+        assert!(steps.iter().all(|step| (step.2 + 1.0).abs() < 1e-9));
+    }
+}
@@ -0,0 +1,116 @@
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+use input::Libinput;
+
+use crate::doctor::{self, Probe};
+use crate::event::{self, InputEvent};
+use crate::hotplug::DeviceHotplugMonitor;
+use crate::libinput_log::{self, LogPriority};
+use crate::{wait_readable, Colors, Interface, SEAT};
+
+// How long the seat gets to announce its devices
+const DEVICE_WAIT: Duration = Duration::from_secs(2);
+// How long to wait for a key press or mouse movement
+const EVENT_WAIT: Duration = Duration::from_secs(5);
+
+// One line of the report
+fn check(passed: bool, label: &str, detail: &str) {
+    if passed {
+        println!("{}✅ PASS{}  {}: {}", Colors::GREEN, Colors::RESET, label, detail);
+    } else {
+        println!("{}❌ FAIL{}  {}: {}", Colors::RED, Colors::RESET, label, detail);
+    }
+}
+
+// Walk through what monitoring needs: readable devices, a seat, devices on it
+// and events arriving. Returns the process exit code: 0 if monitoring would
+// work, 1 if not. No input during the wait is reported but doesn't fail the
+// test, so it can run unattended during provisioning.
+pub fn run() -> i32 {
+    println!("{}{}RustKey self-test{}", Colors::CYAN, Colors::BOLD, Colors::RESET);
+
+    let probe = doctor::probe();
+    let readable = matches!(probe, Probe::Ok);
+    let detail = match probe {
+        Probe::Ok => "input devices are readable",
+        Probe::NoDevices => "no input devices",
+        Probe::Denied { .. } | Probe::Failed { .. } => "input devices can't be opened",
+    };
+    check(readable, "permissions", detail);
+    if !readable {
+        doctor::report(&probe);
+        return finish(false);
+    }
+
+    let mut input = Libinput::new_with_udev(Interface);
+    libinput_log::install(&input, LogPriority::Error);
+    let assigned = input.udev_assign_seat(SEAT).is_ok();
+    check(assigned, "seat", &if assigned { format!("assigned '{}'", SEAT) } else { format!("cannot assign '{}'", SEAT) });
+    if !assigned {
+        return finish(false);
+    }
+
+    // Devices are listed as they show up
+    let mut devices = DeviceHotplugMonitor::default();
+    devices.on_device_added(|name, capabilities| println!("     + {} ({})", name, capabilities.join(", ")));
+    devices.on_device_removed(|name| println!("     - {} (removed)", name));
+    let mut input_seen = collect(&mut input, &mut devices, DEVICE_WAIT);
+    if !devices.devices().is_empty() && !input_seen {
+        println!("   Press a key or move the mouse within {} seconds...", EVENT_WAIT.as_secs());
+        input_seen = collect(&mut input, &mut devices, EVENT_WAIT);
+    }
+
+    let found = !devices.devices().is_empty();
+    check(found, "devices", &format!("{} on '{}'", devices.devices().len(), SEAT));
+    for (_, message) in libinput_log::take_pending() {
+        println!("   {}[libinput] {}{}", Colors::GRAY, message, Colors::RESET);
+    }
+    if !found {
+        return finish(false);
+    }
+
+    if input_seen {
+        check(true, "events", "input received");
+    } else {
+        println!("{}⚠️ SKIP{}  events: no input within {} seconds (not counted as a failure)",
+            Colors::YELLOW, Colors::RESET, EVENT_WAIT.as_secs());
+    }
+    finish(true)
+}
+
+// Dispatch events for up to `wait`, following added and removed devices.
+// Returns true as soon as anything else (actual input) arrives.
+fn collect(input: &mut Libinput, devices: &mut DeviceHotplugMonitor, wait: Duration) -> bool {
+    let deadline = Instant::now() + wait;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || !wait_readable(input.as_raw_fd(), remaining.as_millis() as i32) {
+            return false;
+        }
+        if input.dispatch().is_err() {
+            return false;
+        }
+        let mut input_seen = false;
+        for raw in &mut *input {
+            let timed = event::decode(&raw, event::monotonic_usec());
+            match timed.event {
+                InputEvent::DeviceAdded { .. } | InputEvent::DeviceRemoved { .. } => devices.observe(&timed),
+                _ => input_seen = true,
+            }
+        }
+        if input_seen {
+            return true;
+        }
+    }
+}
+
+fn finish(passed: bool) -> i32 {
+    if passed {
+        println!("{}{}Self-test passed{}", Colors::GREEN, Colors::BOLD, Colors::RESET);
+        0
+    } else {
+        println!("{}{}Self-test failed{}", Colors::RED, Colors::BOLD, Colors::RESET);
+        1
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set from the signal handlers, polled by the main loop
+static EXIT_REQUESTED: AtomicBool = AtomicBool::new(false);
+static HISTORY_REQUESTED: AtomicBool = AtomicBool::new(false);
+static SUMMARY_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_exit_signal(_signum: libc::c_int) {
+    EXIT_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn handle_history_signal(_signum: libc::c_int) {
+    HISTORY_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn handle_summary_signal(_signum: libc::c_int) {
+    SUMMARY_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+// Install handlers so Ctrl+C / SIGTERM end the loop instead of killing the process,
+// giving exporters a chance to flush their files
+pub fn install_exit_handlers() {
+    let handler = handle_exit_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+// SIGUSR2 asks for the recent-events history to be printed
+pub fn install_history_handler() {
+    let handler = handle_history_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGUSR2, handler);
+    }
+}
+
+// SIGUSR1 asks for the --summary-out file to be written; without one the
+// signal keeps its default action
+pub fn install_summary_handler() {
+    let handler = handle_summary_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGUSR1, handler);
+    }
+}
+
+// True once per received SIGUSR1
+pub fn take_summary_request() -> bool {
+    SUMMARY_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+// True once per received SIGUSR2
+pub fn take_history_request() -> bool {
+    HISTORY_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+// Stop the main loop as SIGINT would
+pub fn request_exit() {
+    EXIT_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+pub fn exit_requested() -> bool {
+    EXIT_REQUESTED.load(Ordering::SeqCst)
+}
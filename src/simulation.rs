@@ -0,0 +1,296 @@
+// --simulate: plays a TOML scenario of synthetic events through the normal
+// pipeline, with real delays in between, to exercise the outputs (JSON, CSV,
+// recordings, FIFOs) without any hardware. For example:
+//
+//     seed = 7
+//
+//     [[events]]
+//     type = "key_tap"
+//     key = "H"
+//     delay_ms = { min = 30, max = 100 }
+//
+//     [[events]]
+//     type = "group"
+//     repeat = 3
+//     [[events.events]]
+//     type = "mouse_path"
+//     points = [[0, 0], [200, 100], [400, 0]]
+//     steps = 20
+//     delay_ms = 8
+//     [[events.events]]
+//     type = "click"
+//     button = "left"
+
+use std::path::Path;
+
+// Never constructed without the feature: `load` fails instead
+#[cfg_attr(not(feature = "simulation"), allow(dead_code))]
+pub struct ScenarioRunner {
+    #[cfg(feature = "simulation")]
+    scenario: enabled::Scenario,
+}
+
+#[cfg(feature = "simulation")]
+mod enabled {
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use serde::Deserialize;
+
+    use crate::button_names::button_code;
+    use crate::event::{self, InputEvent, ScrollSource, TimedEvent};
+    use crate::keys;
+    use crate::signals;
+    use crate::Monitor;
+
+    // Random delays come from this seed unless the scenario sets one, so a
+    // scenario always plays out the same
+    const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+    // How long key_tap and click hold the key or button down
+    const DEFAULT_HOLD_MS: u64 = 30;
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct Scenario {
+        seed: Option<u64>,
+        events: Vec<Step>,
+    }
+
+    // A fixed delay, or one drawn uniformly from a range
+    #[derive(Deserialize, Clone, Copy)]
+    #[serde(untagged)]
+    enum Delay {
+        Fixed(u64),
+        Random { min: u64, max: u64 },
+    }
+
+    #[derive(Deserialize)]
+    struct Step {
+        #[serde(flatten)]
+        action: Action,
+        // Wait before the step (and before each of its repeats)
+        delay_ms: Option<Delay>,
+        #[serde(default = "one")]
+        repeat: u32,
+    }
+
+    fn one() -> u32 {
+        1
+    }
+
+    // The step's own keys (delay_ms, repeat) are taken first; whatever is
+    // left has to belong to the action. deny_unknown_fields has no effect on
+    // a struct with a flattened field, so it's checked here instead.
+    #[derive(Deserialize)]
+    #[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+    enum Action {
+        KeyPress { key: String },
+        KeyRelease { key: String },
+        // Press and release
+        KeyTap { key: String, hold_ms: Option<Delay> },
+        ButtonPress { button: String },
+        ButtonRelease { button: String },
+        Click { button: String, hold_ms: Option<Delay> },
+        MouseMove { dx: f64, dy: f64 },
+        // Relative motion along the lines between the points, in `steps`
+        // moves per line; `delay_ms` is the wait between the moves
+        MousePath { points: Vec<[f64; 2]>, steps: Option<u32> },
+        Scroll {
+            #[serde(default)]
+            vertical: f64,
+            #[serde(default)]
+            horizontal: f64,
+        },
+        // Nested steps, usually with a `repeat`
+        Group { events: Vec<Step> },
+    }
+
+    impl Scenario {
+        // Catch unknown keys and buttons before anything is played
+        pub fn check(&self) -> Result<(), String> {
+            check_steps(&self.events)
+        }
+
+        pub fn play(&self, monitor: &mut Monitor) {
+            let mut player = Player { monitor, rng: self.seed.unwrap_or(DEFAULT_SEED).max(1), position: (0.0, 0.0) };
+            player.play(&self.events);
+        }
+    }
+
+    fn check_steps(steps: &[Step]) -> Result<(), String> {
+        for step in steps {
+            match &step.action {
+                Action::KeyPress { key } | Action::KeyRelease { key } | Action::KeyTap { key, .. } => {
+                    key_code(key)?;
+                },
+                Action::ButtonPress { button } | Action::ButtonRelease { button } | Action::Click { button, .. } => {
+                    button_code_of(button)?;
+                },
+                Action::MousePath { points, .. } if points.len() < 2 => {
+                    return Err("a mouse_path needs at least two points".to_string());
+                },
+                Action::Group { events } => check_steps(events)?,
+                _ => {},
+            }
+            if let Some(Delay::Random { min, max }) = step.delay_ms {
+                if min > max {
+                    return Err(format!("delay_ms has min {} above max {}", min, max));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn key_code(key: &str) -> Result<u32, String> {
+        keys::key_code(key).ok_or_else(|| format!("unknown key '{}'", key))
+    }
+
+    fn button_code_of(button: &str) -> Result<u32, String> {
+        button.parse().ok().or_else(|| button_code(button)).ok_or_else(|| format!("unknown button '{}'", button))
+    }
+
+    struct Player<'a> {
+        monitor: &'a mut Monitor,
+        // xorshift state for random delays
+        rng: u64,
+        // Where the mouse path left the pointer
+        position: (f64, f64),
+    }
+
+    impl Player<'_> {
+        // False once the run should stop
+        fn play(&mut self, steps: &[Step]) -> bool {
+            for step in steps {
+                for _ in 0..step.repeat {
+                    if let Some(delay) = step.delay_ms.filter(|_| !matches!(step.action, Action::MousePath { .. })) {
+                        self.wait(delay);
+                    }
+                    if !self.act(step) {
+                        return false;
+                    }
+                }
+            }
+            true
+        }
+
+        fn act(&mut self, step: &Step) -> bool {
+            // Names were checked up front
+            match &step.action {
+                Action::KeyPress { key } => self.emit(InputEvent::Key { code: key_code(key).unwrap(), pressed: true }),
+                Action::KeyRelease { key } => self.emit(InputEvent::Key { code: key_code(key).unwrap(), pressed: false }),
+                Action::KeyTap { key, hold_ms } => {
+                    let code = key_code(key).unwrap();
+                    if !self.emit(InputEvent::Key { code, pressed: true }) {
+                        return false;
+                    }
+                    self.wait(hold_ms.unwrap_or(Delay::Fixed(DEFAULT_HOLD_MS)));
+                    self.emit(InputEvent::Key { code, pressed: false })
+                },
+                Action::ButtonPress { button } => self.emit(InputEvent::PointerButton { button: button_code_of(button).unwrap(), pressed: true }),
+                Action::ButtonRelease { button } => self.emit(InputEvent::PointerButton { button: button_code_of(button).unwrap(), pressed: false }),
+                Action::Click { button, hold_ms } => {
+                    let button = button_code_of(button).unwrap();
+                    if !self.emit(InputEvent::PointerButton { button, pressed: true }) {
+                        return false;
+                    }
+                    self.wait(hold_ms.unwrap_or(Delay::Fixed(DEFAULT_HOLD_MS)));
+                    self.emit(InputEvent::PointerButton { button, pressed: false })
+                },
+                Action::MouseMove { dx, dy } => {
+                    self.position = (self.position.0 + dx, self.position.1 + dy);
+                    self.emit(InputEvent::PointerMotion { dx: *dx, dy: *dy })
+                },
+                Action::MousePath { points, steps } => self.mouse_path(points, steps.unwrap_or(10).max(1), step.delay_ms),
+                Action::Scroll { vertical, horizontal } => {
+                    self.emit(InputEvent::Scroll { source: ScrollSource::Wheel, horizontal: *horizontal, vertical: *vertical })
+                },
+                Action::Group { events } => self.play(events),
+            }
+        }
+
+        // Moves from the current position to the first point, then along the lines
+        fn mouse_path(&mut self, points: &[[f64; 2]], steps: u32, delay: Option<Delay>) -> bool {
+            for &[x, y] in points {
+                let (from_x, from_y) = self.position;
+                for step in 1..=steps {
+                    let fraction = step as f64 / steps as f64;
+                    let target = (from_x + (x - from_x) * fraction, from_y + (y - from_y) * fraction);
+                    let (dx, dy) = (target.0 - self.position.0, target.1 - self.position.1);
+                    if dx == 0.0 && dy == 0.0 {
+                        continue;
+                    }
+                    if let Some(delay) = delay {
+                        self.wait(delay);
+                    }
+                    self.position = target;
+                    if !self.emit(InputEvent::PointerMotion { dx, dy }) {
+                        return false;
+                    }
+                }
+            }
+            true
+        }
+
+        fn emit(&mut self, event: InputEvent) -> bool {
+            if signals::exit_requested() || self.monitor.limit_reached() {
+                return false;
+            }
+            self.monitor.handle_event(&TimedEvent { time_usec: event::monotonic_usec(), event, fingers: None, sysname: None, seat: None });
+            self.monitor.tick();
+            true
+        }
+
+        fn wait(&mut self, delay: Delay) {
+            let ms = match delay {
+                Delay::Fixed(ms) => ms,
+                Delay::Random { min, max } => min + self.next_random() % (max - min + 1),
+            };
+            sleep(Duration::from_millis(ms));
+        }
+
+        fn next_random(&mut self) -> u64 {
+            self.rng ^= self.rng << 13;
+            self.rng ^= self.rng >> 7;
+            self.rng ^= self.rng << 17;
+            self.rng
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn unknown_step_keys_are_rejected() {
+            let scenario = |step: &str| toml::from_str::<Scenario>(&format!("[[events]]\n{}", step));
+            assert!(scenario("type = \"key_tap\"\nkey = \"A\"\ndelay_ms = 5\nrepeat = 2").is_ok());
+            assert!(scenario("type = \"key_tap\"\nkey = \"A\"\ndelay = 5").is_err());
+            assert!(scenario("type = \"mouse_move\"\ndx = 1.0\ndy = 1.0\nsteps = 3").is_err());
+            assert!(scenario("type = \"group\"\n[[events.events]]\ntype = \"click\"\nbutton = \"left\"\nrepaet = 2").is_err());
+        }
+    }
+}
+
+impl ScenarioRunner {
+    #[cfg(feature = "simulation")]
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let scenario: enabled::Scenario = toml::from_str(&text).map_err(|err| err.to_string())?;
+        scenario.check()?;
+        Ok(ScenarioRunner { scenario })
+    }
+
+    #[cfg(not(feature = "simulation"))]
+    pub fn load(_path: &Path) -> Result<Self, String> {
+        Err("simulation support is not compiled in (rebuild with `--features simulation`)".to_string())
+    }
+
+    // Play the scenario once, until it ends, Ctrl+C or --max-events
+    #[cfg(feature = "simulation")]
+    pub fn run(&self, monitor: &mut crate::Monitor) {
+        self.scenario.play(monitor);
+    }
+
+    #[cfg(not(feature = "simulation"))]
+    pub fn run(&self, _monitor: &mut crate::Monitor) {}
+}
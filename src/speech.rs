@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+use std::process::{Child, Command, Stdio};
+
+use crate::button_names::button_name;
+use crate::keys::key_name;
+use crate::modifiers::Modifiers;
+
+const SPEECH_PROGRAM: &str = "espeak-ng";
+
+// Announces key presses (and optionally mouse buttons) through espeak-ng,
+// so users who can't see the screen can check their input arrives
+pub struct Speaker {
+    rate: Option<u32>,
+    mouse_buttons: bool,
+    // Keys currently down; a press of a key that's already down is a repeat
+    held: HashSet<u32>,
+    // The announcement still being spoken, cut off by the next one the way
+    // screen readers do, so fast typing doesn't queue up a backlog
+    speaking: Option<Child>,
+    failed: bool,
+}
+
+impl Speaker {
+    pub fn new(rate: Option<u32>, mouse_buttons: bool) -> Self {
+        Speaker { rate, mouse_buttons, held: HashSet::new(), speaking: None, failed: false }
+    }
+
+    // `modifiers` are the ones held before this key went down
+    pub fn key_pressed(&mut self, code: u32, modifiers: &Modifiers, is_modifier: bool) -> Result<(), String> {
+        if !self.held.insert(code) {
+            return Ok(());
+        }
+        let key = spoken_key(code);
+        if is_modifier || modifiers.is_empty() {
+            return self.say(&key);
+        }
+        let combo: Vec<String> = modifiers.label().split('+').map(spoken_word).collect();
+        self.say(&format!("{} {}", combo.join(" "), key))
+    }
+
+    pub fn key_released(&mut self, code: u32) {
+        self.held.remove(&code);
+    }
+
+    pub fn button_pressed(&mut self, button: u32) -> Result<(), String> {
+        if !self.mouse_buttons {
+            return Ok(());
+        }
+        let name = button_name(button).trim_start_matches("BTN_");
+        self.say(&format!("{} button", spoken_word(name)))
+    }
+
+    // Starts speaking without waiting for it to finish. The first failure
+    // (espeak-ng not installed) is reported once; later ones stay quiet.
+    fn say(&mut self, text: &str) -> Result<(), String> {
+        if self.failed {
+            return Ok(());
+        }
+        if let Some(mut previous) = self.speaking.take() {
+            // Cut off the previous announcement if it's still going; either way it's reaped
+            if let Ok(None) = previous.try_wait() {
+                let _ = previous.kill();
+                let _ = previous.wait();
+            }
+        }
+
+        let mut command = Command::new(SPEECH_PROGRAM);
+        if let Some(rate) = self.rate {
+            command.arg("-s").arg(rate.to_string());
+        }
+        // Without --punct, symbol keys such as "," would be read as silence
+        command.arg("--punct").arg(text).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+
+        match command.spawn() {
+            Ok(child) => {
+                self.speaking = Some(child);
+                Ok(())
+            },
+            Err(err) => {
+                self.failed = true;
+                Err(format!("cannot run {} for --screen-reader: {}", SPEECH_PROGRAM, err))
+            },
+        }
+    }
+}
+
+impl Drop for Speaker {
+    // Let the last announcement finish, so a short session isn't cut off
+    fn drop(&mut self) {
+        if let Some(mut child) = self.speaking.take() {
+            let _ = child.wait();
+        }
+    }
+}
+
+// "LEFT CTRL" -> "Left Control"; keys without a name are read as their code
+fn spoken_key(code: u32) -> String {
+    match key_name(code) {
+        "UNKNOWN KEY" => code.to_string(),
+        name => name.split(' ').map(spoken_word).collect::<Vec<_>>().join(" "),
+    }
+}
+
+fn spoken_word(word: &str) -> String {
+    match word {
+        "CTRL" => "Control".to_string(),
+        "ESC" => "Escape".to_string(),
+        _ => {
+            let mut chars = word.chars();
+            chars.next()
+                .map(|first| first.to_string() + &chars.as_str().to_lowercase())
+                .unwrap_or_default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names_are_spoken_as_words() {
+        assert_eq!(spoken_key(30), "A");
+        assert_eq!(spoken_key(29), "Left Control");
+        assert_eq!(spoken_key(1), "Escape");
+        assert_eq!(spoken_key(102), "Page Up");
+        assert_eq!(spoken_key(999), "999");
+        assert_eq!(spoken_word("SHIFT"), "Shift");
+        assert_eq!(spoken_word(""), "");
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use crate::Colors;
+#[cfg(feature = "sqlite")]
+use crate::event::{InputEvent, TimedEvent};
+#[cfg(feature = "sqlite")]
+use crate::keys::key_name;
+
+// Stored in `PRAGMA user_version`; bump it and add a migration when the schema changes
+#[cfg(feature = "sqlite")]
+const SCHEMA_VERSION: i32 = 1;
+
+// Inserts are grouped into transactions of this many rows
+#[cfg(feature = "sqlite")]
+const BATCH_SIZE: usize = 1000;
+
+#[cfg(feature = "sqlite")]
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY,
+        timestamp_us INTEGER NOT NULL,
+        event_type TEXT NOT NULL,
+        key_code INTEGER,
+        key_name TEXT,
+        button INTEGER,
+        x REAL,
+        y REAL,
+        dx REAL,
+        dy REAL,
+        scroll_x REAL,
+        scroll_y REAL,
+        device_name TEXT
+    );
+    CREATE INDEX IF NOT EXISTS events_timestamp ON events (timestamp_us);
+    CREATE INDEX IF NOT EXISTS events_type ON events (event_type);
+";
+
+#[cfg(feature = "sqlite")]
+const INSERT: &str = "
+    INSERT INTO events (timestamp_us, event_type, key_code, key_name, button, x, y, dx, dy, scroll_x, scroll_y, device_name)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+";
+
+// Options for `rustkey query <db> [<where clause>]`
+#[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+pub struct QueryOptions {
+    pub database: PathBuf,
+    pub filter: Option<String>,
+}
+
+// One row per decoded event in a SQLite database
+#[cfg(feature = "sqlite")]
+pub struct SqliteLog {
+    conn: rusqlite::Connection,
+    // Rows inserted in the open transaction
+    pending: usize,
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub struct SqliteLog;
+
+#[cfg(feature = "sqlite")]
+impl SqliteLog {
+    // Open or create the database and start the first batch
+    pub fn open(path: &std::path::Path) -> Result<SqliteLog, String> {
+        let conn = rusqlite::Connection::open(path).map_err(|err| err.to_string())?;
+
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|err| err.to_string())?;
+        if version > SCHEMA_VERSION {
+            return Err(format!("database schema version {} is newer than this rustkey supports ({})",
+                version, SCHEMA_VERSION));
+        }
+        conn.execute_batch(SCHEMA).map_err(|err| err.to_string())?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(|err| err.to_string())?;
+        conn.execute_batch("BEGIN").map_err(|err| err.to_string())?;
+
+        Ok(SqliteLog { conn, pending: 0 })
+    }
+
+    pub fn insert(&mut self, timed: &TimedEvent) -> Result<(), String> {
+        let mut key_code = None;
+        let mut button = None;
+        let (mut x, mut y, mut dx, mut dy, mut scroll_x, mut scroll_y) = (None, None, None, None, None, None);
+        let mut device_name = None;
+        match &timed.event {
+            InputEvent::Key { code, .. } => key_code = Some(*code),
+            InputEvent::PointerButton { button: code, .. } => button = Some(*code),
+            InputEvent::PointerMotion { dx: motion_x, dy: motion_y } => {
+                dx = Some(*motion_x);
+                dy = Some(*motion_y);
+            },
+            InputEvent::PointerMotionAbsolute { x: position_x, y: position_y } => {
+                x = Some(*position_x);
+                y = Some(*position_y);
+            },
+            InputEvent::Scroll { horizontal, vertical, .. } => {
+                scroll_x = Some(*horizontal);
+                scroll_y = Some(*vertical);
+            },
+            InputEvent::DeviceAdded { device, .. } | InputEvent::DeviceRemoved { device, .. } => {
+                device_name = Some(device.as_str());
+            },
+            _ => {},
+        }
+        // Same names as the JSON "type" field
+        let event_type = serde_json::to_value(&timed.event).ok()
+            .and_then(|value| value["type"].as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        let mut statement = self.conn.prepare_cached(INSERT).map_err(|err| err.to_string())?;
+        statement.execute(rusqlite::params![
+            timed.time_usec as i64, event_type, key_code, key_code.map(key_name), button,
+            x, y, dx, dy, scroll_x, scroll_y, device_name,
+        ]).map_err(|err| err.to_string())?;
+        drop(statement);
+
+        self.pending += 1;
+        if self.pending >= BATCH_SIZE {
+            self.conn.execute_batch("COMMIT; BEGIN").map_err(|err| err.to_string())?;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    // Commit the last batch
+    pub fn finish(self) -> Result<(), String> {
+        self.conn.execute_batch("COMMIT").map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+impl SqliteLog {
+    pub fn open(_path: &std::path::Path) -> Result<SqliteLog, String> {
+        Err("SQLite support is not compiled in (rebuild with `--features sqlite`)".to_string())
+    }
+
+    pub fn insert(&mut self, _timed: &crate::event::TimedEvent) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Print the events matching a WHERE clause as JSON lines; returns the process exit code
+pub fn run_query(options: &QueryOptions) -> i32 {
+    match query(options) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+            2
+        },
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn query(options: &QueryOptions) -> Result<(), String> {
+    use rusqlite::types::ValueRef;
+    use rusqlite::OpenFlags;
+    use serde_json::{Map, Value};
+
+    // Read-only, so the clause can't change the log
+    let conn = rusqlite::Connection::open_with_flags(&options.database, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| format!("cannot open {}: {}", options.database.display(), err))?;
+    let sql = match &options.filter {
+        Some(clause) => format!("SELECT * FROM events WHERE {} ORDER BY id", clause),
+        None => "SELECT * FROM events ORDER BY id".to_string(),
+    };
+
+    let mut statement = conn.prepare(&sql).map_err(|err| format!("invalid query: {}", err))?;
+    let columns: Vec<String> = statement.column_names().iter().map(|name| name.to_string()).collect();
+    let mut rows = statement.query([]).map_err(|err| err.to_string())?;
+
+    while let Some(row) = rows.next().map_err(|err| err.to_string())? {
+        let mut object = Map::new();
+        for (index, column) in columns.iter().enumerate() {
+            let value = match row.get_ref(index).map_err(|err| err.to_string())? {
+                ValueRef::Null => continue,
+                ValueRef::Integer(number) => Value::from(number),
+                ValueRef::Real(number) => Value::from(number),
+                ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).into_owned()),
+                ValueRef::Blob(_) => continue,
+            };
+            object.insert(column.clone(), value);
+        }
+        println!("{}", Value::Object(object));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn query(_options: &QueryOptions) -> Result<(), String> {
+    Err("SQLite support is not compiled in (rebuild with `--features sqlite`)".to_string())
+}
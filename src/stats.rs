@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::bigrams::BigramCounter;
+use crate::combo_stats::ComboFrequencyMap;
+use crate::key_freq::KeyFrequencyMap;
+use crate::priority::EventClass;
+use crate::rhythm::TypingRhythmAnalyzer;
+use crate::strokes::StrokeCounter;
+
+// Rolling window used for the live words-per-minute figure
+const WPM_WINDOW_USEC: u64 = 60_000_000;
+// Standard WPM convention: five key presses make one "word"
+const CHARS_PER_WORD: f64 = 5.0;
+// Clicks per second are counted over the last second
+const CPS_WINDOW_USEC: u64 = 1_000_000;
+// Lower bounds of the events-per-second buckets, roughly log-scaled
+const RATE_BUCKETS: [u64; 9] = [0, 1, 2, 5, 10, 20, 50, 100, 200];
+pub const RATE_BUCKET_LABELS: [&str; 9] = ["0", "1", "2-4", "5-9", "10-19", "20-49", "50-99", "100-199", "200+"];
+
+// Timestamps from the last `window_usec`, for live rates like WPM and CPS
+pub struct RollingWindow {
+    window_usec: u64,
+    times: VecDeque<u64>,
+}
+
+impl RollingWindow {
+    pub fn new(window_usec: u64) -> Self {
+        RollingWindow { window_usec, times: VecDeque::new() }
+    }
+
+    // Add a timestamp and forget the ones that fell out of the window
+    pub fn push(&mut self, time_usec: u64) {
+        self.times.push_back(time_usec);
+        while let Some(&oldest) = self.times.front() {
+            if time_usec.saturating_sub(oldest) > self.window_usec {
+                self.times.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+}
+
+// Keystroke timing derived from libinput's per-event microsecond timestamps
+pub struct TypingStats {
+    idle_threshold_usec: u64,
+    recent_presses: RollingWindow,
+    // Most presses the rolling window ever held
+    peak_presses: usize,
+    last_press: Option<u64>,
+    gap_count: u64,
+    gap_total_usec: u64,
+    longest_gap_usec: u64,
+}
+
+impl TypingStats {
+    pub fn new(idle_threshold_usec: u64) -> Self {
+        TypingStats {
+            idle_threshold_usec,
+            recent_presses: RollingWindow::new(WPM_WINDOW_USEC),
+            peak_presses: 0,
+            last_press: None,
+            gap_count: 0,
+            gap_total_usec: 0,
+            longest_gap_usec: 0,
+        }
+    }
+
+    pub fn record_press(&mut self, time_usec: u64) {
+        if let Some(last) = self.last_press {
+            let gap = time_usec.saturating_sub(last);
+            // Gaps spanning an idle period (e.g. a lunch break) aren't typing rhythm
+            if gap <= self.idle_threshold_usec {
+                self.gap_count += 1;
+                self.gap_total_usec += gap;
+                self.longest_gap_usec = self.longest_gap_usec.max(gap);
+            }
+        }
+        self.last_press = Some(time_usec);
+
+        self.recent_presses.push(time_usec);
+        self.peak_presses = self.peak_presses.max(self.recent_presses.len());
+    }
+
+    // Words per minute over the last minute of typing
+    pub fn wpm(&self) -> f64 {
+        self.recent_presses.len() as f64 / CHARS_PER_WORD
+    }
+
+    // Highest reading the rolling WPM meter reached
+    pub fn peak_wpm(&self) -> f64 {
+        self.peak_presses as f64 / CHARS_PER_WORD
+    }
+
+    // Words per minute over active typing time: the gaps between presses,
+    // leaving out idle periods. None until at least a second of typing.
+    pub fn average_wpm(&self) -> Option<f64> {
+        let minutes = self.gap_total_usec as f64 / 60_000_000.0;
+        (self.gap_total_usec >= 1_000_000).then(|| self.gap_count as f64 / CHARS_PER_WORD / minutes)
+    }
+
+    pub fn longest_gap_ms(&self) -> Option<f64> {
+        (self.gap_count > 0).then(|| self.longest_gap_usec as f64 / 1000.0)
+    }
+
+    pub fn mean_gap_ms(&self) -> Option<f64> {
+        (self.gap_count > 0).then(|| self.gap_total_usec as f64 / self.gap_count as f64 / 1000.0)
+    }
+}
+
+// Mouse clicks per second, overall and per button, with the session peaks
+pub struct ClickRate {
+    recent: RollingWindow,
+    peak: usize,
+    per_button: BTreeMap<u32, (RollingWindow, usize)>,
+}
+
+impl ClickRate {
+    pub fn new() -> Self {
+        ClickRate { recent: RollingWindow::new(CPS_WINDOW_USEC), peak: 0, per_button: BTreeMap::new() }
+    }
+
+    pub fn record_press(&mut self, button: u32, time_usec: u64) {
+        self.recent.push(time_usec);
+        self.peak = self.peak.max(self.recent.len());
+
+        let (recent, peak) = self.per_button.entry(button)
+            .or_insert_with(|| (RollingWindow::new(CPS_WINDOW_USEC), 0));
+        recent.push(time_usec);
+        *peak = (*peak).max(recent.len());
+    }
+
+    // Highest number of clicks within one second
+    pub fn peak_cps(&self) -> usize {
+        self.peak
+    }
+
+    // Per-button peaks, by button code
+    pub fn button_peaks(&self) -> Vec<(u32, usize)> {
+        self.per_button.iter().map(|(&button, &(_, peak))| (button, peak)).collect()
+    }
+}
+
+// How many seconds of the session saw how many events, overall and per
+// event category. Quiet seconds between events count in the overall
+// histogram, so lulls show up next to bursts; a category's own histogram
+// only counts the seconds it was active in.
+pub struct EventRateHistogram {
+    // The second being counted (event time / 1 s) and its counts so far
+    current: Option<u64>,
+    total: u64,
+    per_class: [u64; EventClass::ALL.len()],
+    seconds: [u64; RATE_BUCKETS.len()],
+    class_seconds: [[u64; RATE_BUCKETS.len()]; EventClass::ALL.len()],
+}
+
+impl EventRateHistogram {
+    pub fn new() -> Self {
+        EventRateHistogram {
+            current: None,
+            total: 0,
+            per_class: [0; EventClass::ALL.len()],
+            seconds: [0; RATE_BUCKETS.len()],
+            class_seconds: [[0; RATE_BUCKETS.len()]; EventClass::ALL.len()],
+        }
+    }
+
+    pub fn record(&mut self, time_usec: u64, class: EventClass) {
+        let second = time_usec / 1_000_000;
+        match self.current {
+            Some(current) if second > current => {
+                self.close_second();
+                // Seconds without a single event
+                self.seconds[0] += second - current - 1;
+                self.current = Some(second);
+            },
+            Some(_) => {},
+            None => self.current = Some(second),
+        }
+        self.total += 1;
+        self.per_class[class_index(class)] += 1;
+    }
+
+    fn close_second(&mut self) {
+        self.seconds[rate_bucket(self.total)] += 1;
+        for (class, count) in self.per_class.iter().enumerate().filter(|(_, count)| **count > 0) {
+            self.class_seconds[class][rate_bucket(*count)] += 1;
+        }
+        self.total = 0;
+        self.per_class = [0; EventClass::ALL.len()];
+    }
+
+    // Seconds per bucket, the one in progress included
+    pub fn seconds(&self) -> [u64; RATE_BUCKETS.len()] {
+        let mut seconds = self.seconds;
+        if self.current.is_some() {
+            seconds[rate_bucket(self.total)] += 1;
+        }
+        seconds
+    }
+
+    // Seconds per bucket for each category that had any events
+    pub fn class_seconds(&self) -> Vec<(EventClass, [u64; RATE_BUCKETS.len()])> {
+        EventClass::ALL.iter().enumerate()
+            .map(|(index, class)| {
+                let mut seconds = self.class_seconds[index];
+                if self.per_class[index] > 0 {
+                    seconds[rate_bucket(self.per_class[index])] += 1;
+                }
+                (*class, seconds)
+            })
+            .filter(|(_, seconds)| seconds.iter().any(|count| *count > 0))
+            .collect()
+    }
+}
+
+fn rate_bucket(events: u64) -> usize {
+    RATE_BUCKETS.iter().rposition(|low| events >= *low).unwrap_or(0)
+}
+
+fn class_index(class: EventClass) -> usize {
+    EventClass::ALL.iter().position(|each| *each == class).unwrap_or(0)
+}
+
+// One stretch of activity without a gap longer than the session gap
+#[derive(Debug, Clone, Copy)]
+pub struct ActivitySession {
+    pub start_usec: u64,
+    pub end_usec: u64,
+    pub key_presses: u64,
+}
+
+impl ActivitySession {
+    pub fn duration_usec(&self) -> u64 {
+        self.end_usec - self.start_usec
+    }
+
+    // Average over the session; None if it was too short to tell
+    pub fn wpm(&self) -> Option<f64> {
+        let minutes = self.duration_usec() as f64 / 60_000_000.0;
+        (self.duration_usec() >= 1_000_000).then(|| self.key_presses as f64 / CHARS_PER_WORD / minutes)
+    }
+}
+
+// Splits a long run into work periods: input after a gap longer than
+// `gap_usec` starts a new session
+pub struct ActivitySessions {
+    gap_usec: u64,
+    sessions: Vec<ActivitySession>,
+}
+
+impl ActivitySessions {
+    pub fn new(gap_usec: u64) -> Self {
+        ActivitySessions { gap_usec, sessions: Vec::new() }
+    }
+
+    pub fn record(&mut self, time_usec: u64, key_press: bool) {
+        let resumed = match self.sessions.last() {
+            Some(session) => time_usec.saturating_sub(session.end_usec) > self.gap_usec,
+            None => true,
+        };
+        if resumed {
+            self.sessions.push(ActivitySession { start_usec: time_usec, end_usec: time_usec, key_presses: 0 });
+        }
+
+        let session = self.sessions.last_mut().unwrap();
+        session.end_usec = session.end_usec.max(time_usec);
+        if key_press {
+            session.key_presses += 1;
+        }
+    }
+
+    pub fn sessions(&self) -> &[ActivitySession] {
+        &self.sessions
+    }
+
+    pub fn gap_usec(&self) -> u64 {
+        self.gap_usec
+    }
+}
+
+// Counters accumulated over the whole session
+pub struct SessionStats {
+    pub key_presses: u64,
+    pub mouse_clicks: u64,
+    // Total relative pointer motion, in device counts
+    pub mouse_distance: f64,
+    pub typing: TypingStats,
+    pub rhythm: TypingRhythmAnalyzer,
+    pub key_frequency: KeyFrequencyMap,
+    pub bigrams: BigramCounter,
+    pub strokes: StrokeCounter,
+    pub clicks: ClickRate,
+    pub sessions: ActivitySessions,
+    pub rates: EventRateHistogram,
+    pub combos: ComboFrequencyMap,
+}
+
+impl SessionStats {
+    pub fn new(idle_threshold_usec: u64, session_gap_usec: u64) -> Self {
+        SessionStats {
+            key_presses: 0,
+            mouse_clicks: 0,
+            mouse_distance: 0.0,
+            typing: TypingStats::new(idle_threshold_usec),
+            rhythm: TypingRhythmAnalyzer::new(idle_threshold_usec),
+            key_frequency: KeyFrequencyMap::new(),
+            bigrams: BigramCounter::new(idle_threshold_usec),
+            strokes: StrokeCounter::default(),
+            clicks: ClickRate::new(),
+            sessions: ActivitySessions::new(session_gap_usec),
+            rates: EventRateHistogram::new(),
+            combos: ComboFrequencyMap::default(),
+        }
+    }
+
+    // Empty counters with the same thresholds
+    pub fn fresh(&self) -> Self {
+        SessionStats::new(self.typing.idle_threshold_usec, self.sessions.gap_usec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_histogram_counts_seconds() {
+        let mut rates = EventRateHistogram::new();
+        // Second 10: three keys and a click; 11 and 12 quiet; 13: one motion
+        for time_usec in [10_000_000, 10_200_000, 10_900_000] {
+            rates.record(time_usec, EventClass::Keyboard);
+        }
+        rates.record(10_500_000, EventClass::Button);
+        rates.record(13_100_000, EventClass::Motion);
+
+        assert_eq!(rates.seconds(), [2, 1, 1, 0, 0, 0, 0, 0, 0]);
+        let classes = rates.class_seconds();
+        assert_eq!(classes.len(), 3);
+        assert_eq!(classes[0], (EventClass::Keyboard, [0, 0, 1, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(classes[2], (EventClass::Motion, [0, 1, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(rate_bucket(250), 8);
+    }
+}
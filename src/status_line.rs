@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+
+use crate::keys::key_name;
+use crate::lock_state::LockState;
+use crate::modifiers::Modifiers;
+use crate::rhythm::TypingMode;
+use crate::strokes::StrokeCounter;
+use crate::Colors;
+
+// Redraw at most this often; motion events can arrive at 1000 Hz
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+// Cells of the keystroke category bar
+const STROKE_BAR_WIDTH: usize = 10;
+
+// --status-line: a single line of running totals rewritten in place,
+// instead of a line per event
+#[derive(Default)]
+pub struct StatusLine {
+    last_key: Option<u32>,
+    // Only shown once a touchpad reported its fingers
+    fingers: Option<u32>,
+    typing_mode: Option<TypingMode>,
+    // Only shown with --show-modifiers
+    modifiers: Option<Modifiers>,
+    locks: LockState,
+    last_draw: Option<Instant>,
+}
+
+impl StatusLine {
+    pub fn key_pressed(&mut self, code: u32) {
+        self.last_key = Some(code);
+    }
+
+    pub fn set_fingers(&mut self, fingers: u32) {
+        self.fingers = Some(fingers);
+    }
+
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = Some(modifiers);
+    }
+
+    pub fn set_locks(&mut self, locks: LockState) {
+        self.locks = locks;
+    }
+
+    pub fn set_typing_mode(&mut self, mode: TypingMode) {
+        self.typing_mode = Some(mode);
+    }
+
+    // True if the line should be redrawn now
+    pub fn due(&mut self) -> bool {
+        let now = Instant::now();
+        if self.last_draw.is_some_and(|last| now.duration_since(last) < REDRAW_INTERVAL) {
+            return false;
+        }
+        self.last_draw = Some(now);
+        true
+    }
+
+    pub fn render(&self, key_presses: u64, mouse_clicks: u64, wpm: f64, strokes: &StrokeCounter) -> String {
+        let last_key = self.last_key.map_or("-", key_name);
+        let mut line = format!("⌨️  {}{}{} keys  🖱️  {}{}{} clicks  {}{:.1}{} WPM  last key: {}{}{}",
+            Colors::YELLOW, key_presses, Colors::RESET,
+            Colors::MAGENTA, mouse_clicks, Colors::RESET,
+            Colors::CYAN, wpm, Colors::RESET,
+            Colors::YELLOW, last_key, Colors::RESET);
+        // Productive, delete, navigation, modifier, shortcut and whitespace
+        // shares, in their summary colors
+        if strokes.total() > 0 {
+            line.push_str(&format!("  {}", strokes.bar(STROKE_BAR_WIDTH)));
+        }
+        if let Some(mode) = self.typing_mode {
+            line.push_str(&format!("  {}{}{}", Colors::GREEN, mode, Colors::RESET));
+        }
+        if let Some(modifiers) = self.modifiers {
+            line.push_str(&format!("  {}", modifiers.indicator()));
+        }
+        line.push_str(&format!("  💡 {}", self.locks.indicator()));
+        if let Some(fingers) = self.fingers {
+            line.push_str(&format!("  👆 {}{}{} fingers", Colors::MAGENTA, fingers, Colors::RESET));
+        }
+        line
+    }
+}
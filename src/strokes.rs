@@ -0,0 +1,197 @@
+use crate::keys::is_character_key;
+use crate::modifiers::Modifiers;
+use crate::Colors;
+
+const KEY_SPACE: u32 = 57;
+
+// What a key press was for, for typing ergonomics: how much of the typing
+// produced text and how much went into correcting and moving around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrokeCategory {
+    // Letters, digits and punctuation, including the keypad
+    Productive,
+    // Backspace, Delete
+    Delete,
+    // Arrow keys, Home, End, Page Up, Page Down
+    Navigation,
+    // A modifier pressed and released on its own
+    ModifierOnly,
+    // Any key with Ctrl, Alt or Super held
+    Shortcut,
+    // Space, Tab, Enter
+    Whitespace,
+}
+
+impl StrokeCategory {
+    pub const ALL: [StrokeCategory; 6] = [
+        StrokeCategory::Productive, StrokeCategory::Delete, StrokeCategory::Navigation,
+        StrokeCategory::ModifierOnly, StrokeCategory::Shortcut, StrokeCategory::Whitespace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StrokeCategory::Productive => "PRODUCTIVE",
+            StrokeCategory::Delete => "DELETE",
+            StrokeCategory::Navigation => "NAVIGATION",
+            StrokeCategory::ModifierOnly => "MODIFIER_ONLY",
+            StrokeCategory::Shortcut => "SHORTCUT",
+            StrokeCategory::Whitespace => "WHITESPACE",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            StrokeCategory::Productive => Colors::GREEN,
+            StrokeCategory::Delete => Colors::RED,
+            StrokeCategory::Navigation => Colors::CYAN,
+            StrokeCategory::ModifierOnly => Colors::GRAY,
+            StrokeCategory::Shortcut => Colors::MAGENTA,
+            StrokeCategory::Whitespace => Colors::BLUE,
+        }
+    }
+
+    // Categories that don't depend on the modifiers; function keys, Escape
+    // and the like have none
+    fn of_key(code: u32) -> Option<StrokeCategory> {
+        match code {
+            14 | 111 => Some(StrokeCategory::Delete),
+            102..=109 => Some(StrokeCategory::Navigation),
+            KEY_SPACE | 15 | 28 | 96 => Some(StrokeCategory::Whitespace),
+            // Keypad digits, operators and separators
+            55 | 71..=83 | 98 | 117 | 121 => Some(StrokeCategory::Productive),
+            code if is_character_key(code) => Some(StrokeCategory::Productive),
+            _ => None,
+        }
+    }
+}
+
+// Counts key presses per StrokeCategory
+#[derive(Debug, Default)]
+pub struct StrokeCounter {
+    counts: [u64; 6],
+    // A key was pressed while the current modifiers were held, so releasing
+    // them isn't a modifier on its own
+    chorded: bool,
+}
+
+impl StrokeCounter {
+    // `held` is the modifiers held before this key event. Returns the
+    // category the event was counted under, if any.
+    pub fn record(&mut self, code: u32, pressed: bool, held: Modifiers) -> Option<StrokeCategory> {
+        let mut after = held;
+        if after.update(code, pressed) {
+            if pressed {
+                if held.is_empty() {
+                    self.chorded = false;
+                }
+                return None;
+            }
+            // Counted once the last modifier is released
+            if !after.is_empty() || self.chorded {
+                return None;
+            }
+            return Some(self.add(StrokeCategory::ModifierOnly));
+        }
+        if !pressed {
+            return None;
+        }
+
+        if !held.is_empty() {
+            self.chorded = true;
+        }
+        let category = if held.shortcut_held() { StrokeCategory::Shortcut } else { StrokeCategory::of_key(code)? };
+        Some(self.add(category))
+    }
+
+    pub fn get(&self, category: StrokeCategory) -> u64 {
+        self.counts[category as usize]
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    // PRODUCTIVE / (PRODUCTIVE + DELETE), once anything was typed or deleted
+    pub fn productivity(&self) -> Option<f64> {
+        let productive = self.get(StrokeCategory::Productive);
+        let typed = productive + self.get(StrokeCategory::Delete);
+        (typed > 0).then(|| productive as f64 / typed as f64)
+    }
+
+    // Horizontal bar `width` cells wide, split between the categories in
+    // proportion to their counts
+    pub fn bar(&self, width: usize) -> String {
+        let total = self.total();
+        if total == 0 {
+            return String::new();
+        }
+        let mut bar = String::new();
+        let mut counted = 0;
+        let mut drawn = 0;
+        for category in StrokeCategory::ALL {
+            counted += self.get(category);
+            // Round the running total, so the cells always add up to `width`
+            let end = ((counted * width as u64 + total / 2) / total) as usize;
+            if end > drawn {
+                bar.push_str(&format!("{}{}{}", category.color(), "█".repeat(end - drawn), Colors::RESET));
+                drawn = end;
+            }
+        }
+        bar
+    }
+
+    pub fn add(&mut self, category: StrokeCategory) -> StrokeCategory {
+        self.counts[category as usize] += 1;
+        category
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: u32 = 30;
+    const KEY_C: u32 = 46;
+    const KEY_LEFTCTRL: u32 = 29;
+    const KEY_LEFTSHIFT: u32 = 42;
+    const KEY_BACKSPACE: u32 = 14;
+
+    // Presses and releases `codes` in order, then releases them in reverse
+    fn chord(counter: &mut StrokeCounter, held: &mut Modifiers, codes: &[u32]) -> Vec<StrokeCategory> {
+        let events = codes.iter().map(|code| (*code, true)).chain(codes.iter().rev().map(|code| (*code, false)));
+        events.filter_map(|(code, pressed)| {
+            let category = counter.record(code, pressed, *held);
+            held.update(code, pressed);
+            category
+        }).collect()
+    }
+
+    #[test]
+    fn categorizes_chords() {
+        let mut counter = StrokeCounter::default();
+        let mut held = Modifiers::default();
+
+        assert_eq!(chord(&mut counter, &mut held, &[KEY_A]), [StrokeCategory::Productive]);
+        // A capital letter is still typing; the Shift isn't counted on its own
+        assert_eq!(chord(&mut counter, &mut held, &[KEY_LEFTSHIFT, KEY_A]), [StrokeCategory::Productive]);
+        assert_eq!(chord(&mut counter, &mut held, &[KEY_LEFTCTRL, KEY_C]), [StrokeCategory::Shortcut]);
+        assert_eq!(chord(&mut counter, &mut held, &[KEY_LEFTCTRL, KEY_LEFTSHIFT]), [StrokeCategory::ModifierOnly]);
+        assert_eq!(chord(&mut counter, &mut held, &[KEY_BACKSPACE]), [StrokeCategory::Delete]);
+        assert_eq!(chord(&mut counter, &mut held, &[KEY_SPACE]), [StrokeCategory::Whitespace]);
+
+        assert_eq!(counter.total(), 6);
+        assert_eq!(counter.productivity(), Some(2.0 / 3.0));
+    }
+
+    #[test]
+    fn bar_cells_add_up() {
+        let mut counter = StrokeCounter::default();
+        let held = Modifiers::default();
+        for code in [KEY_A, KEY_A, KEY_A, KEY_BACKSPACE, KEY_SPACE, KEY_SPACE, 103] {
+            counter.record(code, true, held);
+        }
+        let cells = crate::output::strip_ansi(&counter.bar(10)).chars().count();
+        assert_eq!(cells, 10);
+        assert_eq!(StrokeCounter::default().bar(10), "");
+    }
+}
@@ -0,0 +1,58 @@
+use input::event::tablet_pad::{ButtonState, TabletPadEvent, TabletPadEventTrait};
+
+use crate::event::{self, InputEvent};
+
+// Cells in a ring / strip gauge
+const GAUGE_WIDTH: usize = 8;
+
+// Decode the buttons, rings and strips of a tablet pad (e.g. Wacom ExpressKeys).
+// Buttons are numbered from 0 and carry no meaning of their own; `mode` is
+// the mode the control was in (0 on pads without modes), `group` the mode
+// group the button belongs to.
+pub fn decode(pad_event: &TabletPadEvent) -> InputEvent {
+    match pad_event {
+        TabletPadEvent::Button(button) => InputEvent::PadButton {
+            button: button.button_number(),
+            pressed: button.button_state() == ButtonState::Pressed,
+            mode: button.mode(),
+            group: button.mode_group().index(),
+        },
+        TabletPadEvent::Ring(ring) => InputEvent::PadRing {
+            number: ring.number(),
+            position: lifted_as_none(ring.position()),
+            mode: ring.mode(),
+        },
+        TabletPadEvent::Strip(strip) => InputEvent::PadStrip {
+            number: strip.number(),
+            position: lifted_as_none(strip.position()),
+            mode: strip.mode(),
+        },
+        other => event::other_event("tablet_pad", "Key", other),
+    }
+}
+
+// libinput ends a finger interaction with a position of -1
+fn lifted_as_none(position: f64) -> Option<f64> {
+    (position >= 0.0).then_some(position)
+}
+
+// e.g. "RING 0: ████░░░░ 45.2°"; the ring's angle runs counterclockwise from the top
+pub fn ring_gauge(number: u32, degrees: Option<f64>) -> String {
+    match degrees {
+        Some(degrees) => format!("RING {}: {} {:.1}°", number, gauge(degrees / 360.0), degrees),
+        None => format!("RING {}: released", number),
+    }
+}
+
+// e.g. "STRIP 1: ██░░░░░░ 0.25"; 0 is the top (or left) end
+pub fn strip_gauge(number: u32, position: Option<f64>) -> String {
+    match position {
+        Some(position) => format!("STRIP {}: {} {:.2}", number, gauge(position), position),
+        None => format!("STRIP {}: released", number),
+    }
+}
+
+fn gauge(fraction: f64) -> String {
+    let filled = (fraction.clamp(0.0, 1.0) * GAUGE_WIDTH as f64).round() as usize;
+    format!("{}{}", "█".repeat(filled), "░".repeat(GAUGE_WIDTH - filled))
+}
@@ -0,0 +1,188 @@
+use serde_json::{Map, Value};
+
+// Fields a template can use. Event fields come from the event's JSON form
+// (see `JsonEvent`), whichever variant has them; the rest are worked out
+// by the monitor for every event.
+const EVENT_FIELDS: [&str; 32] = [
+    "time_usec", "delta_usec", "elapsed_usec", "type", "fingers", "window", "sysname", "seat",
+    "device", "info", "code", "pressed", "dx", "dy", "x", "y", "button", "source",
+    "horizontal", "vertical", "kind", "slot", "previous", "count", "mode", "group", "number", "position",
+    "finger_count", "scale", "angle", "cancelled",
+];
+const COMPUTED_FIELDS: [&str; 6] = [
+    "timestamp_us", "key_name", "button_name", "wpm", "session_duration_s", "modifier_state",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(String),
+    // A name no event has; rendered as `{?name?}`
+    Unknown(String),
+    // `{?field:...?}`: the inner parts, only when the event has the field
+    Conditional(String, Vec<Part>),
+}
+
+// --output-template: a line per event with `{field}` tokens filled in from
+// the event, e.g. '{type}: {key_name} at {timestamp_us}'.
+// `{?field:text?}` renders the text (which may hold tokens itself) only
+// when the event has that field, e.g. '{?button:button={button}?}'.
+#[derive(Debug, Clone)]
+pub struct TemplateEngine {
+    parts: Vec<Part>,
+}
+
+impl TemplateEngine {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let (parts, _) = parse_parts(template, false)?;
+        Ok(TemplateEngine { parts })
+    }
+
+    // Names used in the template that no event has, for a warning
+    pub fn unknown_fields(&self) -> Vec<&str> {
+        let mut unknown = Vec::new();
+        collect_unknown(&self.parts, &mut unknown);
+        unknown
+    }
+
+    // `fields` holds the event's fields; null or missing ones are absent
+    pub fn render(&self, fields: &Map<String, Value>) -> String {
+        let mut line = String::new();
+        render_parts(&self.parts, fields, &mut line);
+        line
+    }
+}
+
+// Parses up to the end of the text, or up to the `?}` closing a
+// conditional; returns the parts and what follows them
+fn parse_parts(mut text: &str, in_conditional: bool) -> Result<(Vec<Part>, &str), String> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    loop {
+        if in_conditional && text.starts_with("?}") {
+            break;
+        }
+        let Some(c) = text.chars().next() else {
+            if in_conditional {
+                return Err("unterminated '{?field:...?}' in the template".to_string());
+            }
+            break;
+        };
+        if c != '{' {
+            literal.push(c);
+            text = &text[c.len_utf8()..];
+            continue;
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Text(std::mem::take(&mut literal)));
+        }
+        if let Some(conditional) = text.strip_prefix("{?") {
+            let (field, body) = conditional.split_once(':')
+                .filter(|(field, _)| is_name(field))
+                .ok_or_else(|| format!("expected '{{?field:...?}}' at '{}'", text))?;
+            let (inner, rest) = parse_parts(body, true)?;
+            parts.push(Part::Conditional(field.to_string(), inner));
+            text = &rest["?}".len()..];
+        } else {
+            let end = text.find('}').ok_or_else(|| format!("unterminated '{{' at '{}'", text))?;
+            let field = &text[1..end];
+            if !is_name(field) {
+                return Err(format!("invalid field name '{}' in the template", field));
+            }
+            parts.push(if is_known(field) { Part::Field(field.to_string()) } else { Part::Unknown(field.to_string()) });
+            text = &text[end + 1..];
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(Part::Text(literal));
+    }
+    Ok((parts, text))
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_known(field: &str) -> bool {
+    EVENT_FIELDS.contains(&field) || COMPUTED_FIELDS.contains(&field)
+}
+
+fn collect_unknown<'a>(parts: &'a [Part], unknown: &mut Vec<&'a str>) {
+    for part in parts {
+        match part {
+            Part::Unknown(field) if !unknown.contains(&field.as_str()) => unknown.push(field),
+            Part::Conditional(field, inner) => {
+                if !is_known(field) && !unknown.contains(&field.as_str()) {
+                    unknown.push(field);
+                }
+                collect_unknown(inner, unknown);
+            },
+            _ => {},
+        }
+    }
+}
+
+fn render_parts(parts: &[Part], fields: &Map<String, Value>, line: &mut String) {
+    for part in parts {
+        match part {
+            Part::Text(text) => line.push_str(text),
+            Part::Field(field) => {
+                if let Some(value) = field_value(fields, field) {
+                    line.push_str(&value);
+                }
+            },
+            Part::Unknown(field) => line.push_str(&format!("{{?{}?}}", field)),
+            Part::Conditional(field, inner) => {
+                if field_value(fields, field).is_some() {
+                    render_parts(inner, fields, line);
+                }
+            },
+        }
+    }
+}
+
+// Strings without quotes, everything else as JSON
+fn field_value(fields: &Map<String, Value>, field: &str) -> Option<String> {
+    match fields.get(field)? {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn fills_in_fields() {
+        let template = TemplateEngine::parse("{type}: {key_name} at {timestamp_us}").unwrap();
+        let key = fields(json!({"type": "key", "key_name": "A", "timestamp_us": 5000}));
+        assert_eq!(template.render(&key), "key: A at 5000");
+        // Missing fields render empty
+        assert_eq!(template.render(&fields(json!({"type": "pointer_motion"}))), "pointer_motion:  at ");
+    }
+
+    #[test]
+    fn conditionals_need_the_field() {
+        let template = TemplateEngine::parse("{type}{?button: button={button}?}").unwrap();
+        assert_eq!(template.render(&fields(json!({"type": "pointer_button", "button": 272}))), "pointer_button button=272");
+        assert_eq!(template.render(&fields(json!({"type": "key", "button": null}))), "key");
+    }
+
+    #[test]
+    fn unknown_fields_are_marked() {
+        let template = TemplateEngine::parse("{type} {colour}").unwrap();
+        assert_eq!(template.unknown_fields(), ["colour"]);
+        assert_eq!(template.render(&fields(json!({"type": "key"}))), "key {?colour?}");
+
+        assert!(TemplateEngine::parse("{type").is_err());
+        assert!(TemplateEngine::parse("{?button:x").is_err());
+    }
+}
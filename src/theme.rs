@@ -0,0 +1,243 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::Colors;
+
+// --color-theme, as a ColorTheme discriminant
+static THEME: AtomicU8 = AtomicU8::new(ColorTheme::Default as u8);
+// --no-color or NO_COLOR
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorTheme {
+    Default,
+    Solarized,
+    Monokai,
+    Nord,
+    Gruvbox,
+    // Only the 8 basic ANSI colors, for terminals with small palettes
+    HighContrast,
+}
+
+impl ColorTheme {
+    pub const NAMES: &'static str = "default, solarized, monokai, nord, gruvbox, high-contrast";
+
+    pub fn parse(value: &str) -> Option<ColorTheme> {
+        match value {
+            "default" => Some(ColorTheme::Default),
+            "solarized" => Some(ColorTheme::Solarized),
+            "monokai" => Some(ColorTheme::Monokai),
+            "nord" => Some(ColorTheme::Nord),
+            "gruvbox" => Some(ColorTheme::Gruvbox),
+            "high-contrast" => Some(ColorTheme::HighContrast),
+            _ => None,
+        }
+    }
+
+    fn from_u8(value: u8) -> ColorTheme {
+        [ColorTheme::Default, ColorTheme::Solarized, ColorTheme::Monokai, ColorTheme::Nord, ColorTheme::Gruvbox, ColorTheme::HighContrast]
+            .into_iter()
+            .find(|theme| *theme as u8 == value)
+            .unwrap_or(ColorTheme::Default)
+    }
+}
+
+// What a color stands for in the event stream (see the --legend); each one
+// is written with one of the `Colors` constants
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventCategory {
+    // Yellow: key presses
+    KeyPress,
+    // Blue: key and button releases
+    Release,
+    // Magenta: button presses, touches, gestures, combos
+    Button,
+    // Cyan: motion, scrolling, headings
+    Motion,
+    // Green: added devices, totals
+    Added,
+    // Red: removed devices, warnings
+    Warning,
+    // Gray: libinput's own messages and other asides
+    Muted,
+}
+
+impl EventCategory {
+    fn of(color: &str) -> Option<EventCategory> {
+        match color {
+            Colors::YELLOW => Some(EventCategory::KeyPress),
+            Colors::BLUE => Some(EventCategory::Release),
+            Colors::MAGENTA => Some(EventCategory::Button),
+            Colors::CYAN => Some(EventCategory::Motion),
+            Colors::GREEN => Some(EventCategory::Added),
+            Colors::RED => Some(EventCategory::Warning),
+            Colors::GRAY => Some(EventCategory::Muted),
+            _ => None,
+        }
+    }
+}
+
+// The escape sequence a theme uses for a category. The named themes use
+// 24-bit colors from their published palettes.
+pub fn theme_colors(theme: ColorTheme, event_category: EventCategory) -> &'static str {
+    use EventCategory::*;
+    match (theme, event_category) {
+        (ColorTheme::Default, KeyPress) => Colors::YELLOW,
+        (ColorTheme::Default, Release) => Colors::BLUE,
+        (ColorTheme::Default, Button) => Colors::MAGENTA,
+        (ColorTheme::Default, Motion) => Colors::CYAN,
+        (ColorTheme::Default, Added) => Colors::GREEN,
+        (ColorTheme::Default, Warning) => Colors::RED,
+        (ColorTheme::Default, Muted) => Colors::GRAY,
+
+        (ColorTheme::Solarized, KeyPress) => "\x1b[38;2;181;137;0m",
+        (ColorTheme::Solarized, Release) => "\x1b[38;2;38;139;210m",
+        (ColorTheme::Solarized, Button) => "\x1b[38;2;211;54;130m",
+        (ColorTheme::Solarized, Motion) => "\x1b[38;2;42;161;152m",
+        (ColorTheme::Solarized, Added) => "\x1b[38;2;133;153;0m",
+        (ColorTheme::Solarized, Warning) => "\x1b[38;2;220;50;47m",
+        (ColorTheme::Solarized, Muted) => "\x1b[38;2;88;110;117m",
+
+        (ColorTheme::Monokai, KeyPress) => "\x1b[38;2;230;219;116m",
+        (ColorTheme::Monokai, Release) => "\x1b[38;2;102;217;239m",
+        (ColorTheme::Monokai, Button) => "\x1b[38;2;174;129;255m",
+        (ColorTheme::Monokai, Motion) => "\x1b[38;2;161;239;228m",
+        (ColorTheme::Monokai, Added) => "\x1b[38;2;166;226;46m",
+        (ColorTheme::Monokai, Warning) => "\x1b[38;2;249;38;114m",
+        (ColorTheme::Monokai, Muted) => "\x1b[38;2;117;113;94m",
+
+        (ColorTheme::Nord, KeyPress) => "\x1b[38;2;235;203;139m",
+        (ColorTheme::Nord, Release) => "\x1b[38;2;129;161;193m",
+        (ColorTheme::Nord, Button) => "\x1b[38;2;180;142;173m",
+        (ColorTheme::Nord, Motion) => "\x1b[38;2;136;192;208m",
+        (ColorTheme::Nord, Added) => "\x1b[38;2;163;190;140m",
+        (ColorTheme::Nord, Warning) => "\x1b[38;2;191;97;106m",
+        (ColorTheme::Nord, Muted) => "\x1b[38;2;97;110;136m",
+
+        (ColorTheme::Gruvbox, KeyPress) => "\x1b[38;2;250;189;47m",
+        (ColorTheme::Gruvbox, Release) => "\x1b[38;2;131;165;152m",
+        (ColorTheme::Gruvbox, Button) => "\x1b[38;2;211;134;155m",
+        (ColorTheme::Gruvbox, Motion) => "\x1b[38;2;142;192;124m",
+        (ColorTheme::Gruvbox, Added) => "\x1b[38;2;184;187;38m",
+        (ColorTheme::Gruvbox, Warning) => "\x1b[38;2;251;73;52m",
+        (ColorTheme::Gruvbox, Muted) => "\x1b[38;2;146;131;116m",
+
+        // Bold instead of the bright variants, which aren't among the basic 8
+        (ColorTheme::HighContrast, KeyPress) => "\x1b[1;33m",
+        (ColorTheme::HighContrast, Release) => "\x1b[1;37m",
+        (ColorTheme::HighContrast, Button) => "\x1b[1;35m",
+        (ColorTheme::HighContrast, Motion) => "\x1b[1;36m",
+        (ColorTheme::HighContrast, Added) => "\x1b[1;32m",
+        (ColorTheme::HighContrast, Warning) => "\x1b[1;31m",
+        (ColorTheme::HighContrast, Muted) => "\x1b[37m",
+    }
+}
+
+pub fn set(theme: ColorTheme, no_color: bool) {
+    THEME.store(theme as u8, Ordering::Relaxed);
+    NO_COLOR.store(no_color, Ordering::Relaxed);
+}
+
+pub fn no_color() -> bool {
+    NO_COLOR.load(Ordering::Relaxed)
+}
+
+// Output is written with the default colors; this swaps in the theme's (or
+// drops them) on the way out
+pub fn apply(line: &str) -> Cow<'_, str> {
+    if no_color() {
+        return Cow::Owned(strip_colors(line));
+    }
+    let theme = ColorTheme::from_u8(THEME.load(Ordering::Relaxed));
+    if theme == ColorTheme::Default || !line.contains('\x1b') {
+        return Cow::Borrowed(line);
+    }
+
+    let mut themed = String::with_capacity(line.len() + 32);
+    let mut rest = line;
+    while let Some(start) = rest.find("\x1b[") {
+        themed.push_str(&rest[..start]);
+        // Sequences end at their first letter; only colors (ending in m) change
+        let Some(end) = rest[start + 2..].find(|c: char| c.is_ascii_alphabetic()).map(|end| start + 2 + end + 1) else {
+            break;
+        };
+        let sequence = &rest[start..end];
+        match EventCategory::of(sequence) {
+            Some(category) => themed.push_str(theme_colors(theme, category)),
+            None if theme == ColorTheme::HighContrast && sequence.ends_with('m') => themed.push_str(&basic_colors_only(sequence)),
+            None => themed.push_str(sequence),
+        }
+        rest = &rest[end..];
+    }
+    themed.push_str(rest);
+    Cow::Owned(themed)
+}
+
+// Bright colors (90-97, 100-107) turned into their basic counterparts, e.g.
+// the highlighted backgrounds of pattern matches and held modifiers
+fn basic_colors_only(sequence: &str) -> String {
+    let params: Vec<String> = sequence[2..sequence.len() - 1].split(';')
+        .map(|param| match param.parse::<u32>() {
+            Ok(code @ 90..=97) => (code - 60).to_string(),
+            Ok(code @ 100..=107) => (code - 60).to_string(),
+            _ => param.to_string(),
+        })
+        .collect();
+    format!("\x1b[{}m", params.join(";"))
+}
+
+// --no-color: only the color sequences go; the others, e.g. clearing the
+// status line, still have to reach the terminal
+fn strip_colors(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("\x1b[") {
+        plain.push_str(&rest[..start]);
+        let Some(end) = rest[start + 2..].find(|c: char| c.is_ascii_alphabetic()).map(|end| start + 2 + end + 1) else {
+            break;
+        };
+        let sequence = &rest[start..end];
+        if !sequence.ends_with('m') {
+            plain.push_str(sequence);
+        }
+        rest = &rest[end..];
+    }
+    plain.push_str(rest);
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATEGORIES: [EventCategory; 7] = [
+        EventCategory::KeyPress, EventCategory::Release, EventCategory::Button, EventCategory::Motion,
+        EventCategory::Added, EventCategory::Warning, EventCategory::Muted,
+    ];
+
+    #[test]
+    fn high_contrast_uses_only_basic_colors() {
+        for category in CATEGORIES {
+            let sequence = theme_colors(ColorTheme::HighContrast, category);
+            for param in sequence[2..sequence.len() - 1].split(';') {
+                let code: u32 = param.parse().unwrap();
+                assert!(code == 1 || (30..=37).contains(&code), "{:?} uses {}", category, code);
+            }
+        }
+        assert_eq!(basic_colors_only("\x1b[30;103m"), "\x1b[30;43m");
+    }
+
+    #[test]
+    fn default_theme_is_the_plain_colors() {
+        for category in CATEGORIES {
+            assert_eq!(EventCategory::of(theme_colors(ColorTheme::Default, category)), Some(category));
+        }
+        assert_eq!(ColorTheme::parse("high-contrast"), Some(ColorTheme::HighContrast));
+        assert_eq!(ColorTheme::parse("dracula"), None);
+    }
+
+    #[test]
+    fn no_color_keeps_cursor_sequences() {
+        assert_eq!(strip_colors("\r\x1b[2K\x1b[1;32mKEY_A\x1b[0m pressed"), "\r\x1b[2KKEY_A pressed");
+    }
+}
@@ -0,0 +1,80 @@
+const WINDOW_USEC: u64 = 1_000_000;
+
+// --max-lines-per-sec: caps how many events are printed per second, so a
+// stuck autorepeat or a flood of motion can't saturate the terminal. Seconds
+// are measured in event time, so a replay is throttled the way the live
+// session would have been. Events over the cap are still counted, just not
+// printed, and the number dropped is reported once per second.
+pub struct LineThrottle {
+    max_per_window: u32,
+    window_start_usec: Option<u64>,
+    shown: u32,
+    // Dropped in the current window, not yet reported
+    suppressed: u64,
+    total_suppressed: u64,
+}
+
+impl LineThrottle {
+    pub fn new(max_per_sec: u32) -> Self {
+        LineThrottle { max_per_window: max_per_sec, window_start_usec: None, shown: 0, suppressed: 0, total_suppressed: 0 }
+    }
+
+    // Whether the next event may be printed
+    pub fn admit(&mut self, now_usec: u64) -> bool {
+        self.window_start_usec.get_or_insert(now_usec);
+        if self.shown < self.max_per_window {
+            self.shown += 1;
+            return true;
+        }
+        self.suppressed += 1;
+        self.total_suppressed += 1;
+        false
+    }
+
+    // Starts a new window once the current one is over; returns how many
+    // events the old one dropped, if any
+    pub fn roll(&mut self, now_usec: u64) -> Option<u64> {
+        let start = self.window_start_usec?;
+        if now_usec.saturating_sub(start) < WINDOW_USEC {
+            return None;
+        }
+        self.window_start_usec = None;
+        self.shown = 0;
+        self.take_suppressed()
+    }
+
+    // Drops not reported yet, e.g. at exit
+    pub fn take_suppressed(&mut self) -> Option<u64> {
+        let suppressed = std::mem::take(&mut self.suppressed);
+        (suppressed > 0).then_some(suppressed)
+    }
+
+    pub fn max_per_sec(&self) -> u32 {
+        self.max_per_window
+    }
+
+    pub fn total_suppressed(&self) -> u64 {
+        self.total_suppressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_over_the_cap_and_reports_once() {
+        let start = 5_000_000;
+        let mut throttle = LineThrottle::new(3);
+        let admitted = (0..10).filter(|_| throttle.admit(start)).count();
+        assert_eq!(admitted, 3);
+
+        // Still in the same second: nothing to report yet
+        assert_eq!(throttle.roll(start + 500_000), None);
+        assert_eq!(throttle.roll(start + WINDOW_USEC), Some(7));
+        assert_eq!(throttle.roll(start + WINDOW_USEC * 2), None);
+
+        assert!(throttle.admit(start + WINDOW_USEC * 2));
+        assert_eq!(throttle.total_suppressed(), 7);
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+// Centroid travel (mm) needed to call a multi-finger gesture a scroll / swipe
+const MIN_TRAVEL_MM: f64 = 5.0;
+// Change in finger spread needed to call it a pinch (1.25 = 25% wider)
+const PINCH_SCALE: f64 = 1.25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchGestureKind {
+    // Two fingers moving together
+    Scroll,
+    // Three or more fingers moving together
+    Swipe,
+    PinchIn,
+    PinchOut,
+}
+
+// A gesture inferred from raw touch points once its fingers lift
+#[derive(Debug, Clone, Copy)]
+pub struct TouchGesture {
+    pub kind: TouchGestureKind,
+    pub fingers: usize,
+    // Net centroid movement in mm; positive is down / right
+    pub dx: f64,
+    pub dy: f64,
+    // Final finger spread relative to the start
+    pub scale: f64,
+    pub duration_usec: u64,
+}
+
+struct Contact {
+    fingers: usize,
+    start_usec: u64,
+    start_centroid: (f64, f64),
+    start_spread: f64,
+    // As of the last frame with all fingers still down
+    last_usec: u64,
+    centroid: (f64, f64),
+    spread: f64,
+}
+
+// Follows touch slots between frames and infers scroll, swipe and pinch
+// gestures when libinput delivers raw touches instead of gesture events
+#[derive(Default)]
+pub struct TouchTracker {
+    // Seat slot -> last known position (mm)
+    slots: HashMap<u32, (f64, f64)>,
+    contact: Option<Contact>,
+    // Set by Up / Cancel, evaluated at the end of the frame
+    lifted: bool,
+    // After a lift, the remaining fingers don't start a new gesture
+    wait_for_release: bool,
+}
+
+impl TouchTracker {
+    pub fn down(&mut self, slot: u32, x: f64, y: f64) {
+        self.slots.insert(slot, (x, y));
+    }
+
+    pub fn motion(&mut self, slot: u32, x: f64, y: f64) {
+        self.slots.insert(slot, (x, y));
+    }
+
+    pub fn up(&mut self, slot: u32) {
+        self.slots.remove(&slot);
+        self.lifted = true;
+    }
+
+    pub fn cancel(&mut self) {
+        self.slots.clear();
+        self.contact = None;
+    }
+
+    // End of a hardware frame, when all slots are consistent. Returns the
+    // gesture that ended in it, if its fingers did something recognizable.
+    pub fn frame(&mut self, time_usec: u64) -> Option<TouchGesture> {
+        let lifted = std::mem::take(&mut self.lifted);
+        let fingers = self.slots.len();
+        let (centroid, spread) = (self.centroid(), self.spread());
+
+        let mut finished = None;
+        if let Some(contact) = self.contact.as_mut() {
+            if !lifted && fingers == contact.fingers {
+                contact.last_usec = time_usec;
+                contact.centroid = centroid;
+                contact.spread = spread;
+                return None;
+            }
+            finished = self.contact.take().and_then(classify);
+        }
+
+        if lifted {
+            self.wait_for_release = true;
+        }
+        if fingers == 0 {
+            self.wait_for_release = false;
+        }
+
+        if fingers >= 2 && !self.wait_for_release {
+            self.contact = Some(Contact {
+                fingers,
+                start_usec: time_usec,
+                start_centroid: centroid,
+                start_spread: spread,
+                last_usec: time_usec,
+                centroid,
+                spread,
+            });
+        }
+
+        finished
+    }
+
+    fn centroid(&self) -> (f64, f64) {
+        let count = self.slots.len().max(1) as f64;
+        let (sum_x, sum_y) = self.slots.values().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+        (sum_x / count, sum_y / count)
+    }
+
+    // Mean distance of the fingers from their centroid
+    fn spread(&self) -> f64 {
+        let (cx, cy) = self.centroid();
+        let count = self.slots.len().max(1) as f64;
+        self.slots.values().map(|(x, y)| ((x - cx).powi(2) + (y - cy).powi(2)).sqrt()).sum::<f64>() / count
+    }
+}
+
+fn classify(contact: Contact) -> Option<TouchGesture> {
+    let dx = contact.centroid.0 - contact.start_centroid.0;
+    let dy = contact.centroid.1 - contact.start_centroid.1;
+    let scale = if contact.start_spread > 0.0 { contact.spread / contact.start_spread } else { 1.0 };
+
+    let kind = if scale >= PINCH_SCALE {
+        TouchGestureKind::PinchOut
+    } else if scale <= 1.0 / PINCH_SCALE {
+        TouchGestureKind::PinchIn
+    } else if (dx * dx + dy * dy).sqrt() >= MIN_TRAVEL_MM {
+        if contact.fingers == 2 { TouchGestureKind::Scroll } else { TouchGestureKind::Swipe }
+    } else {
+        return None;
+    };
+
+    Some(TouchGesture {
+        kind,
+        fingers: contact.fingers,
+        dx,
+        dy,
+        scale,
+        duration_usec: contact.last_usec.saturating_sub(contact.start_usec),
+    })
+}
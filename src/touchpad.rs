@@ -0,0 +1,68 @@
+use std::collections::{HashMap, HashSet};
+
+use input::event::EventTrait;
+use input::{DeviceCapability, Event};
+
+use crate::event::{InputEvent, TimedEvent};
+
+// libinput usually folds touchpad contacts into pointer and gesture events.
+// Devices that report raw touches *and* move the pointer keep their slots
+// visible; this follows them per device, so pointer events can say how many
+// fingers were down when they happened.
+#[derive(Default)]
+pub struct TouchpadMonitor {
+    // Device sysname -> seat slots currently down
+    slots: HashMap<String, HashSet<u32>>,
+    // Finger count last reported per device
+    reported: HashMap<String, u32>,
+}
+
+impl TouchpadMonitor {
+    // Tag pointer events with their device's finger count, and return a
+    // finger count change event when a touch frame changes it
+    pub fn observe(&mut self, event: &Event, timed: &mut TimedEvent) -> Option<TimedEvent> {
+        if let InputEvent::DeviceRemoved { .. } = timed.event {
+            let sysname = event.device().sysname().to_string();
+            self.slots.remove(&sysname);
+            self.reported.remove(&sysname);
+            return None;
+        }
+        if !matches!(event, Event::Touch(_) | Event::Pointer(_)) {
+            return None;
+        }
+        let device = event.device();
+        if !device.has_capability(DeviceCapability::Touch) || !device.has_capability(DeviceCapability::Pointer) {
+            return None;
+        }
+        let sysname = device.sysname().to_string();
+
+        match &timed.event {
+            InputEvent::Touch { kind, slot, .. } => {
+                let slots = self.slots.entry(sysname.clone()).or_default();
+                match (kind.as_str(), slot) {
+                    ("Down", Some(slot)) => {
+                        slots.insert(*slot);
+                    },
+                    ("Up", Some(slot)) => {
+                        slots.remove(slot);
+                    },
+                    ("Cancel", _) => slots.clear(),
+                    // Slots are only consistent at the end of a frame
+                    ("Frame", _) => {
+                        let count = slots.len() as u32;
+                        let previous = self.reported.insert(sysname, count).unwrap_or(0);
+                        if count != previous {
+                            let event = InputEvent::TouchpadFingerCountChange { previous, count };
+                            return Some(TimedEvent { time_usec: timed.time_usec, event, fingers: None, sysname: timed.sysname.clone(), seat: timed.seat.clone() });
+                        }
+                    },
+                    _ => {},
+                }
+            },
+            _ => {
+                timed.fingers = self.reported.get(&sysname).copied().filter(|fingers| *fingers > 0);
+            },
+        }
+        None
+    }
+}
@@ -0,0 +1,228 @@
+use std::path::Path;
+
+use crate::Colors;
+
+// Seconds to switch to the target window before anything is typed
+const COUNTDOWN_SECS: u64 = 3;
+
+// `--replay-into-uinput <recording> --confirm-inject`: play a recording
+// back as real input through a virtual device, with the original timing.
+// Keys, mouse buttons, relative motion and wheel scrolling are injected;
+// everything else is skipped. Returns the process exit code.
+pub fn run(path: &Path) -> i32 {
+    match enabled::replay(path, COUNTDOWN_SECS) {
+        Ok(report) => {
+            eprintln!("{}Injected {} events ({} not injectable, skipped) in {:.1}s{}{}",
+                Colors::GREEN, report.injected, report.skipped, report.elapsed_secs,
+                if report.interrupted { ", stopped early" } else { "" }, Colors::RESET);
+            0
+        },
+        Err(err) => {
+            eprintln!("{}error: {}{}", Colors::RED, err, Colors::RESET);
+            1
+        },
+    }
+}
+
+#[cfg_attr(not(feature = "uinput-replay"), allow(dead_code))]
+pub struct ReplayReport {
+    injected: u64,
+    skipped: u64,
+    elapsed_secs: f64,
+    interrupted: bool,
+}
+
+#[cfg(feature = "uinput-replay")]
+mod enabled {
+    use std::collections::BTreeSet;
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Write};
+    use std::mem;
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::Path;
+    use std::slice;
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    use super::ReplayReport;
+    use crate::event::{InputEvent, ScrollSource};
+    use crate::recording;
+    use crate::signals;
+    use crate::Colors;
+
+    // From <linux/uinput.h> and <linux/input-event-codes.h>
+    const UI_DEV_CREATE: libc::c_ulong = 0x5501;
+    const UI_DEV_DESTROY: libc::c_ulong = 0x5502;
+    const UI_DEV_SETUP: libc::c_ulong = 0x405c_5503;
+    const UI_SET_EVBIT: libc::c_ulong = 0x4004_5564;
+    const UI_SET_KEYBIT: libc::c_ulong = 0x4004_5565;
+    const UI_SET_RELBIT: libc::c_ulong = 0x4004_5566;
+    const EV_SYN: u16 = 0x00;
+    const EV_KEY: u16 = 0x01;
+    const EV_REL: u16 = 0x02;
+    const SYN_REPORT: u16 = 0;
+    const REL_X: u16 = 0x00;
+    const REL_Y: u16 = 0x01;
+    const REL_HWHEEL: u16 = 0x06;
+    const REL_WHEEL: u16 = 0x08;
+    const REL_WHEEL_HI_RES: u16 = 0x0b;
+    const REL_HWHEEL_HI_RES: u16 = 0x0c;
+    const KEY_MAX: u16 = 0x2ff;
+    const BUS_VIRTUAL: u16 = 0x06;
+    const DEVICE_NAME: &str = "rustkey replay";
+    // Time for udev and the compositor to pick up the new device
+    const SETTLE: Duration = Duration::from_millis(500);
+    // libinput's v120 units per wheel notch
+    const V120_PER_NOTCH: f64 = 120.0;
+
+    // A uinput keyboard and mouse, removed again when dropped
+    struct VirtualDevice {
+        file: File,
+    }
+
+    impl VirtualDevice {
+        fn create() -> Result<Self, String> {
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open("/dev/uinput")
+                .map_err(|err| format!("cannot open /dev/uinput: {}", err))?;
+            let fd = file.as_raw_fd();
+
+            let mut setup: libc::uinput_setup = unsafe { mem::zeroed() };
+            setup.id.bustype = BUS_VIRTUAL;
+            for (slot, byte) in setup.name.iter_mut().zip(DEVICE_NAME.bytes()) {
+                *slot = byte as libc::c_char;
+            }
+            let rel = [REL_X, REL_Y, REL_HWHEEL, REL_WHEEL, REL_WHEEL_HI_RES, REL_HWHEEL_HI_RES];
+            let created = unsafe {
+                libc::ioctl(fd, UI_SET_EVBIT, libc::c_int::from(EV_KEY)) >= 0
+                    && libc::ioctl(fd, UI_SET_EVBIT, libc::c_int::from(EV_REL)) >= 0
+                    && (1..=KEY_MAX).all(|code| libc::ioctl(fd, UI_SET_KEYBIT, libc::c_int::from(code)) >= 0)
+                    && rel.iter().all(|code| libc::ioctl(fd, UI_SET_RELBIT, libc::c_int::from(*code)) >= 0)
+                    && libc::ioctl(fd, UI_DEV_SETUP, &setup) >= 0
+                    && libc::ioctl(fd, UI_DEV_CREATE) >= 0
+            };
+            if !created {
+                return Err(format!("cannot create a uinput device: {}", io::Error::last_os_error()));
+            }
+            Ok(VirtualDevice { file })
+        }
+
+        // One frame: the events, then SYN_REPORT
+        fn send(&mut self, events: &[(u16, u16, i32)]) -> io::Result<()> {
+            for &(kind, code, value) in events.iter().chain([(EV_SYN, SYN_REPORT, 0)].iter()) {
+                let mut event: libc::input_event = unsafe { mem::zeroed() };
+                event.type_ = kind;
+                event.code = code;
+                event.value = value;
+                let bytes = unsafe {
+                    slice::from_raw_parts(&event as *const libc::input_event as *const u8, mem::size_of::<libc::input_event>())
+                };
+                self.file.write_all(bytes)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for VirtualDevice {
+        fn drop(&mut self) {
+            unsafe {
+                libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY);
+            }
+        }
+    }
+
+    pub fn replay(path: &Path, countdown_secs: u64) -> Result<ReplayReport, String> {
+        let recorded = recording::read_recording(path).map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        signals::install_exit_handlers();
+
+        eprintln!("{}{}⚠️  --replay-into-uinput: {} events from {} will be typed and clicked for real{}",
+            Colors::RED, Colors::BOLD, recorded.len(), path.display(), Colors::RESET);
+        eprintln!("{}   into whichever window has the focus. Press Ctrl+C to stop at any time.{}", Colors::RED, Colors::RESET);
+        let mut device = VirtualDevice::create()?;
+        for remaining in (1..=countdown_secs).rev() {
+            if signals::exit_requested() {
+                break;
+            }
+            eprintln!("   Starting in {}...", remaining);
+            sleep(Duration::from_secs(1));
+        }
+        sleep(SETTLE);
+
+        let mut report = ReplayReport { injected: 0, skipped: 0, elapsed_secs: 0.0, interrupted: false };
+        // Keys and buttons down right now, released again whatever happens
+        let mut held = BTreeSet::new();
+        // Sub-unit motion carried over to the next event
+        let mut remainder = (0.0, 0.0);
+        let start = Instant::now();
+        let first_usec = recorded.first().map_or(0, |first| first.event.time_usec);
+
+        let result = (|| -> io::Result<()> {
+            for recorded in &recorded {
+                let due = Duration::from_micros(recorded.event.time_usec.saturating_sub(first_usec));
+                // Sleep in short steps so Ctrl+C is noticed
+                while start.elapsed() < due && !signals::exit_requested() {
+                    sleep(due.saturating_sub(start.elapsed()).min(Duration::from_millis(50)));
+                }
+                if signals::exit_requested() {
+                    report.interrupted = true;
+                    break;
+                }
+
+                let frame = match &recorded.event.event {
+                    InputEvent::Key { code, pressed } | InputEvent::PointerButton { button: code, pressed } => {
+                        let Ok(code) = u16::try_from(*code) else {
+                            report.skipped += 1;
+                            continue;
+                        };
+                        if *pressed {
+                            held.insert(code);
+                        } else {
+                            held.remove(&code);
+                        }
+                        vec![(EV_KEY, code, *pressed as i32)]
+                    },
+                    InputEvent::PointerMotion { dx, dy } => {
+                        let (x, y) = (remainder.0 + dx, remainder.1 + dy);
+                        remainder = (x - x.round(), y - y.round());
+                        vec![(EV_REL, REL_X, x.round() as i32), (EV_REL, REL_Y, y.round() as i32)]
+                    },
+                    // libinput's v120 is positive downwards / rightwards, REL_WHEEL upwards
+                    InputEvent::Scroll { source: ScrollSource::Wheel, horizontal, vertical } => vec![
+                        (EV_REL, REL_WHEEL, (-vertical / V120_PER_NOTCH).round() as i32),
+                        (EV_REL, REL_WHEEL_HI_RES, -vertical.round() as i32),
+                        (EV_REL, REL_HWHEEL, (horizontal / V120_PER_NOTCH).round() as i32),
+                        (EV_REL, REL_HWHEEL_HI_RES, horizontal.round() as i32),
+                    ],
+                    _ => {
+                        report.skipped += 1;
+                        continue;
+                    },
+                };
+                device.send(&frame)?;
+                report.injected += 1;
+            }
+            Ok(())
+        })();
+
+        // Never leave a key stuck down, even after a write error
+        let releases: Vec<_> = held.iter().map(|code| (EV_KEY, *code, 0)).collect();
+        let released = if releases.is_empty() { Ok(()) } else { device.send(&releases) };
+        report.elapsed_secs = start.elapsed().as_secs_f64();
+        result.and(released).map_err(|err| format!("cannot write to /dev/uinput: {}", err))?;
+        Ok(report)
+    }
+}
+
+#[cfg(not(feature = "uinput-replay"))]
+mod enabled {
+    use std::path::Path;
+
+    use super::ReplayReport;
+
+    pub fn replay(_path: &Path, _countdown_secs: u64) -> Result<ReplayReport, String> {
+        Err("--replay-into-uinput is not compiled in (rebuild with `--features uinput-replay`)".to_string())
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use crate::event::{InputEvent, TimedEvent};
+
+// Where the --warmup window is measured from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarmupStart {
+    // The first event of any kind; live, that is libinput adding the devices
+    // right after startup
+    Startup,
+    // The first event that isn't a device being added or removed
+    FirstInput,
+}
+
+impl WarmupStart {
+    pub fn parse(value: &str) -> Option<WarmupStart> {
+        match value {
+            "startup" => Some(WarmupStart::Startup),
+            "first-event" => Some(WarmupStart::FirstInput),
+            _ => None,
+        }
+    }
+}
+
+// --warmup: events in the first few seconds are left out of the statistics
+// (and, unless --warmup-show, out of the output too). Device added and
+// removed events are never held back, so the device list stays complete.
+pub struct Warmup {
+    duration_usec: u64,
+    start: WarmupStart,
+    ends_usec: Option<u64>,
+    discarded: u64,
+}
+
+impl Warmup {
+    pub fn new(duration: Duration, start: WarmupStart) -> Self {
+        Warmup { duration_usec: duration.as_micros() as u64, start, ends_usec: None, discarded: 0 }
+    }
+
+    // True for events that fall inside the window
+    pub fn discards(&mut self, timed: &TimedEvent) -> bool {
+        let device = matches!(timed.event, InputEvent::DeviceAdded { .. } | InputEvent::DeviceRemoved { .. });
+        if self.ends_usec.is_none() && (!device || self.start == WarmupStart::Startup) {
+            self.ends_usec = Some(timed.time_usec + self.duration_usec);
+        }
+        let inside = !device && self.ends_usec.is_some_and(|ends| timed.time_usec < ends);
+        if inside {
+            self.discarded += 1;
+        }
+        inside
+    }
+
+    pub fn discarded(&self) -> u64 {
+        self.discarded
+    }
+
+    pub fn duration_usec(&self) -> u64 {
+        self.duration_usec
+    }
+
+    pub fn start(&self) -> WarmupStart {
+        self.start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time_usec: u64, event: InputEvent) -> TimedEvent {
+        TimedEvent { time_usec, event, fingers: None, sysname: None, seat: None }
+    }
+
+    #[test]
+    fn window_starts_at_startup_or_first_input() {
+        let added = || InputEvent::DeviceAdded { device: "Test Keyboard".to_string(), info: None };
+        let key = || InputEvent::Key { code: 30, pressed: true };
+
+        let mut startup = Warmup::new(Duration::from_secs(2), WarmupStart::Startup);
+        assert!(!startup.discards(&at(1_000_000, added())));
+        assert!(startup.discards(&at(2_500_000, key())));
+        assert!(!startup.discards(&at(3_000_000, key())));
+
+        let mut first_input = Warmup::new(Duration::from_secs(2), WarmupStart::FirstInput);
+        assert!(!first_input.discards(&at(1_000_000, added())));
+        assert!(first_input.discards(&at(2_500_000, key())));
+        assert!(first_input.discards(&at(3_000_000, key())));
+        assert!(!first_input.discards(&at(4_500_000, key())));
+        assert_eq!(first_input.discarded(), 2);
+    }
+}
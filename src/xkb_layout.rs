@@ -0,0 +1,126 @@
+#[cfg(feature = "xkb")]
+use std::cell::RefCell;
+#[cfg(feature = "xkb")]
+use std::collections::HashMap;
+#[cfg(feature = "xkb")]
+use std::env;
+#[cfg(feature = "xkb")]
+use std::fs;
+
+#[cfg(feature = "xkb")]
+use xkbcommon::xkb;
+
+// evdev key codes are offset by 8 in XKB
+#[cfg(feature = "xkb")]
+const EVDEV_OFFSET: u32 = 8;
+
+// Where Debian-style systems (and `localectl` there) keep the console and
+// X11 keyboard layout
+#[cfg(feature = "xkb")]
+const KEYBOARD_DEFAULTS: &str = "/etc/default/keyboard";
+
+// --xkb: key labels from the user's XKB layout, following the keys as they
+// go down and up so Shift, AltGr and the lock keys pick the level, e.g. "é"
+// or "@" instead of the key's US name. Dead keys read "DEAD ACUTE" and the
+// like. Keys that produce no character (modifiers, arrows, F-keys) keep the
+// built-in names. The keymap lives on the monitor's thread.
+#[cfg(feature = "xkb")]
+struct XkbLabels {
+    state: xkb::State,
+    // Each distinct label is leaked once, like the --alias labels
+    labels: HashMap<String, &'static str>,
+}
+
+#[cfg(feature = "xkb")]
+thread_local! {
+    static XKB: RefCell<Option<XkbLabels>> = const { RefCell::new(None) };
+}
+
+// Compile the layout from XKB_DEFAULT_LAYOUT / XKB_DEFAULT_VARIANT (and the
+// other XKB_DEFAULT_* variables, which libxkbcommon reads itself), or else
+// from /etc/default/keyboard
+#[cfg(feature = "xkb")]
+pub fn init() -> Result<(), String> {
+    let defaults = fs::read_to_string(KEYBOARD_DEFAULTS).unwrap_or_default();
+    let setting = |variable: &str, key: &str| env::var(variable).ok().or_else(|| defaults_value(&defaults, key)).unwrap_or_default();
+    let layout = setting("XKB_DEFAULT_LAYOUT", "XKBLAYOUT");
+    let variant = setting("XKB_DEFAULT_VARIANT", "XKBVARIANT");
+    let model = setting("XKB_DEFAULT_MODEL", "XKBMODEL");
+
+    let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+    let keymap = xkb::Keymap::new_from_names(&context, "", &model, &layout, &variant, None, xkb::KEYMAP_COMPILE_NO_FLAGS)
+        .ok_or_else(|| format!("cannot compile the XKB keymap for layout '{}'", if layout.is_empty() { "us" } else { &layout }))?;
+    let labels = XkbLabels { state: xkb::State::new(&keymap), labels: HashMap::new() };
+    XKB.with(|xkb| *xkb.borrow_mut() = Some(labels));
+    Ok(())
+}
+
+#[cfg(not(feature = "xkb"))]
+pub fn init() -> Result<(), String> {
+    Err("xkb support is not compiled in (rebuild with `--features xkb`)".to_string())
+}
+
+// e.g. XKBLAYOUT="de,us" gives "de,us"
+#[cfg(feature = "xkb")]
+fn defaults_value(text: &str, key: &str) -> Option<String> {
+    text.lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+// Follow a key going down or up; call it after labelling the event, so a
+// key's own press doesn't change its label
+#[cfg(feature = "xkb")]
+pub fn update(code: u32, pressed: bool) {
+    XKB.with(|xkb| {
+        if let Some(labels) = xkb.borrow_mut().as_mut() {
+            let direction = if pressed { xkb::KeyDirection::Down } else { xkb::KeyDirection::Up };
+            labels.state.update_key(xkb::Keycode::new(code + EVDEV_OFFSET), direction);
+        }
+    });
+}
+
+#[cfg(not(feature = "xkb"))]
+pub fn update(_code: u32, _pressed: bool) {}
+
+// What the key produces right now, if it produces a character
+#[cfg(feature = "xkb")]
+pub fn label(code: u32) -> Option<&'static str> {
+    XKB.with(|xkb| {
+        let mut xkb = xkb.borrow_mut();
+        let labels = xkb.as_mut()?;
+        let keycode = xkb::Keycode::new(code + EVDEV_OFFSET);
+        let text = labels.state.key_get_utf8(keycode);
+        let label = if !text.trim().is_empty() && !text.chars().any(char::is_control) {
+            text
+        } else {
+            let name = xkb::keysym_get_name(labels.state.key_get_one_sym(keycode));
+            let dead = name.strip_prefix("dead_")?;
+            format!("DEAD {}", dead.replace('_', " ").to_uppercase())
+        };
+        let interned = *labels.labels.entry(label).or_insert_with_key(|label| Box::leak(label.clone().into_boxed_str()));
+        Some(interned)
+    })
+}
+
+#[cfg(not(feature = "xkb"))]
+pub fn label(_code: u32) -> Option<&'static str> {
+    None
+}
+
+
+#[cfg(all(test, feature = "xkb"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_values_from_etc_default_keyboard() {
+        let text = "# KEYBOARD CONFIGURATION FILE\n# XKBLAYOUT=fr\nXKBMODEL=\"pc105\"\n  XKBLAYOUT = \"de,us\"\nXKBVARIANT=nodeadkeys,\nXKBOPTIONS=\"\"\n";
+        assert_eq!(defaults_value(text, "XKBLAYOUT").as_deref(), Some("de,us"));
+        assert_eq!(defaults_value(text, "XKBMODEL").as_deref(), Some("pc105"));
+        assert_eq!(defaults_value(text, "XKBVARIANT").as_deref(), Some("nodeadkeys,"));
+        assert_eq!(defaults_value(text, "XKBOPTIONS").as_deref(), Some(""));
+        assert_eq!(defaults_value(text, "BACKSPACE"), None);
+    }
+}
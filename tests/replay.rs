@@ -0,0 +1,840 @@
+// Drives recorded fixtures through the binary with --replay, so the decoding
+// and formatting pipeline is covered without input devices or root
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Tests run in parallel, often on the same fixture; each replay gets its own directory
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+struct ReplayOutput {
+    log: String,
+    csv: String,
+    recording: String,
+}
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn replay(name: &str) -> ReplayOutput {
+    replay_with(name, &[])
+}
+
+fn replay_with(name: &str, extra_args: &[&str]) -> ReplayOutput {
+    let run = RUNS.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("rustkey-replay-{}-{}-{}", std::process::id(), run, name));
+    fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("events.log");
+    let csv = dir.join("activity.csv");
+    let recording = dir.join("replayed.rkey");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--replay").arg(fixture(name))
+        .arg("--log-file").arg(&log)
+        .arg("--health-export").arg(&csv)
+        .arg("--record").arg(&recording)
+        .args(extra_args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "replay of {} failed: {}", name, status);
+
+    let output = ReplayOutput {
+        log: fs::read_to_string(&log).unwrap(),
+        csv: fs::read_to_string(&csv).unwrap(),
+        recording: fs::read_to_string(&recording).unwrap(),
+    };
+    fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+// Sum of one activity column over every minute row of the health CSV
+fn csv_total(csv: &str, activity: &str) -> f64 {
+    csv.lines()
+        .skip(1)
+        .map(|line| line.split(',').collect::<Vec<_>>())
+        .filter(|fields| fields[1] == activity)
+        .map(|fields| fields[2].parse::<f64>().unwrap())
+        .sum()
+}
+
+#[test]
+fn keys_are_named_and_counted() {
+    let output = replay_with("keys.rkey", &["--show-codes"]);
+
+    assert!(output.log.contains("YOU PRESSED: [ H ] (Total key presses: 1)"));
+    assert!(output.log.contains("YOU PRESSED: [ I ] (Total key presses: 2)"));
+    assert!(output.log.contains("KEY RELEASE DETECTED --> I <-- (code: 23)"));
+    assert!(output.log.contains("Key presses: 2"));
+    // Ties are listed by keycode: I (23) before H (35)
+    assert!(output.log.contains("Most pressed keys: I ×1, H ×1"));
+    assert!(output.log.contains("Most typed key pairs: H→I ×1"));
+    assert!(output.log.contains("200-500 ms"));
+    assert_eq!(csv_total(&output.csv, "typing"), 2.0);
+}
+
+#[test]
+fn highlight_patterns_match_key_sequences() {
+    let output = replay_with("combo.rkey", &["--highlight-pattern", "CTRL SHIFT T", "--highlight-pattern", "A"]);
+    assert!(output.log.contains("[MATCH: CTRL SHIFT T] CTRL SHIFT T"));
+    assert_eq!(output.log.matches("[MATCH:").count(), 1);
+
+    let output = replay_with("combo.rkey", &["--format", "json", "--highlight-pattern", "SHIFT T"]);
+    let found: serde_json::Value = output.log.lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|line| line["type"] == "pattern_match")
+        .unwrap();
+    assert_eq!(found["pattern_index"], 0);
+    assert_eq!(found["text"], "SHIFT T");
+}
+
+#[test]
+fn summary_reports_peak_and_average_typing_speed() {
+    let output = replay("macro.rkey");
+
+    // 13 presses never fill the minute-long meter, but they came quickly
+    assert!(output.log.contains("Peak typing speed: 2.6 WPM, average over active time: 67.0 WPM"));
+    // Two presses 200 ms apart aren't enough typing to average
+    assert!(!replay("keys.rkey").log.contains("Peak typing speed"));
+}
+
+#[test]
+fn activity_is_split_into_sessions() {
+    let output = replay_with("keys.rkey", &["--session-gap", "0.1"]);
+
+    assert!(output.log.contains("Activity sessions"));
+    let sessions = &output.log[output.log.find("Activity sessions").unwrap()..];
+    let rows = sessions.lines().filter(|line| line.trim_start().starts_with(|c: char| c.is_ascii_digit())).count();
+    assert_eq!(rows, 2);
+}
+
+#[test]
+fn combos_collapse_left_and_right_modifiers() {
+    let output = replay("combo.rkey");
+
+    assert!(output.log.contains("YOU PRESSED: [ RIGHT CTRL ]"));
+    assert!(output.log.contains("YOU PRESSED: [ LEFT SHIFT ]"));
+    assert!(output.log.contains("COMBO: CTRL+SHIFT+T"));
+    // Modifiers on their own are not combos
+    assert_eq!(output.log.matches("COMBO:").count(), 1);
+}
+
+#[test]
+fn summary_lists_the_most_used_shortcuts() {
+    let output = replay("combo.rkey");
+
+    let block: Vec<&str> = output.log.lines()
+        .skip_while(|line| !line.contains("Most used shortcuts:"))
+        .skip(1)
+        .take_while(|line| line.starts_with("  "))
+        .collect();
+    assert_eq!(block, ["  Ctrl+Shift+T         ×1"]);
+}
+
+#[test]
+fn clicks_use_button_names_and_track_position() {
+    let output = replay("clicks.rkey");
+
+    assert!(output.log.contains("Mouse motion - Position: (3.00, 4.00), Delta: (3.00, 4.00)"));
+    assert!(output.log.contains("Mouse button LEFT - PRESSED at position: (3.00, 4.00) (Total clicks: 1)"));
+    assert!(output.log.contains("Mouse button RIGHT - RELEASED"));
+    assert!(output.log.contains("Mouse clicks: 2"));
+    // Both presses fall within one second
+    assert!(output.log.contains("Peak click rate: 2 CPS (LEFT 1, RIGHT 1)"));
+    assert_eq!(csv_total(&output.csv, "mouse_movement"), 5.0);
+}
+
+#[test]
+fn mouse_path_is_drawn_as_svg() {
+    let svg_path = std::env::temp_dir().join(format!("rustkey-path-{}.svg", std::process::id()));
+    let output = replay_with("clicks.rkey", &["--record-mouse-path", svg_path.to_str().unwrap(), "--screen", "800x600"]);
+    let svg = fs::read_to_string(&svg_path).unwrap();
+    assert!(output.log.contains("Mouse path: 1 points simplified to 1 (1.0x smaller)"));
+    fs::remove_file(&svg_path).unwrap();
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(r#"viewBox="0 0 800 600""#));
+    assert!(svg.contains(r#"fill="green""#));
+    assert!(svg.contains(r#"fill="red""#));
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn devices_are_tracked_across_hotplug_events() {
+    let output = replay("hotplug.rkey");
+    assert!(output.log.contains("Device Added: Test Mouse (pointer, gesture)"));
+    assert!(output.log.contains("Device Removed: Test Mouse"));
+    assert!(output.log.contains("Devices connected at exit: 2 (Test Keyboard, Old Recording Device)"));
+
+    let json = replay_with("hotplug.rkey", &["--format", "json"]).log;
+    let removed: serde_json::Value = json.lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|line| line["type"] == "device_removed")
+        .unwrap();
+    assert_eq!(removed["info"]["sysfs_path"], "/sys/class/input/event4");
+    assert_eq!(removed["info"]["capabilities"], serde_json::json!(["pointer", "gesture"]));
+}
+
+#[test]
+fn sanitize_hides_typed_keys_and_device_names() {
+    let output = replay_with("hotplug.rkey", &["--sanitize"]);
+    assert!(output.log.contains("YOU PRESSED: [ LETTER ]"));
+    assert!(output.log.contains("Device Added: Device 2 (pointer, gesture)"));
+    assert!(output.log.contains("Device Removed: Device 2"));
+    for log in [&output.log, &output.recording] {
+        assert!(!log.contains("Test Keyboard") && !log.contains("Test Mouse"));
+    }
+    // H becomes the letter representative, KEY_A
+    assert!(output.recording.contains(r#""type":"key","code":30,"pressed":true"#));
+}
+
+#[test]
+fn constrained_pointer_motion_is_annotated() {
+    let output = replay_with("clicks.rkey", &["--constrain-pointer", "0,0,2,2"]);
+    assert!(output.log.contains("[CONSTRAINED: would be (3, 4)]"));
+    // The overlay doesn't change the tracked position
+    assert!(output.log.contains("Mouse button LEFT - PRESSED at position: (3.00, 4.00)"));
+
+    let output = replay_with("clicks.rkey", &["--constrain-pointer", "0,0,10,10"]);
+    assert!(!output.log.contains("CONSTRAINED"));
+}
+
+#[test]
+fn modifier_changes_are_shown() {
+    let output = replay_with("combo.rkey", &["--show-modifiers"]);
+    let lines: Vec<&str> = output.log.lines().filter(|line| line.starts_with("MODIFIERS:")).collect();
+    assert_eq!(lines, [
+        "MODIFIERS: [CTRL] shift alt super",
+        "MODIFIERS: [CTRL] [SHIFT] alt super",
+        "MODIFIERS: [CTRL] shift alt super",
+        "MODIFIERS: ctrl shift alt super",
+    ]);
+}
+
+#[test]
+fn key_bindings_run_their_command() {
+    let marker = std::env::temp_dir().join(format!("rustkey-on-key-{}", std::process::id()));
+    let binding = format!("h=echo \"$RUSTKEY_KEY\" >> {}", marker.display());
+    let output = replay_with("keys.rkey", &["--on-key", &binding]);
+    assert!(output.log.contains("Running --on-key command: echo"));
+
+    // The command runs in the background
+    for _ in 0..50 {
+        if fs::read_to_string(&marker).is_ok_and(|text| text.ends_with('\n')) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert_eq!(fs::read_to_string(&marker).unwrap(), "H\n");
+    fs::remove_file(&marker).unwrap();
+}
+
+#[test]
+fn side_and_high_mouse_buttons_are_named() {
+    let output = replay("extra_buttons.rkey");
+
+    assert!(output.log.contains("Mouse button SIDE - PRESSED"));
+    assert!(output.log.contains("Mouse button MOUSE_9 - PRESSED"));
+    assert!(output.log.contains("Mouse button STYLUS - PRESSED"));
+    assert!(!output.log.contains("UNKNOWN BUTTON"));
+}
+
+#[test]
+fn lost_button_releases_are_reported() {
+    let output = replay("lost_release.rkey");
+
+    assert!(output.log.contains("Lost button release: BTN_LEFT (272) was pressed again without a release"));
+    assert!(output.log.contains("Mouse button MIDDLE - PRESSED at position: (0.00, 0.00) (Total clicks: 3)"));
+    assert!(output.log.contains(" LEFT+MIDDLE]"));
+    assert!(output.log.contains("Lost button release: BTN_MIDDLE (274) was still held at exit"));
+}
+
+#[test]
+fn scroll_bursts_become_gestures() {
+    let output = replay("scroll.rkey");
+
+    assert!(output.log.contains("Scrolled down ~3.0 lines (3 events over 40 ms)"));
+    assert!(output.log.contains("Scrolled up ~1.0 lines (1 events over 0 ms)"));
+    assert!(!output.log.contains("Scroll wheel:"));
+    assert_eq!(csv_total(&output.csv, "scrolling"), 4.0);
+}
+
+#[test]
+fn gestures_are_reported_in_order() {
+    let output = replay("gesture.rkey");
+
+    let begin = output.log.find("Gesture Event: SwipeBegin").unwrap();
+    let update = output.log.find("Gesture Event: SwipeUpdate").unwrap();
+    let end = output.log.find("Gesture Event: SwipeEnd").unwrap();
+    assert!(begin < update && update < end);
+}
+
+#[test]
+fn raw_touches_are_inferred_as_gestures() {
+    let output = replay("touch.rkey");
+
+    assert!(output.log.contains("Touch Event: Down (slot 1 at 20.0, 10.0 mm)"));
+    assert!(output.log.contains("Inferred 2-finger scroll down 12.0 mm"));
+    assert!(output.log.contains("Inferred 2-finger pinch out (×2.00)"));
+}
+
+#[test]
+fn touchpad_finger_counts_tag_pointer_events() {
+    let output = replay("touchpad.rkey");
+    assert!(output.log.contains("Touchpad fingers: 0 → 2"));
+    assert!(output.log.contains("Touchpad fingers: 2 → 0"));
+
+    let lines: Vec<serde_json::Value> = replay_with("touchpad.rkey", &["--format", "json"]).log.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines[0]["type"], "touchpad_finger_count_change");
+    assert_eq!(lines[1]["fingers"], 2);
+    assert!(lines[3].get("fingers").is_none());
+}
+
+#[test]
+fn tablet_pad_controls_are_shown_as_gauges() {
+    let output = replay("pad.rkey");
+
+    assert!(output.log.contains("Pad button 2 pressed (mode 1, group 0)"));
+    assert!(output.log.contains("Pad button 2 released"));
+    assert!(output.log.contains("RING 0: █░░░░░░░ 45.2° (mode 1)"));
+    assert!(output.log.contains("RING 0: released"));
+    assert!(output.log.contains("STRIP 1: ██░░░░░░ 0.25"));
+}
+
+#[test]
+fn max_events_stops_the_replay_early() {
+    let output = replay_with("keys.rkey", &["--max-events", "3", "--format", "json"]);
+
+    assert_eq!(output.log.lines().count(), 3);
+}
+
+#[test]
+fn macros_become_xdotool_scripts() {
+    let script = std::env::temp_dir().join(format!("rustkey-macro-{}.sh", std::process::id()));
+    let record_macro = |extra_args: &[&str]| {
+        let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+            .args(["record-macro", "--replay"]).arg(fixture("macro.rkey"))
+            .arg("--output").arg(&script)
+            .args(extra_args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        fs::read_to_string(&script).unwrap()
+    };
+
+    let timed = record_macro(&[]);
+    assert!(timed.starts_with("#!/bin/sh\n"));
+    assert!(timed.contains("xdotool type -- \"Hi 1\"\nxdotool sleep 1.100\nxdotool key ctrl+c\n"));
+    assert!(timed.contains("xdotool key Return\n"));
+    // Keys after the stop hotkey aren't recorded
+    assert!(!timed.contains("xdotool type -- \"a\""));
+
+    let untimed = record_macro(&["--ignore-timing"]);
+    fs::remove_file(&script).unwrap();
+    assert!(!untimed.contains("xdotool sleep"));
+}
+
+#[test]
+fn warmup_leaves_the_first_events_out() {
+    let output = replay_with("macro.rkey", &["--warmup", "0.5s"]);
+    assert!(!output.log.contains("YOU PRESSED: [ H ]"));
+    assert!(output.log.contains("Key presses: 8"));
+    assert!(output.log.contains("Warmup: 10 events in the first 0.5s after startup"));
+
+    // Shown, but still not counted
+    let output = replay_with("macro.rkey", &["--warmup", "0.5", "--warmup-show"]);
+    assert!(output.log.contains("YOU PRESSED: [ H ]"));
+    assert!(output.log.contains("Key presses: 8"));
+}
+
+#[test]
+fn filter_config_applies_per_device() {
+    let config = std::env::temp_dir().join(format!("rustkey-filters-{}.toml", std::process::id()));
+    fs::write(&config, "types = [\"keyboard\"]\n[device_filters.\"mouse\"]\ntypes = [\"motion\"]\n").unwrap();
+    let output = replay_with("device_filters.rkey", &["--filter-config", config.to_str().unwrap()]);
+    fs::remove_file(&config).unwrap();
+
+    assert!(output.log.contains("Device Added: Test Mouse"));
+    assert!(output.log.contains("Key presses: 1"));
+    // The keyboard's motion is dropped, the mouse's kept, its clicks dropped
+    assert!(output.log.contains("Mouse distance: 5 counts"));
+    assert!(output.log.contains("Mouse clicks: 0"));
+}
+
+#[test]
+fn summary_out_gets_the_summary_alone() {
+    let summary = std::env::temp_dir().join(format!("rustkey-summary-{}.txt", std::process::id()));
+    let summary_arg = summary.to_str().unwrap();
+
+    let output = replay_with("combo.rkey", &["--summary-out", summary_arg]);
+    let text = fs::read_to_string(&summary).unwrap();
+    assert!(output.log.contains("YOU PRESSED"));
+    assert!(!text.contains("YOU PRESSED"));
+    assert!(text.contains("SESSION SUMMARY"));
+    assert!(text.contains("Key presses: 3"));
+
+    // Replaced each run unless appending
+    replay_with("combo.rkey", &["--summary-out", summary_arg, "--format", "json"]);
+    replay_with("combo.rkey", &["--summary-out", summary_arg, "--format", "json", "--summary-append"]);
+    let lines: Vec<serde_json::Value> = fs::read_to_string(&summary).unwrap().lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    fs::remove_file(&summary).unwrap();
+    let totals: Vec<&serde_json::Value> = lines.iter().filter(|line| line["type"] == "session_summary").collect();
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[0]["key_presses"], 3);
+    assert!(lines.iter().any(|line| line["type"] == "combo_frequency" && line["combos"]["Ctrl+Shift+T"] == 1));
+}
+
+#[test]
+fn no_emoji_uses_ascii_tags() {
+    let output = replay_with("clicks.rkey", &["--no-emoji"]);
+
+    assert!(output.log.contains("[MOUSE]  Mouse button LEFT - PRESSED"));
+    assert!(!output.log.contains("🖱"));
+}
+
+#[test]
+fn flush_policy_does_not_change_the_output() {
+    let buffered = replay_with("clicks.rkey", &["--format", "json"]).log;
+    for policy in ["immediate", "line", "block"] {
+        assert_eq!(replay_with("clicks.rkey", &["--format", "json", "--flush", policy]).log, buffered);
+    }
+}
+
+#[test]
+fn json_events_carry_timestamps_and_deltas() {
+    let output = replay_with("keys.rkey", &["--format", "json"]);
+
+    let lines: Vec<serde_json::Value> = output.log.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0]["time_usec"], 1_000_000);
+    assert_eq!(lines[0]["delta_usec"], 0);
+    assert_eq!(lines[1]["delta_usec"], 80_000);
+    assert_eq!(lines[2]["delta_usec"], 120_000);
+    assert_eq!(lines[3]["type"], "key");
+    assert_eq!(lines[3]["code"], 23);
+    assert_eq!(lines[3]["pressed"], false);
+}
+
+#[test]
+fn binary_output_decodes_to_the_same_json() {
+    let dir = std::env::temp_dir().join(format!("rustkey-binary-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let binary = dir.join("events.bin");
+    let decoded = dir.join("events.json");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--replay").arg(fixture("clicks.rkey"))
+        .args(["--format", "binary", "--output"]).arg(&binary)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let bytes = fs::read(&binary).unwrap();
+    assert_eq!(&bytes[0..4], b"RKEY");
+    assert_eq!(bytes.len(), 20 + 5 * 40);
+    assert_eq!(u32::from_le_bytes(bytes[16..20].try_into().unwrap()), 5);
+
+    let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("decode").arg(&binary)
+        .arg("--output").arg(&decoded)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let decoded = fs::read_to_string(&decoded).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(decoded, replay_with("clicks.rkey", &["--format", "json"]).log);
+}
+
+#[test]
+fn msgpack_frames_match_json_lines() {
+    let dir = std::env::temp_dir().join(format!("rustkey-msgpack-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let frames = dir.join("events.msgpack");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--replay").arg(fixture("clicks.rkey"))
+        .args(["--format", "msgpack", "--output"]).arg(&frames)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let bytes = fs::read(&frames).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    // u32 big-endian length, then the MessagePack map
+    let mut events = Vec::new();
+    let mut rest = bytes.as_slice();
+    while !rest.is_empty() {
+        let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let event: serde_json::Value = rmp_serde::from_slice(&rest[4..4 + length]).unwrap();
+        events.push(event);
+        rest = &rest[4 + length..];
+    }
+    let json: Vec<serde_json::Value> = replay_with("clicks.rkey", &["--format", "json"]).log.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events, json);
+}
+
+#[test]
+fn interactive_replay_steps_through_events() {
+    use std::io::Write;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--replay").arg(fixture("keys.rkey"))
+        .arg("--interactive")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // next, back to the first event, find I, then quit before its release
+    child.stdin.take().unwrap().write_all(b"n\np\nf i\nq\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("YOU PRESSED: [ H ]").count(), 2);
+    assert_eq!(stdout.matches("YOU PRESSED: [ I ]").count(), 1);
+    assert!(!stdout.contains("KEY RELEASE DETECTED --> I"));
+    assert!(!stdout.contains("SESSION SUMMARY"));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("[event 3/4  +0.200s]"));
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_log_can_be_queried() {
+    let dir = std::env::temp_dir().join(format!("rustkey-sqlite-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let database = dir.join("events.db");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--replay").arg(fixture("keys.rkey"))
+        .arg("--log-sqlite").arg(&database)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("query").arg(&database)
+        .arg("event_type = 'key' AND key_name = 'I'")
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(output.status.success());
+
+    let rows: Vec<serde_json::Value> = String::from_utf8(output.stdout).unwrap().lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["key_code"], 23);
+    assert_eq!(rows[0]["timestamp_us"], 1_200_000);
+}
+
+#[test]
+fn recordings_survive_a_replay_unchanged() {
+    for name in ["keys.rkey", "combo.rkey", "clicks.rkey", "scroll.rkey", "gesture.rkey", "swipes.rkey", "lost_release.rkey", "touch.rkey", "touchpad.rkey", "pad.rkey"] {
+        let original = fs::read_to_string(fixture(name)).unwrap();
+        assert_eq!(replay(name).recording, original, "{} changed when re-recorded", name);
+    }
+}
+
+#[test]
+fn aliases_relabel_keys() {
+    let aliases = std::env::temp_dir().join(format!("rustkey-aliases-{}", std::process::id()));
+    fs::write(&aliases, "# remapped keys\n35=HOME ROW\n23=FROM FILE\n").unwrap();
+    let output = replay_with("keys.rkey", &["--alias-file", aliases.to_str().unwrap(), "--alias", "I=FROM FLAG"]);
+    fs::remove_file(&aliases).unwrap();
+
+    assert!(output.log.contains("KEY PRESS DETECTED --> HOME ROW <--"));
+    // The command line wins over the file
+    assert!(output.log.contains("KEY PRESS DETECTED --> FROM FLAG <--"));
+    assert!(!output.log.contains("FROM FILE"));
+}
+
+#[test]
+fn ghost_keys_are_flagged() {
+    let output = replay_with("ghosting.rkey", &["--detect-ghosting"]);
+
+    // D appeared while A and S were held and went up with S
+    assert!(output.log.contains("GHOST: D was reported while A + S were held"));
+    // Q W E R were pressed and released one at a time
+    assert_eq!(output.log.matches("GHOST:").count(), 1);
+    assert!(output.log.contains("Key rollover: up to 4 keys held at once, 1 ghost key presses, 0 dropped presses"));
+}
+
+#[test]
+fn summary_has_an_event_rate_histogram() {
+    let output = replay("keys.rkey");
+    assert!(output.log.contains("Events per second (1 s):"));
+    assert!(output.log.contains("  keyboard:   2-4/s 100%"));
+}
+
+#[test]
+fn precision_sets_the_decimals() {
+    let output = replay_with("clicks.rkey", &["--precision", "0"]);
+    assert!(output.log.contains("Mouse motion - Position: (3, 4), Delta: (3, 4)"));
+    let output = replay_with("clicks.rkey", &["--precision", "4"]);
+    assert!(output.log.contains("Mouse motion - Position: (3.0000, 4.0000), Delta: (3.0000, 4.0000)"));
+}
+
+#[test]
+fn events_keep_their_seat() {
+    let output = replay_with("seats.rkey", &[]);
+    assert!(output.log.lines().any(|line| line.starts_with("[seat1] ") && line.contains("KEY PRESS") && line.contains("B")));
+    assert!(output.log.lines().any(|line| line.starts_with("[seat0] ") && line.contains("KEY PRESS")));
+    // Summary lines have no seat
+    assert!(output.log.lines().any(|line| line.starts_with("Key presses: 2")));
+
+    let json = replay_with("seats.rkey", &["--format", "json"]);
+    let first: serde_json::Value = serde_json::from_str(json.log.lines().next().unwrap()).unwrap();
+    assert_eq!(first["seat"], "seat0");
+}
+
+#[test]
+fn rollover_test_flags_late_presses() {
+    let output = replay_with("rollover.rkey", &["--rollover-test"]);
+
+    // F went down in the same scan that A went up, with three keys held
+    assert!(output.log.contains("DROPPED PRESS: F only arrived as A went up, with 3 keys held"));
+    assert!(!output.log.contains("KEY PRESS"));
+    assert!(output.log.contains("Key rollover: up to 3 keys held at once, 0 ghost key presses, 1 dropped presses"));
+}
+
+#[test]
+fn libinput_debug_format_matches_debug_events() {
+    let output = replay_with("clicks.rkey", &["--format", "libinput-debug"]);
+    let lines: Vec<&str> = output.log.lines().collect();
+    assert_eq!(lines[0], "-unknown  POINTER_MOTION          +0.000s\t  3.00/  4.00 (  3.00/  4.00)");
+    assert_eq!(lines[1], " unknown  POINTER_BUTTON          +0.010s\tBTN_LEFT (272) pressed, seat count: 1");
+    // The summary stays out of the event lines
+    assert!(!output.log.contains("SESSION SUMMARY"));
+}
+
+#[test]
+fn evtest_format_shows_raw_events() {
+    let output = replay_with("keys.rkey", &["--format", "evtest"]);
+    let lines: Vec<&str> = output.log.lines().collect();
+    assert!(lines[0].starts_with("Event: time 1.000000, type 1 (EV_KEY), code 35 (KEY_H), value 1 "));
+    assert!(lines[0].ends_with("# H"));
+    assert_eq!(lines[1], "Event: time 1.000000, -------------- SYN_REPORT ------------");
+    assert_eq!(output.log.matches("SYN_REPORT").count(), 4);
+}
+
+#[test]
+fn output_template_formats_events() {
+    let output = replay_with("keys.rkey", &["--output-template", "{type}: {key_name} at {timestamp_us}{?pressed: pressed={pressed}?}"]);
+    let lines: Vec<&str> = output.log.lines().take(2).collect();
+    assert_eq!(lines, ["key: H at 1000000 pressed=true", "key: H at 1080000 pressed=false"]);
+    // The summary is still printed
+    assert!(output.log.contains("Key presses: 2"));
+
+    let output = replay_with("keys.rkey", &["--output-template", "{key_name}", "--sanitize"]);
+    assert_eq!(output.log.lines().next(), Some("LETTER"));
+}
+
+#[test]
+fn excess_lines_are_suppressed() {
+    // The events span well under a second, so only the first two print
+    let output = replay_with("clicks.rkey", &["--max-lines-per-sec", "2"]);
+    assert_eq!(output.log.matches("Mouse button").count(), 1);
+    assert!(output.log.contains("... 3 events suppressed ..."));
+    // Dropped events still count
+    assert!(output.log.contains("Mouse clicks: 2"));
+    assert!(output.log.contains("Output throttled: 3 events not printed (over 2 per second)"));
+}
+
+#[test]
+fn presses_are_classified() {
+    // H is held 80 ms and I 60 ms
+    let output = replay_with("keys.rkey", &["--classify-presses", "--long-press-threshold", "70"]);
+    assert!(output.log.contains("LONG PRESS: H (80 ms)"));
+    assert!(output.log.contains("TAP: I (60 ms)"));
+    assert!(output.log.contains("Presses: 1 taps, 1 long presses (70 ms or more), 0 incomplete"));
+
+    // The middle button is never released
+    let output = replay_with("lost_release.rkey", &["--classify-presses"]);
+    assert!(output.log.contains("Incomplete press: button MIDDLE still held at exit"));
+    assert!(output.log.contains("1 incomplete"));
+}
+
+#[test]
+fn gestures_are_mapped_to_actions() {
+    // A three-finger swipe left, then a pinch in
+    let output = replay("swipes.rkey");
+    assert!(output.log.contains("Gesture Event: SwipeUpdate (3 fingers, dx -80.00, dy 4.00)"));
+    assert!(output.log.contains("Gesture Event: SwipeEnd (3 fingers) → SWIPE_3_LEFT: browser_back"));
+    assert!(output.log.contains("Gesture Event: PinchEnd (2 fingers, scale 0.70) → PINCH_IN: zoom_out"));
+
+    let json: Vec<serde_json::Value> = replay_with("swipes.rkey", &["--format", "json"]).log.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(json[3]["mapped_action"], "browser_back");
+    assert_eq!(json[7]["mapped_action"], "zoom_out");
+    assert!(json[2].get("mapped_action").is_none());
+
+    let map = std::env::temp_dir().join(format!("rustkey-gestures-{}.toml", std::process::id()));
+    fs::write(&map, "[actions]\nSWIPE_3_LEFT = \"previous_tab\"\nPINCH_IN = \"\"\n").unwrap();
+    let output = replay_with("swipes.rkey", &["--gesture-map", map.to_str().unwrap()]);
+    fs::remove_file(&map).unwrap();
+    assert!(output.log.contains("SWIPE_3_LEFT: previous_tab"));
+    assert!(output.log.contains("PINCH_IN (no action)"));
+}
+
+#[test]
+fn uinput_replay_needs_confirmation() {
+    let output = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--replay-into-uinput").arg(fixture("keys.rkey"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("add --confirm-inject"));
+}
+
+#[test]
+fn grab_needs_confirmation_and_a_device_path() {
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_libinput_project")).args(args).output().unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+    assert!(run(&["--device-path", "/dev/input/event0", "--grab"]).contains("add --confirm-grab"));
+    assert!(run(&["--grab", "--confirm-grab"]).contains("--grab is only used with --device-path"));
+}
+
+#[test]
+fn json_elapsed_time_starts_at_the_first_event() {
+    let output = replay_with("clicks.rkey", &["--format", "json"]);
+    let lines: Vec<serde_json::Value> = output.log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let first_usec = lines[0]["time_usec"].as_u64().unwrap();
+    assert_eq!(lines[0]["elapsed_usec"], 0);
+    for line in &lines {
+        assert_eq!(line["elapsed_usec"].as_u64().unwrap(), line["time_usec"].as_u64().unwrap() - first_usec);
+    }
+}
+
+#[test]
+fn one_shot_prints_only_the_first_match() {
+    let one_shot = |kind: &str| Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--replay").arg(fixture("clicks.rkey"))
+        .args(["--format", "json", "--one-shot", kind])
+        .output()
+        .unwrap();
+
+    let output = one_shot("button");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> = stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["type"], "pointer_button");
+    assert_eq!(lines[0]["pressed"], true);
+
+    // The recording ends without a key press
+    let output = one_shot("keyboard");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn smooth_scroll_spreads_wheel_notches() {
+    // Three notches down, then one up
+    let output = replay_with("scroll.rkey", &["--format", "json", "--smooth-scroll", "--scroll-friction", "0.5"]);
+    let steps: Vec<f64> = output.log.lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|line| line["type"] == "scroll")
+        .map(|line| {
+            assert_eq!(line["source"], "continuous");
+            line["vertical"].as_f64().unwrap()
+        })
+        .collect();
+    assert!(steps.len() > 4);
+    // 15 continuous units per notch
+    assert!((steps.iter().sum::<f64>() - 30.0).abs() < 1e-9);
+
+    let output = replay_with("scroll.rkey", &["--smooth-scroll"]);
+    assert!(output.log.contains("Scrolled down ~3.0 lines"));
+    // The recording keeps the wheel notches
+    assert_eq!(output.recording.matches("\"source\":\"wheel\"").count(), 4);
+    assert!(!output.recording.contains("continuous"));
+}
+
+#[test]
+fn identical_events_are_coalesced() {
+    // Three identical wheel ticks, then one the other way
+    let output = replay_with("scroll.rkey", &["--coalesce", "--raw-scroll"]);
+    assert!(output.log.contains("SCROLL (0.00, 120.00) x3"));
+    assert!(!output.log.contains("-120.00) x"));
+    assert_eq!(output.log.matches("Scroll wheel").count(), replay_with("scroll.rkey", &["--raw-scroll"]).log.matches("Scroll wheel").count() - 2);
+}
+
+#[test]
+fn lock_keys_toggle_their_state() {
+    // Caps Lock twice, Num Lock once; a replay can't read the LEDs
+    let output = replay_with("locks.rkey", &[]);
+    assert!(output.log.contains("CAPS LOCK: ON (state before was unknown, taken as off)"));
+    assert!(output.log.contains("NUM LOCK: ON (state before was unknown, taken as off)"));
+    assert!(output.log.contains("CAPS LOCK: OFF"));
+    assert!(!output.log.contains("CAPS LOCK: OFF (state"));
+}
+
+#[cfg(feature = "simulation")]
+#[test]
+fn scenarios_are_played_through_the_pipeline() {
+    let dir = std::env::temp_dir().join(format!("rustkey-simulate-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let recording = dir.join("simulated.rkey");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("--simulate").arg(fixture("scenario.toml"))
+        .arg("--record").arg(&recording)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let events: Vec<serde_json::Value> = fs::read_to_string(&recording).unwrap().lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    fs::remove_dir_all(&dir).unwrap();
+    let count = |kind: &str| events.iter().filter(|event| event["type"] == kind).count();
+    assert_eq!(count("key"), 4);
+    assert_eq!(count("pointer_button"), 4);
+    assert_eq!(count("scroll"), 3);
+
+    // Each pass of the path ends at its last point
+    let (dx, dy) = events.iter().filter(|event| event["type"] == "pointer_motion")
+        .fold((0.0, 0.0), |(x, y), event| (x + event["dx"].as_f64().unwrap(), y + event["dy"].as_f64().unwrap()));
+    assert!((dx - 30.0).abs() < 1e-9 && (dy - 40.0).abs() < 1e-9, "moved ({}, {})", dx, dy);
+}
+
+#[test]
+fn json_output_exports_to_keylogger_stats() {
+    // With the pattern_match and press_classified records in between
+    let output = replay_with("combo.rkey", &["--format", "json", "--highlight-pattern", "SHIFT T", "--classify-presses"]);
+    assert!(output.log.contains("\"pattern_match\""));
+
+    let dir = std::env::temp_dir().join(format!("rustkey-export-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (capture, stats) = (dir.join("capture.json"), dir.join("stats.json"));
+    fs::write(&capture, &output.log).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_libinput_project"))
+        .arg("export").arg("--format").arg("keylogger-stats")
+        .arg("--input").arg(&capture)
+        .arg("--output").arg(&stats)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let stats: serde_json::Value = serde_json::from_str(&fs::read_to_string(&stats).unwrap()).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(stats["total"], 3);
+    assert_eq!(stats["keys"]["T"], 1);
+    assert_eq!(stats["keys"]["LEFT SHIFT"], 1);
+}